@@ -0,0 +1,103 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Running sum with Neumaier compensation
+///
+/// Plain `+=` loses the low bits of every small value added to a large
+/// total, so long-running totals such as execution times drift. This keeps
+/// the lost low-order part in a separate term and folds it back in when the
+/// value is read. It serializes as a plain number.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug, PartialEq)]
+#[serde(from = "f64", into = "f64")]
+pub(crate) struct CompensatedSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl CompensatedSum {
+    pub fn add(&mut self, value: f64) {
+        let total = self.sum + value;
+        if self.sum.abs() >= value.abs() {
+            self.compensation += (self.sum - total) + value;
+        } else {
+            self.compensation += (value - total) + self.sum;
+        }
+        self.sum = total;
+    }
+    
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+impl From<f64> for CompensatedSum {
+    fn from(value: f64) -> Self {
+        CompensatedSum { sum: value, compensation: 0.0 }
+    }
+}
+
+impl From<CompensatedSum> for f64 {
+    fn from(sum: CompensatedSum) -> Self {
+        sum.value()
+    }
+}
+
+/// PreciseAccumulator sums long streams of values without float drift
+///
+/// Hosts aggregating over long runs (hours of per-tick values) can use it in
+/// place of a JS `+=` loop, whose error grows with the number of values.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct PreciseAccumulator {
+    sum: CompensatedSum,
+    count: u64,
+}
+
+#[wasm_bindgen]
+impl PreciseAccumulator {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> PreciseAccumulator {
+        PreciseAccumulator::default()
+    }
+    
+    /// Add one value
+    #[wasm_bindgen]
+    pub fn add(&mut self, value: f64) {
+        self.sum.add(value);
+        self.count += 1;
+    }
+    
+    /// Add every value of an array
+    #[wasm_bindgen(js_name = addAll)]
+    pub fn add_all(&mut self, values: &[f64]) {
+        for value in values {
+            self.add(*value);
+        }
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn sum(&self) -> f64 {
+        self.sum.value()
+    }
+    
+    /// Number of values added
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+    
+    /// Mean of the values added (0 before any were added)
+    #[wasm_bindgen(getter)]
+    pub fn mean(&self) -> f64 {
+        if self.count > 0 {
+            self.sum.value() / self.count as f64
+        } else {
+            0.0
+        }
+    }
+    
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        *self = PreciseAccumulator::default();
+    }
+}
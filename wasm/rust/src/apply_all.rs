@@ -0,0 +1,115 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+
+use crate::call_log;
+use crate::{apply_rule, OrganismState, RuleRegistry};
+
+/// Parameters of one `applyRules` entry: positional, or named as for `applyRuleNamed`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpecParams {
+    Positional(Vec<f64>),
+    Named(serde_json::Map<String, serde_json::Value>),
+}
+
+/// One entry of `applyRules`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct RuleSpec {
+    rule_id: String,
+    #[serde(default)]
+    params: Option<SpecParams>,
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Apply every enabled rule to `state` in one call and return the results as JSON
+    ///
+    /// Rules run in the order ticks run them, each with `params`, and their
+    /// timings are recorded as `applyRule` records them. Unlike `tick`,
+    /// modes, schedules, triggers and hotspot throttling are ignored.
+    /// Returns `{ adaptationScore, totalTimeMs, results }`, where `results`
+    /// holds `{ ruleId, result, timeMs }` per rule. Throws at the first
+    /// rule that fails; the rules before it stay applied.
+    #[wasm_bindgen(js_name = applyAllRules)]
+    pub fn apply_all_rules(&mut self, state: &mut OrganismState, params: Vec<f64>) -> Result<String, JsValue> {
+        let _call = call_log::enter("applyAllRules", || serde_json::json!({ "params": params }));
+        let ids: Vec<String> = self.run_order()
+            .into_iter()
+            .filter(|id| self.rules.get(*id).is_some_and(|rule| rule.enabled))
+            .cloned()
+            .collect();
+        
+        let mut results = Vec::with_capacity(ids.len());
+        let mut total_time_ms = 0.0;
+        for id in ids {
+            let result = apply_rule(self, state, &id, params.clone())?;
+            let time_ms = self.rules.get(&id).map_or(0.0, |rule| rule.last_execution_time_ms);
+            total_time_ms += time_ms;
+            results.push(serde_json::json!({ "ruleId": id, "result": result, "timeMs": time_ms }));
+        }
+        
+        let summary = serde_json::json!({
+            "adaptationScore": state.adaptation_score(),
+            "totalTimeMs": total_time_ms,
+            "results": results,
+        });
+        serde_json::to_string(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule results: {}", e)))
+    }
+}
+
+/// Apply several rules in order as one all-or-nothing step
+///
+/// `specs_json` is an array of `{ ruleId, params }`, where `params` is
+/// either an array of numbers or an object of named parameters (see
+/// `applyRuleNamed`) and may be omitted. Every rule and its parameters are
+/// checked first. If a rule fails, the state is restored to what it was
+/// before the first one ran and the error names the failing entry, e.g.
+/// `Rule batch failed at #1 (drought): ...`. Execution statistics of the
+/// rules that did run are kept. Returns `{ adaptationScore, results }`,
+/// with `{ ruleId, result }` per entry.
+#[wasm_bindgen(js_name = applyRules)]
+pub fn apply_rules(registry: &mut RuleRegistry, state: &mut OrganismState, specs_json: &str) -> Result<String, JsValue> {
+    let _call = call_log::enter("applyRules", || serde_json::json!({ "specs": call_log::text(specs_json) }));
+    let specs: Vec<RuleSpec> = serde_json::from_str(specs_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse rule specs: {}", e)))?;
+    
+    let mut resolved = Vec::with_capacity(specs.len());
+    for (index, spec) in specs.iter().enumerate() {
+        let invalid = |e: String| JsValue::from_str(&format!("Invalid rule spec #{} ({}): {}", index, spec.rule_id, e));
+        if !registry.rules.contains_key(&spec.rule_id) {
+            return Err(invalid(format!("Rule not found: {}", spec.rule_id)));
+        }
+        let params = match &spec.params {
+            None => Vec::new(),
+            Some(SpecParams::Positional(params)) => params.clone(),
+            Some(SpecParams::Named(values)) => registry.resolve_params(&spec.rule_id, values).map_err(invalid)?,
+        };
+        resolved.push(params);
+    }
+    
+    let before = state.clone();
+    let mut results = Vec::with_capacity(specs.len());
+    for (index, (spec, params)) in specs.iter().zip(resolved).enumerate() {
+        match apply_rule(registry, state, &spec.rule_id, params) {
+            Ok(result) => results.push(serde_json::json!({ "ruleId": spec.rule_id, "result": result })),
+            Err(e) => {
+                *state = before;
+                return Err(JsValue::from_str(&format!(
+                    "Rule batch failed at #{} ({}): {}",
+                    index,
+                    spec.rule_id,
+                    e.as_string().unwrap_or_default()
+                )));
+            }
+        }
+    }
+    
+    let summary = serde_json::json!({
+        "adaptationScore": state.adaptation_score(),
+        "results": results,
+    });
+    serde_json::to_string(&summary)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule results: {}", e)))
+}
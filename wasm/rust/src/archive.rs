@@ -0,0 +1,209 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::clock;
+use crate::{Bookmarks, ColonySystem, ConflictResolver, OrganismState, RuleRegistry, TerritoryMap};
+
+/// Format tag written into every world archive
+const WORLD_FORMAT: &str = "ouroboros-world";
+
+/// Archive layout version, bumped whenever a section changes incompatibly
+const WORLD_VERSION: u32 = 1;
+
+/// The serialized archive
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WorldFile {
+    format: String,
+    version: u32,
+    #[serde(default)]
+    label: String,
+    #[serde(default)]
+    created_at: f64,
+    sections: BTreeMap<String, serde_json::Value>,
+}
+
+/// WorldArchive bundles every part of a simulation into one versioned file
+///
+/// Add the parts to save, then `exportWorld()` produces a single JSON
+/// document. `WorldArchive.importWorld(json)` reads it back and the
+/// `restore*` methods put each part into place. Parts that weren't added are
+/// simply absent.
+#[wasm_bindgen]
+pub struct WorldArchive {
+    label: String,
+    created_at: f64,
+    sections: BTreeMap<String, serde_json::Value>,
+}
+
+impl WorldArchive {
+    fn put(&mut self, section: &str, snapshot_json: &str) -> Result<(), JsValue> {
+        let value = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to archive {}: {}", section, e)))?;
+        self.sections.insert(section.to_string(), value);
+        Ok(())
+    }
+    
+    fn section(&self, section: &str) -> Result<&serde_json::Value, JsValue> {
+        self.sections.get(section)
+            .ok_or_else(|| JsValue::from_str(&format!("World archive has no {} section", section)))
+    }
+    
+    fn section_json(&self, section: &str) -> Result<String, JsValue> {
+        serde_json::to_string(self.section(section)?)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read {} section: {}", section, e)))
+    }
+}
+
+#[wasm_bindgen]
+impl WorldArchive {
+    /// Create an empty archive
+    #[wasm_bindgen(constructor)]
+    pub fn new(label: &str) -> WorldArchive {
+        WorldArchive {
+            label: label.to_string(),
+            created_at: clock::now_ms(),
+            sections: BTreeMap::new(),
+        }
+    }
+    
+    /// Label given when the archive was created
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> String {
+        self.label.clone()
+    }
+    
+    /// Milliseconds since the epoch at which the archive was created, as read from `Clock`
+    #[wasm_bindgen(getter, js_name = createdAt)]
+    pub fn created_at(&self) -> f64 {
+        self.created_at
+    }
+    
+    /// Names of the sections in the archive
+    #[wasm_bindgen]
+    pub fn sections(&self) -> Vec<String> {
+        self.sections.keys().cloned().collect()
+    }
+    
+    /// Whether the archive holds a section
+    #[wasm_bindgen(js_name = hasSection)]
+    pub fn has_section(&self, section: &str) -> bool {
+        self.sections.contains_key(section)
+    }
+    
+    /// Add an organism state
+    #[wasm_bindgen(js_name = addState)]
+    pub fn add_state(&mut self, state: &OrganismState) -> Result<(), JsValue> {
+        self.put("state", &state.get_snapshot()?)
+    }
+    
+    /// Add a rule registry
+    #[wasm_bindgen(js_name = addRegistry)]
+    pub fn add_registry(&mut self, registry: &RuleRegistry) -> Result<(), JsValue> {
+        self.put("registry", &registry.export_registry()?)
+    }
+    
+    /// Add a territory map
+    #[wasm_bindgen(js_name = addTerritory)]
+    pub fn add_territory(&mut self, territory: &TerritoryMap) -> Result<(), JsValue> {
+        self.put("territory", &territory.get_snapshot()?)
+    }
+    
+    /// Add a colony system
+    #[wasm_bindgen(js_name = addColonies)]
+    pub fn add_colonies(&mut self, colonies: &ColonySystem) -> Result<(), JsValue> {
+        self.put("colonies", &colonies.get_snapshot()?)
+    }
+    
+    /// Add a bookmark list
+    #[wasm_bindgen(js_name = addBookmarks)]
+    pub fn add_bookmarks(&mut self, bookmarks: &Bookmarks) -> Result<(), JsValue> {
+        self.put("bookmarks", &bookmarks.get_snapshot()?)
+    }
+    
+    /// Add a conflict resolver, including its RNG state so contests replay identically
+    #[wasm_bindgen(js_name = addConflictResolver)]
+    pub fn add_conflict_resolver(&mut self, resolver: &ConflictResolver) -> Result<(), JsValue> {
+        let value = serde_json::to_value(resolver)
+            .map_err(|e| JsValue::from_str(&format!("Failed to archive conflicts: {}", e)))?;
+        self.sections.insert("conflicts".to_string(), value);
+        Ok(())
+    }
+    
+    /// Serialize the whole archive as one JSON document
+    #[wasm_bindgen(js_name = exportWorld)]
+    pub fn export_world(&self) -> Result<String, JsValue> {
+        let file = WorldFile {
+            format: WORLD_FORMAT.to_string(),
+            version: WORLD_VERSION,
+            label: self.label.clone(),
+            created_at: self.created_at,
+            sections: self.sections.clone(),
+        };
+        
+        serde_json::to_string(&file)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export world: {}", e)))
+    }
+    
+    /// Read an archive produced by `exportWorld`
+    #[wasm_bindgen(js_name = importWorld)]
+    pub fn import_world(json: &str) -> Result<WorldArchive, JsValue> {
+        let file: WorldFile = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse world archive: {}", e)))?;
+        
+        if file.format != WORLD_FORMAT {
+            return Err(JsValue::from_str(&format!("Unsupported world archive format: {}", file.format)));
+        }
+        if file.version > WORLD_VERSION {
+            return Err(JsValue::from_str(&format!(
+                "World archive version {} is newer than supported version {}",
+                file.version, WORLD_VERSION
+            )));
+        }
+        
+        Ok(WorldArchive {
+            label: file.label,
+            created_at: file.created_at,
+            sections: file.sections,
+        })
+    }
+    
+    /// Restore an organism state from the archive
+    #[wasm_bindgen(js_name = restoreState)]
+    pub fn restore_state(&self, state: &mut OrganismState) -> Result<(), JsValue> {
+        state.load_snapshot(&self.section_json("state")?)
+    }
+    
+    /// Restore a rule registry from the archive
+    #[wasm_bindgen(js_name = restoreRegistry)]
+    pub fn restore_registry(&self, registry: &mut RuleRegistry) -> Result<(), JsValue> {
+        registry.import_registry(&self.section_json("registry")?)
+    }
+    
+    /// Restore a territory map from the archive
+    #[wasm_bindgen(js_name = restoreTerritory)]
+    pub fn restore_territory(&self, territory: &mut TerritoryMap) -> Result<(), JsValue> {
+        territory.load_snapshot(&self.section_json("territory")?)
+    }
+    
+    /// Restore a colony system from the archive
+    #[wasm_bindgen(js_name = restoreColonies)]
+    pub fn restore_colonies(&self, colonies: &mut ColonySystem) -> Result<(), JsValue> {
+        colonies.load_snapshot(&self.section_json("colonies")?)
+    }
+    
+    /// Restore a bookmark list from the archive
+    #[wasm_bindgen(js_name = restoreBookmarks)]
+    pub fn restore_bookmarks(&self, bookmarks: &mut Bookmarks) -> Result<(), JsValue> {
+        bookmarks.load_snapshot(&self.section_json("bookmarks")?)
+    }
+    
+    /// Restore a conflict resolver, including its RNG state
+    #[wasm_bindgen(js_name = restoreConflictResolver)]
+    pub fn restore_conflict_resolver(&self, resolver: &mut ConflictResolver) -> Result<(), JsValue> {
+        *resolver = serde_json::from_value(self.section("conflicts")?.clone())
+            .map_err(|e| JsValue::from_str(&format!("Failed to restore conflicts: {}", e)))?;
+        Ok(())
+    }
+}
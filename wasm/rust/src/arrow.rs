@@ -0,0 +1,285 @@
+use wasm_bindgen::prelude::*;
+
+use crate::history::{HistoryRecorder, HISTORY_SERIES};
+use crate::simulation::Simulation;
+
+/// Marks the start of every encapsulated IPC message
+const CONTINUATION: u32 = 0xFFFF_FFFF;
+
+/// `MetadataVersion.V5`, the current IPC format
+const METADATA_V5: i16 = 4;
+
+/// `MessageHeader` union tags
+const HEADER_SCHEMA: u8 = 1;
+const HEADER_RECORD_BATCH: u8 = 3;
+
+/// `Type` union tags
+const TYPE_INT: u8 = 2;
+const TYPE_FLOATING_POINT: u8 = 3;
+
+/// `Precision.DOUBLE`
+const PRECISION_DOUBLE: i16 = 2;
+
+/// A value in a flatbuffer table slot
+enum Slot {
+    U8(u8),
+    Bool(bool),
+    I16(i16),
+    I32(i32),
+    I64(i64),
+    /// Offset to an object stored after the table
+    Ref(Object),
+}
+
+impl Slot {
+    fn size(&self) -> usize {
+        match self {
+            Slot::U8(_) | Slot::Bool(_) => 1,
+            Slot::I16(_) => 2,
+            Slot::I32(_) | Slot::Ref(_) => 4,
+            Slot::I64(_) => 8,
+        }
+    }
+}
+
+/// A flatbuffer object the Arrow metadata is built from
+enum Object {
+    /// Fields by their ID in the schema
+    Table(Vec<(u16, Slot)>),
+    Str(String),
+    Tables(Vec<Object>),
+    /// Vector of structs made of two `long`s (`FieldNode` and `Buffer` both are)
+    Pairs(Vec<(i64, i64)>),
+}
+
+/// Writes flatbuffers front to back
+///
+/// Objects an offset points to are written after the offset (offsets
+/// are unsigned), and a table's vtable just before the table. Arrow
+/// only needs a valid flatbuffer, not the canonical back-to-front
+/// layout `flatc` produces.
+struct Builder {
+    buf: Vec<u8>,
+}
+
+impl Builder {
+    fn align(&mut self, to: usize) {
+        while !self.buf.len().is_multiple_of(to) {
+            self.buf.push(0);
+        }
+    }
+    
+    fn patch_u32(&mut self, at: usize, value: u32) {
+        self.buf[at..at + 4].copy_from_slice(&value.to_le_bytes());
+    }
+    
+    fn patch_u16(&mut self, at: usize, value: u16) {
+        self.buf[at..at + 2].copy_from_slice(&value.to_le_bytes());
+    }
+    
+    /// Point the offset at `at` to the object at `target`
+    fn link(&mut self, at: usize, target: usize) {
+        self.patch_u32(at, (target - at) as u32);
+    }
+    
+    /// Write an object and return where it starts
+    fn object(&mut self, object: Object) -> usize {
+        match object {
+            Object::Table(slots) => self.table(slots),
+            Object::Str(text) => {
+                self.align(4);
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(text.len() as u32).to_le_bytes());
+                self.buf.extend_from_slice(text.as_bytes());
+                self.buf.push(0);
+                start
+            }
+            Object::Tables(tables) => {
+                self.align(4);
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+                let first = self.buf.len();
+                self.buf.resize(first + 4 * tables.len(), 0);
+                for (index, table) in tables.into_iter().enumerate() {
+                    let target = self.object(table);
+                    self.link(first + 4 * index, target);
+                }
+                start
+            }
+            Object::Pairs(pairs) => {
+                // The length sits just before 8-aligned elements
+                self.align(4);
+                if self.buf.len().is_multiple_of(8) {
+                    self.buf.extend_from_slice(&[0; 4]);
+                }
+                let start = self.buf.len();
+                self.buf.extend_from_slice(&(pairs.len() as u32).to_le_bytes());
+                for (a, b) in pairs {
+                    self.buf.extend_from_slice(&a.to_le_bytes());
+                    self.buf.extend_from_slice(&b.to_le_bytes());
+                }
+                start
+            }
+        }
+    }
+    
+    fn table(&mut self, slots: Vec<(u16, Slot)>) -> usize {
+        let fields = slots.iter().map(|(id, _)| *id as usize + 1).max().unwrap_or(0);
+        self.align(2);
+        let vtable = self.buf.len();
+        self.buf.extend_from_slice(&((4 + 2 * fields) as u16).to_le_bytes());
+        self.buf.resize(vtable + 4 + 2 * fields, 0);
+        
+        // Tables start 8-aligned so slot alignment within them is absolute
+        self.align(8);
+        let table = self.buf.len();
+        self.buf.extend_from_slice(&((table - vtable) as i32).to_le_bytes());
+        let mut children = Vec::new();
+        for (id, slot) in slots {
+            self.align(slot.size());
+            let at = self.buf.len();
+            self.patch_u16(vtable + 4 + 2 * id as usize, (at - table) as u16);
+            match slot {
+                Slot::U8(v) => self.buf.push(v),
+                Slot::Bool(v) => self.buf.push(v as u8),
+                Slot::I16(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                Slot::I32(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                Slot::I64(v) => self.buf.extend_from_slice(&v.to_le_bytes()),
+                Slot::Ref(child) => {
+                    self.buf.extend_from_slice(&[0; 4]);
+                    children.push((at, child));
+                }
+            }
+        }
+        let size = self.buf.len() - table;
+        self.patch_u16(vtable + 2, size as u16);
+        
+        for (at, child) in children {
+            let target = self.object(child);
+            self.link(at, target);
+        }
+        table
+    }
+    
+    /// Encode a `Message` table as a whole flatbuffer
+    fn finish(message: Object) -> Vec<u8> {
+        let mut builder = Builder { buf: vec![0; 4] };
+        let root = builder.object(message);
+        builder.link(0, root);
+        builder.align(8);
+        builder.buf
+    }
+}
+
+fn message(header_type: u8, header: Object, body_length: i64) -> Object {
+    Object::Table(vec![
+        (0, Slot::I16(METADATA_V5)),
+        (1, Slot::U8(header_type)),
+        (2, Slot::Ref(header)),
+        (3, Slot::I64(body_length)),
+    ])
+}
+
+/// Append an encapsulated message: continuation marker, metadata length, metadata and body
+fn write_message(out: &mut Vec<u8>, metadata: Vec<u8>, body: &[u8]) {
+    out.extend_from_slice(&CONTINUATION.to_le_bytes());
+    out.extend_from_slice(&(metadata.len() as i32).to_le_bytes());
+    out.extend_from_slice(&metadata);
+    out.extend_from_slice(body);
+}
+
+/// A non-nullable column of 64-bit values
+struct Column {
+    name: &'static str,
+    /// Signed integers when true, doubles otherwise
+    integer: bool,
+    data: Vec<u8>,
+}
+
+impl Column {
+    fn field(&self) -> Object {
+        let (type_type, type_table) = if self.integer {
+            (TYPE_INT, Object::Table(vec![(0, Slot::I32(64)), (1, Slot::Bool(true))]))
+        } else {
+            (TYPE_FLOATING_POINT, Object::Table(vec![(0, Slot::I16(PRECISION_DOUBLE))]))
+        };
+        Object::Table(vec![
+            (0, Slot::Ref(Object::Str(self.name.to_string()))),
+            (1, Slot::Bool(false)),
+            (2, Slot::U8(type_type)),
+            (3, Slot::Ref(type_table)),
+            (5, Slot::Ref(Object::Tables(Vec::new()))),
+        ])
+    }
+}
+
+/// Encode columns of equal length as an Arrow IPC stream with a single record batch
+fn ipc_stream(columns: &[Column], rows: usize) -> Vec<u8> {
+    let mut out = Vec::new();
+    
+    let schema = Object::Table(vec![(1, Slot::Ref(Object::Tables(columns.iter().map(Column::field).collect())))]);
+    write_message(&mut out, Builder::finish(message(HEADER_SCHEMA, schema, 0)), &[]);
+    
+    // Each column has an empty validity buffer (no nulls) and its data buffer
+    let mut body = Vec::new();
+    let mut buffers = Vec::new();
+    for column in columns {
+        buffers.push((body.len() as i64, 0));
+        buffers.push((body.len() as i64, column.data.len() as i64));
+        body.extend_from_slice(&column.data);
+        while !body.len().is_multiple_of(8) {
+            body.push(0);
+        }
+    }
+    let nodes = columns.iter().map(|_| (rows as i64, 0)).collect();
+    let batch = Object::Table(vec![
+        (0, Slot::I64(rows as i64)),
+        (1, Slot::Ref(Object::Pairs(nodes))),
+        (2, Slot::Ref(Object::Pairs(buffers))),
+    ]);
+    write_message(&mut out, Builder::finish(message(HEADER_RECORD_BATCH, batch, body.len() as i64)), &body);
+    
+    // End-of-stream marker
+    out.extend_from_slice(&CONTINUATION.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes());
+    out
+}
+
+#[wasm_bindgen]
+impl HistoryRecorder {
+    /// Get the held samples as Arrow IPC stream bytes, oldest first
+    ///
+    /// The stream holds one record batch with an `int64` `step` column
+    /// and `float64` `population`, `energy`, `mutationRate` and
+    /// `adaptationScore` columns, none of them nullable. It loads with
+    /// `tableFromIPC` in Arrow JS, `pyarrow.ipc.open_stream`,
+    /// `polars.read_ipc_stream` or DuckDB's Arrow readers.
+    #[wasm_bindgen(js_name = exportHistoryArrow)]
+    pub fn export_history_arrow(&self) -> Vec<u8> {
+        let rows = self.length();
+        let mut columns: Vec<Column> = HISTORY_SERIES
+            .iter()
+            .enumerate()
+            .map(|(series, name)| Column { name, integer: series == 0, data: Vec::with_capacity(rows * 8) })
+            .collect();
+        for sample in self.samples() {
+            columns[0].data.extend_from_slice(&(sample.step as i64).to_le_bytes());
+            for (series, column) in columns.iter_mut().enumerate().skip(1) {
+                column.data.extend_from_slice(&sample.value(series).to_le_bytes());
+            }
+        }
+        ipc_stream(&columns, rows)
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Get the recorded history as Arrow IPC stream bytes, as `HistoryRecorder.exportHistoryArrow` does
+    #[wasm_bindgen(js_name = exportHistoryArrow)]
+    pub fn export_history_arrow(&self) -> Result<Vec<u8>, JsValue> {
+        self.history
+            .as_deref()
+            .map(HistoryRecorder::export_history_arrow)
+            .ok_or_else(|| JsValue::from_str("History is not enabled; call enableHistory first"))
+    }
+}
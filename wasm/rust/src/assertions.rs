@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+/// Failures kept before the oldest are dropped
+const FAILURE_CAPACITY: usize = 256;
+
+/// How serious a failed assertion is
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub(crate) enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    fn parse(name: &str) -> Result<Severity, String> {
+        match name {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(format!("Unknown assertion severity: {} (expected info, warning or error)", name)),
+        }
+    }
+    
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// One failed assertion
+struct Failure {
+    seq: u64,
+    severity: Severity,
+    name: &'static str,
+    message: String,
+}
+
+/// Runtime assertion switch, recent failures and the host's failure handler
+#[derive(Default)]
+struct AssertionConfig {
+    // Lowest severity checked; None while assertions are off
+    level: Option<Severity>,
+    failures: VecDeque<Failure>,
+    total_failures: u64,
+    handler: Option<js_sys::Function>,
+}
+
+thread_local! {
+    static ASSERTIONS: RefCell<AssertionConfig> = RefCell::new(AssertionConfig::default());
+}
+
+/// Heap bytes of the recorded failures
+pub(crate) fn buffer_bytes() -> usize {
+    ASSERTIONS.with(|a| {
+        let config = a.borrow();
+        config.failures.capacity() * std::mem::size_of::<Failure>()
+            + config.failures.iter().map(|f| f.message.capacity()).sum::<usize>()
+    })
+}
+
+/// Evaluate an assertion if its severity is switched on and record it when it fails
+///
+/// `holds` and `message` are closures so a switched-off assertion costs a
+/// single comparison.
+pub(crate) fn check(severity: Severity, name: &'static str, holds: impl FnOnce() -> bool, message: impl FnOnce() -> String) {
+    let enabled = ASSERTIONS.with(|a| a.borrow().level.is_some_and(|level| severity >= level));
+    if !enabled || holds() {
+        return;
+    }
+    
+    let message = message();
+    let (handler, seq) = ASSERTIONS.with(|a| {
+        let mut config = a.borrow_mut();
+        let seq = config.total_failures;
+        config.total_failures += 1;
+        config.failures.push_back(Failure { seq, severity, name, message: message.clone() });
+        while config.failures.len() > FAILURE_CAPACITY {
+            config.failures.pop_front();
+        }
+        (config.handler.clone(), seq)
+    });
+    
+    // Called outside the borrow so the handler may call back into Assertions
+    if let Some(handler) = handler {
+        let failure = serde_json::json!({
+            "seq": seq,
+            "severity": severity.as_str(),
+            "name": name,
+            "message": message,
+        });
+        let _ = handler.call1(&JsValue::NULL, &JsValue::from_str(&failure.to_string()));
+    }
+}
+
+/// Assertions are runtime safety checks that release builds can switch on
+///
+/// Checks throughout the engine (finite metrics after a step, sane rule
+/// timings, ...) are off by default and cost almost nothing. `enable(level)`
+/// turns on every check of at least that severity ("info", "warning" or
+/// "error"). Failures never throw; they are kept in a bounded log and passed
+/// to the handler, if one is set, as JSON.
+#[wasm_bindgen]
+pub struct Assertions;
+
+#[wasm_bindgen]
+impl Assertions {
+    /// Check assertions of at least `level` severity
+    #[wasm_bindgen]
+    pub fn enable(level: &str) -> Result<(), JsValue> {
+        let level = Severity::parse(level).map_err(|e| JsValue::from_str(&e))?;
+        ASSERTIONS.with(|a| a.borrow_mut().level = Some(level));
+        Ok(())
+    }
+    
+    /// Stop checking assertions (recorded failures are kept)
+    #[wasm_bindgen]
+    pub fn disable() {
+        ASSERTIONS.with(|a| a.borrow_mut().level = None);
+    }
+    
+    /// Lowest severity being checked (undefined while disabled)
+    #[wasm_bindgen]
+    pub fn level() -> Option<String> {
+        ASSERTIONS.with(|a| a.borrow().level.map(|level| level.as_str().to_string()))
+    }
+    
+    /// Call `handler` with each failure as JSON, e.g. to break in the debugger
+    #[wasm_bindgen(js_name = setHandler)]
+    pub fn set_handler(handler: js_sys::Function) {
+        ASSERTIONS.with(|a| a.borrow_mut().handler = Some(handler));
+    }
+    
+    #[wasm_bindgen(js_name = clearHandler)]
+    pub fn clear_handler() {
+        ASSERTIONS.with(|a| a.borrow_mut().handler = None);
+    }
+    
+    /// Number of failures since the last `clear`, including dropped ones
+    #[wasm_bindgen(js_name = failureCount)]
+    pub fn failure_count() -> u64 {
+        ASSERTIONS.with(|a| a.borrow().total_failures)
+    }
+    
+    /// Get the most recent failures as JSON (`[{ seq, severity, name, message }]`)
+    #[wasm_bindgen]
+    pub fn failures() -> Result<String, JsValue> {
+        let failures: Vec<serde_json::Value> = ASSERTIONS.with(|a| {
+            a.borrow().failures
+                .iter()
+                .map(|f| serde_json::json!({
+                    "seq": f.seq,
+                    "severity": f.severity.as_str(),
+                    "name": f.name,
+                    "message": f.message,
+                }))
+                .collect()
+        });
+        
+        serde_json::to_string(&failures)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize assertion failures: {}", e)))
+    }
+    
+    /// Forget recorded failures
+    #[wasm_bindgen]
+    pub fn clear() {
+        ASSERTIONS.with(|a| {
+            let mut config = a.borrow_mut();
+            config.failures.clear();
+            config.total_failures = 0;
+        });
+    }
+}
@@ -0,0 +1,85 @@
+use wasm_bindgen::prelude::*;
+
+use crate::call_log;
+use crate::OrganismState;
+
+/// Sampled values closer than this (relative to their size) count as one attractor point
+const ATTRACTOR_TOLERANCE: f64 = 1e-6;
+
+/// Collapse samples into the distinct values visited, in ascending order
+fn attractor_points(mut samples: Vec<f64>) -> Vec<f64> {
+    samples.retain(|v| v.is_finite());
+    samples.sort_by(|a, b| a.total_cmp(b));
+    let mut points: Vec<f64> = Vec::new();
+    for value in samples {
+        match points.last() {
+            Some(last) if (value - last).abs() <= ATTRACTOR_TOLERANCE * last.abs().max(1.0) => {}
+            _ => points.push(value),
+        }
+    }
+    points
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Scan a parameter across a range and record the attractor of a metric
+    ///
+    /// For each entry of `values` (typically an evenly spaced range), a clone
+    /// of this state with `param` set to it runs `transient_ticks` steps to
+    /// settle, then `sample_ticks` more while `metric` is sampled. The distinct sampled
+    /// values form the attractor: one point for a steady state, `k` points
+    /// for a period-`k` cycle, many for chaotic or slowly converging runs.
+    /// Returns a JSON array of `{ value, attractor, points }`, ready to plot
+    /// as a bifurcation diagram. This state isn't changed.
+    #[wasm_bindgen(js_name = bifurcationScan)]
+    pub fn bifurcation_scan(
+        &self,
+        param: &str,
+        values: Vec<f64>,
+        transient_ticks: u32,
+        sample_ticks: u32,
+        metric: &str,
+        delta_time: f64,
+    ) -> Result<String, JsValue> {
+        let _call = call_log::enter("bifurcationScan", || serde_json::json!({
+            "param": param,
+            "values": values,
+            "transientTicks": transient_ticks,
+            "sampleTicks": sample_ticks,
+            "metric": metric,
+            "deltaTime": delta_time,
+        }));
+        let param = self.writable_metric(param).map_err(|e| JsValue::from_str(&e))?;
+        if self.read_any_metric(metric).is_none() {
+            return Err(JsValue::from_str(&format!("Unknown metric: {}", metric)));
+        }
+        if sample_ticks == 0 {
+            return Err(JsValue::from_str("A bifurcation scan needs at least one sample tick"));
+        }
+        
+        let mut scan = Vec::with_capacity(values.len());
+        for value in values {
+            let mut state = self.clone();
+            state.write_any_metric(&param, value);
+            for _ in 0..transient_ticks {
+                state.step(delta_time);
+            }
+            let samples: Vec<f64> = (0..sample_ticks)
+                .map(|_| {
+                    state.step(delta_time);
+                    state.read_any_metric(metric).unwrap_or(f64::NAN)
+                })
+                .collect();
+            
+            let attractor = attractor_points(samples);
+            scan.push(serde_json::json!({
+                "value": value,
+                "points": attractor.len(),
+                "attractor": attractor,
+            }));
+        }
+        
+        serde_json::to_string(&scan)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bifurcation scan: {}", e)))
+    }
+}
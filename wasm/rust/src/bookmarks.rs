@@ -0,0 +1,125 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::OrganismState;
+
+/// A named moment of a run
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct Bookmark {
+    name: String,
+    note: String,
+    tick: u64,
+    created_at: f64,
+    state: OrganismState,
+}
+
+/// Bookmarks marks interesting moments of a run so they can be revisited
+///
+/// Each bookmark keeps the tick, a note and a copy of the organism state,
+/// which is small enough to take freely while a run is in progress.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Bookmarks {
+    bookmarks: Vec<Bookmark>,
+}
+
+#[wasm_bindgen]
+impl Bookmarks {
+    /// Create an empty bookmark list
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Bookmarks {
+        Bookmarks::default()
+    }
+    
+    /// Bookmark the current moment, replacing any bookmark with the same name
+    #[wasm_bindgen]
+    pub fn bookmark(&mut self, name: &str, note: &str, state: &OrganismState, tick: u64) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Bookmark name cannot be empty"));
+        }
+        
+        let bookmark = Bookmark {
+            name: name.to_string(),
+            note: note.to_string(),
+            tick,
+            created_at: clock::now_ms(),
+            state: state.clone(),
+        };
+        match self.bookmarks.iter_mut().find(|b| b.name == name) {
+            Some(existing) => *existing = bookmark,
+            None => self.bookmarks.push(bookmark),
+        }
+        Ok(())
+    }
+    
+    /// Restore the state saved in a bookmark and return its tick
+    #[wasm_bindgen]
+    pub fn jump(&self, name: &str, state: &mut OrganismState) -> Result<u64, JsValue> {
+        let bookmark = self.bookmarks.iter().find(|b| b.name == name)
+            .ok_or_else(|| JsValue::from_str(&format!("Bookmark not found: {}", name)))?;
+        
+        *state = bookmark.state.clone();
+        state.sync_state_vector();
+        Ok(bookmark.tick)
+    }
+    
+    /// Update the note of a bookmark
+    #[wasm_bindgen(js_name = setNote)]
+    pub fn set_note(&mut self, name: &str, note: &str) -> Result<(), JsValue> {
+        let bookmark = self.bookmarks.iter_mut().find(|b| b.name == name)
+            .ok_or_else(|| JsValue::from_str(&format!("Bookmark not found: {}", name)))?;
+        bookmark.note = note.to_string();
+        Ok(())
+    }
+    
+    /// Remove a bookmark
+    #[wasm_bindgen]
+    pub fn remove(&mut self, name: &str) -> bool {
+        let before = self.bookmarks.len();
+        self.bookmarks.retain(|b| b.name != name);
+        self.bookmarks.len() != before
+    }
+    
+    /// Number of bookmarks
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.bookmarks.len()
+    }
+    
+    /// List bookmarks ordered by tick as JSON (`name`, `note`, `tick`, `createdAt`)
+    #[wasm_bindgen]
+    pub fn list(&self) -> Result<String, JsValue> {
+        let mut entries: Vec<&Bookmark> = self.bookmarks.iter().collect();
+        entries.sort_by_key(|b| b.tick);
+        
+        let list: Vec<serde_json::Value> = entries
+            .into_iter()
+            .map(|b| serde_json::json!({
+                "name": b.name,
+                "note": b.note,
+                "tick": b.tick,
+                "createdAt": b.created_at,
+            }))
+            .collect();
+        
+        serde_json::to_string(&list)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bookmarks: {}", e)))
+    }
+    
+    /// Get all bookmarks, including their saved states, as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bookmarks: {}", e)))
+    }
+    
+    /// Restore bookmarks from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        *self = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse bookmarks: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lisp, RuleRegistry};
+
+/// Format tag written into every exported bundle
+const BUNDLE_FORMAT: &str = "ouroboros-rule-bundle";
+
+/// Separator between a rule's namespace and its local ID ("metabolism/growth")
+pub(crate) const NAMESPACE_SEPARATOR: char = '/';
+
+/// Get the namespace part of a rule ID (empty for un-namespaced rules)
+pub(crate) fn rule_namespace(id: &str) -> &str {
+    id.split_once(NAMESPACE_SEPARATOR).map_or("", |(namespace, _)| namespace)
+}
+
+/// A dependency of a bundle on another installed bundle
+#[derive(Serialize, Deserialize, Clone)]
+struct BundleDependency {
+    name: String,
+    version: String,
+}
+
+/// A rule as it appears inside a bundle, with its namespace stripped
+#[derive(Serialize, Deserialize, Clone)]
+struct BundleRule {
+    id: String,
+    code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    doc: Option<String>,
+}
+
+/// The shareable bundle format
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RuleBundle {
+    #[serde(default)]
+    format: String,
+    name: String,
+    version: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    author: String,
+    #[serde(default)]
+    dependencies: Vec<BundleDependency>,
+    rules: Vec<BundleRule>,
+}
+
+/// Bookkeeping for a bundle installed into a registry
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct InstalledBundle {
+    name: String,
+    version: String,
+    description: String,
+    author: String,
+    dependencies: Vec<BundleDependency>,
+    rule_ids: Vec<String>,
+}
+
+/// Parse a "major.minor.patch" version (missing parts count as 0)
+fn parse_version(version: &str) -> Result<(u64, u64, u64), String> {
+    let mut parts = [0u64; 3];
+    let pieces: Vec<&str> = version.trim().split('.').collect();
+    
+    if pieces.is_empty() || pieces.len() > 3 {
+        return Err(format!("Invalid version: {}", version));
+    }
+    for (slot, piece) in parts.iter_mut().zip(pieces) {
+        *slot = piece.parse()
+            .map_err(|_| format!("Invalid version: {}", version))?;
+    }
+    
+    Ok((parts[0], parts[1], parts[2]))
+}
+
+/// Check a caret-style requirement: same major version, at least the required version
+fn version_satisfies(installed: &str, required: &str) -> Result<bool, String> {
+    let have = parse_version(installed)?;
+    let want = parse_version(required)?;
+    Ok(have.0 == want.0 && have >= want)
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Install a rule bundle from JSON
+    ///
+    /// Rules are registered under the bundle's namespace ("name/ruleId").
+    /// Every dependency must already be installed with a compatible version
+    /// (same major, at least the requested version). Reinstalling a bundle
+    /// replaces its previous version, removing rules it no longer ships.
+    /// Every rule's code is checked as `registerRule` checks it before
+    /// anything changes, so a bundle with a broken rule installs nothing.
+    /// Returns a JSON summary of the installed rules.
+    #[wasm_bindgen(js_name = installBundle)]
+    pub fn install_bundle(&mut self, json: &str) -> Result<String, JsValue> {
+        let bundle: RuleBundle = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse bundle: {}", e)))?;
+        
+        if !bundle.format.is_empty() && bundle.format != BUNDLE_FORMAT {
+            return Err(JsValue::from_str(&format!("Unsupported bundle format: {}", bundle.format)));
+        }
+        if bundle.name.is_empty() || bundle.name.contains(NAMESPACE_SEPARATOR) {
+            return Err(JsValue::from_str(&format!("Invalid bundle name: {:?}", bundle.name)));
+        }
+        parse_version(&bundle.version).map_err(|e| JsValue::from_str(&e))?;
+        
+        for dependency in &bundle.dependencies {
+            let installed = self.bundles.get(&dependency.name).ok_or_else(|| {
+                JsValue::from_str(&format!(
+                    "Bundle {} requires {}@{}, which is not installed",
+                    bundle.name, dependency.name, dependency.version
+                ))
+            })?;
+            
+            if !version_satisfies(&installed.version, &dependency.version).map_err(|e| JsValue::from_str(&e))? {
+                return Err(JsValue::from_str(&format!(
+                    "Bundle {} requires {}@{}, but {} is installed",
+                    bundle.name, dependency.name, dependency.version, installed.version
+                )));
+            }
+        }
+        
+        let prefix = format!("{}{}", bundle.name, NAMESPACE_SEPARATOR);
+        let mut rule_ids = Vec::with_capacity(bundle.rules.len());
+        for rule in &bundle.rules {
+            let local_id = rule.id.strip_prefix(&prefix).unwrap_or(&rule.id);
+            if local_id.is_empty() {
+                return Err(JsValue::from_str(&format!("Bundle {} contains a rule with an empty ID", bundle.name)));
+            }
+            lisp::check_code(&rule.code).map_err(|e| {
+                JsValue::from_str(&format!("Bundle {} rule {}: {}", bundle.name, local_id, e))
+            })?;
+            rule_ids.push(format!("{}{}", prefix, local_id));
+        }
+        
+        // Drop rules the previous version shipped but this one doesn't
+        if let Some(previous) = self.bundles.remove(&bundle.name) {
+            for old_id in previous.rule_ids.iter().filter(|id| !rule_ids.contains(id)) {
+                self.remove_rule(old_id);
+            }
+        }
+        
+        for (id, rule) in rule_ids.iter().zip(&bundle.rules) {
+            self.register_rule(id, &rule.code, None)?;
+        }
+        
+        let summary = serde_json::json!({
+            "name": bundle.name,
+            "version": bundle.version,
+            "installed": rule_ids,
+        });
+        
+        self.bundles.insert(bundle.name.clone(), InstalledBundle {
+            name: bundle.name,
+            version: bundle.version,
+            description: bundle.description,
+            author: bundle.author,
+            dependencies: bundle.dependencies,
+            rule_ids,
+        });
+        
+        serde_json::to_string(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bundle summary: {}", e)))
+    }
+    
+    /// Export all rules in a namespace as a bundle JSON
+    ///
+    /// Metadata comes from the installed bundle of the same name when there is
+    /// one; otherwise the bundle is exported as version 0.1.0 with no
+    /// dependencies.
+    #[wasm_bindgen(js_name = exportBundle)]
+    pub fn export_bundle(&self, namespace: &str) -> Result<String, JsValue> {
+        let prefix = format!("{}{}", namespace, NAMESPACE_SEPARATOR);
+        let rules: Vec<BundleRule> = self.execution_order
+            .iter()
+            .filter(|id| rule_namespace(id) == namespace)
+            .filter_map(|id| self.rules.get(id))
+            .map(|rule| BundleRule {
+                id: rule.id.strip_prefix(&prefix).unwrap_or(&rule.id).to_string(),
+                code: rule.lisp_code.clone(),
+                doc: rule.doc.clone(),
+            })
+            .collect();
+        
+        if rules.is_empty() {
+            return Err(JsValue::from_str(&format!("No rules found in namespace: {}", namespace)));
+        }
+        
+        let installed = self.bundles.get(namespace);
+        let bundle = RuleBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            name: namespace.to_string(),
+            version: installed.map_or_else(|| "0.1.0".to_string(), |b| b.version.clone()),
+            description: installed.map(|b| b.description.clone()).unwrap_or_default(),
+            author: installed.map(|b| b.author.clone()).unwrap_or_default(),
+            dependencies: installed.map(|b| b.dependencies.clone()).unwrap_or_default(),
+            rules,
+        };
+        
+        serde_json::to_string(&bundle)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export bundle: {}", e)))
+    }
+    
+    /// Remove an installed bundle and its rules
+    ///
+    /// Fails if another installed bundle depends on it.
+    #[wasm_bindgen(js_name = uninstallBundle)]
+    pub fn uninstall_bundle(&mut self, name: &str) -> Result<(), JsValue> {
+        if let Some(dependent) = self.bundles
+            .values()
+            .find(|b| b.dependencies.iter().any(|d| d.name == name))
+        {
+            return Err(JsValue::from_str(&format!("Bundle {} is required by {}", name, dependent.name)));
+        }
+        
+        let bundle = self.bundles.remove(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Bundle not installed: {}", name)))?;
+        for id in &bundle.rule_ids {
+            self.remove_rule(id);
+        }
+        
+        Ok(())
+    }
+    
+    /// Get metadata for all installed bundles as JSON
+    #[wasm_bindgen(js_name = getInstalledBundles)]
+    pub fn get_installed_bundles(&self) -> Result<String, JsValue> {
+        let bundles: Vec<serde_json::Value> = self.bundles
+            .values()
+            .map(|b| serde_json::json!({
+                "name": b.name,
+                "version": b.version,
+                "description": b.description,
+                "author": b.author,
+                "dependencies": b.dependencies,
+                "ruleCount": b.rule_ids.len(),
+            }))
+            .collect();
+        
+        serde_json::to_string(&bundles)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize bundles: {}", e)))
+    }
+}
@@ -0,0 +1,164 @@
+use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use crate::features::{self, Feature};
+use crate::memory;
+
+/// Calls kept by default
+const DEFAULT_CAPACITY: usize = 256;
+
+/// Longest string argument kept; longer ones are cut to bound memory
+const MAX_ARG_CHARS: usize = 4096;
+
+/// One recorded external call
+struct Call {
+    seq: u64,
+    name: &'static str,
+    args: serde_json::Value,
+}
+
+/// Ring buffer of the most recent external calls
+struct CallBuffer {
+    calls: VecDeque<Call>,
+    capacity: usize,
+    next_seq: u64,
+    // Calls in progress; only the outermost is recorded
+    depth: u32,
+}
+
+thread_local! {
+    static CALLS: RefCell<CallBuffer> = const {
+        RefCell::new(CallBuffer {
+            calls: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            next_seq: 0,
+            depth: 0,
+        })
+    };
+}
+
+/// Marks an external call in progress until dropped
+pub(crate) struct CallGuard;
+
+impl Drop for CallGuard {
+    fn drop(&mut self) {
+        CALLS.with(|c| {
+            let mut buffer = c.borrow_mut();
+            buffer.depth = buffer.depth.saturating_sub(1);
+        });
+    }
+}
+
+/// Record an external call with its arguments, unless it is made from inside another recorded call
+///
+/// Keep the returned guard alive for the duration of the call, so calls it
+/// makes internally (a `tick` applying rules, an ensemble stepping states)
+/// aren't recorded on their own and a replay doesn't run them twice.
+pub(crate) fn enter(name: &'static str, args: impl FnOnce() -> serde_json::Value) -> CallGuard {
+    CALLS.with(|c| {
+        let mut buffer = c.borrow_mut();
+        buffer.depth += 1;
+        if buffer.depth > 1 || buffer.capacity == 0 || !features::enabled(Feature::CallLog) {
+            return;
+        }
+        let seq = buffer.next_seq;
+        buffer.next_seq += 1;
+        let args = features::measure(Feature::CallLog, args);
+        buffer.calls.push_back(Call { seq, name, args });
+        while buffer.calls.len() > buffer.capacity {
+            buffer.calls.pop_front();
+        }
+    });
+    CallGuard
+}
+
+/// Estimated heap bytes of the held calls
+pub(crate) fn buffer_bytes() -> usize {
+    CALLS.with(|c| {
+        let buffer = c.borrow();
+        buffer.calls.capacity() * std::mem::size_of::<Call>()
+            + buffer.calls.iter().map(|call| memory::json_bytes(&call.args)).sum::<usize>()
+    })
+}
+
+/// A string argument, cut to `MAX_ARG_CHARS` characters
+pub(crate) fn text(value: &str) -> serde_json::Value {
+    match value.char_indices().nth(MAX_ARG_CHARS) {
+        Some((cut, _)) => serde_json::json!({
+            "truncated": &value[..cut],
+            "bytes": value.len(),
+        }),
+        None => serde_json::Value::from(value),
+    }
+}
+
+/// CallLog keeps the last N external API calls for reproducing bugs
+///
+/// It is always on and bounded: once `capacity` calls are held the oldest
+/// are dropped, and long string arguments (snapshots, rule code) are cut to
+/// 4096 characters. Export it when something goes wrong to replay the
+/// calls that led up to it locally.
+#[wasm_bindgen]
+pub struct CallLog;
+
+#[wasm_bindgen]
+impl CallLog {
+    /// Keep at most `capacity` calls (0 stops recording)
+    #[wasm_bindgen(js_name = setCapacity)]
+    pub fn set_capacity(capacity: usize) {
+        CALLS.with(|c| {
+            let mut buffer = c.borrow_mut();
+            buffer.capacity = capacity;
+            while buffer.calls.len() > capacity {
+                buffer.calls.pop_front();
+            }
+        });
+    }
+    
+    #[wasm_bindgen]
+    pub fn capacity() -> usize {
+        CALLS.with(|c| c.borrow().capacity)
+    }
+    
+    /// Number of calls held
+    #[wasm_bindgen]
+    pub fn length() -> usize {
+        CALLS.with(|c| c.borrow().calls.len())
+    }
+    
+    /// Export the held calls, oldest first, as JSON
+    ///
+    /// Returns `{ format, engineVersion, dropped, calls: [{ seq, call, args }] }`,
+    /// where `dropped` counts older calls no longer held.
+    #[wasm_bindgen]
+    pub fn export() -> Result<String, JsValue> {
+        let exported = CALLS.with(|c| {
+            let buffer = c.borrow();
+            let calls: Vec<serde_json::Value> = buffer.calls
+                .iter()
+                .map(|call| serde_json::json!({ "seq": call.seq, "call": call.name, "args": call.args }))
+                .collect();
+            let held = buffer.calls.front().map_or(buffer.next_seq, |call| call.seq);
+            serde_json::json!({
+                "format": "ouroboros-calls",
+                "engineVersion": env!("CARGO_PKG_VERSION"),
+                "dropped": held,
+                "calls": calls,
+            })
+        });
+        
+        serde_json::to_string(&exported)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export call log: {}", e)))
+    }
+    
+    /// Forget held calls
+    #[wasm_bindgen]
+    pub fn clear() {
+        CALLS.with(|c| {
+            let mut buffer = c.borrow_mut();
+            buffer.calls.clear();
+            buffer.next_seq = 0;
+        });
+    }
+}
@@ -0,0 +1,209 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+use crate::simulation::Simulation;
+use crate::subsystem::{Subsystem, SubsystemContext};
+use crate::{OrganismState, RuleRegistry};
+
+/// Name the random event scheduler runs under as a subsystem
+const SCHEDULER_NAME: &str = "catastrophes";
+
+/// Event kind catastrophes are recorded under in the registry's event log
+const EVENT_KIND: &str = "catastrophe";
+
+fn default_magnitude() -> f64 {
+    0.5
+}
+
+/// A shock to a state, hitting `magnitude` (0 to 1) of what it strikes
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Catastrophe {
+    /// Energy loses `magnitude` of itself
+    Drought,
+    /// Population loses `magnitude` of itself
+    Plague,
+    /// Population and energy both lose `magnitude`
+    Bottleneck,
+}
+
+impl Catastrophe {
+    fn from_name(name: &str) -> Result<Catastrophe, String> {
+        serde_json::from_value(serde_json::json!(name))
+            .map_err(|_| format!("Unknown event kind: {} (expected drought, plague or bottleneck)", name))
+    }
+    
+    /// Apply the shock and describe it for the event log
+    fn strike(self, state: &mut OrganismState, magnitude: f64, scheduled: bool) -> serde_json::Value {
+        let (population, energy) = (state.population(), state.energy());
+        let keep = 1.0 - magnitude;
+        match self {
+            Catastrophe::Drought => state.set_energy(energy * keep),
+            Catastrophe::Plague => state.set_population(population * keep),
+            Catastrophe::Bottleneck => {
+                state.set_population(population * keep);
+                state.set_energy(energy * keep);
+            }
+        }
+        serde_json::json!({
+            "kind": self,
+            "magnitude": magnitude,
+            "scheduled": scheduled,
+            "population": { "before": population, "after": state.population() },
+            "energy": { "before": energy, "after": state.energy() },
+        })
+    }
+}
+
+fn check_magnitude(name: &str, magnitude: f64) -> Result<(), String> {
+    if !magnitude.is_finite() || !(0.0..=1.0).contains(&magnitude) {
+        return Err(format!("{} must be between 0 and 1, got {}", name, magnitude));
+    }
+    Ok(())
+}
+
+/// A random event the scheduler may fire each tick
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ScheduledEvent {
+    kind: Catastrophe,
+    /// Chance of firing per tick
+    probability: f64,
+    #[serde(default = "default_magnitude")]
+    magnitude: f64,
+    /// When given, the magnitude is drawn uniformly between `magnitude` and this
+    #[serde(default)]
+    max_magnitude: Option<f64>,
+}
+
+impl ScheduledEvent {
+    fn check(&self) -> Result<(), String> {
+        check_magnitude("probability", self.probability)?;
+        check_magnitude("magnitude", self.magnitude)?;
+        if let Some(max) = self.max_magnitude {
+            check_magnitude("maxMagnitude", max)?;
+            if max < self.magnitude {
+                return Err(format!("maxMagnitude ({}) is below magnitude ({})", max, self.magnitude));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Fires scheduled events at random before each tick
+#[derive(Clone)]
+struct EventScheduler {
+    events: Vec<ScheduledEvent>,
+    rng: Rng,
+}
+
+impl Subsystem for EventScheduler {
+    fn name(&self) -> &str {
+        SCHEDULER_NAME
+    }
+    
+    fn pre_tick(&mut self, context: &mut SubsystemContext) -> Result<(), String> {
+        for event in &self.events {
+            if self.rng.next_f64() >= event.probability {
+                continue;
+            }
+            let magnitude = match event.max_magnitude {
+                Some(max) => event.magnitude + (max - event.magnitude) * self.rng.next_f64(),
+                None => event.magnitude,
+            };
+            let record = event.kind.strike(context.state, magnitude, true);
+            context.registry.record_catastrophe(context.tick, record);
+        }
+        Ok(())
+    }
+    
+    fn serialize(&self) -> serde_json::Value {
+        serde_json::json!({ "events": self.events, "rng": self.rng })
+    }
+    
+    fn restore(&mut self, value: &serde_json::Value) -> Result<(), String> {
+        if let Some(events) = value.get("events") {
+            let events: Vec<ScheduledEvent> = serde_json::from_value(events.clone()).map_err(|e| e.to_string())?;
+            events.iter().try_for_each(ScheduledEvent::check)?;
+            self.events = events;
+        }
+        if let Some(rng) = value.get("rng") {
+            self.rng = serde_json::from_value(rng.clone()).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+    
+    fn box_clone(&self) -> Box<dyn Subsystem> {
+        Box::new(self.clone())
+    }
+}
+
+impl Simulation {
+    fn strike(&mut self, kind: &str, magnitude: f64) -> Result<String, JsValue> {
+        let catastrophe = Catastrophe::from_name(kind).map_err(|e| JsValue::from_str(&e))?;
+        check_magnitude("Event magnitude", magnitude).map_err(|e| JsValue::from_str(&e))?;
+        let tick = self.ticks();
+        let (state, registry) = self.parts_mut();
+        let record = catastrophe.strike(state, magnitude, false);
+        registry.record_catastrophe(tick, record.clone());
+        Ok(record.to_string())
+    }
+}
+
+impl RuleRegistry {
+    fn record_catastrophe(&mut self, tick: u64, record: serde_json::Value) -> u64 {
+        self.events.record(tick, EVENT_KIND, record)
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Strike the state with a catastrophe now and return the event as JSON
+    ///
+    /// `kind` is `drought` (energy loses `magnitude` of itself), `plague`
+    /// (population does) or `bottleneck` (both do), with `magnitude` from
+    /// 0 to 1. The event is recorded in the registry's event log as a
+    /// `catastrophe` with the values before and after the shock.
+    #[wasm_bindgen(js_name = triggerEvent)]
+    pub fn trigger_event(&mut self, kind: &str, magnitude: f64) -> Result<String, JsValue> {
+        let result = self.strike(kind, magnitude);
+        self.record_input("triggerEvent", || serde_json::json!({ "kind": kind, "magnitude": magnitude }), result.is_ok());
+        result
+    }
+    
+    /// Fire catastrophes at random from now on, replacing any earlier schedule
+    ///
+    /// `json` is an array of `{ kind, probability, magnitude, maxMagnitude }`:
+    /// before every tick each entry fires with `probability`, striking
+    /// with `magnitude` (0.5), or a magnitude drawn uniformly up to
+    /// `maxMagnitude` when that is given. Fired events are recorded as
+    /// by `triggerEvent`, marked `scheduled`. The draws come from an RNG
+    /// seeded with `seed`; the schedule runs as the `catastrophes`
+    /// subsystem.
+    #[wasm_bindgen(js_name = scheduleEvents)]
+    pub fn schedule_events(&mut self, json: &str, seed: u64) -> Result<(), JsValue> {
+        let events: Vec<ScheduledEvent> = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse event schedule: {}", e)))?;
+        for (index, event) in events.iter().enumerate() {
+            event.check().map_err(|e| JsValue::from_str(&format!("Invalid event schedule: {}: {}", index, e)))?;
+        }
+        let args = self.recording.is_some().then(|| serde_json::json!({ "events": events, "seed": seed }));
+        let scheduler = Box::new(EventScheduler { events, rng: Rng::new(seed) });
+        let result = match self.subsystems.iter().position(|s| s.name() == SCHEDULER_NAME) {
+            Some(index) => {
+                self.subsystems[index] = scheduler;
+                Ok(())
+            }
+            None => self.add_subsystem(scheduler).map_err(|e| JsValue::from_str(&e)),
+        };
+        self.record_input("scheduleEvents", || args.unwrap_or_default(), result.is_ok());
+        result
+    }
+    
+    /// Get the registry's events (catastrophes among them) with a sequence number of at least `since_seq`
+    #[wasm_bindgen(js_name = getEvents)]
+    pub fn get_events(&self, since_seq: u64) -> Result<String, JsValue> {
+        self.registry().get_events(since_seq)
+    }
+}
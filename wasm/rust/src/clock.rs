@@ -0,0 +1,138 @@
+use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+
+/// Where the engine reads the time from
+enum ClockSource {
+    /// `Date.now()`
+    System,
+    /// A time set by the host, advanced by `auto_advance_ms` on every read
+    Manual { now_ms: f64, auto_advance_ms: f64 },
+    /// A host function returning milliseconds
+    Host(js_sys::Function),
+}
+
+thread_local! {
+    static CLOCK: RefCell<ClockSource> = const { RefCell::new(ClockSource::System) };
+}
+
+/// Current time in milliseconds from the active clock
+///
+/// Every time source in the engine (rule timing, creation timestamps,
+/// archives, bookmarks) reads through here.
+pub(crate) fn now_ms() -> f64 {
+    let host = CLOCK.with(|c| match &mut *c.borrow_mut() {
+        ClockSource::System => Err(js_sys::Date::now()),
+        ClockSource::Manual { now_ms, auto_advance_ms } => {
+            let now = *now_ms;
+            *now_ms += *auto_advance_ms;
+            Err(now)
+        }
+        ClockSource::Host(function) => Ok(function.clone()),
+    });
+    
+    // The host function is called outside the borrow so it may use Clock itself
+    match host {
+        Err(now) => now,
+        Ok(function) => function
+            .call0(&JsValue::NULL)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or_else(js_sys::Date::now),
+    }
+}
+
+/// The manual clock's time and auto-advance step, if the manual clock is active
+pub(crate) fn manual_state() -> Option<(f64, f64)> {
+    CLOCK.with(|c| match &*c.borrow() {
+        ClockSource::Manual { now_ms, auto_advance_ms } => Some((*now_ms, *auto_advance_ms)),
+        _ => None,
+    })
+}
+
+/// Switch to a manual clock at `now_ms`, advancing `auto_advance_ms` per read
+pub(crate) fn use_manual_state(now_ms: f64, auto_advance_ms: f64) {
+    CLOCK.with(|c| *c.borrow_mut() = ClockSource::Manual { now_ms, auto_advance_ms });
+}
+
+/// Clock controls the time the engine sees
+///
+/// By default the engine uses `Date.now()`. Tests and hosts can switch to
+/// a manual clock they set and advance themselves, which makes rule timing
+/// statistics and timestamps deterministic, or supply their own time
+/// function (a game clock, `performance.now`, ...).
+#[wasm_bindgen]
+pub struct Clock;
+
+#[wasm_bindgen]
+impl Clock {
+    /// Use `Date.now()` again
+    #[wasm_bindgen(js_name = useSystem)]
+    pub fn use_system() {
+        CLOCK.with(|c| *c.borrow_mut() = ClockSource::System);
+    }
+    
+    /// Use a manual clock starting at `start_ms`
+    ///
+    /// Time only moves through `setTime`, `advance` and the auto-advance
+    /// step, which is added after every read (0 by default, so timed rule
+    /// executions take 0 ms).
+    #[wasm_bindgen(js_name = useManual)]
+    pub fn use_manual(start_ms: f64) {
+        CLOCK.with(|c| *c.borrow_mut() = ClockSource::Manual { now_ms: start_ms, auto_advance_ms: 0.0 });
+    }
+    
+    /// Use a host function returning the time in milliseconds
+    ///
+    /// If it throws or returns a non-number, `Date.now()` is used for that read.
+    #[wasm_bindgen(js_name = useHost)]
+    pub fn use_host(now: js_sys::Function) {
+        CLOCK.with(|c| *c.borrow_mut() = ClockSource::Host(now));
+    }
+    
+    /// Set the manual clock's time
+    #[wasm_bindgen(js_name = setTime)]
+    pub fn set_time(ms: f64) -> Result<(), JsValue> {
+        Clock::with_manual(|now_ms, _| *now_ms = ms)
+    }
+    
+    /// Move the manual clock forward
+    #[wasm_bindgen]
+    pub fn advance(ms: f64) -> Result<(), JsValue> {
+        Clock::with_manual(|now_ms, _| *now_ms += ms)
+    }
+    
+    /// Set how far the manual clock moves after every read
+    #[wasm_bindgen(js_name = setAutoAdvance)]
+    pub fn set_auto_advance(ms: f64) -> Result<(), JsValue> {
+        Clock::with_manual(|_, auto_advance_ms| *auto_advance_ms = ms)
+    }
+    
+    /// Read the current time (this counts as a read for auto-advance)
+    #[wasm_bindgen]
+    pub fn now() -> f64 {
+        now_ms()
+    }
+    
+    /// Name of the active clock: "system", "manual" or "host"
+    #[wasm_bindgen]
+    pub fn source() -> String {
+        CLOCK.with(|c| match &*c.borrow() {
+            ClockSource::System => "system",
+            ClockSource::Manual { .. } => "manual",
+            ClockSource::Host(_) => "host",
+        })
+        .to_string()
+    }
+}
+
+impl Clock {
+    fn with_manual(update: impl FnOnce(&mut f64, &mut f64)) -> Result<(), JsValue> {
+        CLOCK.with(|c| match &mut *c.borrow_mut() {
+            ClockSource::Manual { now_ms, auto_advance_ms } => {
+                update(now_ms, auto_advance_ms);
+                Ok(())
+            }
+            _ => Err(JsValue::from_str("The manual clock is not in use (call Clock.useManual first)")),
+        })
+    }
+}
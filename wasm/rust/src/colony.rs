@@ -0,0 +1,366 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::OrganismState;
+
+/// A member of a colony with its last reported individual fitness
+#[derive(Serialize, Deserialize, Clone)]
+struct ColonyMember {
+    id: u32,
+    fitness: f64,
+    energy_contributed: f64,
+}
+
+/// A colony groups organisms around a shared energy pool
+#[derive(Serialize, Deserialize, Clone)]
+struct Colony {
+    id: u32,
+    name: String,
+    members: Vec<ColonyMember>,
+    energy_pool: f64,
+}
+
+impl Colony {
+    fn mean_fitness(&self) -> f64 {
+        if self.members.is_empty() {
+            return 0.0;
+        }
+        self.members.iter().map(|m| m.fitness).sum::<f64>() / self.members.len() as f64
+    }
+    
+    /// Cohesion in (0, 1]: 1 when members are equally fit, lower as fitness spreads out
+    fn cohesion(&self) -> f64 {
+        if self.members.len() < 2 {
+            return if self.members.is_empty() { 0.0 } else { 1.0 };
+        }
+        
+        let mean = self.mean_fitness();
+        let variance = self.members
+            .iter()
+            .map(|m| (m.fitness - mean).powi(2))
+            .sum::<f64>() / self.members.len() as f64;
+        let spread = if mean.abs() > f64::EPSILON {
+            variance.sqrt() / mean.abs()
+        } else {
+            variance.sqrt()
+        };
+        
+        1.0 / (1.0 + spread)
+    }
+    
+    /// Group-level fitness used when selection acts on colonies
+    fn group_fitness(&self) -> f64 {
+        self.mean_fitness() * self.cohesion()
+    }
+    
+    fn metrics(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "name": self.name,
+            "size": self.members.len(),
+            "energyPool": self.energy_pool,
+            "energyPerMember": if self.members.is_empty() { 0.0 } else { self.energy_pool / self.members.len() as f64 },
+            "meanFitness": self.mean_fitness(),
+            "cohesion": self.cohesion(),
+            "groupFitness": self.group_fitness(),
+        })
+    }
+}
+
+/// ColonySystem manages colony membership, shared energy pools and group metrics
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ColonySystem {
+    colonies: BTreeMap<u32, Colony>,
+    membership: HashMap<u32, u32>,
+    next_colony_id: u32,
+    
+    // Group selection settings
+    group_selection: bool,
+    group_weight: f64,
+}
+
+#[wasm_bindgen]
+impl ColonySystem {
+    /// Create an empty colony system
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ColonySystem {
+        ColonySystem {
+            colonies: BTreeMap::new(),
+            membership: HashMap::new(),
+            next_colony_id: 1,
+            group_selection: false,
+            group_weight: 0.5,
+        }
+    }
+    
+    /// Create a new colony and return its ID
+    #[wasm_bindgen(js_name = createColony)]
+    pub fn create_colony(&mut self, name: &str) -> u32 {
+        let id = self.next_colony_id;
+        self.next_colony_id += 1;
+        
+        self.colonies.insert(id, Colony {
+            id,
+            name: name.to_string(),
+            members: Vec::new(),
+            energy_pool: 0.0,
+        });
+        
+        id
+    }
+    
+    /// Dissolve a colony, releasing its members; returns the energy left in its pool
+    #[wasm_bindgen(js_name = dissolveColony)]
+    pub fn dissolve_colony(&mut self, colony_id: u32) -> Result<f64, JsValue> {
+        let colony = self.colonies.remove(&colony_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Colony not found: {}", colony_id)))?;
+        
+        for member in &colony.members {
+            self.membership.remove(&member.id);
+        }
+        
+        Ok(colony.energy_pool)
+    }
+    
+    /// Add an organism to a colony, moving it out of any colony it already belongs to
+    #[wasm_bindgen]
+    pub fn join(&mut self, colony_id: u32, member_id: u32, fitness: f64) -> Result<(), JsValue> {
+        if !self.colonies.contains_key(&colony_id) {
+            return Err(JsValue::from_str(&format!("Colony not found: {}", colony_id)));
+        }
+        
+        self.leave(member_id);
+        
+        if let Some(colony) = self.colonies.get_mut(&colony_id) {
+            colony.members.push(ColonyMember {
+                id: member_id,
+                fitness,
+                energy_contributed: 0.0,
+            });
+        }
+        self.membership.insert(member_id, colony_id);
+        
+        Ok(())
+    }
+    
+    /// Remove an organism from its colony
+    #[wasm_bindgen]
+    pub fn leave(&mut self, member_id: u32) -> bool {
+        match self.membership.remove(&member_id) {
+            Some(colony_id) => {
+                if let Some(colony) = self.colonies.get_mut(&colony_id) {
+                    colony.members.retain(|m| m.id != member_id);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Get the colony an organism belongs to (undefined when unaffiliated)
+    #[wasm_bindgen(js_name = colonyOf)]
+    pub fn colony_of(&self, member_id: u32) -> Option<u32> {
+        self.membership.get(&member_id).copied()
+    }
+    
+    /// Report a member's latest individual fitness
+    #[wasm_bindgen(js_name = updateMember)]
+    pub fn update_member(&mut self, member_id: u32, fitness: f64) -> Result<(), JsValue> {
+        let member = self.member_mut(member_id)?;
+        member.fitness = fitness;
+        Ok(())
+    }
+    
+    /// Add energy to a colony's shared pool
+    #[wasm_bindgen]
+    pub fn deposit(&mut self, colony_id: u32, amount: f64) -> Result<(), JsValue> {
+        let colony = self.colony_mut(colony_id)?;
+        colony.energy_pool += amount.max(0.0);
+        Ok(())
+    }
+    
+    /// Take up to `amount` energy from a colony's pool, returning what was withdrawn
+    #[wasm_bindgen]
+    pub fn withdraw(&mut self, colony_id: u32, amount: f64) -> Result<f64, JsValue> {
+        let colony = self.colony_mut(colony_id)?;
+        let taken = amount.max(0.0).min(colony.energy_pool);
+        colony.energy_pool -= taken;
+        Ok(taken)
+    }
+    
+    /// Move a fraction of an organism's energy into its colony's pool
+    #[wasm_bindgen]
+    pub fn contribute(&mut self, member_id: u32, state: &mut OrganismState, fraction: f64) -> Result<f64, JsValue> {
+        let amount = state.energy() * fraction.clamp(0.0, 1.0);
+        let colony_id = self.colony_of(member_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism {} is not in a colony", member_id)))?;
+        
+        self.deposit(colony_id, amount)?;
+        self.member_mut(member_id)?.energy_contributed += amount;
+        state.set_energy(state.energy() - amount);
+        
+        Ok(amount)
+    }
+    
+    /// Give an organism its equal share of the colony pool (at most `max_amount`)
+    #[wasm_bindgen(js_name = drawShare)]
+    pub fn draw_share(&mut self, member_id: u32, state: &mut OrganismState, max_amount: f64) -> Result<f64, JsValue> {
+        let colony_id = self.colony_of(member_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism {} is not in a colony", member_id)))?;
+        let colony = self.colony_mut(colony_id)?;
+        
+        let share = colony.energy_pool / colony.members.len().max(1) as f64;
+        let taken = share.min(max_amount.max(0.0));
+        colony.energy_pool -= taken;
+        state.set_energy(state.energy() + taken);
+        
+        Ok(taken)
+    }
+    
+    /// Get the number of colonies
+    #[wasm_bindgen(js_name = getColonyCount)]
+    pub fn get_colony_count(&self) -> usize {
+        self.colonies.len()
+    }
+    
+    /// Get group-level metrics for a colony as JSON
+    #[wasm_bindgen(js_name = getColonyMetrics)]
+    pub fn get_colony_metrics(&self, colony_id: u32) -> Result<String, JsValue> {
+        let colony = self.colonies.get(&colony_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Colony not found: {}", colony_id)))?;
+        
+        serde_json::to_string(&colony.metrics())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize colony metrics: {}", e)))
+    }
+    
+    /// Get group-level metrics for every colony as JSON
+    #[wasm_bindgen(js_name = getAllColonyMetrics)]
+    pub fn get_all_colony_metrics(&self) -> Result<String, JsValue> {
+        let all: Vec<serde_json::Value> = self.colonies.values().map(Colony::metrics).collect();
+        
+        serde_json::to_string(&all)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize colony metrics: {}", e)))
+    }
+    
+    #[wasm_bindgen(getter, js_name = groupSelection)]
+    pub fn group_selection(&self) -> bool {
+        self.group_selection
+    }
+    
+    /// Enable or disable selection acting at the group level
+    #[wasm_bindgen(setter, js_name = groupSelection)]
+    pub fn set_group_selection(&mut self, enabled: bool) {
+        self.group_selection = enabled;
+    }
+    
+    #[wasm_bindgen(getter, js_name = groupWeight)]
+    pub fn group_weight(&self) -> f64 {
+        self.group_weight
+    }
+    
+    /// Set how much of a member's effective fitness comes from its colony (0 to 1)
+    #[wasm_bindgen(setter, js_name = groupWeight)]
+    pub fn set_group_weight(&mut self, value: f64) {
+        self.group_weight = value.clamp(0.0, 1.0);
+    }
+    
+    /// Fitness selection should use for an organism
+    ///
+    /// With group selection disabled this is the individual fitness. Otherwise
+    /// it blends individual and colony fitness according to `groupWeight`.
+    /// Unaffiliated organisms only count their individual fitness.
+    #[wasm_bindgen(js_name = effectiveFitness)]
+    pub fn effective_fitness(&self, member_id: u32, individual_fitness: f64) -> f64 {
+        if !self.group_selection {
+            return individual_fitness;
+        }
+        
+        match self.membership.get(&member_id).and_then(|id| self.colonies.get(id)) {
+            Some(colony) => {
+                (1.0 - self.group_weight) * individual_fitness + self.group_weight * colony.group_fitness()
+            }
+            None => individual_fitness,
+        }
+    }
+    
+    /// Apply selection at the group level, dissolving the least fit colonies
+    ///
+    /// Keeps the top `survival_fraction` of colonies ranked by group fitness
+    /// (at least one survives while any exist) and returns the IDs of the
+    /// dissolved colonies as JSON.
+    #[wasm_bindgen(js_name = selectColonies)]
+    pub fn select_colonies(&mut self, survival_fraction: f64) -> Result<String, JsValue> {
+        let mut ranked: Vec<(u32, f64)> = self.colonies
+            .values()
+            .map(|colony| (colony.id, colony.group_fitness()))
+            .collect();
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        
+        let keep = ((ranked.len() as f64) * survival_fraction.clamp(0.0, 1.0)).ceil() as usize;
+        let keep = keep.max(1).min(ranked.len());
+        
+        let dissolved: Vec<u32> = ranked.iter().skip(keep).map(|(id, _)| *id).collect();
+        for id in &dissolved {
+            self.dissolve_colony(*id)?;
+        }
+        
+        serde_json::to_string(&dissolved)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize selection result: {}", e)))
+    }
+    
+    /// Estimated heap bytes held by colonies, members and memberships
+    #[wasm_bindgen(js_name = memoryBytes)]
+    pub fn memory_bytes(&self) -> usize {
+        let colonies: usize = self.colonies
+            .values()
+            .map(|colony| {
+                std::mem::size_of::<(u32, Colony)>()
+                    + colony.name.capacity()
+                    + colony.members.capacity() * std::mem::size_of::<ColonyMember>()
+            })
+            .sum();
+        colonies + self.membership.capacity() * std::mem::size_of::<(u32, u32)>()
+    }
+    
+    /// Get a snapshot of all colonies as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize colonies: {}", e)))
+    }
+    
+    /// Restore colonies from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: ColonySystem = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse colony snapshot: {}", e)))?;
+        
+        *self = loaded;
+        Ok(())
+    }
+}
+
+impl ColonySystem {
+    fn colony_mut(&mut self, colony_id: u32) -> Result<&mut Colony, JsValue> {
+        self.colonies.get_mut(&colony_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Colony not found: {}", colony_id)))
+    }
+    
+    fn member_mut(&mut self, member_id: u32) -> Result<&mut ColonyMember, JsValue> {
+        let colony_id = *self.membership.get(&member_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism {} is not in a colony", member_id)))?;
+        
+        self.colonies
+            .get_mut(&colony_id)
+            .and_then(|colony| colony.members.iter_mut().find(|m| m.id == member_id))
+            .ok_or_else(|| JsValue::from_str(&format!("Organism {} is not in a colony", member_id)))
+    }
+}
+
+impl Default for ColonySystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,350 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::dynamics::check_custom_metric_name;
+use crate::import_guard;
+use crate::solver::SolverKind;
+use crate::validation::{self, Problem};
+use crate::OrganismState;
+
+/// Highest energy a state can hold (the clamp applied by `step`)
+const MAX_ENERGY: f64 = 10000.0;
+
+/// Keys `fromJson` understands, in both camelCase and the snake_case of snapshots
+const CONFIG_KEYS: [&str; 12] = [
+    "population", "energy", "generation", "age",
+    "mutationRate", "mutation_rate", "selectionPressure", "selection_pressure",
+    "customMetrics", "custom_metrics", "solver", "core_solver",
+];
+
+/// Check a value for one of the core metrics a config or patch may set
+///
+/// Population must be at least 0, energy between 0 and 10000, rates
+/// between 0 and 1. `name` is the camelCase metric name.
+pub(crate) fn check_core_value(name: &str, value: f64) -> Result<(), String> {
+    let (min, max) = match name {
+        "population" => (0.0, f64::INFINITY),
+        "energy" => (0.0, MAX_ENERGY),
+        "mutationRate" | "selectionPressure" => (0.0, 1.0),
+        _ => return Err(format!("Unknown metric: {}", name)),
+    };
+    if !value.is_finite() || value < min || value > max {
+        return Err(if max.is_finite() {
+            format!("{} must be between {} and {} (got {})", name, min, max, value)
+        } else {
+            format!("{} must be at least {} (got {})", name, min, value)
+        });
+    }
+    Ok(())
+}
+
+/// What `fromJson` does with keys it doesn't understand
+#[derive(Clone, Copy, PartialEq)]
+enum UnknownFields {
+    Reject,
+    Warn,
+    Ignore,
+}
+
+impl UnknownFields {
+    fn parse(name: &str) -> Result<UnknownFields, String> {
+        match name {
+            "reject" => Ok(UnknownFields::Reject),
+            "warn" => Ok(UnknownFields::Warn),
+            "ignore" => Ok(UnknownFields::Ignore),
+            _ => Err(format!("Unknown field policy: {} (expected reject, warn or ignore)", name)),
+        }
+    }
+}
+
+/// Config as written in JSON; absent fields keep their defaults
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfigSpec {
+    population: Option<f64>,
+    energy: Option<f64>,
+    generation: Option<u64>,
+    age: Option<u64>,
+    #[serde(alias = "mutation_rate")]
+    mutation_rate: Option<f64>,
+    #[serde(alias = "selection_pressure")]
+    selection_pressure: Option<f64>,
+    #[serde(alias = "custom_metrics")]
+    custom_metrics: Option<BTreeMap<String, f64>>,
+    #[serde(alias = "core_solver")]
+    solver: Option<String>,
+}
+
+/// OrganismConfig describes a starting state and validates it before building
+///
+/// Start from the defaults of `new OrganismState()`, override values with
+/// the `with*` methods or read them with `fromJson`, then `build()`. Values
+/// are only checked by `validate` and `build`, which report every problem
+/// at once: population must be at least 0, energy between 0 and 10000,
+/// rates between 0 and 1, and custom metric names valid.
+#[wasm_bindgen]
+#[derive(Clone)]
+pub struct OrganismConfig {
+    population: f64,
+    energy: f64,
+    generation: u64,
+    age: u64,
+    mutation_rate: f64,
+    selection_pressure: f64,
+    custom_metrics: BTreeMap<String, f64>,
+    solver: String,
+    warnings: Vec<String>,
+}
+
+impl OrganismConfig {
+    /// Read a config from JSON text, applying the import guard and `unknown` policy
+    pub(crate) fn parse(json: &str, unknown: &str, strict: bool) -> Result<OrganismConfig, String> {
+        let mut problems = Vec::new();
+        let config = OrganismConfig::read(json, unknown, strict, &mut problems)?;
+        if !problems.is_empty() {
+            return Err(validation::summarize(&problems));
+        }
+        Ok(config)
+    }
+    
+    /// Read a config, noting a problem for each bad field and keeping the default for it
+    ///
+    /// Only input that can't be read as a JSON object at all is an error.
+    fn read(json: &str, unknown: &str, strict: bool, problems: &mut Vec<Problem>) -> Result<OrganismConfig, String> {
+        let unknown = UnknownFields::parse(unknown)?;
+        import_guard::check_structure("Config", json, strict)?;
+        let mut value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let object = value.as_object_mut().ok_or("Config must be a JSON object")?;
+        
+        let mut config = OrganismConfig::new();
+        for key in object.keys().filter(|key| !CONFIG_KEYS.contains(&key.as_str())) {
+            match unknown {
+                UnknownFields::Reject => problems.push(Problem::new(key, "Unknown config field")),
+                UnknownFields::Warn => config.warnings.push(format!("Ignored unknown config field: {}", key)),
+                UnknownFields::Ignore => {}
+            }
+        }
+        object.retain(|key, _| CONFIG_KEYS.contains(&key.as_str()));
+        // JSON.stringify writes NaN and Infinity as null
+        if strict {
+            for (key, _) in object.iter().filter(|(_, v)| v.is_null()) {
+                problems.push(Problem::new(key, "null (NaN or Infinity in the source)"));
+            }
+        }
+        for key in ["customMetrics", "custom_metrics"] {
+            if let Some(custom) = object.get_mut(key).and_then(|v| v.as_object_mut()) {
+                custom.retain(|name, v| match v {
+                    serde_json::Value::Number(_) => true,
+                    serde_json::Value::Null if !strict => false,
+                    _ => {
+                        problems.push(Problem::new(&format!("{}.{}", key, name), format!("expected a number, got {}", v)));
+                        false
+                    }
+                });
+            }
+        }
+        // Check fields one at a time so each bad one is reported and the rest still apply
+        object.retain(|key, v| {
+            let single = serde_json::json!({ key.as_str(): v });
+            match serde_json::from_value::<ConfigSpec>(single) {
+                Ok(_) => !v.is_null(),
+                Err(e) => {
+                    problems.push(Problem::new(key, e.to_string()));
+                    false
+                }
+            }
+        });
+        
+        let spec: ConfigSpec = serde_json::from_value(value).map_err(|e| e.to_string())?;
+        if let Some(v) = spec.population { config.population = v; }
+        if let Some(v) = spec.energy { config.energy = v; }
+        if let Some(v) = spec.generation { config.generation = v; }
+        if let Some(v) = spec.age { config.age = v; }
+        if let Some(v) = spec.mutation_rate { config.mutation_rate = v; }
+        if let Some(v) = spec.selection_pressure { config.selection_pressure = v; }
+        if let Some(v) = spec.custom_metrics { config.custom_metrics = v; }
+        if let Some(v) = spec.solver { config.solver = v; }
+        Ok(config)
+    }
+    
+    fn errors(&self) -> Vec<Problem> {
+        let mut errors = Vec::new();
+        let checks = [
+            ("population", self.population),
+            ("energy", self.energy),
+            ("mutationRate", self.mutation_rate),
+            ("selectionPressure", self.selection_pressure),
+        ];
+        for (name, value) in checks {
+            if let Err(e) = check_core_value(name, value) {
+                errors.push(Problem::new(name, e));
+            }
+        }
+        
+        for (name, value) in &self.custom_metrics {
+            let path = format!("customMetrics.{}", name);
+            if let Err(e) = check_custom_metric_name(name) {
+                errors.push(Problem::new(&path, e));
+            } else if !value.is_finite() {
+                errors.push(Problem::new(&path, format!("Custom metric {} must be a finite number (got {})", name, value)));
+            }
+        }
+        if let Err(e) = SolverKind::parse(&self.solver) {
+            errors.push(Problem::new("solver", e));
+        }
+        errors
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismConfig {
+    /// Create a config with the defaults of a new state
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> OrganismConfig {
+        let defaults = OrganismState::new();
+        OrganismConfig {
+            population: defaults.population,
+            energy: defaults.energy,
+            generation: defaults.generation,
+            age: defaults.age,
+            mutation_rate: defaults.mutation_rate,
+            selection_pressure: defaults.selection_pressure,
+            custom_metrics: BTreeMap::new(),
+            solver: defaults.core_solver.as_str().to_string(),
+            warnings: Vec::new(),
+        }
+    }
+    
+    /// Read a config from JSON
+    ///
+    /// Fields may be camelCase or snake_case and absent ones keep their
+    /// defaults. `unknown_fields` decides what other keys do: "reject"
+    /// throws, "warn" records them in `warnings`, "ignore" drops them.
+    /// The error names every field that couldn't be read, not just the first.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str, unknown_fields: &str) -> Result<OrganismConfig, JsValue> {
+        OrganismConfig::parse(json, unknown_fields, false)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse config: {}", e)))
+    }
+    
+    #[wasm_bindgen(js_name = withPopulation)]
+    pub fn with_population(mut self, value: f64) -> OrganismConfig {
+        self.population = value;
+        self
+    }
+    
+    #[wasm_bindgen(js_name = withEnergy)]
+    pub fn with_energy(mut self, value: f64) -> OrganismConfig {
+        self.energy = value;
+        self
+    }
+    
+    #[wasm_bindgen(js_name = withGeneration)]
+    pub fn with_generation(mut self, value: u64) -> OrganismConfig {
+        self.generation = value;
+        self
+    }
+    
+    #[wasm_bindgen(js_name = withAge)]
+    pub fn with_age(mut self, value: u64) -> OrganismConfig {
+        self.age = value;
+        self
+    }
+    
+    #[wasm_bindgen(js_name = withMutationRate)]
+    pub fn with_mutation_rate(mut self, value: f64) -> OrganismConfig {
+        self.mutation_rate = value;
+        self
+    }
+    
+    #[wasm_bindgen(js_name = withSelectionPressure)]
+    pub fn with_selection_pressure(mut self, value: f64) -> OrganismConfig {
+        self.selection_pressure = value;
+        self
+    }
+    
+    /// Add a custom metric with its initial value
+    #[wasm_bindgen(js_name = withCustomMetric)]
+    pub fn with_custom_metric(mut self, name: &str, value: f64) -> OrganismConfig {
+        self.custom_metrics.insert(name.to_string(), value);
+        self
+    }
+    
+    /// Set the solver of the core population and energy dynamics
+    #[wasm_bindgen(js_name = withSolver)]
+    pub fn with_solver(mut self, solver: &str) -> OrganismConfig {
+        self.solver = solver.to_string();
+        self
+    }
+    
+    /// Every problem with the config (empty when it is valid)
+    #[wasm_bindgen]
+    pub fn validate(&self) -> Vec<String> {
+        self.errors().into_iter().map(|problem| problem.message).collect()
+    }
+    
+    /// Check config JSON without building, returning every problem found
+    ///
+    /// Returns a JSON array of `{ path, message }` (empty when the config
+    /// is valid) covering fields `fromJson` can't read, unknown fields
+    /// under the "reject" policy and the checks of `validate`.
+    #[wasm_bindgen(js_name = checkJson)]
+    pub fn check_json(json: &str, unknown_fields: &str) -> Result<String, JsValue> {
+        let mut problems = Vec::new();
+        match OrganismConfig::read(json, unknown_fields, false, &mut problems) {
+            Ok(config) => problems.extend(config.errors()),
+            Err(e) => problems.push(Problem::new("", e)),
+        }
+        validation::to_json(&problems)
+    }
+    
+    /// Unknown fields noted by `fromJson` with the "warn" policy
+    #[wasm_bindgen(getter)]
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+    
+    /// Build a state, throwing with every problem when the config is invalid
+    #[wasm_bindgen]
+    pub fn build(&self) -> Result<OrganismState, JsValue> {
+        let errors = self.validate();
+        if !errors.is_empty() {
+            return Err(JsValue::from_str(&format!("Invalid organism config: {}", errors.join("; "))));
+        }
+        
+        let mut state = OrganismState::new();
+        state.population = self.population;
+        state.energy = self.energy;
+        state.generation = self.generation;
+        state.age = self.age;
+        state.mutation_rate = self.mutation_rate;
+        state.selection_pressure = self.selection_pressure;
+        state.custom_metrics = self.custom_metrics.clone();
+        state.core_solver = SolverKind::parse(&self.solver).map_err(|e| JsValue::from_str(&e))?;
+        state.sync_state_vector();
+        Ok(state)
+    }
+    
+    /// Serialize the config as camelCase JSON accepted by `fromJson`
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "population": self.population,
+            "energy": self.energy,
+            "generation": self.generation,
+            "age": self.age,
+            "mutationRate": self.mutation_rate,
+            "selectionPressure": self.selection_pressure,
+            "customMetrics": self.custom_metrics,
+            "solver": self.solver,
+        })
+        .to_string()
+    }
+}
+
+impl Default for OrganismConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
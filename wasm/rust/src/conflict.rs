@@ -0,0 +1,278 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::events::EventLog;
+use crate::rng::Rng;
+use crate::{OrganismState, TerritoryMap};
+
+/// Strength assumed for contestants that never had a trait assigned
+const DEFAULT_STRENGTH: f64 = 1.0;
+
+/// Result of a single contest between two organisms
+struct ContestOutcome {
+    winner: u32,
+    loser: u32,
+    attacker_win_probability: f64,
+    roll: f64,
+}
+
+impl ContestOutcome {
+    fn attacker_won(&self, attacker: u32) -> bool {
+        self.winner == attacker
+    }
+}
+
+/// ConflictResolver settles contests over cells and territory
+///
+/// Outcomes are probabilistic: an attacker with strength `a` beats a defender
+/// with strength `b` with probability `a^k / (a^k + b^k)`, where `k` is the
+/// decisiveness. Rolls come from the resolver's seeded RNG and every contest
+/// is recorded in its event log.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ConflictResolver {
+    rng: Rng,
+    events: EventLog,
+    strengths: HashMap<u32, f64>,
+    tick: u64,
+    
+    // Contest parameters
+    decisiveness: f64,
+    winner_cost: f64,
+    loser_cost: f64,
+}
+
+#[wasm_bindgen]
+impl ConflictResolver {
+    /// Create a resolver with a seeded RNG
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> ConflictResolver {
+        ConflictResolver {
+            rng: Rng::new(seed),
+            events: EventLog::new(),
+            strengths: HashMap::new(),
+            tick: 0,
+            decisiveness: 2.0,
+            winner_cost: 0.05,
+            loser_cost: 0.15,
+        }
+    }
+    
+    /// Restart the contest RNG from `seed`; strengths and the event log are kept
+    #[wasm_bindgen(js_name = setSeed)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    
+    /// Set the strength trait of a contestant
+    #[wasm_bindgen(js_name = setStrength)]
+    pub fn set_strength(&mut self, id: u32, strength: f64) {
+        self.strengths.insert(id, strength.max(0.0));
+    }
+    
+    /// Get the strength trait of a contestant
+    #[wasm_bindgen(js_name = getStrength)]
+    pub fn get_strength(&self, id: u32) -> f64 {
+        self.strengths.get(&id).copied().unwrap_or(DEFAULT_STRENGTH)
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    
+    /// Set the tick stamped on recorded contest events
+    #[wasm_bindgen(setter)]
+    pub fn set_tick(&mut self, value: u64) {
+        self.tick = value;
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn decisiveness(&self) -> f64 {
+        self.decisiveness
+    }
+    
+    /// Set how strongly strength differences decide outcomes (0 = coin flip)
+    #[wasm_bindgen(setter)]
+    pub fn set_decisiveness(&mut self, value: f64) {
+        self.decisiveness = value.max(0.0);
+    }
+    
+    #[wasm_bindgen(getter, js_name = winnerCost)]
+    pub fn winner_cost(&self) -> f64 {
+        self.winner_cost
+    }
+    
+    /// Set the fraction of energy the winner spends on a contest
+    #[wasm_bindgen(setter, js_name = winnerCost)]
+    pub fn set_winner_cost(&mut self, value: f64) {
+        self.winner_cost = value.clamp(0.0, 1.0);
+    }
+    
+    #[wasm_bindgen(getter, js_name = loserCost)]
+    pub fn loser_cost(&self) -> f64 {
+        self.loser_cost
+    }
+    
+    /// Set the fraction of energy the loser spends on a contest
+    #[wasm_bindgen(setter, js_name = loserCost)]
+    pub fn set_loser_cost(&mut self, value: f64) {
+        self.loser_cost = value.clamp(0.0, 1.0);
+    }
+    
+    /// Probability that the attacker wins against the defender
+    #[wasm_bindgen(js_name = winProbability)]
+    pub fn win_probability(&self, attacker_id: u32, defender_id: u32) -> f64 {
+        let a = self.get_strength(attacker_id).powf(self.decisiveness);
+        let b = self.get_strength(defender_id).powf(self.decisiveness);
+        
+        if a + b <= 0.0 || !(a + b).is_finite() {
+            0.5
+        } else {
+            a / (a + b)
+        }
+    }
+    
+    /// Resolve a contest between two organisms and return the outcome as JSON
+    ///
+    /// Energy costs are reported as fractions for the host to apply; use
+    /// `contestStates` to have them applied to `OrganismState`s directly.
+    #[wasm_bindgen]
+    pub fn contest(&mut self, attacker_id: u32, defender_id: u32) -> Result<String, JsValue> {
+        let outcome = self.resolve(attacker_id, defender_id);
+        let record = self.outcome_json(attacker_id, &outcome, serde_json::json!({
+            "winnerEnergyCostFraction": self.winner_cost,
+            "loserEnergyCostFraction": self.loser_cost,
+        }));
+        
+        self.events.record(self.tick, "contest", record.clone());
+        to_json(&record)
+    }
+    
+    /// Resolve a contest and charge both organisms' energy
+    #[wasm_bindgen(js_name = contestStates)]
+    pub fn contest_states(
+        &mut self,
+        attacker_id: u32,
+        attacker: &mut OrganismState,
+        defender_id: u32,
+        defender: &mut OrganismState,
+    ) -> Result<String, JsValue> {
+        let outcome = self.resolve(attacker_id, defender_id);
+        let (attacker_fraction, defender_fraction) = if outcome.attacker_won(attacker_id) {
+            (self.winner_cost, self.loser_cost)
+        } else {
+            (self.loser_cost, self.winner_cost)
+        };
+        
+        let attacker_cost = attacker.energy() * attacker_fraction;
+        let defender_cost = defender.energy() * defender_fraction;
+        attacker.set_energy(attacker.energy() - attacker_cost);
+        defender.set_energy(defender.energy() - defender_cost);
+        
+        let record = self.outcome_json(attacker_id, &outcome, serde_json::json!({
+            "attackerEnergyCost": attacker_cost,
+            "defenderEnergyCost": defender_cost,
+        }));
+        
+        self.events.record(self.tick, "contest", record.clone());
+        to_json(&record)
+    }
+    
+    /// Contest a territory cell against its current holder
+    ///
+    /// Unowned cells and cells the attacker already holds are claimed without
+    /// a fight. Otherwise the winner holds the cell with its own strength.
+    #[wasm_bindgen(js_name = contestCell)]
+    pub fn contest_cell(&mut self, map: &mut TerritoryMap, x: u32, y: u32, attacker_id: u32) -> Result<String, JsValue> {
+        let attacker_strength = self.get_strength(attacker_id);
+        
+        let defender_id = match map.owner_at(x, y)? {
+            Some(holder) if holder != attacker_id => holder,
+            _ => {
+                let result = map.claim(x, y, attacker_id, attacker_strength)?;
+                return to_json(&serde_json::json!({
+                    "attacker": attacker_id,
+                    "winner": attacker_id,
+                    "cell": [x, y],
+                    "result": result,
+                }));
+            }
+        };
+        
+        let outcome = self.resolve(attacker_id, defender_id);
+        let holder_strength = self.get_strength(outcome.winner);
+        map.set_holder(x, y, Some(outcome.winner), holder_strength)?;
+        
+        let record = self.outcome_json(attacker_id, &outcome, serde_json::json!({
+            "cell": [x, y],
+            "winnerEnergyCostFraction": self.winner_cost,
+            "loserEnergyCostFraction": self.loser_cost,
+        }));
+        
+        self.events.record(self.tick, "cell_contest", record.clone());
+        to_json(&record)
+    }
+    
+    /// Get recorded contest events with a sequence number of at least `since_seq`
+    #[wasm_bindgen(js_name = getEvents)]
+    pub fn get_events(&self, since_seq: u64) -> Result<String, JsValue> {
+        self.events.to_json_since(since_seq)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize events: {}", e)))
+    }
+    
+    /// Get the number of events currently held in the log
+    #[wasm_bindgen(js_name = getEventCount)]
+    pub fn get_event_count(&self) -> usize {
+        self.events.len()
+    }
+    
+    /// Drop all recorded events
+    #[wasm_bindgen(js_name = clearEvents)]
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl ConflictResolver {
+    /// Roll the contest between two organisms
+    fn resolve(&mut self, attacker_id: u32, defender_id: u32) -> ContestOutcome {
+        let p = self.win_probability(attacker_id, defender_id);
+        let roll = self.rng.next_f64();
+        
+        let (winner, loser) = if roll < p {
+            (attacker_id, defender_id)
+        } else {
+            (defender_id, attacker_id)
+        };
+        
+        ContestOutcome {
+            winner,
+            loser,
+            attacker_win_probability: p,
+            roll,
+        }
+    }
+    
+    fn outcome_json(&self, attacker_id: u32, outcome: &ContestOutcome, extra: serde_json::Value) -> serde_json::Value {
+        let mut record = serde_json::json!({
+            "attacker": attacker_id,
+            "defender": if outcome.attacker_won(attacker_id) { outcome.loser } else { outcome.winner },
+            "winner": outcome.winner,
+            "loser": outcome.loser,
+            "attackerWinProbability": outcome.attacker_win_probability,
+            "roll": outcome.roll,
+        });
+        
+        if let (Some(record), serde_json::Value::Object(extra)) = (record.as_object_mut(), extra) {
+            record.extend(extra);
+        }
+        record
+    }
+}
+
+fn to_json(value: &serde_json::Value) -> Result<String, JsValue> {
+    serde_json::to_string(value)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize contest outcome: {}", e)))
+}
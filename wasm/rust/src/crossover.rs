@@ -0,0 +1,197 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+
+use crate::lisp::{self, Expr, Node};
+use crate::rng::Rng;
+use crate::rule_versions;
+use crate::{clock, CompensatedSum, HotspotState, Rule, RuleRegistry};
+
+/// Which parent a rule defined by both comes from
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+enum ConflictPolicy {
+    /// Pick a parent at random with `preferB` as the odds of `b`
+    #[default]
+    Random,
+    A,
+    B,
+}
+
+fn default_one() -> f64 {
+    1.0
+}
+
+fn default_half() -> f64 {
+    0.5
+}
+
+fn default_mutation_scale() -> f64 {
+    0.1
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CrossoverOptions {
+    #[serde(default)]
+    seed: u64,
+    /// Chance that a rule only one parent has is inherited
+    #[serde(default = "default_one")]
+    inherit_rate: f64,
+    #[serde(default)]
+    conflict: ConflictPolicy,
+    #[serde(default = "default_half")]
+    prefer_b: f64,
+    /// Chance that each numeric constant in an inherited rule is mutated
+    #[serde(default)]
+    mutation_rate: f64,
+    /// Standard deviation of a mutation, relative to the constant
+    #[serde(default = "default_mutation_scale")]
+    mutation_scale: f64,
+}
+
+impl CrossoverOptions {
+    fn parse(json: &str) -> Result<CrossoverOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: CrossoverOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut problems = Vec::new();
+        for (name, value) in [
+            ("inheritRate", options.inherit_rate),
+            ("preferB", options.prefer_b),
+            ("mutationRate", options.mutation_rate),
+        ] {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("{} must be between 0 and 1, got {}", name, value));
+            }
+        }
+        if !options.mutation_scale.is_finite() || options.mutation_scale < 0.0 {
+            problems.push(format!("mutationScale must be non-negative, got {}", options.mutation_scale));
+        }
+        if problems.is_empty() {
+            Ok(options)
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// Perturb the numeric constants of a form in place, returning how many changed
+///
+/// Whole numbers stay whole so counts and indices keep their meaning.
+fn mutate_constants(node: &mut Node, rng: &mut Rng, options: &CrossoverOptions) -> usize {
+    match &mut node.expr {
+        Expr::Number(value) => {
+            if rng.next_f64() >= options.mutation_rate {
+                return 0;
+            }
+            // Zero has no scale of its own, so it moves by the absolute amount
+            let spread = if *value == 0.0 { options.mutation_scale } else { value.abs() * options.mutation_scale };
+            let mut mutated = *value + rng.next_normal() * spread;
+            if value.fract() == 0.0 {
+                mutated = mutated.round();
+            }
+            let changed = mutated != *value;
+            *value = mutated;
+            usize::from(changed)
+        }
+        Expr::List(items) => items.iter_mut().map(|item| mutate_constants(item, rng, options)).sum(),
+        _ => 0,
+    }
+}
+
+/// Copy a parent's rule into a child with fresh statistics, mutating its constants
+fn inherit(rule: &Rule, rng: &mut Rng, options: &CrossoverOptions) -> Rule {
+    let mut code = rule.lisp_code.clone();
+    if options.mutation_rate > 0.0 {
+        // Rules that fail to parse are inherited as they are
+        if let Ok(mut forms) = lisp::parse(&code) {
+            let mutations: usize = forms.iter_mut().map(|form| mutate_constants(form, rng, options)).sum();
+            if mutations > 0 {
+                code = forms.iter().map(|form| form.to_string()).collect::<Vec<_>>().join("\n");
+            }
+        }
+    }
+    
+    Rule {
+        id: rule.id.clone(),
+        doc: lisp::extract_docstring(&code),
+        access: lisp::analyze_access(&code),
+        program: lisp::Program::compile(&code),
+        lisp_code: code,
+        execution_count: 0,
+        total_execution_time_ms: CompensatedSum::default(),
+        last_execution_time_ms: 0.0,
+        error_count: 0,
+        last_error: None,
+        created_at: clock::now_ms() as u64,
+        tests: rule.tests.clone(),
+        shadow: None,
+        modes: rule.modes.clone(),
+        params: rule.params.clone(),
+        enabled: rule.enabled,
+        priority: rule.priority,
+        schedule: rule.schedule.clone(),
+        trigger: rule.trigger.clone(),
+        runs_after: rule.runs_after.clone(),
+        tags: rule.tags.clone(),
+        limits: rule.limits.clone(),
+        version: rule_versions::default_version(),
+        versions: Vec::new(),
+        hotspot: HotspotState::default(),
+    }
+}
+
+/// Breed two registries into a child registry
+pub(crate) fn crossover(a: &RuleRegistry, b: &RuleRegistry, options_json: &str) -> Result<RuleRegistry, String> {
+    let options = CrossoverOptions::parse(options_json)
+        .map_err(|e| format!("Invalid crossover options: {}", e))?;
+    let mut rng = Rng::new(options.seed);
+    
+    // a's order first, then the rules only b has in b's order
+    let mut ids: Vec<&String> = a.execution_order.iter().collect();
+    ids.extend(b.execution_order.iter().filter(|id| !a.rules.contains_key(*id)));
+    
+    let mut child = RuleRegistry::new();
+    for id in ids {
+        let rule = match (a.rules.get(id), b.rules.get(id)) {
+            (Some(from_a), Some(from_b)) => {
+                let take_b = match options.conflict {
+                    ConflictPolicy::A => false,
+                    ConflictPolicy::B => true,
+                    ConflictPolicy::Random => rng.next_f64() < options.prefer_b,
+                };
+                if take_b { from_b } else { from_a }
+            }
+            (Some(rule), None) | (None, Some(rule)) => {
+                if rng.next_f64() >= options.inherit_rate {
+                    continue;
+                }
+                rule
+            }
+            (None, None) => continue,
+        };
+        
+        let rule = inherit(rule, &mut rng, &options);
+        child.execution_order.push(id.clone());
+        child.rules.insert(id.clone(), rule);
+    }
+    
+    Ok(child)
+}
+
+/// Breed two rule registries into a new one
+///
+/// Rules only one parent has are inherited with probability
+/// `inheritRate`; for rules both define, `conflict` picks the parent
+/// ("random" by default, weighted by `preferB`, or always "a" or "b").
+/// Each numeric constant of an inherited rule is then perturbed with
+/// probability `mutationRate` by a normal step of `mutationScale` times
+/// its size; mutated rules are reprinted from their parsed form. The same
+/// `seed` always breeds the same child.
+///
+/// The child's rules start with fresh statistics and keep their tests,
+/// modes and parameters; modes, invariants and goals are not inherited.
+/// `options_json` may be empty for the defaults.
+#[wasm_bindgen(js_name = crossoverRegistries)]
+pub fn crossover_registries(a: &RuleRegistry, b: &RuleRegistry, options_json: &str) -> Result<RuleRegistry, JsValue> {
+    crossover(a, b, options_json).map_err(|e| JsValue::from_str(&e))
+}
@@ -0,0 +1,118 @@
+use wasm_bindgen::prelude::*;
+
+use crate::history::{HistoryRecorder, HISTORY_SERIES};
+use crate::simulation::Simulation;
+use crate::RuleRegistry;
+
+/// Record separator, as RFC 4180 specifies
+const LINE_END: &str = "\r\n";
+
+/// Quote a field if it holds a separator, quote or line break, doubling any quotes
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) || value.starts_with(' ') || value.ends_with(' ') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A number as a field; NaN becomes an empty field, which readers take as missing
+fn number(value: f64) -> String {
+    if value.is_nan() {
+        String::new()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Join rows of fields into a CSV document, ending every row with a line break
+fn document(header: &[&str], rows: impl Iterator<Item = Vec<String>>) -> String {
+    let mut out = header.iter().map(|h| field(h)).collect::<Vec<_>>().join(",");
+    out.push_str(LINE_END);
+    for row in rows {
+        out.push_str(&row.join(","));
+        out.push_str(LINE_END);
+    }
+    out
+}
+
+#[wasm_bindgen]
+impl HistoryRecorder {
+    /// Get the held samples as CSV, oldest first
+    ///
+    /// The header is `step,population,energy,mutationRate,adaptationScore`
+    /// and every sample is one row. Rows end with `\r\n`.
+    #[wasm_bindgen(js_name = exportHistoryCsv)]
+    pub fn export_history_csv(&self) -> String {
+        document(
+            &HISTORY_SERIES,
+            self.samples().map(|s| {
+                let mut row = vec![s.step.to_string()];
+                row.extend((1..HISTORY_SERIES.len()).map(|series| number(s.value(series))));
+                row
+            }),
+        )
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Get the recorded history as CSV, as `HistoryRecorder.exportHistoryCsv` does
+    #[wasm_bindgen(js_name = exportHistoryCsv)]
+    pub fn export_history_csv(&self) -> Result<String, JsValue> {
+        self.history
+            .as_deref()
+            .map(HistoryRecorder::export_history_csv)
+            .ok_or_else(|| JsValue::from_str("History is not enabled; call enableHistory first"))
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Get execution statistics for all rules as CSV, one row per rule in the order ticks run them
+    ///
+    /// Columns match `getAllStats`: `id`, `executionCount`,
+    /// `totalExecutionTimeMs`, `lastExecutionTimeMs`,
+    /// `averageExecutionTimeMs`, `createdAt`, `errorCount`, `lastError`,
+    /// `reads`, `writes`, `enabled`, `priority` and `bytecode`. `reads` and
+    /// `writes` list metrics separated by spaces.
+    /// Fields holding commas, quotes or line breaks are quoted.
+    #[wasm_bindgen(js_name = exportRuleStatsCsv)]
+    pub fn export_rule_stats_csv(&self) -> String {
+        let header = [
+            "id",
+            "executionCount",
+            "totalExecutionTimeMs",
+            "lastExecutionTimeMs",
+            "averageExecutionTimeMs",
+            "createdAt",
+            "errorCount",
+            "lastError",
+            "reads",
+            "writes",
+            "enabled",
+            "priority",
+            "bytecode",
+        ];
+        let rows = self.run_order().into_iter().filter_map(|id| self.rules.get(id)).map(|rule| {
+            let total = rule.total_execution_time_ms.value();
+            let average = if rule.execution_count > 0 { total / rule.execution_count as f64 } else { 0.0 };
+            vec![
+                field(&rule.id),
+                rule.execution_count.to_string(),
+                number(total),
+                number(rule.last_execution_time_ms),
+                number(average),
+                rule.created_at.to_string(),
+                rule.error_count.to_string(),
+                field(rule.last_error.as_deref().unwrap_or("")),
+                field(&rule.access.reads.iter().copied().collect::<Vec<_>>().join(" ")),
+                field(&rule.access.writes.iter().copied().collect::<Vec<_>>().join(" ")),
+                rule.enabled.to_string(),
+                rule.priority.to_string(),
+                rule.program.is_bytecode().to_string(),
+            ]
+        });
+        document(&header, rows)
+    }
+}
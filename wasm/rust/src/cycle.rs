@@ -0,0 +1,292 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::dynamics::ENERGY_REGEN;
+use crate::environment::Environment;
+use crate::simulation::Simulation;
+use crate::subsystem::{Subsystem, SubsystemContext};
+
+/// Most samples a custom waveform may have
+const MAX_SAMPLES: usize = 4096;
+
+fn default_period() -> f64 {
+    100.0
+}
+
+fn default_amplitude() -> f64 {
+    0.5
+}
+
+fn default_targets() -> Vec<CycleTarget> {
+    vec![CycleTarget::Regeneration, CycleTarget::Capacity]
+}
+
+/// Shape of one period of a cycle, from -1 to 1
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Waveform {
+    #[default]
+    Sine,
+    /// 1 for the first half of the period, -1 for the second
+    Square,
+    /// Rises from 0 to 1, falls to -1 and climbs back, like a sine
+    Triangle,
+    /// Rises from 0 to 1 over the first half, jumps to -1 and rises back to 0
+    Sawtooth,
+    /// `values` sampled evenly over the period, interpolated linearly
+    Custom,
+}
+
+/// A parameter a cycle modulates
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+enum CycleTarget {
+    /// Energy regeneration of a simulation's state, and an environment's regrowth
+    Regeneration,
+    /// An environment's cell capacities
+    Capacity,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct CycleOptions {
+    /// Time units per cycle
+    #[serde(default = "default_period")]
+    period: f64,
+    /// How far the multiplier swings either side of 1
+    #[serde(default = "default_amplitude")]
+    amplitude: f64,
+    /// Offset into the cycle at time 0, as a fraction of the period
+    #[serde(default)]
+    phase: f64,
+    #[serde(default)]
+    waveform: Waveform,
+    /// Samples of a custom waveform
+    #[serde(default)]
+    values: Vec<f64>,
+    #[serde(default = "default_targets")]
+    modulates: Vec<CycleTarget>,
+}
+
+impl CycleOptions {
+    fn parse(json: &str) -> Result<CycleOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: CycleOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut problems = Vec::new();
+        if !options.period.is_finite() || options.period <= 0.0 {
+            problems.push(format!("period must be positive, got {}", options.period));
+        }
+        if !options.amplitude.is_finite() || options.amplitude < 0.0 {
+            problems.push(format!("amplitude must be non-negative, got {}", options.amplitude));
+        }
+        if !options.phase.is_finite() {
+            problems.push(format!("phase must be finite, got {}", options.phase));
+        }
+        match options.waveform {
+            Waveform::Custom if options.values.is_empty() || options.values.len() > MAX_SAMPLES => {
+                problems.push(format!("a custom waveform needs 1 to {} values, got {}", MAX_SAMPLES, options.values.len()));
+            }
+            Waveform::Custom => {
+                if let Some(bad) = options.values.iter().find(|v| !v.is_finite()) {
+                    problems.push(format!("values must be finite, got {}", bad));
+                }
+            }
+            _ if !options.values.is_empty() => {
+                problems.push("values are only used by the custom waveform".to_string());
+            }
+            _ => {}
+        }
+        if problems.is_empty() {
+            Ok(options)
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+    
+    /// The waveform at `position` through the period, in [0, 1)
+    fn wave(&self, position: f64) -> f64 {
+        match self.waveform {
+            Waveform::Sine => (2.0 * PI * position).sin(),
+            Waveform::Square => if position < 0.5 { 1.0 } else { -1.0 },
+            Waveform::Triangle => {
+                if position < 0.25 {
+                    4.0 * position
+                } else if position < 0.75 {
+                    2.0 - 4.0 * position
+                } else {
+                    4.0 * position - 4.0
+                }
+            }
+            Waveform::Sawtooth => 2.0 * (position + 0.5).fract() - 1.0,
+            Waveform::Custom => {
+                let n = self.values.len();
+                let at = position * n as f64;
+                let index = (at.floor() as usize).min(n - 1);
+                let t = at - index as f64;
+                self.values[index] * (1.0 - t) + self.values[(index + 1) % n] * t
+            }
+        }
+    }
+}
+
+/// EnvironmentCycle is a periodic multiplier for seasonal forcing
+///
+/// At time `t` the multiplier is `1 + amplitude * wave(t / period + phase)`,
+/// never below 0, where the wave runs from -1 to 1. Added to a
+/// `Simulation` with `addCycle`, it scales the state's energy
+/// regeneration each tick; `applyTo` scales an environment's regrowth
+/// and capacities. `modulates` picks which of the two it touches.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EnvironmentCycle {
+    options: CycleOptions,
+    time: f64,
+}
+
+impl EnvironmentCycle {
+    fn multiplier(&self, target: CycleTarget) -> f64 {
+        if self.options.modulates.contains(&target) {
+            self.value_at(self.time)
+        } else {
+            1.0
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl EnvironmentCycle {
+    /// Create a cycle from JSON options
+    ///
+    /// Options (all optional): `period` (100), `amplitude` (0.5), `phase`
+    /// (0, a fraction of the period), `waveform` (`sine`, `square`,
+    /// `triangle`, `sawtooth` or `custom`), `values` (the samples of a
+    /// custom waveform, spread evenly over one period) and `modulates`
+    /// (`["regeneration", "capacity"]`).
+    #[wasm_bindgen(constructor)]
+    pub fn new(options_json: &str) -> Result<EnvironmentCycle, JsValue> {
+        let options = CycleOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid cycle options: {}", e)))?;
+        Ok(EnvironmentCycle { options, time: 0.0 })
+    }
+    
+    /// Time into the cycle, advanced by `advance`
+    #[wasm_bindgen(getter)]
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+    
+    #[wasm_bindgen(setter)]
+    pub fn set_time(&mut self, value: f64) {
+        if value.is_finite() {
+            self.time = value;
+        }
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn period(&self) -> f64 {
+        self.options.period
+    }
+    
+    /// The multiplier now
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> f64 {
+        self.value_at(self.time)
+    }
+    
+    /// The multiplier at any time
+    #[wasm_bindgen(js_name = valueAt)]
+    pub fn value_at(&self, time: f64) -> f64 {
+        let position = (time / self.options.period + self.options.phase).rem_euclid(1.0);
+        (1.0 + self.options.amplitude * self.options.wave(position)).max(0.0)
+    }
+    
+    /// Move the cycle on by `delta_time` and return the new multiplier
+    #[wasm_bindgen]
+    pub fn advance(&mut self, delta_time: f64) -> f64 {
+        if delta_time.is_finite() {
+            self.time += delta_time;
+        }
+        self.value()
+    }
+    
+    /// Scale an environment's regrowth and capacities by the multiplier now, as `modulates` says
+    #[wasm_bindgen(js_name = applyTo)]
+    pub fn apply_to(&self, environment: &mut Environment) {
+        environment.set_regrowth_scale(self.multiplier(CycleTarget::Regeneration));
+        environment.set_capacity_scale(self.multiplier(CycleTarget::Capacity));
+    }
+    
+    /// Get the cycle, options and time, as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize cycle: {}", e)))
+    }
+    
+    /// Restore the cycle from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: EnvironmentCycle = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse cycle snapshot: {}", e)))?;
+        *self = loaded;
+        Ok(())
+    }
+}
+
+/// An `EnvironmentCycle` running as a simulation subsystem before every tick
+#[derive(Clone)]
+struct CycleDriver {
+    name: String,
+    cycle: EnvironmentCycle,
+}
+
+impl Subsystem for CycleDriver {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    /// Regeneration enters the energy derivative as a constant, so the
+    /// seasonal part of it can be added up front
+    fn pre_tick(&mut self, context: &mut SubsystemContext) -> Result<(), String> {
+        let extra = (self.cycle.multiplier(CycleTarget::Regeneration) - 1.0) * ENERGY_REGEN * context.delta_time;
+        if extra.is_finite() && extra != 0.0 {
+            let energy = context.state.energy();
+            context.state.set_energy(energy + extra);
+        }
+        self.cycle.advance(context.delta_time);
+        Ok(())
+    }
+    
+    fn serialize(&self) -> serde_json::Value {
+        serde_json::json!({ "time": self.cycle.time, "value": self.cycle.value() })
+    }
+    
+    fn restore(&mut self, value: &serde_json::Value) -> Result<(), String> {
+        let time = value.get("time").and_then(|t| t.as_f64())
+            .ok_or_else(|| "expected {\"time\": number}".to_string())?;
+        self.cycle.set_time(time);
+        Ok(())
+    }
+    
+    fn box_clone(&self) -> Box<dyn Subsystem> {
+        Box::new(self.clone())
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Run a copy of a cycle as subsystem `name`, scaling energy regeneration every tick
+    ///
+    /// Each tick's regeneration is multiplied by the cycle's value at the
+    /// start of the tick, after which the cycle moves on by the tick's
+    /// time step. Capacity modulation has nothing to act on in a
+    /// simulation and is ignored.
+    #[wasm_bindgen(js_name = addCycle)]
+    pub fn add_cycle(&mut self, name: &str, cycle: &EnvironmentCycle) -> Result<(), JsValue> {
+        let result = self.add_subsystem(Box::new(CycleDriver { name: name.to_string(), cycle: cycle.clone() }))
+            .map_err(|e| JsValue::from_str(&e));
+        self.record_input("addCycle", || serde_json::json!({ "name": name, "cycle": cycle }), result.is_ok());
+        result
+    }
+}
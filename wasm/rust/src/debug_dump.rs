@@ -0,0 +1,90 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{OrganismState, RuleRegistry};
+
+impl OrganismState {
+    fn debug_json(&self) -> Result<serde_json::Value, String> {
+        let groups: Vec<serde_json::Value> = self.dynamics.iter().map(|g| g.debug_json()).collect();
+        Ok(serde_json::json!({
+            "serialized": serde_json::to_value(self).map_err(|e| e.to_string())?,
+            "stateVector": self.state_vector,
+            "dynamicsError": self.dynamics_error,
+            "dynamicsCache": groups,
+        }))
+    }
+}
+
+impl RuleRegistry {
+    fn debug_json(&self, state: &OrganismState) -> Result<serde_json::Value, String> {
+        let mut rules = serde_json::Map::new();
+        for (id, rule) in &self.rules {
+            let mut entry = serde_json::to_value(rule).map_err(|e| e.to_string())?;
+            if let Some(object) = entry.as_object_mut() {
+                object.insert("access".to_string(), rule.access.to_json());
+                object.insert("hotspot".to_string(), rule.hotspot.debug_json());
+            }
+            rules.insert(id.clone(), entry);
+        }
+        
+        // What the next tick would run, without running it
+        let next_step = (self.current_step as u32).wrapping_add(1);
+        let next_mode = self.evaluate_mode(state)
+            .map_err(|e| e.as_string().unwrap_or_default());
+        let due: Vec<String> = match &next_mode {
+            Ok(mode) => match self.evaluate_triggers(state, mode.as_deref()) {
+                Ok(triggers) => self.due_rules(mode.as_deref(), next_step, self.generation_changed(state.generation()), &triggers)
+                    .into_iter()
+                    .cloned()
+                    .collect(),
+                Err(_) => Vec::new(),
+            },
+            Err(_) => Vec::new(),
+        };
+        
+        Ok(serde_json::json!({
+            "rules": rules,
+            "executionOrder": self.execution_order,
+            "bundles": self.bundles,
+            "modes": self.modes,
+            "activeMode": self.active_mode,
+            "currentStep": self.current_step,
+            "lastGeneration": self.last_generation,
+            "hotspotPolicy": self.hotspot_policy.as_ref().map(|p| p.debug_json()),
+            "events": serde_json::to_value(&self.events).map_err(|e| e.to_string())?,
+            "invariants": {
+                "list": self.invariants.list,
+                "debug": self.invariants.debug,
+                "lastViolation": self.invariants.last_violation,
+            },
+            "goals": self.goals,
+            "fuelLimit": self.fuel_limit,
+            "nextTick": {
+                "step": next_step,
+                "mode": next_mode.as_ref().ok(),
+                "modeError": next_mode.as_ref().err(),
+                "due": due,
+            },
+        }))
+    }
+}
+
+/// Dump everything about a state and registry as verbose JSON for bug reports
+///
+/// Unlike snapshots and exports, this includes internals that are normally
+/// hidden or recomputed: the noise RNG state, the state vector, parse
+/// caches of metric dynamics, each rule's access analysis and hotspot
+/// latency window, the event log, invariant state and which rules the next
+/// tick would run. The layout is for humans and may change between
+/// versions; it can't be loaded back.
+#[wasm_bindgen(js_name = debugDump)]
+pub fn debug_dump(state: &OrganismState, registry: &RuleRegistry) -> Result<String, JsValue> {
+    let dump = serde_json::json!({
+        "engineVersion": env!("CARGO_PKG_VERSION"),
+        "state": state.debug_json().map_err(|e| JsValue::from_str(&format!("Failed to dump state: {}", e)))?,
+        "registry": registry.debug_json(state)
+            .map_err(|e| JsValue::from_str(&format!("Failed to dump registry: {}", e)))?,
+    });
+    
+    serde_json::to_string_pretty(&dump)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize debug dump: {}", e)))
+}
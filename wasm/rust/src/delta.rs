@@ -0,0 +1,248 @@
+use wasm_bindgen::prelude::*;
+use serde_json::{Map, Value};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::hash::fnv1a64_hex;
+use crate::msgpack;
+use crate::world::World;
+
+/// Baselines a world remembers; marking another drops the oldest
+const MAX_BASELINES: usize = 16;
+
+/// Format name written into every delta
+const DELTA_FORMAT: &str = "ouroboros-world-delta";
+
+/// Snapshots a world can take deltas against, by ID
+#[derive(Clone, Default)]
+pub(crate) struct Baselines {
+    next_id: u32,
+    snapshots: BTreeMap<u32, Value>,
+}
+
+fn fingerprint(value: &Value) -> String {
+    fnv1a64_hex(value.to_string().as_bytes())
+}
+
+/// IDs of an array of objects that each carry a unique numeric `id`
+fn element_ids(items: &[Value]) -> Option<Vec<u64>> {
+    let ids: Vec<u64> = items.iter().map(|item| item.get("id").and_then(Value::as_u64)).collect::<Option<_>>()?;
+    let unique: HashSet<u64> = ids.iter().copied().collect();
+    (unique.len() == ids.len()).then_some(ids)
+}
+
+/// The change from `base` to `current`, or `None` when they are equal
+///
+/// A change is one of:
+/// - `{"r": value}` replaces the value outright
+/// - `{"o": {key: change}, "x": [keys]}` changes and removes object fields
+/// - `{"k": [ids], "c": {id: change}, "n": [items]}` rebuilds an array of
+///   objects with IDs in the listed order, changing the ones the base had
+///   and adding the new ones
+/// - `{"a": [items]}` appends to an array
+/// - `{"i": {index: change}}` changes elements of an array of the same length
+fn diff(base: &Value, current: &Value) -> Option<Value> {
+    if base == current {
+        return None;
+    }
+    let replace = || Some(serde_json::json!({ "r": current }));
+    match (base, current) {
+        (Value::Object(old), Value::Object(new)) => {
+            let changed: Map<String, Value> = new
+                .iter()
+                .filter_map(|(key, value)| match old.get(key) {
+                    Some(previous) => diff(previous, value).map(|change| (key.clone(), change)),
+                    None => Some((key.clone(), serde_json::json!({ "r": value }))),
+                })
+                .collect();
+            let removed: Vec<&String> = old.keys().filter(|key| !new.contains_key(*key)).collect();
+            Some(serde_json::json!({ "o": changed, "x": removed }))
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            if let (Some(old_ids), Some(new_ids)) = (element_ids(old), element_ids(new)) {
+                let by_id: HashMap<u64, &Value> = old_ids.iter().copied().zip(old).collect();
+                let mut changed = Map::new();
+                let mut added = Vec::new();
+                for (id, item) in new_ids.iter().zip(new) {
+                    match by_id.get(id) {
+                        Some(previous) => {
+                            if let Some(change) = diff(previous, item) {
+                                changed.insert(id.to_string(), change);
+                            }
+                        }
+                        None => added.push(item),
+                    }
+                }
+                return Some(serde_json::json!({ "k": new_ids, "c": changed, "n": added }));
+            }
+            if new.len() > old.len() && new.starts_with(old) {
+                return Some(serde_json::json!({ "a": &new[old.len()..] }));
+            }
+            if new.len() == old.len() {
+                let changed: Map<String, Value> = old
+                    .iter()
+                    .zip(new)
+                    .enumerate()
+                    .filter_map(|(index, (a, b))| diff(a, b).map(|change| (index.to_string(), change)))
+                    .collect();
+                return Some(serde_json::json!({ "i": changed }));
+            }
+            replace()
+        }
+        _ => replace(),
+    }
+}
+
+/// Apply a change made by `diff` to the value it was taken against
+fn apply(base: Value, change: &Value) -> Result<Value, String> {
+    let change = change.as_object().ok_or("a change must be an object")?;
+    if let Some(value) = change.get("r") {
+        return Ok(value.clone());
+    }
+    let entries = |name: &str| {
+        change.get(name).and_then(Value::as_object).ok_or_else(|| format!("change is missing its {} entries", name))
+    };
+    let list = |name: &str| {
+        change.get(name).and_then(Value::as_array).ok_or_else(|| format!("change is missing its {} list", name))
+    };
+    match base {
+        Value::Object(mut object) if change.contains_key("o") => {
+            for key in list("x")? {
+                object.remove(key.as_str().ok_or("removed keys must be strings")?);
+            }
+            for (key, nested) in entries("o")? {
+                let previous = object.remove(key).unwrap_or(Value::Null);
+                object.insert(key.clone(), apply(previous, nested)?);
+            }
+            Ok(Value::Object(object))
+        }
+        Value::Array(items) if change.contains_key("k") => {
+            let ids = element_ids(&items).ok_or("array elements have no unique IDs")?;
+            let mut by_id: HashMap<u64, Value> = ids.into_iter().zip(items).collect();
+            let changed = entries("c")?;
+            let mut added = list("n")?.iter();
+            list("k")?
+                .iter()
+                .map(|id| {
+                    let id = id.as_u64().ok_or("element IDs must be numbers")?;
+                    match by_id.remove(&id) {
+                        Some(item) => match changed.get(&id.to_string()) {
+                            Some(nested) => apply(item, nested),
+                            None => Ok(item),
+                        },
+                        None => added.next().cloned().ok_or_else(|| format!("element {} is missing", id)),
+                    }
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array)
+        }
+        Value::Array(mut items) if change.contains_key("a") => {
+            items.extend(list("a")?.iter().cloned());
+            Ok(Value::Array(items))
+        }
+        Value::Array(mut items) if change.contains_key("i") => {
+            for (index, nested) in entries("i")? {
+                let index: usize = index.parse().map_err(|_| format!("bad element index {}", index))?;
+                let slot = items.get_mut(index).ok_or_else(|| format!("element {} is out of range", index))?;
+                *slot = apply(std::mem::take(slot), nested)?;
+            }
+            Ok(Value::Array(items))
+        }
+        _ => Err("change does not fit the value it applies to".to_string()),
+    }
+}
+
+impl World {
+    fn snapshot_value(&self) -> Result<Value, JsValue> {
+        serde_json::to_value(self).map_err(|e| JsValue::from_str(&format!("Failed to serialize world: {}", e)))
+    }
+}
+
+#[wasm_bindgen]
+impl World {
+    /// Remember the world as it is now as a baseline for deltas and return its ID
+    ///
+    /// Save the full `getSnapshot()` taken at the same moment; deltas
+    /// from `getDeltaSince` only restore on top of it. The world keeps
+    /// the 16 newest baselines.
+    #[wasm_bindgen(js_name = markBaseline)]
+    pub fn mark_baseline(&mut self) -> Result<u32, JsValue> {
+        let snapshot = self.snapshot_value()?;
+        let baselines = &mut self.baselines;
+        baselines.next_id += 1;
+        baselines.snapshots.insert(baselines.next_id, snapshot);
+        while baselines.snapshots.len() > MAX_BASELINES {
+            baselines.snapshots.pop_first();
+        }
+        Ok(baselines.next_id)
+    }
+    
+    /// Forget a baseline; returns whether it was held
+    #[wasm_bindgen(js_name = dropBaseline)]
+    pub fn drop_baseline(&mut self, id: u32) -> bool {
+        self.baselines.snapshots.remove(&id).is_some()
+    }
+    
+    /// IDs of the baselines held, oldest first
+    #[wasm_bindgen(js_name = getBaselines)]
+    pub fn get_baselines(&self) -> Vec<u32> {
+        self.baselines.snapshots.keys().copied().collect()
+    }
+    
+    /// Get what changed since a baseline as MessagePack bytes
+    ///
+    /// Only changed fields are written, and organisms are matched by
+    /// ID, so an organism that merely aged costs a few bytes. Each delta
+    /// stands alone against its baseline: keep the newest one and drop
+    /// older ones.
+    #[wasm_bindgen(js_name = getDeltaSince)]
+    pub fn get_delta_since(&self, baseline_id: u32) -> Result<Vec<u8>, JsValue> {
+        let base = self.baselines.snapshots
+            .get(&baseline_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Baseline not found: {}", baseline_id)))?;
+        let current = self.snapshot_value()?;
+        let delta = serde_json::json!({
+            "format": DELTA_FORMAT,
+            "base": fingerprint(base),
+            "target": fingerprint(&current),
+            "changes": diff(base, &current),
+        });
+        let mut out = Vec::new();
+        msgpack::write_value(&mut out, &delta);
+        Ok(out)
+    }
+    
+    /// Apply `getDeltaSince` bytes to a world restored from the delta's baseline snapshot
+    ///
+    /// The world must match the baseline the delta was taken against,
+    /// and the result must match the world it was taken from; either
+    /// mismatch throws and leaves the world unchanged. Baselines held
+    /// are kept.
+    #[wasm_bindgen(js_name = applyDelta)]
+    pub fn apply_delta(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let fail = |e: String| JsValue::from_str(&format!("Failed to apply delta: {}", e));
+        let mut reader = msgpack::Reader::new(bytes).map_err(fail)?;
+        let delta = reader.value(0).map_err(fail)?;
+        if !reader.done() || delta.get("format").and_then(Value::as_str) != Some(DELTA_FORMAT) {
+            return Err(fail("not a world delta".to_string()));
+        }
+        let field = |name: &str| delta.get(name).and_then(Value::as_str).unwrap_or_default();
+        
+        let current = self.snapshot_value()?;
+        if fingerprint(&current) != field("base") {
+            return Err(fail("the world does not match the delta's baseline".to_string()));
+        }
+        let result = match delta.get("changes") {
+            Some(Value::Null) | None => current,
+            Some(changes) => apply(current, changes).map_err(fail)?,
+        };
+        if fingerprint(&result) != field("target") {
+            return Err(fail("the result does not match the world the delta was taken from".to_string()));
+        }
+        let loaded: World = serde_json::from_value(result).map_err(|e| fail(e.to_string()))?;
+        loaded.options.validate().map_err(|e| fail(format!("invalid world options: {}", e)))?;
+        let baselines = std::mem::take(&mut self.baselines);
+        *self = loaded;
+        self.baselines = baselines;
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use wasm_bindgen::prelude::*;
+
+use crate::call_log;
+use crate::rng::Rng;
+use crate::{lisp, self_check_golden_run, OrganismState, RuleRegistry};
+
+/// A diagnostic check, returning a detail on success and an error otherwise
+type Check = fn() -> Result<String, String>;
+
+/// Seed used by the RNG determinism check
+const DIAGNOSTIC_SEED: u64 = 0x5eed;
+
+fn js_error(e: JsValue) -> String {
+    e.as_string().unwrap_or_else(|| format!("{:?}", e))
+}
+
+fn check_interpreter() -> Result<String, String> {
+    let mut state = OrganismState::new();
+    let forms = lisp::parse("(+ 1 (* 2 3))").map_err(|e| e.message)?;
+    match lisp::run(&forms, &mut state, &[], None).map_err(|e| e.message)? {
+        lisp::Value::Number(n) if n != 7.0 => return Err(format!("(+ 1 (* 2 3)) evaluated to {}", n)),
+        lisp::Value::Number(_) => {}
+        other => return Err(format!("(+ 1 (* 2 3)) evaluated to a {}", other.type_name())),
+    }
+    if !lisp::eval_predicate("(< energy 2000)", &state)? {
+        return Err("(< energy 2000) was false for a new state".to_string());
+    }
+    Ok("arithmetic and predicates evaluate".to_string())
+}
+
+fn check_rng() -> Result<String, String> {
+    let mut first = Rng::new(DIAGNOSTIC_SEED);
+    let mut second = Rng::new(DIAGNOSTIC_SEED);
+    for draw in 0..64 {
+        let (a, b) = (first.next_f64(), second.next_f64());
+        if a != b {
+            return Err(format!("draw {} differs between equally seeded generators", draw));
+        }
+        if !(0.0..1.0).contains(&a) {
+            return Err(format!("draw {} is outside [0, 1): {}", draw, a));
+        }
+    }
+    Ok("64 draws match".to_string())
+}
+
+fn check_state_round_trip() -> Result<String, String> {
+    let mut state = OrganismState::new();
+    state.define_custom_metric("diagnostic", 0.1 + 0.2).map_err(js_error)?;
+    state.step(0.1);
+    let snapshot = state.get_snapshot().map_err(js_error)?;
+    let mut restored = OrganismState::new();
+    restored.load_snapshot(&snapshot).map_err(js_error)?;
+    if restored.get_snapshot().map_err(js_error)? != snapshot {
+        return Err("snapshot changed after a load".to_string());
+    }
+    Ok(format!("{} byte snapshot round-trips", snapshot.len()))
+}
+
+fn check_registry_round_trip() -> Result<String, String> {
+    let mut registry = RuleRegistry::new();
+    registry.register_rule("diagnostic", "(+ energy 1)", None).map_err(js_error)?;
+    registry.define_mode("diagnostic", "(> energy 0)").map_err(js_error)?;
+    let exported = registry.export_registry().map_err(js_error)?;
+    let mut restored = RuleRegistry::new();
+    restored.import_registry(&exported).map_err(js_error)?;
+    
+    let parse = |json: &str| serde_json::from_str::<serde_json::Value>(json).map_err(|e| e.to_string());
+    if parse(&restored.export_registry().map_err(js_error)?)? != parse(&exported)? {
+        return Err("registry changed after an import".to_string());
+    }
+    Ok("registry round-trips".to_string())
+}
+
+fn check_timing() -> Result<String, String> {
+    let first = js_sys::Date::now();
+    let second = js_sys::Date::now();
+    if !first.is_finite() || first <= 0.0 {
+        return Err(format!("Date.now() returned {}", first));
+    }
+    if second < first {
+        return Err("Date.now() went backwards".to_string());
+    }
+    let performance = js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("performance"))
+        .map(|p| !p.is_undefined())
+        .unwrap_or(false);
+    Ok(format!("Date.now() works; performance API {}", if performance { "available" } else { "missing" }))
+}
+
+fn check_golden_run() -> Result<String, String> {
+    let first = self_check_golden_run(1, 100).map_err(js_error)?;
+    let second = self_check_golden_run(1, 100).map_err(js_error)?;
+    if first != second {
+        return Err(format!("repeated golden runs hashed to {} and {}", first, second));
+    }
+    Ok(format!("golden run hash {}", first))
+}
+
+/// Exercise the engine in the current environment and report what works
+///
+/// Checks the Lisp interpreter, RNG determinism, state and registry
+/// serialization round-trips, timing facilities and a repeated golden run.
+/// Returns JSON with `passed` (every check passed) and `checks`, each with
+/// its `name`, `passed` and a `detail` or error message, suitable for
+/// attaching to a support ticket.
+#[wasm_bindgen(js_name = runDiagnostics)]
+pub fn run_diagnostics() -> String {
+    let _call = call_log::enter("runDiagnostics", || serde_json::json!({}));
+    let checks: [(&str, Check); 6] = [
+        ("interpreter", check_interpreter),
+        ("rng-determinism", check_rng),
+        ("state-round-trip", check_state_round_trip),
+        ("registry-round-trip", check_registry_round_trip),
+        ("timing", check_timing),
+        ("golden-run", check_golden_run),
+    ];
+    
+    let results: Vec<serde_json::Value> = checks
+        .iter()
+        .map(|(name, check)| {
+            let (passed, detail) = match check() {
+                Ok(detail) => (true, detail),
+                Err(error) => (false, error),
+            };
+            serde_json::json!({ "name": name, "passed": passed, "detail": detail })
+        })
+        .collect();
+    let passed = results.iter().all(|r| r["passed"] == true);
+    
+    serde_json::json!({
+        "passed": passed,
+        "engineVersion": env!("CARGO_PKG_VERSION"),
+        "checks": results,
+    })
+    .to_string()
+}
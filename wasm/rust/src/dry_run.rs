@@ -0,0 +1,71 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::lisp::Program;
+use crate::{apply_rule_logic, OrganismState, RuleRegistry, CORE_METRICS};
+
+/// Every core and custom metric of a state, by canonical name
+fn metric_values(state: &OrganismState) -> BTreeMap<String, f64> {
+    let mut metrics: BTreeMap<String, f64> = CORE_METRICS
+        .iter()
+        .filter_map(|metric| Some((metric.to_string(), state.metric(metric)?)))
+        .collect();
+    metrics.extend(state.custom_metrics.iter().map(|(name, value)| (name.clone(), *value)));
+    metrics
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Run a rule against a copy of `state` and return what it would change, as JSON
+    ///
+    /// Returns `{ ruleId, result, error, changes }`, where `changes` maps
+    /// each metric the rule would change to `{ before, after, delta }`. A
+    /// rule that fails reports its error with no changes instead of
+    /// throwing. Neither `state` nor the rule's execution statistics are
+    /// touched, and shadow candidates don't run, so editors can try rules
+    /// on live state safely.
+    #[wasm_bindgen(js_name = dryRunRule)]
+    pub fn dry_run_rule(&self, state: &OrganismState, rule_id: &str, params: Vec<f64>) -> Result<String, JsValue> {
+        let rule = self.rules.get(rule_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", rule_id)))?;
+        
+        // Rules loaded from a snapshot haven't been compiled yet
+        let compiled;
+        let program = if rule.program.is_compiled() {
+            &rule.program
+        } else {
+            compiled = Program::compile(&rule.lisp_code);
+            &compiled
+        };
+        
+        let mut copy = state.clone();
+        let (result, error) = match apply_rule_logic(program, &mut copy, &params, self.budget(rule_id)) {
+            Ok(result) => (Some(result), None),
+            Err(e) => (None, Some(e)),
+        };
+        
+        let before = metric_values(state);
+        let changes: serde_json::Map<String, serde_json::Value> = metric_values(&copy)
+            .into_iter()
+            .filter(|(metric, after)| before.get(metric).is_none_or(|old| old.to_bits() != after.to_bits()))
+            .map(|(metric, after)| {
+                let old = before.get(&metric).copied();
+                let change = serde_json::json!({
+                    "before": old,
+                    "after": after,
+                    "delta": old.map(|old| after - old),
+                });
+                (metric, change)
+            })
+            .collect();
+        
+        let report = serde_json::json!({
+            "ruleId": rule_id,
+            "result": result,
+            "error": error,
+            "changes": changes,
+        });
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize dry run: {}", e)))
+    }
+}
@@ -0,0 +1,370 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::call_log;
+use crate::lisp::{self, Node};
+use crate::memory;
+use crate::solver::SolverKind;
+use crate::{canonical_metric, OrganismState};
+
+/// Name of the built-in group integrating population and energy
+pub(crate) const CORE_GROUP: &str = "core";
+
+/// Metrics owned by the core group; user groups can't integrate them
+const CORE_GROUP_METRICS: [&str; 2] = ["population", "energy"];
+
+/// Energy regenerated per unit time by `step`; `stepIn` harvests up to this from an environment
+pub(crate) const ENERGY_REGEN: f64 = 10.0;
+
+/// Derivatives of the core group (`[population, energy]`) used by `step`
+pub(crate) fn core_derivatives(y: &[f64], mutation_rate: f64, energy_regen: f64) -> Vec<f64> {
+    let (population, energy) = (y[0], y[1]);
+    let growth_rate = (energy / 1000.0) * (1.0 - mutation_rate);
+    vec![population * growth_rate, energy_regen - population * 0.1]
+}
+
+/// One metric and the Lisp expression giving its rate of change
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Equation {
+    metric: String,
+    derivative: String,
+    #[serde(skip)]
+    forms: Vec<Node>,
+}
+
+/// Metrics integrated together by one solver
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct MetricGroup {
+    name: String,
+    solver: SolverKind,
+    equations: Vec<Equation>,
+}
+
+impl MetricGroup {
+    /// Parse derivative code that isn't parsed yet (e.g. after loading a snapshot)
+    fn prepare(&mut self) -> Result<(), String> {
+        for equation in &mut self.equations {
+            if equation.forms.is_empty() {
+                equation.forms = lisp::parse(&equation.derivative)
+                    .map_err(|e| format!("{}:{}: {}", e.span.line, e.span.column, e.message))?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Heap bytes of the group's parse cache
+    pub(crate) fn forms_bytes(&self) -> usize {
+        self.equations.iter().map(|eq| memory::forms_bytes(&eq.forms)).sum()
+    }
+    
+    /// The group with its parse cache (forms per equation, 0 when not parsed yet), for debug dumps
+    pub(crate) fn debug_json(&self) -> serde_json::Value {
+        let equations: Vec<serde_json::Value> = self.equations
+            .iter()
+            .map(|eq| serde_json::json!({
+                "metric": eq.metric,
+                "derivative": eq.derivative,
+                "cachedForms": eq.forms.len(),
+            }))
+            .collect();
+        serde_json::json!({
+            "name": self.name,
+            "solver": self.solver.as_str(),
+            "equations": equations,
+        })
+    }
+}
+
+/// Check that `name` can name a custom metric: a single Lisp symbol that isn't a core metric
+pub(crate) fn check_custom_metric_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.contains(char::is_whitespace) || name.contains(['(', ')', '"', ';']) {
+        return Err(format!("Invalid metric name: {:?}", name));
+    }
+    if canonical_metric(name).is_some() || lisp::lookup_metric(name).is_some() {
+        return Err(format!("{} is a core metric", name));
+    }
+    Ok(())
+}
+
+impl OrganismState {
+    /// Read a core or custom metric
+    pub(crate) fn read_any_metric(&self, name: &str) -> Option<f64> {
+        self.metric(name).or_else(|| self.custom_metrics.get(name).copied())
+    }
+    
+    pub(crate) fn write_any_metric(&mut self, name: &str, value: f64) {
+        if canonical_metric(name).is_some() {
+            let _ = self.set_metric(name, value);
+        } else if let Some(slot) = self.custom_metrics.get_mut(name) {
+            *slot = value;
+        }
+    }
+    
+    /// Advance one user group by `dt`, leaving its metrics unchanged on failure
+    fn advance_group(&mut self, group: &MetricGroup, dt: f64) -> Result<(), String> {
+        let start: Vec<f64> = group.equations
+            .iter()
+            .map(|eq| self.read_any_metric(&eq.metric).unwrap_or(0.0))
+            .collect();
+        
+        let result = {
+            let mut system = |y: &[f64]| -> Result<Vec<f64>, String> {
+                for (equation, value) in group.equations.iter().zip(y) {
+                    self.write_any_metric(&equation.metric, *value);
+                }
+                group.equations
+                    .iter()
+                    .map(|equation| match lisp::run(&equation.forms, self, &[], None) {
+                        Ok(lisp::Value::Number(rate)) => Ok(rate),
+                        Ok(other) => Err(format!(
+                            "derivative of {} returned {}, not a number",
+                            equation.metric, other.type_name()
+                        )),
+                        Err(e) => Err(format!("derivative of {}: {}", equation.metric, e.message)),
+                    })
+                    .collect()
+            };
+            group.solver.solver().advance(&mut system, &start, dt)
+        };
+        
+        let values = match result {
+            Ok(values) => values,
+            Err(e) => {
+                for (equation, value) in group.equations.iter().zip(&start) {
+                    self.write_any_metric(&equation.metric, *value);
+                }
+                return Err(e);
+            }
+        };
+        for (equation, value) in group.equations.iter().zip(values) {
+            self.write_any_metric(&equation.metric, value);
+        }
+        Ok(())
+    }
+    
+    /// Advance every user group by `dt` in definition order
+    pub(crate) fn step_dynamics(&mut self, dt: f64) {
+        let mut groups = std::mem::take(&mut self.dynamics);
+        for group in &mut groups {
+            let outcome = group.prepare().and_then(|()| self.advance_group(group, dt));
+            if let Err(e) = outcome {
+                self.dynamics_error = Some(format!("Dynamics group {} failed: {}", group.name, e));
+            }
+        }
+        self.dynamics = groups;
+    }
+    
+    /// Resolve a metric that can be written: a writable core metric (by its
+    /// canonical name) or a defined custom metric
+    pub(crate) fn writable_metric(&self, metric: &str) -> Result<String, String> {
+        match lisp::lookup_metric(metric) {
+            Some(core) if !core.writable => Err(format!("Metric {} is read-only", metric)),
+            Some(core) => Ok(core.canonical.to_string()),
+            None if self.custom_metrics.contains_key(metric) => Ok(metric.to_string()),
+            None => Err(format!("Unknown metric: {}", metric)),
+        }
+    }
+    
+    fn group_owning(&self, metric: &str) -> Option<&str> {
+        if CORE_GROUP_METRICS.contains(&metric) {
+            return Some(CORE_GROUP);
+        }
+        self.dynamics
+            .iter()
+            .find(|group| group.equations.iter().any(|eq| eq.metric == metric))
+            .map(|group| group.name.as_str())
+    }
+}
+
+/// Equation as given to `defineDynamics`
+#[derive(Deserialize)]
+struct EquationSpec {
+    metric: String,
+    derivative: String,
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Add a custom metric with an initial value, or reset an existing one
+    ///
+    /// Custom metrics are saved with the state and can be read by rule code
+    /// and by derivative expressions under their exact name.
+    #[wasm_bindgen(js_name = defineCustomMetric)]
+    pub fn define_custom_metric(&mut self, name: &str, value: f64) -> Result<(), JsValue> {
+        let _call = call_log::enter("defineCustomMetric", || serde_json::json!({
+            "name": name,
+            "value": value,
+        }));
+        check_custom_metric_name(name).map_err(|e| JsValue::from_str(&e))?;
+        self.custom_metrics.insert(name.to_string(), value);
+        Ok(())
+    }
+    
+    /// Get a custom metric (undefined when it isn't defined)
+    #[wasm_bindgen(js_name = getCustomMetric)]
+    pub fn get_custom_metric(&self, name: &str) -> Option<f64> {
+        self.custom_metrics.get(name).copied()
+    }
+    
+    /// Set a custom metric that was defined with `defineCustomMetric`
+    #[wasm_bindgen(js_name = setCustomMetric)]
+    pub fn set_custom_metric(&mut self, name: &str, value: f64) -> Result<(), JsValue> {
+        let _call = call_log::enter("setCustomMetric", || serde_json::json!({
+            "name": name,
+            "value": value,
+        }));
+        let slot = self.custom_metrics.get_mut(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Custom metric not defined: {}", name)))?;
+        *slot = value;
+        Ok(())
+    }
+    
+    /// Remove a custom metric, along with its metadata and any equation integrating it
+    #[wasm_bindgen(js_name = removeCustomMetric)]
+    pub fn remove_custom_metric(&mut self, name: &str) -> bool {
+        for group in &mut self.dynamics {
+            group.equations.retain(|eq| eq.metric != name);
+        }
+        self.dynamics.retain(|group| !group.equations.is_empty());
+        self.metric_meta.remove(name);
+        self.custom_metrics.remove(name).is_some()
+    }
+    
+    /// Get all custom metrics as a JSON object
+    #[wasm_bindgen(js_name = getCustomMetrics)]
+    pub fn get_custom_metrics(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.custom_metrics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize custom metrics: {}", e)))
+    }
+    
+    /// Define or replace a group of metric dynamics integrated by `step`
+    ///
+    /// `equations_json` is an array of `{ metric, derivative }`, where
+    /// `derivative` is Lisp code giving the metric's rate of change, e.g.
+    /// `(- (* 0.01 population) (* 0.5 toxin))`. Metrics may be custom ones
+    /// or writable core metrics other than population and energy, which
+    /// belong to the "core" group. Groups run after the core group, in
+    /// definition order, each with its own `solver`.
+    #[wasm_bindgen(js_name = defineDynamics)]
+    pub fn define_dynamics(&mut self, group: &str, solver: &str, equations_json: &str) -> Result<(), JsValue> {
+        let _call = call_log::enter("defineDynamics", || serde_json::json!({
+            "group": group,
+            "solver": solver,
+            "equations": call_log::text(equations_json),
+        }));
+        if group.is_empty() || group == CORE_GROUP {
+            return Err(JsValue::from_str(&format!("Invalid dynamics group name: {:?}", group)));
+        }
+        let solver = SolverKind::parse(solver).map_err(|e| JsValue::from_str(&e))?;
+        let specs: Vec<EquationSpec> = serde_json::from_str(equations_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse equations: {}", e)))?;
+        if specs.is_empty() {
+            return Err(JsValue::from_str("A dynamics group needs at least one equation"));
+        }
+        
+        let mut equations: Vec<Equation> = Vec::with_capacity(specs.len());
+        for spec in specs {
+            let metric = self.writable_metric(&spec.metric).map_err(|e| JsValue::from_str(&e))?;
+            if let Some(owner) = self.group_owning(&metric).filter(|owner| *owner != group) {
+                return Err(JsValue::from_str(&format!(
+                    "Metric {} is already integrated by group {}",
+                    metric, owner
+                )));
+            }
+            if equations.iter().any(|eq| eq.metric == metric) {
+                return Err(JsValue::from_str(&format!("Metric {} has more than one equation", metric)));
+            }
+            
+            let forms = lisp::parse(&spec.derivative).map_err(|e| {
+                JsValue::from_str(&format!(
+                    "Failed to parse derivative of {} at {}:{}: {}",
+                    metric, e.span.line, e.span.column, e.message
+                ))
+            })?;
+            if !lisp::analyze_access(&spec.derivative).writes.is_empty() {
+                return Err(JsValue::from_str(&format!("Derivative of {} must not write metrics", metric)));
+            }
+            
+            equations.push(Equation { metric, derivative: spec.derivative, forms });
+        }
+        
+        let defined = MetricGroup { name: group.to_string(), solver, equations };
+        match self.dynamics.iter_mut().find(|g| g.name == group) {
+            Some(existing) => *existing = defined,
+            None => self.dynamics.push(defined),
+        }
+        Ok(())
+    }
+    
+    /// Remove a dynamics group
+    #[wasm_bindgen(js_name = removeDynamics)]
+    pub fn remove_dynamics(&mut self, group: &str) -> bool {
+        let before = self.dynamics.len();
+        self.dynamics.retain(|g| g.name != group);
+        self.dynamics.len() != before
+    }
+    
+    /// Choose the solver of a group ("core" or a group from `defineDynamics`)
+    ///
+    /// Solvers: "semi-implicit-euler" (the default), "euler", "heun", "rk4"
+    /// and "implicit-euler" for stiff dynamics.
+    #[wasm_bindgen(js_name = setSolver)]
+    pub fn set_solver(&mut self, group: &str, solver: &str) -> Result<(), JsValue> {
+        let solver = SolverKind::parse(solver).map_err(|e| JsValue::from_str(&e))?;
+        if group == CORE_GROUP {
+            self.core_solver = solver;
+            return Ok(());
+        }
+        let group = self.dynamics.iter_mut().find(|g| g.name == group)
+            .ok_or_else(|| JsValue::from_str(&format!("Dynamics group not found: {}", group)))?;
+        group.solver = solver;
+        Ok(())
+    }
+    
+    /// Get the solver of a group
+    #[wasm_bindgen(js_name = getSolver)]
+    pub fn get_solver(&self, group: &str) -> Result<String, JsValue> {
+        if group == CORE_GROUP {
+            return Ok(self.core_solver.as_str().to_string());
+        }
+        self.dynamics.iter()
+            .find(|g| g.name == group)
+            .map(|g| g.solver.as_str().to_string())
+            .ok_or_else(|| JsValue::from_str(&format!("Dynamics group not found: {}", group)))
+    }
+    
+    /// Get all dynamics groups, "core" first, as JSON (`group`, `solver`, `equations`)
+    #[wasm_bindgen(js_name = getDynamics)]
+    pub fn get_dynamics(&self) -> Result<String, JsValue> {
+        let mut groups = vec![serde_json::json!({
+            "group": CORE_GROUP,
+            "solver": self.core_solver.as_str(),
+            "metrics": CORE_GROUP_METRICS,
+        })];
+        for group in &self.dynamics {
+            groups.push(serde_json::json!({
+                "group": group.name,
+                "solver": group.solver.as_str(),
+                "equations": group.equations.iter()
+                    .map(|eq| serde_json::json!({ "metric": eq.metric, "derivative": eq.derivative }))
+                    .collect::<Vec<_>>(),
+            }));
+        }
+        
+        serde_json::to_string(&groups)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize dynamics: {}", e)))
+    }
+    
+    /// Error from the last dynamics group that failed during `step`, if any
+    ///
+    /// A failing group keeps its previous values. Cleared by `clearDynamicsError`.
+    #[wasm_bindgen(getter, js_name = lastDynamicsError)]
+    pub fn last_dynamics_error(&self) -> Option<String> {
+        self.dynamics_error.clone()
+    }
+    
+    #[wasm_bindgen(js_name = clearDynamicsError)]
+    pub fn clear_dynamics_error(&mut self) {
+        self.dynamics_error = None;
+    }
+}
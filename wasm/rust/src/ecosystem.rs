@@ -0,0 +1,370 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::dynamics::ENERGY_REGEN;
+use crate::solver::SolverKind;
+use crate::OrganismState;
+
+/// Most species an ecosystem holds
+const MAX_SPECIES: usize = 256;
+
+fn default_population() -> f64 {
+    100.0
+}
+
+fn default_solver() -> SolverKind {
+    SolverKind::Rk4
+}
+
+fn default_extinction_threshold() -> f64 {
+    1.0
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct SpeciesSpec {
+    name: String,
+    #[serde(default = "default_population")]
+    population: f64,
+    /// Per-capita growth rate with no other species around
+    growth_rate: f64,
+    /// Starting energy; the state default when left out
+    #[serde(default)]
+    energy: Option<f64>,
+    #[serde(default)]
+    mutation_rate: Option<f64>,
+}
+
+/// How species affect each other: `a[i][j]` is the per-capita effect of one individual of `j` on the growth of `i`
+#[derive(Deserialize, Clone, Debug)]
+#[serde(untagged)]
+enum InteractionSpec {
+    /// Rows in species order
+    Matrix(Vec<Vec<f64>>),
+    /// `{ affected: { by: coefficient } }`; pairs left out are 0
+    Named(BTreeMap<String, BTreeMap<String, f64>>),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct EcosystemSpec {
+    species: Vec<SpeciesSpec>,
+    #[serde(default)]
+    interactions: Option<InteractionSpec>,
+    #[serde(default = "default_solver")]
+    solver: SolverKind,
+    #[serde(default = "default_extinction_threshold")]
+    extinction_threshold: f64,
+}
+
+impl EcosystemSpec {
+    fn build(self) -> Result<Ecosystem, String> {
+        let mut problems = Vec::new();
+        let n = self.species.len();
+        if n == 0 || n > MAX_SPECIES {
+            problems.push(format!("species must list 1 to {} species, got {}", MAX_SPECIES, n));
+        }
+        let mut index = BTreeMap::new();
+        for (i, spec) in self.species.iter().enumerate() {
+            if spec.name.trim().is_empty() {
+                problems.push(format!("species {} has no name", i));
+            } else if index.insert(spec.name.clone(), i).is_some() {
+                problems.push(format!("species {} is listed twice", spec.name));
+            }
+            if !spec.population.is_finite() || spec.population < 0.0 {
+                problems.push(format!("{}: population must be non-negative, got {}", spec.name, spec.population));
+            }
+            if !spec.growth_rate.is_finite() {
+                problems.push(format!("{}: growthRate must be finite, got {}", spec.name, spec.growth_rate));
+            }
+            if let Some(energy) = spec.energy.filter(|e| !e.is_finite() || *e < 0.0) {
+                problems.push(format!("{}: energy must be non-negative, got {}", spec.name, energy));
+            }
+            if let Some(rate) = spec.mutation_rate.filter(|r| !r.is_finite() || !(0.0..=1.0).contains(r)) {
+                problems.push(format!("{}: mutationRate must be between 0 and 1, got {}", spec.name, rate));
+            }
+        }
+        if !self.extinction_threshold.is_finite() || self.extinction_threshold < 0.0 {
+            problems.push(format!("extinctionThreshold must be non-negative, got {}", self.extinction_threshold));
+        }
+        
+        let mut interactions = vec![0.0; n * n];
+        match self.interactions {
+            None => {}
+            Some(InteractionSpec::Matrix(rows)) => {
+                if rows.len() != n || rows.iter().any(|row| row.len() != n) {
+                    problems.push(format!("interactions must be a {}x{} matrix", n, n));
+                } else {
+                    interactions = rows.concat();
+                }
+            }
+            Some(InteractionSpec::Named(named)) => {
+                for (affected, row) in &named {
+                    for (by, value) in row {
+                        match (index.get(affected), index.get(by)) {
+                            (Some(i), Some(j)) => interactions[i * n + j] = *value,
+                            _ => problems.push(format!(
+                                "interactions.{}.{}: unknown species",
+                                affected, by
+                            )),
+                        }
+                    }
+                }
+            }
+        }
+        if let Some(bad) = interactions.iter().find(|v| !v.is_finite()) {
+            problems.push(format!("interactions must be finite, got {}", bad));
+        }
+        if !problems.is_empty() {
+            return Err(problems.join("; "));
+        }
+        
+        let species = self.species
+            .into_iter()
+            .map(|spec| {
+                let mut state = OrganismState::new();
+                state.set_population(spec.population);
+                if let Some(energy) = spec.energy {
+                    state.set_energy(energy);
+                }
+                if let Some(rate) = spec.mutation_rate {
+                    state.set_mutation_rate(rate);
+                }
+                state.adaptation_score = state.calculate_adaptation_score();
+                Species { name: spec.name, growth_rate: spec.growth_rate, state }
+            })
+            .collect();
+        Ok(Ecosystem {
+            species,
+            interactions,
+            solver: self.solver,
+            extinction_threshold: self.extinction_threshold,
+            ticks: 0,
+            dynamics_error: None,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Species {
+    name: String,
+    growth_rate: f64,
+    state: OrganismState,
+}
+
+/// Ecosystem couples several species through generalized Lotka–Volterra dynamics
+///
+/// Each species has its own `OrganismState` and a growth rate `r`, and
+/// `step` integrates `dN_i/dt = N_i * (r_i + sum_j a_ij * N_j)` over all
+/// populations together. A negative `a_ij` means `j` harms `i` (a
+/// predator eating it, or a competitor), a positive one that it helps
+/// (prey feeding it, or a mutualist); `a_ii` is self-limitation. Energy
+/// follows the core equation of `OrganismState.step` per species.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Ecosystem {
+    species: Vec<Species>,
+    /// Row-major `a_ij`
+    interactions: Vec<f64>,
+    solver: SolverKind,
+    extinction_threshold: f64,
+    ticks: u64,
+    #[serde(skip)]
+    dynamics_error: Option<String>,
+}
+
+impl Ecosystem {
+    fn index(&self, name: &str) -> Result<usize, JsValue> {
+        self.species
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| JsValue::from_str(&format!("Species not found: {}", name)))
+    }
+    
+    fn derivatives(&self, populations: &[f64]) -> Vec<f64> {
+        let n = self.species.len();
+        (0..n)
+            .map(|i| {
+                let pressure: f64 = (0..n).map(|j| self.interactions[i * n + j] * populations[j]).sum();
+                populations[i] * (self.species[i].growth_rate + pressure)
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Ecosystem {
+    /// Create an ecosystem from JSON
+    ///
+    /// `json` is `{ species, interactions, solver, extinctionThreshold }`:
+    /// `species` lists `{ name, growthRate, population (100), energy,
+    /// mutationRate }`; `interactions` is either an n x n matrix in
+    /// species order or `{ affected: { by: coefficient } }` (pairs left
+    /// out are 0); `solver` is one of the core solvers (`rk4`); and a
+    /// population that falls below `extinctionThreshold` (1) is set to 0.
+    #[wasm_bindgen(constructor)]
+    pub fn new(json: &str) -> Result<Ecosystem, JsValue> {
+        let spec: EcosystemSpec = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse ecosystem: {}", e)))?;
+        spec.build().map_err(|e| JsValue::from_str(&format!("Invalid ecosystem: {}", e)))
+    }
+    
+    /// Advance every species by `delta_time` and return the total population
+    ///
+    /// If the solver fails the populations are left as they were and the
+    /// error is kept for `lastError`.
+    #[wasm_bindgen]
+    pub fn step(&mut self, delta_time: f64) -> f64 {
+        let current: Vec<f64> = self.species.iter().map(|s| s.state.population).collect();
+        let next = {
+            let mut system = |y: &[f64]| Ok(self.derivatives(y));
+            self.solver.solver().advance(&mut system, &current, delta_time)
+        };
+        match next {
+            Ok(next) => {
+                self.dynamics_error = None;
+                for (species, population) in self.species.iter_mut().zip(next) {
+                    let population = if population.is_finite() { population.max(0.0) } else { 0.0 };
+                    species.state.population = if population < self.extinction_threshold { 0.0 } else { population };
+                }
+            }
+            Err(e) => self.dynamics_error = Some(e),
+        }
+        
+        for species in &mut self.species {
+            let state = &mut species.state;
+            state.age += 1;
+            let slope = ENERGY_REGEN - state.population * 0.1;
+            state.energy = (state.energy + slope * delta_time).clamp(0.0, 10000.0);
+            state.sync_state_vector();
+            state.adaptation_score = state.calculate_adaptation_score();
+        }
+        self.ticks += 1;
+        self.species.iter().map(|s| s.state.population).sum()
+    }
+    
+    /// Steps taken
+    #[wasm_bindgen(getter)]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    
+    /// Why the last step left the populations unchanged, if it did
+    #[wasm_bindgen(getter, js_name = lastError)]
+    pub fn last_error(&self) -> Option<String> {
+        self.dynamics_error.clone()
+    }
+    
+    #[wasm_bindgen(getter, js_name = speciesCount)]
+    pub fn species_count(&self) -> usize {
+        self.species.len()
+    }
+    
+    /// Species names in matrix order
+    #[wasm_bindgen(js_name = getSpeciesNames)]
+    pub fn get_species_names(&self) -> Vec<String> {
+        self.species.iter().map(|s| s.name.clone()).collect()
+    }
+    
+    /// Every species' population in matrix order
+    #[wasm_bindgen(js_name = getPopulations)]
+    pub fn get_populations(&self) -> Vec<f64> {
+        self.species.iter().map(|s| s.state.population).collect()
+    }
+    
+    /// A copy of one species' state
+    #[wasm_bindgen(js_name = getSpeciesState)]
+    pub fn get_species_state(&self, name: &str) -> Result<OrganismState, JsValue> {
+        Ok(self.species[self.index(name)?].state.clone())
+    }
+    
+    /// Replace one species' state with a copy of `state`
+    #[wasm_bindgen(js_name = setSpeciesState)]
+    pub fn set_species_state(&mut self, name: &str, state: &OrganismState) -> Result<(), JsValue> {
+        let index = self.index(name)?;
+        self.species[index].state = state.clone();
+        Ok(())
+    }
+    
+    /// Per-species metrics as JSON: `[{name, growthRate, population, energy, age, mutationRate, adaptationScore}]`
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self) -> Result<String, JsValue> {
+        let metrics: Vec<serde_json::Value> = self.species
+            .iter()
+            .map(|s| serde_json::json!({
+                "name": s.name,
+                "growthRate": s.growth_rate,
+                "population": s.state.population,
+                "energy": s.state.energy,
+                "age": s.state.age,
+                "mutationRate": s.state.mutation_rate,
+                "adaptationScore": s.state.adaptation_score,
+            }))
+            .collect();
+        serde_json::to_string(&metrics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize ecosystem metrics: {}", e)))
+    }
+    
+    /// The per-capita effect of `by` on the growth of `affected`
+    #[wasm_bindgen]
+    pub fn interaction(&self, affected: &str, by: &str) -> Result<f64, JsValue> {
+        let (i, j) = (self.index(affected)?, self.index(by)?);
+        Ok(self.interactions[i * self.species.len() + j])
+    }
+    
+    #[wasm_bindgen(js_name = setInteraction)]
+    pub fn set_interaction(&mut self, affected: &str, by: &str, value: f64) -> Result<(), JsValue> {
+        if !value.is_finite() {
+            return Err(JsValue::from_str(&format!("Interaction must be finite, got {}", value)));
+        }
+        let (i, j) = (self.index(affected)?, self.index(by)?);
+        let n = self.species.len();
+        self.interactions[i * n + j] = value;
+        Ok(())
+    }
+    
+    /// The interaction matrix, row-major in species order
+    #[wasm_bindgen(js_name = getInteractions)]
+    pub fn get_interactions(&self) -> Vec<f64> {
+        self.interactions.clone()
+    }
+    
+    #[wasm_bindgen(js_name = growthRate)]
+    pub fn growth_rate(&self, name: &str) -> Result<f64, JsValue> {
+        Ok(self.species[self.index(name)?].growth_rate)
+    }
+    
+    #[wasm_bindgen(js_name = setGrowthRate)]
+    pub fn set_growth_rate(&mut self, name: &str, value: f64) -> Result<(), JsValue> {
+        if !value.is_finite() {
+            return Err(JsValue::from_str(&format!("Growth rate must be finite, got {}", value)));
+        }
+        let index = self.index(name)?;
+        self.species[index].growth_rate = value;
+        Ok(())
+    }
+    
+    /// Get the ecosystem as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize ecosystem: {}", e)))
+    }
+    
+    /// Restore the ecosystem from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let mut loaded: Ecosystem = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse ecosystem snapshot: {}", e)))?;
+        let n = loaded.species.len();
+        if n == 0 || loaded.interactions.len() != n * n {
+            return Err(JsValue::from_str("Failed to parse ecosystem snapshot: interaction matrix doesn't match the species"));
+        }
+        for species in &mut loaded.species {
+            species.state.sync_state_vector();
+        }
+        *self = loaded;
+        Ok(())
+    }
+}
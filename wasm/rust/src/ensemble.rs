@@ -0,0 +1,142 @@
+use wasm_bindgen::prelude::*;
+
+use crate::call_log;
+use crate::OrganismState;
+
+/// Two-sided 95% Student t critical values for 1 to 30 degrees of freedom
+const T_95: [f64; 30] = [
+    12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228,
+    2.201, 2.179, 2.160, 2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086,
+    2.080, 2.074, 2.069, 2.064, 2.060, 2.056, 2.052, 2.048, 2.045, 2.042,
+];
+
+/// Two-sided 95% t critical value, using the Cornish-Fisher expansion past the table
+fn t_critical_95(df: usize) -> f64 {
+    if df == 0 {
+        return f64::NAN;
+    }
+    if df <= T_95.len() {
+        return T_95[df - 1];
+    }
+    let z: f64 = 1.959_964;
+    let df = df as f64;
+    z + (z.powi(3) + z) / (4.0 * df) + (5.0 * z.powi(5) + 16.0 * z.powi(3) + 3.0 * z) / (96.0 * df * df)
+}
+
+/// Running mean, variance and range of one value across replicates (Welford)
+#[derive(Clone, Default)]
+struct Accumulator {
+    count: usize,
+    mean: f64,
+    m2: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Accumulator {
+    fn push(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+    
+    fn sd(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            (self.m2 / (self.count - 1) as f64).sqrt()
+        }
+    }
+    
+    /// Half-width of the 95% confidence interval of the mean
+    fn ci_half_width(&self) -> f64 {
+        if self.count < 2 {
+            0.0
+        } else {
+            t_critical_95(self.count - 1) * self.sd() / (self.count as f64).sqrt()
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Run `runs` replicates for `ticks` steps and summarize metrics over time
+    ///
+    /// Replicate `i` is a clone of this state with its noise RNG seeded with
+    /// `base_seed + i`; without noise terms every replicate is identical. The
+    /// result is JSON with, for each requested metric, arrays indexed by tick
+    /// (index 0 is the starting value): `mean`, `sd`, `ciLower` and `ciUpper`
+    /// (95% confidence interval of the mean), `min` and `max`. This state
+    /// isn't changed.
+    #[wasm_bindgen(js_name = runEnsemble)]
+    pub fn run_ensemble(
+        &self,
+        runs: u32,
+        ticks: u32,
+        delta_time: f64,
+        base_seed: u64,
+        metrics: Vec<String>,
+    ) -> Result<String, JsValue> {
+        let _call = call_log::enter("runEnsemble", || serde_json::json!({
+            "runs": runs,
+            "ticks": ticks,
+            "deltaTime": delta_time,
+            "baseSeed": base_seed,
+            "metrics": metrics,
+        }));
+        if runs == 0 {
+            return Err(JsValue::from_str("An ensemble needs at least one run"));
+        }
+        if let Some(unknown) = metrics.iter().find(|name| self.read_any_metric(name).is_none()) {
+            return Err(JsValue::from_str(&format!("Unknown metric: {}", unknown)));
+        }
+        
+        // One row of accumulators (one per metric) for each tick
+        let mut series = vec![vec![Accumulator::default(); metrics.len()]; ticks as usize + 1];
+        for run in 0..runs {
+            let mut state = self.clone();
+            state.set_noise_seed(base_seed.wrapping_add(run as u64));
+            for (tick, row) in series.iter_mut().enumerate() {
+                if tick > 0 {
+                    state.step(delta_time);
+                }
+                for (accumulator, name) in row.iter_mut().zip(&metrics) {
+                    accumulator.push(state.read_any_metric(name).unwrap_or(f64::NAN));
+                }
+            }
+        }
+        
+        let summary: serde_json::Map<String, serde_json::Value> = metrics
+            .iter()
+            .enumerate()
+            .map(|(index, name)| {
+                let column = |f: &dyn Fn(&Accumulator) -> f64| series.iter().map(|row| f(&row[index])).collect::<Vec<f64>>();
+                (name.clone(), serde_json::json!({
+                    "mean": column(&|a| a.mean),
+                    "sd": column(&|a| a.sd()),
+                    "ciLower": column(&|a| a.mean - a.ci_half_width()),
+                    "ciUpper": column(&|a| a.mean + a.ci_half_width()),
+                    "min": column(&|a| a.min),
+                    "max": column(&|a| a.max),
+                }))
+            })
+            .collect();
+        
+        let result = serde_json::json!({
+            "runs": runs,
+            "ticks": ticks,
+            "deltaTime": delta_time,
+            "metrics": summary,
+        });
+        serde_json::to_string(&result)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize ensemble: {}", e)))
+    }
+}
@@ -0,0 +1,447 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::dynamics::ENERGY_REGEN;
+use crate::rng::Rng;
+use crate::world::{foraging_efficiency, World};
+use crate::worldgen::GeneratedWorld;
+use crate::OrganismState;
+
+/// Largest grid an environment holds, in cells
+const MAX_CELLS: usize = 1 << 20;
+
+/// Largest share of the difference to a neighbor exchanged in one diffusion substep
+///
+/// The explicit scheme stays stable below 1/4 of it for four neighbors.
+const MAX_DIFFUSION_STEP: f64 = 0.2;
+
+fn default_diffusion_rate() -> f64 {
+    0.05
+}
+
+fn default_regrowth_rate() -> f64 {
+    0.1
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// Environment is a 2D grid of cells holding resources for organisms to harvest
+///
+/// `OrganismState.stepIn` and `World.stepIn` step against an environment
+/// instead of the fixed energy supply of `step`: what the organisms eat
+/// is taken from the cells, so a grid that is grazed bare stops feeding
+/// them. Between harvests resources diffuse to neighboring cells and
+/// regrow logistically towards each cell's capacity, so gradients form
+/// around rich ground and grazed cells recover. Grids are row-major,
+/// `y * width + x`.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Environment {
+    width: u32,
+    height: u32,
+    resources: Vec<f64>,
+    /// Resources each cell regrows towards; cells with none don't regrow
+    #[serde(default)]
+    capacity: Vec<f64>,
+    /// Share of the difference to each neighbor that flows per unit time
+    #[serde(default = "default_diffusion_rate")]
+    diffusion_rate: f64,
+    /// Logistic growth rate per unit time
+    #[serde(default = "default_regrowth_rate")]
+    regrowth_rate: f64,
+    /// Multiplier on every cell's capacity, e.g. set by an `EnvironmentCycle`
+    #[serde(default = "default_scale")]
+    capacity_scale: f64,
+    /// Multiplier on the regrowth rate
+    #[serde(default = "default_scale")]
+    regrowth_scale: f64,
+}
+
+impl Environment {
+    fn index(&self, x: u32, y: u32) -> Result<usize, JsValue> {
+        if x >= self.width || y >= self.height {
+            return Err(JsValue::from_str(&format!(
+                "Cell ({}, {}) is outside the {}x{} environment",
+                x, y, self.width, self.height
+            )));
+        }
+        Ok(y as usize * self.width as usize + x as usize)
+    }
+    
+    /// Check a whole grid of non-negative values, naming them `what` in errors
+    fn check_grid(&self, what: &str, values: &[f64]) -> Result<(), JsValue> {
+        if values.len() != self.resources.len() {
+            return Err(JsValue::from_str(&format!(
+                "Expected {} {} values for a {}x{} environment, got {}",
+                self.resources.len(), what, self.width, self.height, values.len()
+            )));
+        }
+        if let Some(bad) = values.iter().find(|value| !value.is_finite() || **value < 0.0) {
+            return Err(JsValue::from_str(&format!("Every {} value must be non-negative, got {}", what, bad)));
+        }
+        Ok(())
+    }
+    
+    /// Take up to `amount` from one cell and return what was taken
+    fn take(&mut self, index: usize, amount: f64) -> f64 {
+        let taken = amount.clamp(0.0, self.resources[index]);
+        self.resources[index] -= taken;
+        taken
+    }
+    
+    /// Take up to `amount` from the whole grid, from each cell in proportion to what it holds
+    fn take_spread(&mut self, amount: f64) -> f64 {
+        let total: f64 = self.resources.iter().sum();
+        if total <= 0.0 || amount <= 0.0 {
+            return 0.0;
+        }
+        let fraction = (amount / total).min(1.0);
+        for cell in &mut self.resources {
+            *cell -= *cell * fraction;
+        }
+        total * fraction
+    }
+    
+    /// The richest of a cell and its four neighbors, staying put on ties
+    fn richest_near(&self, (x, y): (u32, u32)) -> (u32, u32) {
+        let mut best = (x, y);
+        let mut most = self.resources[y as usize * self.width as usize + x as usize];
+        let neighbors = [
+            (x.wrapping_sub(1), y),
+            (x + 1, y),
+            (x, y.wrapping_sub(1)),
+            (x, y + 1),
+        ];
+        for (nx, ny) in neighbors {
+            if nx < self.width && ny < self.height {
+                let amount = self.resources[ny as usize * self.width as usize + nx as usize];
+                if amount > most {
+                    best = (nx, ny);
+                    most = amount;
+                }
+            }
+        }
+        best
+    }
+    
+    /// Diffuse and regrow resources over `dt`
+    pub(crate) fn advance(&mut self, dt: f64) {
+        if !dt.is_finite() || dt <= 0.0 {
+            return;
+        }
+        self.diffuse(dt);
+        self.regrow(dt);
+    }
+    
+    /// Exchange resources with the four neighbors; nothing flows off the edges
+    fn diffuse(&mut self, dt: f64) {
+        let flow = self.diffusion_rate * dt;
+        if flow <= 0.0 {
+            return;
+        }
+        let substeps = (flow / MAX_DIFFUSION_STEP).ceil().max(1.0) as usize;
+        let share = flow / substeps as f64;
+        let (width, height) = (self.width as usize, self.height as usize);
+        let mut next = self.resources.clone();
+        for _ in 0..substeps {
+            for y in 0..height {
+                for x in 0..width {
+                    let index = y * width + x;
+                    let here = self.resources[index];
+                    let mut exchange = 0.0;
+                    if x > 0 {
+                        exchange += self.resources[index - 1] - here;
+                    }
+                    if x + 1 < width {
+                        exchange += self.resources[index + 1] - here;
+                    }
+                    if y > 0 {
+                        exchange += self.resources[index - width] - here;
+                    }
+                    if y + 1 < height {
+                        exchange += self.resources[index + width] - here;
+                    }
+                    next[index] = (here + share * exchange).max(0.0);
+                }
+            }
+            std::mem::swap(&mut self.resources, &mut next);
+        }
+    }
+    
+    /// Logistic regrowth towards the scaled capacity, solved exactly so large steps don't overshoot
+    fn regrow(&mut self, dt: f64) {
+        let rate = self.regrowth_rate * self.regrowth_scale;
+        if rate <= 0.0 || self.capacity.len() != self.resources.len() {
+            return;
+        }
+        let decay = (-rate * dt).exp();
+        for (amount, &capacity) in self.resources.iter_mut().zip(&self.capacity) {
+            let capacity = capacity * self.capacity_scale;
+            if capacity > 0.0 && *amount > 0.0 {
+                *amount = capacity / (1.0 + (capacity - *amount) / *amount * decay);
+            }
+        }
+    }
+    
+    fn random_cell(&self, rng: &mut Rng) -> (u32, u32) {
+        let pick = |rng: &mut Rng, len: u32| ((rng.next_f64() * len as f64) as u32).min(len - 1);
+        (pick(rng, self.width), pick(rng, self.height))
+    }
+}
+
+#[wasm_bindgen]
+impl Environment {
+    /// Create a `width` x `height` grid with `initial_resource` in every cell, which is also each cell's capacity
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32, initial_resource: f64) -> Result<Environment, JsValue> {
+        if width == 0 || height == 0 {
+            return Err(JsValue::from_str("Environment width and height must be positive"));
+        }
+        if (width as usize).saturating_mul(height as usize) > MAX_CELLS {
+            return Err(JsValue::from_str(&format!("Environment must have at most {} cells", MAX_CELLS)));
+        }
+        if !initial_resource.is_finite() || initial_resource < 0.0 {
+            return Err(JsValue::from_str(&format!("Resources must be non-negative, got {}", initial_resource)));
+        }
+        Ok(Environment {
+            width,
+            height,
+            resources: vec![initial_resource; width as usize * height as usize],
+            capacity: vec![initial_resource; width as usize * height as usize],
+            diffusion_rate: default_diffusion_rate(),
+            regrowth_rate: default_regrowth_rate(),
+            capacity_scale: 1.0,
+            regrowth_scale: 1.0,
+        })
+    }
+    
+    /// Create an environment holding a generated world's resources, which are also the cells' capacities
+    #[wasm_bindgen(js_name = fromGenerated)]
+    pub fn from_generated(world: &GeneratedWorld) -> Environment {
+        Environment {
+            width: world.width(),
+            height: world.height(),
+            resources: world.resources().to_vec(),
+            capacity: world.resources().to_vec(),
+            diffusion_rate: default_diffusion_rate(),
+            regrowth_rate: default_regrowth_rate(),
+            capacity_scale: 1.0,
+            regrowth_scale: 1.0,
+        }
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    
+    /// Share of the difference to each neighbor that diffuses per unit time
+    #[wasm_bindgen(getter, js_name = diffusionRate)]
+    pub fn diffusion_rate(&self) -> f64 {
+        self.diffusion_rate
+    }
+    
+    #[wasm_bindgen(setter, js_name = diffusionRate)]
+    pub fn set_diffusion_rate(&mut self, value: f64) {
+        if value.is_finite() {
+            self.diffusion_rate = value.max(0.0);
+        }
+    }
+    
+    /// Logistic regrowth rate per unit time
+    #[wasm_bindgen(getter, js_name = regrowthRate)]
+    pub fn regrowth_rate(&self) -> f64 {
+        self.regrowth_rate
+    }
+    
+    #[wasm_bindgen(setter, js_name = regrowthRate)]
+    pub fn set_regrowth_rate(&mut self, value: f64) {
+        if value.is_finite() {
+            self.regrowth_rate = value.max(0.0);
+        }
+    }
+    
+    /// Multiplier on every cell's capacity, 1 unless something such as a seasonal cycle changes it
+    #[wasm_bindgen(getter, js_name = capacityScale)]
+    pub fn capacity_scale(&self) -> f64 {
+        self.capacity_scale
+    }
+    
+    #[wasm_bindgen(setter, js_name = capacityScale)]
+    pub fn set_capacity_scale(&mut self, value: f64) {
+        if value.is_finite() {
+            self.capacity_scale = value.max(0.0);
+        }
+    }
+    
+    /// Multiplier on `regrowthRate`
+    #[wasm_bindgen(getter, js_name = regrowthScale)]
+    pub fn regrowth_scale(&self) -> f64 {
+        self.regrowth_scale
+    }
+    
+    #[wasm_bindgen(setter, js_name = regrowthScale)]
+    pub fn set_regrowth_scale(&mut self, value: f64) {
+        if value.is_finite() {
+            self.regrowth_scale = value.max(0.0);
+        }
+    }
+    
+    /// Diffuse and regrow resources by `delta_time` without anyone harvesting
+    ///
+    /// `stepIn` does this after every harvest.
+    #[wasm_bindgen]
+    pub fn update(&mut self, delta_time: f64) {
+        self.advance(delta_time);
+    }
+    
+    /// Resources summed over every cell
+    #[wasm_bindgen(getter, js_name = totalResources)]
+    pub fn total_resources(&self) -> f64 {
+        self.resources.iter().sum()
+    }
+    
+    #[wasm_bindgen(js_name = resourceAt)]
+    pub fn resource_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        Ok(self.resources[self.index(x, y)?])
+    }
+    
+    #[wasm_bindgen(js_name = setResource)]
+    pub fn set_resource(&mut self, x: u32, y: u32, value: f64) -> Result<(), JsValue> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(JsValue::from_str(&format!("Resources must be non-negative, got {}", value)));
+        }
+        let index = self.index(x, y)?;
+        self.resources[index] = value;
+        Ok(())
+    }
+    
+    /// Take up to `amount` from a cell and return what it held to give
+    #[wasm_bindgen]
+    pub fn harvest(&mut self, x: u32, y: u32, amount: f64) -> Result<f64, JsValue> {
+        let index = self.index(x, y)?;
+        Ok(self.take(index, if amount.is_finite() { amount } else { 0.0 }))
+    }
+    
+    #[wasm_bindgen(js_name = capacityAt)]
+    pub fn capacity_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        Ok(self.capacity[self.index(x, y)?])
+    }
+    
+    #[wasm_bindgen(js_name = setCapacity)]
+    pub fn set_capacity(&mut self, x: u32, y: u32, value: f64) -> Result<(), JsValue> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(JsValue::from_str(&format!("Capacity must be non-negative, got {}", value)));
+        }
+        let index = self.index(x, y)?;
+        self.capacity[index] = value;
+        Ok(())
+    }
+    
+    /// Capacity of every cell
+    #[wasm_bindgen(js_name = getCapacities)]
+    pub fn get_capacities(&self) -> Vec<f64> {
+        self.capacity.clone()
+    }
+    
+    /// Resources in every cell, for rendering as a heatmap
+    #[wasm_bindgen(js_name = getResources)]
+    pub fn get_resources(&self) -> Vec<f64> {
+        self.resources.clone()
+    }
+    
+    /// Replace every cell's resources; `resources` must hold `width * height` values
+    #[wasm_bindgen(js_name = setResources)]
+    pub fn set_resources(&mut self, resources: Vec<f64>) -> Result<(), JsValue> {
+        self.check_grid("resource", &resources)?;
+        self.resources = resources;
+        Ok(())
+    }
+    
+    /// Replace every cell's capacity; `capacities` must hold `width * height` values
+    #[wasm_bindgen(js_name = setCapacities)]
+    pub fn set_capacities(&mut self, capacities: Vec<f64>) -> Result<(), JsValue> {
+        self.check_grid("capacity", &capacities)?;
+        self.capacity = capacities;
+        Ok(())
+    }
+    
+    /// Get the environment as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize environment: {}", e)))
+    }
+    
+    /// Restore the environment from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let mut loaded: Environment = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse environment snapshot: {}", e)))?;
+        let cells = loaded.width as usize * loaded.height as usize;
+        if cells == 0 || loaded.resources.len() != cells {
+            return Err(JsValue::from_str("Failed to parse environment snapshot: grid size doesn't match its dimensions"));
+        }
+        if loaded.capacity.len() != cells {
+            // Snapshots from before capacities regrow towards what the cells held
+            loaded.capacity = loaded.resources.clone();
+        }
+        *self = loaded;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Perform one step, harvesting energy from `environment` instead of regenerating it
+    ///
+    /// The population takes up to the energy `step` would regenerate from
+    /// the grid, from each cell in proportion to what it holds, and only
+    /// what it got flows in; the environment then diffuses and regrows.
+    /// Returns the adaptation score, as `step` does.
+    #[wasm_bindgen(js_name = stepIn)]
+    pub fn step_in(&mut self, environment: &mut Environment, delta_time: f64) -> f64 {
+        let dt = if delta_time.is_finite() { delta_time.max(0.0) } else { 0.0 };
+        let harvested = environment.take_spread(ENERGY_REGEN * dt);
+        let energy_regen = if dt > 0.0 { harvested / dt } else { 0.0 };
+        let score = self.step_with_regen(delta_time, energy_regen);
+        environment.advance(dt);
+        score
+    }
+}
+
+#[wasm_bindgen]
+impl World {
+    /// Step every organism by `delta_time`, feeding them from `environment`, and return how many are alive
+    ///
+    /// Instead of sharing `foodPerStep`, each organism moves to the richest
+    /// of its cell and the four next to it (staying on ties) and harvests
+    /// up to `appetite` per unit time there, scaled by its genome as
+    /// foraging is. Organisms without a cell, or with one outside this
+    /// grid, are placed at random first; children start in their parent's.
+    /// The environment diffuses and regrows once everyone has eaten.
+    #[wasm_bindgen(js_name = stepIn)]
+    pub fn step_in(&mut self, environment: &mut Environment, delta_time: f64) -> usize {
+        let dt = if delta_time.is_finite() { delta_time.max(0.0) } else { 0.0 };
+        let appetite = self.options.appetite * dt;
+        let alive = self.advance(dt, |organism, rng| {
+            let cell = match organism.cell {
+                Some((x, y)) if x < environment.width && y < environment.height => (x, y),
+                _ => environment.random_cell(rng),
+            };
+            let (x, y) = environment.richest_near(cell);
+            organism.cell = Some((x, y));
+            let index = y as usize * environment.width as usize + x as usize;
+            environment.take(index, appetite * foraging_efficiency(&organism.genome))
+        });
+        environment.advance(dt);
+        alive
+    }
+}
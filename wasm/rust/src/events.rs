@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Default number of events kept before the oldest are dropped
+const DEFAULT_CAPACITY: usize = 1024;
+
+/// A single recorded simulation event
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SimEvent {
+    pub seq: u64,
+    pub tick: u64,
+    pub kind: String,
+    pub data: serde_json::Value,
+}
+
+/// Bounded log of simulation events (conflicts, shocks, completions, ...)
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EventLog {
+    events: VecDeque<SimEvent>,
+    capacity: usize,
+    next_seq: u64,
+}
+
+impl EventLog {
+    pub fn new() -> EventLog {
+        EventLog::with_capacity(DEFAULT_CAPACITY)
+    }
+    
+    pub fn with_capacity(capacity: usize) -> EventLog {
+        EventLog {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            next_seq: 0,
+        }
+    }
+    
+    /// Record an event, evicting the oldest one when the log is full
+    pub fn record(&mut self, tick: u64, kind: &str, data: serde_json::Value) -> u64 {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        
+        if self.events.len() == self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(SimEvent {
+            seq,
+            tick,
+            kind: kind.to_string(),
+            data,
+        });
+        
+        seq
+    }
+    
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+    
+    /// Serialize the events with a sequence number of at least `since_seq`
+    pub fn to_json_since(&self, since_seq: u64) -> Result<String, serde_json::Error> {
+        let selected: Vec<&SimEvent> = self.events.iter().filter(|e| e.seq >= since_seq).collect();
+        serde_json::to_string(&selected)
+    }
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
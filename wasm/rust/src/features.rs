@@ -0,0 +1,172 @@
+use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::accumulate::CompensatedSum;
+use crate::clock;
+
+/// Subsystem that can be switched off at runtime
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Feature {
+    CallLog,
+    Dynamics,
+    Hotspot,
+    Invariants,
+    Noise,
+    Shadow,
+    Tracing,
+}
+
+const ALL_FEATURES: [Feature; 7] = [
+    Feature::CallLog,
+    Feature::Dynamics,
+    Feature::Hotspot,
+    Feature::Invariants,
+    Feature::Noise,
+    Feature::Shadow,
+    Feature::Tracing,
+];
+
+impl Feature {
+    fn as_str(self) -> &'static str {
+        match self {
+            Feature::CallLog => "call-log",
+            Feature::Dynamics => "dynamics",
+            Feature::Hotspot => "hotspot",
+            Feature::Invariants => "invariants",
+            Feature::Noise => "noise",
+            Feature::Shadow => "shadow",
+            Feature::Tracing => "tracing",
+        }
+    }
+    
+    fn parse(name: &str) -> Result<Feature, String> {
+        ALL_FEATURES.iter().copied().find(|f| f.as_str() == name).ok_or_else(|| {
+            let names: Vec<&str> = ALL_FEATURES.iter().map(|f| f.as_str()).collect();
+            format!("Unknown feature: {} (expected one of {})", name, names.join(", "))
+        })
+    }
+}
+
+/// Time spent in one feature while profiling
+#[derive(Default)]
+struct Overhead {
+    calls: u64,
+    total_ms: CompensatedSum,
+}
+
+#[derive(Default)]
+struct FeatureState {
+    disabled: BTreeSet<Feature>,
+    profiling: bool,
+    overhead: BTreeMap<Feature, Overhead>,
+}
+
+thread_local! {
+    static FEATURES: RefCell<FeatureState> = RefCell::new(FeatureState::default());
+}
+
+/// Whether a feature is switched on (all are by default)
+pub(crate) fn enabled(feature: Feature) -> bool {
+    FEATURES.with(|f| !f.borrow().disabled.contains(&feature))
+}
+
+/// Run a feature's work, adding its duration to the feature's overhead while profiling
+///
+/// The clock is only read while profiling, so a manual clock with
+/// auto-advance isn't moved by feature bookkeeping otherwise.
+pub(crate) fn measure<T>(feature: Feature, work: impl FnOnce() -> T) -> T {
+    if !FEATURES.with(|f| f.borrow().profiling) {
+        return work();
+    }
+    let start = clock::now_ms();
+    let result = work();
+    let elapsed = clock::now_ms() - start;
+    FEATURES.with(|f| {
+        let mut state = f.borrow_mut();
+        let overhead = state.overhead.entry(feature).or_default();
+        overhead.calls += 1;
+        overhead.total_ms.add(elapsed);
+    });
+    result
+}
+
+/// Features switches engine subsystems on and off at runtime
+///
+/// Every feature is on by default. Switching one off skips its work
+/// entirely: "noise" and "dynamics" stop stochastic terms and user metric
+/// dynamics in `step`, "hotspot" stops latency tracking, "invariants" stops
+/// the debug-mode checks in `tick`, "shadow" stops shadow runs, "call-log"
+/// stops recording calls and "tracing" makes `traceRule` throw. With
+/// profiling on, the time spent in each feature is reported.
+#[wasm_bindgen]
+pub struct Features;
+
+#[wasm_bindgen]
+impl Features {
+    #[wasm_bindgen]
+    pub fn enable(name: &str) -> Result<(), JsValue> {
+        let feature = Feature::parse(name).map_err(|e| JsValue::from_str(&e))?;
+        FEATURES.with(|f| f.borrow_mut().disabled.remove(&feature));
+        Ok(())
+    }
+    
+    #[wasm_bindgen]
+    pub fn disable(name: &str) -> Result<(), JsValue> {
+        let feature = Feature::parse(name).map_err(|e| JsValue::from_str(&e))?;
+        FEATURES.with(|f| f.borrow_mut().disabled.insert(feature));
+        Ok(())
+    }
+    
+    #[wasm_bindgen(js_name = isEnabled)]
+    pub fn is_enabled(name: &str) -> Result<bool, JsValue> {
+        let feature = Feature::parse(name).map_err(|e| JsValue::from_str(&e))?;
+        Ok(enabled(feature))
+    }
+    
+    /// Names of every feature
+    #[wasm_bindgen]
+    pub fn list() -> Vec<String> {
+        ALL_FEATURES.iter().map(|f| f.as_str().to_string()).collect()
+    }
+    
+    /// Turn measuring the time spent in each feature on or off
+    #[wasm_bindgen(js_name = setProfiling)]
+    pub fn set_profiling(enabled: bool) {
+        FEATURES.with(|f| f.borrow_mut().profiling = enabled);
+    }
+    
+    /// Forget measured overhead
+    #[wasm_bindgen(js_name = resetOverhead)]
+    pub fn reset_overhead() {
+        FEATURES.with(|f| f.borrow_mut().overhead.clear());
+    }
+    
+    /// Get every feature as JSON (`[{ name, enabled, calls, totalMs, meanMs }]`)
+    ///
+    /// `calls` and the timings only cover the time profiling was on.
+    #[wasm_bindgen]
+    pub fn report() -> Result<String, JsValue> {
+        let report: Vec<serde_json::Value> = FEATURES.with(|f| {
+            let state = f.borrow();
+            ALL_FEATURES
+                .iter()
+                .map(|feature| {
+                    let (calls, total_ms) = state.overhead
+                        .get(feature)
+                        .map_or((0, 0.0), |o| (o.calls, o.total_ms.value()));
+                    serde_json::json!({
+                        "name": feature.as_str(),
+                        "enabled": !state.disabled.contains(feature),
+                        "calls": calls,
+                        "totalMs": total_ms,
+                        "meanMs": if calls > 0 { total_ms / calls as f64 } else { 0.0 },
+                    })
+                })
+                .collect()
+        });
+        
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize features: {}", e)))
+    }
+}
@@ -0,0 +1,359 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+
+/// Longest genome mutation may grow to, whatever `maxLength` says
+const MAX_GENES: usize = 4096;
+
+fn default_point() -> f64 {
+    0.1
+}
+
+fn default_gaussian() -> f64 {
+    1.0
+}
+
+fn default_indel() -> f64 {
+    0.05
+}
+
+fn default_sigma() -> f64 {
+    0.1
+}
+
+fn default_min() -> f64 {
+    -1.0
+}
+
+fn default_max() -> f64 {
+    1.0
+}
+
+fn default_min_length() -> usize {
+    1
+}
+
+fn default_max_length() -> usize {
+    64
+}
+
+fn default_alpha() -> f64 {
+    0.5
+}
+
+/// How mutation treats a genome
+///
+/// Each operator's weight scales the mutation rate into a per-gene
+/// probability, so `{ duplication: 0 }` switches duplication off and
+/// `{ point: 1, gaussian: 0 }` makes every mutation a fresh draw.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct MutationOptions {
+    /// Replace a gene with a uniform draw from `min..max`
+    #[serde(default = "default_point")]
+    point: f64,
+    /// Add Gaussian noise with standard deviation `sigma` to a gene
+    #[serde(default = "default_gaussian")]
+    gaussian: f64,
+    /// Insert a copy of a gene after it
+    #[serde(default = "default_indel")]
+    duplication: f64,
+    /// Remove a gene
+    #[serde(default = "default_indel")]
+    deletion: f64,
+    #[serde(default = "default_sigma")]
+    sigma: f64,
+    #[serde(default = "default_min")]
+    min: f64,
+    #[serde(default = "default_max")]
+    max: f64,
+    #[serde(default = "default_min_length")]
+    min_length: usize,
+    #[serde(default = "default_max_length")]
+    max_length: usize,
+}
+
+impl Default for MutationOptions {
+    fn default() -> Self {
+        MutationOptions {
+            point: default_point(),
+            gaussian: default_gaussian(),
+            duplication: default_indel(),
+            deletion: default_indel(),
+            sigma: default_sigma(),
+            min: default_min(),
+            max: default_max(),
+            min_length: default_min_length(),
+            max_length: default_max_length(),
+        }
+    }
+}
+
+impl MutationOptions {
+    pub(crate) fn parse(json: &str) -> Result<MutationOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: MutationOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        options.validate()?;
+        Ok(options)
+    }
+    
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        for (name, value) in [
+            ("point", self.point),
+            ("gaussian", self.gaussian),
+            ("duplication", self.duplication),
+            ("deletion", self.deletion),
+            ("sigma", self.sigma),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(format!("{} must be non-negative, got {}", name, value));
+            }
+        }
+        if !(self.min.is_finite() && self.max.is_finite() && self.min < self.max) {
+            problems.push(format!("min must be below max, got {} and {}", self.min, self.max));
+        }
+        if self.min_length == 0 || self.min_length > self.max_length || self.max_length > MAX_GENES {
+            problems.push(format!(
+                "lengths must satisfy 1 <= minLength <= maxLength <= {}, got {} and {}",
+                MAX_GENES, self.min_length, self.max_length
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+    
+    /// Range genes are drawn from and clamped to
+    pub(crate) fn range(&self) -> (f64, f64) {
+        (self.min, self.max)
+    }
+    
+    pub(crate) fn length_bounds(&self) -> (usize, usize) {
+        (self.min_length, self.max_length)
+    }
+}
+
+/// How two genomes recombine
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum CrossoverKind {
+    /// The first parent's genes up to a random cut, then the second's
+    OnePoint,
+    /// Each gene from either parent with equal odds
+    #[default]
+    Uniform,
+    /// Each gene drawn between the parents' values (BLX-alpha)
+    Blend,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct CrossoverOptions {
+    #[serde(default)]
+    kind: CrossoverKind,
+    /// How far past the parents' values a blended gene may land, as a fraction of their distance
+    #[serde(default = "default_alpha")]
+    alpha: f64,
+}
+
+impl Default for CrossoverOptions {
+    fn default() -> Self {
+        CrossoverOptions { kind: CrossoverKind::default(), alpha: default_alpha() }
+    }
+}
+
+impl CrossoverOptions {
+    fn parse(json: &str) -> Result<CrossoverOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: CrossoverOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        options.validate()?;
+        Ok(options)
+    }
+    
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if !self.alpha.is_finite() || self.alpha < 0.0 {
+            return Err(format!("alpha must be non-negative, got {}", self.alpha));
+        }
+        Ok(())
+    }
+}
+
+/// What one call to `mutate` changed
+#[derive(Serialize, Default, Clone, Copy, Debug)]
+pub(crate) struct MutationCounts {
+    pub point: u32,
+    pub gaussian: u32,
+    pub duplications: u32,
+    pub deletions: u32,
+}
+
+/// Genome is a vector of real-valued genes
+///
+/// Organisms in a `World` each carry one, and children inherit their
+/// parent's genome with mutations. It can also be used on its own.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Genome {
+    genes: Vec<f64>,
+}
+
+#[wasm_bindgen]
+impl Genome {
+    /// Create a genome from gene values
+    #[wasm_bindgen(constructor)]
+    pub fn new(genes: Vec<f64>) -> Result<Genome, JsValue> {
+        if genes.is_empty() || genes.len() > MAX_GENES {
+            return Err(JsValue::from_str(&format!("A genome needs 1 to {} genes, got {}", MAX_GENES, genes.len())));
+        }
+        if let Some(bad) = genes.iter().find(|g| !g.is_finite()) {
+            return Err(JsValue::from_str(&format!("Genes must be finite, got {}", bad)));
+        }
+        Ok(Genome { genes })
+    }
+    
+    /// A genome of `length` genes drawn uniformly from -1..1
+    #[wasm_bindgen]
+    pub fn random(length: usize, seed: u64) -> Result<Genome, JsValue> {
+        if length == 0 || length > MAX_GENES {
+            return Err(JsValue::from_str(&format!("A genome needs 1 to {} genes, got {}", MAX_GENES, length)));
+        }
+        Ok(Genome::random_in(length, (default_min(), default_max()), &mut Rng::new(seed)))
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.genes.len()
+    }
+    
+    #[wasm_bindgen(js_name = getGenes)]
+    pub fn get_genes(&self) -> Vec<f64> {
+        self.genes.clone()
+    }
+    
+    /// Mean gene value
+    #[wasm_bindgen]
+    pub fn mean(&self) -> f64 {
+        if self.genes.is_empty() {
+            return 0.0;
+        }
+        self.genes.iter().sum::<f64>() / self.genes.len() as f64
+    }
+    
+    /// Mutate in place and return what changed as JSON
+    ///
+    /// Each gene undergoes each operator with probability
+    /// `mutation_rate` times the operator's weight. Options: `point` (0.1),
+    /// `gaussian` (1), `duplication` (0.05), `deletion` (0.05), `sigma`
+    /// (0.1), `min` and `max` (-1 and 1, the range genes are clamped to),
+    /// `minLength` (1) and `maxLength` (64).
+    #[wasm_bindgen]
+    pub fn mutate(&mut self, mutation_rate: f64, options_json: &str, seed: u64) -> Result<String, JsValue> {
+        let options = MutationOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid mutation options: {}", e)))?;
+        if !mutation_rate.is_finite() || !(0.0..=1.0).contains(&mutation_rate) {
+            return Err(JsValue::from_str(&format!("Mutation rate must be between 0 and 1, got {}", mutation_rate)));
+        }
+        let counts = self.mutate_with(mutation_rate, &options, &mut Rng::new(seed));
+        serde_json::to_string(&counts)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize mutation counts: {}", e)))
+    }
+    
+    /// Recombine two genomes into a child
+    ///
+    /// Options: `kind` (`one-point`, `uniform` or `blend`; default
+    /// `uniform`) and `alpha` (0.5), how far past the parents' values a
+    /// blended gene may land as a fraction of their distance. Genomes of
+    /// different lengths cross over where they overlap; a one-point child
+    /// ends with the second parent's remaining genes, and the other kinds
+    /// take the rest from a parent chosen at random.
+    #[wasm_bindgen]
+    pub fn crossover(a: &Genome, b: &Genome, options_json: &str, seed: u64) -> Result<Genome, JsValue> {
+        let options = CrossoverOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid crossover options: {}", e)))?;
+        Ok(Genome::crossover_with(a, b, &options, (f64::NEG_INFINITY, f64::INFINITY), &mut Rng::new(seed)))
+    }
+}
+
+impl Genome {
+    pub(crate) fn genes(&self) -> &[f64] {
+        &self.genes
+    }
+    
+    pub(crate) fn random_in(length: usize, (min, max): (f64, f64), rng: &mut Rng) -> Genome {
+        Genome { genes: (0..length).map(|_| min + (max - min) * rng.next_f64()).collect() }
+    }
+    
+    /// Recombine two genomes, clamping blended genes to `range`
+    pub(crate) fn crossover_with(
+        a: &Genome,
+        b: &Genome,
+        options: &CrossoverOptions,
+        (min, max): (f64, f64),
+        rng: &mut Rng,
+    ) -> Genome {
+        let overlap = a.genes.len().min(b.genes.len());
+        let mut genes = Vec::with_capacity(a.genes.len().max(b.genes.len()));
+        match options.kind {
+            CrossoverKind::OnePoint => {
+                // The cut sits between genes, so either parent may contribute nothing
+                let cut = (rng.next_f64() * (overlap + 1) as f64) as usize;
+                genes.extend_from_slice(&a.genes[..cut.min(overlap)]);
+                genes.extend_from_slice(&b.genes[cut.min(overlap)..]);
+                return Genome { genes };
+            }
+            CrossoverKind::Uniform => {
+                for i in 0..overlap {
+                    genes.push(if rng.next_f64() < 0.5 { a.genes[i] } else { b.genes[i] });
+                }
+            }
+            CrossoverKind::Blend => {
+                for i in 0..overlap {
+                    let (low, high) = (a.genes[i].min(b.genes[i]), a.genes[i].max(b.genes[i]));
+                    let reach = options.alpha * (high - low);
+                    let gene = low - reach + (high - low + 2.0 * reach) * rng.next_f64();
+                    genes.push(gene.clamp(min, max));
+                }
+            }
+        }
+        let rest = if rng.next_f64() < 0.5 { a } else { b };
+        genes.extend_from_slice(&rest.genes[overlap..]);
+        Genome { genes }
+    }
+    
+    /// Apply every mutation operator, gene by gene
+    pub(crate) fn mutate_with(&mut self, rate: f64, options: &MutationOptions, rng: &mut Rng) -> MutationCounts {
+        let mut counts = MutationCounts::default();
+        let (min, max) = options.range();
+        let mut genes = Vec::with_capacity(self.genes.len());
+        
+        for (index, &gene) in self.genes.iter().enumerate() {
+            let remaining = self.genes.len() - index - 1;
+            let mut gene = gene;
+            if rng.next_f64() < rate * options.point {
+                gene = min + (max - min) * rng.next_f64();
+                counts.point += 1;
+            }
+            if rng.next_f64() < rate * options.gaussian {
+                gene = (gene + options.sigma * rng.next_normal()).clamp(min, max);
+                counts.gaussian += 1;
+            }
+            // Keep the genome within its length bounds, counting the genes still to come
+            if rng.next_f64() < rate * options.deletion && genes.len() + remaining >= options.min_length {
+                counts.deletions += 1;
+                continue;
+            }
+            genes.push(gene);
+            if rng.next_f64() < rate * options.duplication && genes.len() + remaining < options.max_length {
+                genes.push(gene);
+                counts.duplications += 1;
+            }
+        }
+        
+        self.genes = genes;
+        counts
+    }
+}
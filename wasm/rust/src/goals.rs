@@ -0,0 +1,181 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lisp, OrganismState, RuleRegistry};
+
+/// How a threshold goal compares its metric with the target
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+enum Comparison {
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<=")]
+    AtMost,
+}
+
+fn default_comparison() -> Comparison {
+    Comparison::AtLeast
+}
+
+/// Where a goal stands
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+enum GoalStatus {
+    #[default]
+    Active,
+    Completed,
+    Failed,
+}
+
+/// A condition the player is trying to reach, with its progress
+///
+/// Either `predicate` (Lisp) or `metric` with `target` is set. A goal
+/// with a `deadline` fails when it isn't reached before that step.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Goal {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    predicate: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    metric: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    target: Option<f64>,
+    #[serde(default = "default_comparison")]
+    comparison: Comparison,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    deadline: Option<u64>,
+    #[serde(default)]
+    status: GoalStatus,
+    #[serde(default)]
+    progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    finished_at: Option<u64>,
+}
+
+impl Goal {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    
+    fn check(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Goal name cannot be empty".to_string());
+        }
+        match (&self.predicate, &self.metric, self.target) {
+            (Some(predicate), None, None) => lisp::parse(predicate).map(|_| ()).map_err(|e| {
+                format!("Failed to parse predicate of goal {} at {}:{}: {}", self.name, e.span.line, e.span.column, e.message)
+            }),
+            (None, Some(_), Some(target)) if target.is_finite() => Ok(()),
+            (None, Some(_), Some(target)) => Err(format!("Goal {} target must be finite (got {})", self.name, target)),
+            _ => Err(format!("Goal {} needs either a predicate or a metric and target", self.name)),
+        }
+    }
+    
+    /// Progress towards the goal, from 0 to 1
+    fn measure(&self, state: &OrganismState) -> Result<f64, String> {
+        if let Some(predicate) = &self.predicate {
+            return lisp::eval_predicate(predicate, state).map(|met| if met { 1.0 } else { 0.0 });
+        }
+        let metric = self.metric.as_deref().unwrap_or_default();
+        let value = state.read_any_metric(metric).ok_or_else(|| format!("Unknown metric: {}", metric))?;
+        let target = self.target.unwrap_or_default();
+        let progress = match self.comparison {
+            Comparison::AtLeast if value >= target => 1.0,
+            Comparison::AtMost if value <= target => 1.0,
+            Comparison::AtLeast if target > 0.0 => value / target,
+            Comparison::AtMost if value > 0.0 => target / value,
+            _ => 0.0,
+        };
+        Ok(progress.clamp(0.0, 1.0))
+    }
+}
+
+impl RuleRegistry {
+    /// Update every active goal after a tick, recording and returning the ones that finished
+    ///
+    /// A goal whose predicate or metric can't be evaluated keeps its
+    /// progress and stays active.
+    pub(crate) fn update_goals(&mut self, state: &OrganismState) -> Vec<serde_json::Value> {
+        let step = self.current_step;
+        let mut finished = Vec::new();
+        for goal in self.goals.iter_mut().filter(|goal| goal.status == GoalStatus::Active) {
+            if let Ok(progress) = goal.measure(state) {
+                goal.progress = progress;
+            }
+            if goal.progress >= 1.0 {
+                goal.status = GoalStatus::Completed;
+            } else if goal.deadline.is_some_and(|deadline| step + 1 >= deadline) {
+                goal.status = GoalStatus::Failed;
+            } else {
+                continue;
+            }
+            goal.finished_at = Some(step);
+            finished.push(serde_json::json!({
+                "name": goal.name,
+                "status": goal.status,
+                "step": step,
+            }));
+        }
+        for event in &finished {
+            let kind = if event["status"] == "completed" { "goal_completed" } else { "goal_failed" };
+            self.events.record(step, kind, event.clone());
+        }
+        finished
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Define or replace a goal
+    ///
+    /// `json` is `{ name, description, predicate }` with a Lisp predicate
+    /// such as `(and (> population 500) (< mutation-rate 0.05))`, or
+    /// `{ name, metric, target, comparison }` for a threshold where
+    /// `comparison` is ">=" (the default) or "<=". An optional `deadline`
+    /// is the step the goal must be reached before, e.g. "population
+    /// 10000 before step 5000". `tick` updates goals after the rules run,
+    /// reports the ones that finish in its summary and records
+    /// `goal_completed` and `goal_failed` events.
+    #[wasm_bindgen(js_name = defineGoal)]
+    pub fn define_goal(&mut self, json: &str) -> Result<(), JsValue> {
+        let mut goal: Goal = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse goal: {}", e)))?;
+        goal.check().map_err(|e| JsValue::from_str(&e))?;
+        goal.status = GoalStatus::Active;
+        goal.progress = 0.0;
+        goal.finished_at = None;
+        
+        match self.goals.iter_mut().find(|g| g.name == goal.name) {
+            Some(existing) => *existing = goal,
+            None => self.goals.push(goal),
+        }
+        Ok(())
+    }
+    
+    /// Remove a goal
+    #[wasm_bindgen(js_name = removeGoal)]
+    pub fn remove_goal(&mut self, name: &str) -> bool {
+        let before = self.goals.len();
+        self.goals.retain(|g| g.name != name);
+        self.goals.len() != before
+    }
+    
+    /// Get all goals with their `status` ("active", "completed", "failed"), `progress` and `finishedAt`
+    #[wasm_bindgen(js_name = getGoals)]
+    pub fn get_goals(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.goals)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize goals: {}", e)))
+    }
+    
+    /// Make every goal active again with no progress
+    #[wasm_bindgen(js_name = resetGoals)]
+    pub fn reset_goals(&mut self) {
+        for goal in &mut self.goals {
+            goal.status = GoalStatus::Active;
+            goal.progress = 0.0;
+            goal.finished_at = None;
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use wasm_bindgen::prelude::*;
+
+use crate::call_log;
+use crate::hash::fnv1a64_hex;
+use crate::{OrganismState, CORE_METRICS};
+
+/// Time step of the canonical scenario
+const GOLDEN_DT: f64 = 0.1;
+
+/// Build the canonical scenario: core dynamics plus a custom resource
+/// integrated with RK4, and both white and colored noise driven by `seed`
+fn golden_scenario(seed: u64) -> Result<OrganismState, JsValue> {
+    let mut state = OrganismState::new();
+    state.define_custom_metric("resource", 500.0)?;
+    state.define_dynamics(
+        "golden",
+        "rk4",
+        r#"[
+            { "metric": "resource", "derivative": "(- 20 (* 0.00001 resource population))" },
+            { "metric": "mutationRate", "derivative": "(* 0.001 (- 0.05 mutationRate))" }
+        ]"#,
+    )?;
+    state.set_noise_seed(seed);
+    state.add_wiener_noise("energy", 2.0)?;
+    state.add_ou_noise("resource", 0.5, 1.0)?;
+    Ok(state)
+}
+
+/// Hash the exact bits of every core and custom metric
+fn state_digest(state: &OrganismState) -> String {
+    let mut bytes = Vec::new();
+    for metric in CORE_METRICS {
+        bytes.extend_from_slice(metric.as_bytes());
+        bytes.extend_from_slice(&state.metric(metric).unwrap_or(f64::NAN).to_bits().to_le_bytes());
+    }
+    for (name, value) in &state.custom_metrics {
+        bytes.extend_from_slice(name.as_bytes());
+        bytes.extend_from_slice(&value.to_bits().to_le_bytes());
+    }
+    fnv1a64_hex(&bytes)
+}
+
+/// Run the canonical scenario for `ticks` steps and return a hash of the final state
+///
+/// The scenario exercises the core solver, user dynamics, the Lisp
+/// evaluator and the noise RNG. The same `seed` and `ticks` give the same
+/// hash on every platform, so an app can record the hash once and compare
+/// after upgrading the engine: a different hash means simulation behavior
+/// changed. Only metric values are hashed, so snapshot format changes
+/// don't affect it.
+#[wasm_bindgen(js_name = selfCheckGoldenRun)]
+pub fn self_check_golden_run(seed: u64, ticks: u32) -> Result<String, JsValue> {
+    let _call = call_log::enter("selfCheckGoldenRun", || serde_json::json!({ "seed": seed, "ticks": ticks }));
+    let mut state = golden_scenario(seed)?;
+    for _ in 0..ticks {
+        state.step(GOLDEN_DT);
+    }
+    if let Some(error) = state.last_dynamics_error() {
+        return Err(JsValue::from_str(&format!("Golden run failed: {}", error)));
+    }
+    Ok(state_digest(&state))
+}
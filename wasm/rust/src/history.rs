@@ -0,0 +1,255 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::simulation::Simulation;
+use crate::{canonical_metric, OrganismState};
+
+/// Most samples a recorder keeps
+const MAX_CAPACITY: usize = 10_000_000;
+
+/// Series a recorder samples, in column order
+pub(crate) const HISTORY_SERIES: [&str; 5] = ["step", "population", "energy", "mutationRate", "adaptationScore"];
+
+/// One sample of a state
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub(crate) struct Sample {
+    pub(crate) step: u64,
+    pub(crate) population: f64,
+    pub(crate) energy: f64,
+    pub(crate) mutation_rate: f64,
+    pub(crate) adaptation_score: f64,
+}
+
+impl Sample {
+    fn of(state: &OrganismState, step: u64) -> Sample {
+        Sample {
+            step,
+            population: state.population,
+            energy: state.energy,
+            mutation_rate: state.mutation_rate,
+            adaptation_score: state.adaptation_score,
+        }
+    }
+    
+    pub(crate) fn value(&self, series: usize) -> f64 {
+        match series {
+            0 => self.step as f64,
+            1 => self.population,
+            2 => self.energy,
+            3 => self.mutation_rate,
+            _ => self.adaptation_score,
+        }
+    }
+}
+
+/// Index into `HISTORY_SERIES` of a series name, which may use a metric alias
+fn series_index(name: &str) -> Result<usize, String> {
+    let series = match name {
+        "step" => "step",
+        other => match canonical_metric(other) {
+            Some("population") => "population",
+            Some("energy") => "energy",
+            Some("mutation_rate") => "mutationRate",
+            Some("adaptation_score") => "adaptationScore",
+            _ => "",
+        },
+    };
+    HISTORY_SERIES.iter().position(|s| *s == series).ok_or_else(|| {
+        format!("Unknown history series: {} (expected step, population, energy, mutationRate or adaptationScore)", name)
+    })
+}
+
+/// HistoryRecorder samples a state's core metrics into a ring buffer
+///
+/// Every `interval`-th observed step is sampled: the step number,
+/// population, energy, mutation rate and adaptation score. Once
+/// `capacity` samples are held the oldest is dropped for each new one.
+/// Series come back as `Float64Array`s, so a chart reads a window with
+/// one call instead of one per step.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct HistoryRecorder {
+    capacity: usize,
+    interval: u64,
+    samples: VecDeque<Sample>,
+    /// Steps observed so far
+    steps: u64,
+    /// Samples taken so far, including dropped ones
+    taken: u64,
+}
+
+impl HistoryRecorder {
+    fn sample(&mut self, state: &OrganismState) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample::of(state, self.steps));
+        self.taken += 1;
+    }
+    
+    /// Held samples, oldest first
+    pub(crate) fn samples(&self) -> impl Iterator<Item = &Sample> {
+        self.samples.iter()
+    }
+}
+
+#[wasm_bindgen]
+impl HistoryRecorder {
+    /// Create a recorder holding up to `capacity` samples, taken every `interval` steps
+    #[wasm_bindgen(constructor)]
+    pub fn new(capacity: usize, interval: u32) -> Result<HistoryRecorder, JsValue> {
+        if capacity == 0 || capacity > MAX_CAPACITY {
+            return Err(JsValue::from_str(&format!("History capacity must be between 1 and {}, got {}", MAX_CAPACITY, capacity)));
+        }
+        if interval == 0 {
+            return Err(JsValue::from_str("History interval must be at least 1 step"));
+        }
+        Ok(HistoryRecorder {
+            capacity,
+            interval: interval as u64,
+            samples: VecDeque::new(),
+            steps: 0,
+            taken: 0,
+        })
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn interval(&self) -> u32 {
+        self.interval as u32
+    }
+    
+    /// Samples held
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.samples.len()
+    }
+    
+    /// Steps observed
+    #[wasm_bindgen(getter)]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+    
+    /// Samples taken, counting those dropped to make room
+    #[wasm_bindgen(getter, js_name = totalSamples)]
+    pub fn total_samples(&self) -> u64 {
+        self.taken
+    }
+    
+    /// Count one step of `state` and sample it if the step is due; returns whether it was
+    #[wasm_bindgen]
+    pub fn observe(&mut self, state: &OrganismState) -> bool {
+        self.steps += 1;
+        let due = self.steps.is_multiple_of(self.interval);
+        if due {
+            self.sample(state);
+        }
+        due
+    }
+    
+    /// Sample `state` now, off the interval
+    #[wasm_bindgen(js_name = sampleNow)]
+    pub fn sample_now(&mut self, state: &OrganismState) {
+        self.sample(state);
+    }
+    
+    /// Step `state` `n` times by `delta_time`, observing every step, and return the samples taken
+    #[wasm_bindgen]
+    pub fn record(&mut self, state: &mut OrganismState, n: u32, delta_time: f64) -> u32 {
+        let mut taken = 0;
+        for _ in 0..n {
+            state.step(delta_time);
+            if self.observe(state) {
+                taken += 1;
+            }
+        }
+        taken
+    }
+    
+    /// Every held value of a series, oldest first
+    ///
+    /// `name` is `step`, `population`, `energy`, `mutationRate` or
+    /// `adaptationScore` (metric aliases such as `mutation_rate` work).
+    #[wasm_bindgen(js_name = getSeries)]
+    pub fn get_series(&self, name: &str) -> Result<Vec<f64>, JsValue> {
+        self.get_window(name, 0, self.samples.len())
+    }
+    
+    /// `count` values of a series from the `start`-th held sample (0 is the oldest)
+    #[wasm_bindgen(js_name = getWindow)]
+    pub fn get_window(&self, name: &str, start: usize, count: usize) -> Result<Vec<f64>, JsValue> {
+        let series = series_index(name).map_err(|e| JsValue::from_str(&e))?;
+        Ok(self.samples.iter().skip(start).take(count).map(|s| s.value(series)).collect())
+    }
+    
+    /// The newest `count` values of a series, oldest first
+    #[wasm_bindgen(js_name = getLatest)]
+    pub fn get_latest(&self, name: &str, count: usize) -> Result<Vec<f64>, JsValue> {
+        let start = self.samples.len().saturating_sub(count);
+        self.get_window(name, start, count)
+    }
+    
+    /// Drop every sample and restart the step count
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.steps = 0;
+        self.taken = 0;
+    }
+    
+    /// Get the recorder and its samples as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize history: {}", e)))
+    }
+    
+    /// Restore the recorder from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: HistoryRecorder = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse history snapshot: {}", e)))?;
+        if loaded.capacity == 0
+            || loaded.capacity > MAX_CAPACITY
+            || loaded.interval == 0
+            || loaded.interval > u32::MAX as u64
+            || loaded.samples.len() > loaded.capacity
+        {
+            return Err(JsValue::from_str("Failed to parse history snapshot: capacity or interval is invalid"));
+        }
+        *self = loaded;
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Record the state's history from now on, replacing any earlier recorder
+    ///
+    /// Every tick is observed after the state steps, so the recorder
+    /// samples every `interval` ticks and keeps the last `capacity`.
+    #[wasm_bindgen(js_name = enableHistory)]
+    pub fn enable_history(&mut self, capacity: usize, interval: u32) -> Result<(), JsValue> {
+        self.history = Some(Rc::new(HistoryRecorder::new(capacity, interval)?));
+        Ok(())
+    }
+    
+    /// Stop recording and drop the history
+    #[wasm_bindgen(js_name = disableHistory)]
+    pub fn disable_history(&mut self) {
+        self.history = None;
+    }
+    
+    /// A copy of the recorder, if history is enabled
+    #[wasm_bindgen(js_name = getHistory)]
+    pub fn get_history(&self) -> Option<HistoryRecorder> {
+        self.history.as_deref().cloned()
+    }
+}
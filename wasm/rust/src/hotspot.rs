@@ -0,0 +1,228 @@
+use wasm_bindgen::prelude::*;
+use std::collections::VecDeque;
+
+use crate::RuleRegistry;
+
+/// Percentile of the latency window compared against the threshold
+const HOTSPOT_PERCENTILE: f64 = 0.95;
+
+/// What happens to a rule once it is flagged as a hotspot
+#[derive(Clone, Copy, PartialEq)]
+enum HotspotAction {
+    /// Only record the event
+    Flag,
+    /// Stop running the rule from `tick`
+    Quarantine,
+    /// Run the rule from `tick` only every N steps
+    Demote,
+}
+
+impl HotspotAction {
+    fn parse(name: &str) -> Result<HotspotAction, String> {
+        match name {
+            "flag" => Ok(HotspotAction::Flag),
+            "quarantine" => Ok(HotspotAction::Quarantine),
+            "demote" => Ok(HotspotAction::Demote),
+            _ => Err(format!("Unknown hotspot action: {} (expected flag, quarantine or demote)", name)),
+        }
+    }
+    
+    fn as_str(self) -> &'static str {
+        match self {
+            HotspotAction::Flag => "flag",
+            HotspotAction::Quarantine => "quarantine",
+            HotspotAction::Demote => "demote",
+        }
+    }
+}
+
+/// Latency policy applied to every rule execution
+#[derive(Clone)]
+pub(crate) struct HotspotPolicy {
+    threshold_ms: f64,
+    window: usize,
+    action: HotspotAction,
+    demote_every: u32,
+}
+
+/// Per-rule latency window and hotspot status
+#[derive(Clone, Default)]
+pub(crate) struct HotspotState {
+    recent: VecDeque<f64>,
+    // p95 latency at the time the rule was flagged
+    flagged_p95_ms: Option<f64>,
+    quarantined: bool,
+    run_every: u32,
+}
+
+impl HotspotPolicy {
+    /// Every field of the policy, for debug dumps
+    pub(crate) fn debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "thresholdMs": self.threshold_ms,
+            "window": self.window,
+            "action": self.action.as_str(),
+            "demoteEvery": self.demote_every,
+        })
+    }
+}
+
+impl HotspotState {
+    /// Whether `tick` should run the rule at `step_index`
+    pub fn is_due(&self, step_index: u32) -> bool {
+        !self.quarantined && (self.run_every <= 1 || step_index.is_multiple_of(self.run_every))
+    }
+    
+    /// Heap bytes of the latency window
+    pub(crate) fn heap_bytes(&self) -> usize {
+        self.recent.capacity() * std::mem::size_of::<f64>()
+    }
+    
+    /// Every field of the state, including the latency window, for debug dumps
+    pub(crate) fn debug_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "recentMs": self.recent,
+            "flaggedP95Ms": self.flagged_p95_ms,
+            "quarantined": self.quarantined,
+            "runEvery": self.run_every,
+        })
+    }
+}
+
+/// Nearest-rank percentile of a non-empty set of samples
+fn percentile(samples: &VecDeque<f64>, fraction: f64) -> f64 {
+    let mut sorted: Vec<f64> = samples.iter().copied().collect();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let rank = (fraction * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+impl RuleRegistry {
+    /// Feed one execution time into the rule's window and apply the policy
+    pub(crate) fn observe_latency(&mut self, id: &str, execution_time_ms: f64) {
+        let policy = match &self.hotspot_policy {
+            Some(policy) => policy.clone(),
+            None => return,
+        };
+        let rule = match self.rules.get_mut(id) {
+            Some(rule) => rule,
+            None => return,
+        };
+        
+        let hotspot = &mut rule.hotspot;
+        hotspot.recent.push_back(execution_time_ms);
+        while hotspot.recent.len() > policy.window {
+            hotspot.recent.pop_front();
+        }
+        if hotspot.flagged_p95_ms.is_some() || hotspot.recent.len() < policy.window {
+            return;
+        }
+        
+        let p95 = percentile(&hotspot.recent, HOTSPOT_PERCENTILE);
+        if p95 <= policy.threshold_ms {
+            return;
+        }
+        
+        hotspot.flagged_p95_ms = Some(p95);
+        match policy.action {
+            HotspotAction::Flag => {}
+            HotspotAction::Quarantine => hotspot.quarantined = true,
+            HotspotAction::Demote => hotspot.run_every = policy.demote_every,
+        }
+        
+        self.events.record(self.current_step, "hotspot", serde_json::json!({
+            "ruleId": id,
+            "p95Ms": p95,
+            "thresholdMs": policy.threshold_ms,
+            "window": policy.window,
+            "action": policy.action.as_str(),
+        }));
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Enable hotspot detection
+    ///
+    /// Once a rule has `window` recorded executions, it is flagged when the
+    /// 95th percentile of those latencies exceeds `threshold_ms`. A "hotspot"
+    /// event is recorded and `action` is applied: "flag" only records,
+    /// "quarantine" stops `tick` from running the rule, and "demote" makes
+    /// `tick` run it only every `demote_every` steps. Direct `applyRule`
+    /// calls are never blocked.
+    #[wasm_bindgen(js_name = setHotspotPolicy)]
+    pub fn set_hotspot_policy(
+        &mut self,
+        threshold_ms: f64,
+        window: usize,
+        action: &str,
+        demote_every: u32,
+    ) -> Result<(), JsValue> {
+        let action = HotspotAction::parse(action).map_err(|e| JsValue::from_str(&e))?;
+        if !threshold_ms.is_finite() || threshold_ms < 0.0 {
+            return Err(JsValue::from_str("Hotspot threshold must be a non-negative number of milliseconds"));
+        }
+        if window == 0 {
+            return Err(JsValue::from_str("Hotspot window must hold at least one execution"));
+        }
+        if action == HotspotAction::Demote && demote_every < 2 {
+            return Err(JsValue::from_str("demote_every must be at least 2 for the demote action"));
+        }
+        
+        self.hotspot_policy = Some(HotspotPolicy {
+            threshold_ms,
+            window,
+            action,
+            demote_every,
+        });
+        Ok(())
+    }
+    
+    /// Disable hotspot detection (flagged rules stay flagged until released)
+    #[wasm_bindgen(js_name = clearHotspotPolicy)]
+    pub fn clear_hotspot_policy(&mut self) {
+        self.hotspot_policy = None;
+    }
+    
+    /// Get all flagged rules as JSON
+    #[wasm_bindgen(js_name = getHotspots)]
+    pub fn get_hotspots(&self) -> Result<String, JsValue> {
+        let hotspots: Vec<serde_json::Value> = self.execution_order
+            .iter()
+            .filter_map(|id| self.rules.get(id))
+            .filter_map(|rule| {
+                rule.hotspot.flagged_p95_ms.map(|p95| serde_json::json!({
+                    "ruleId": rule.id,
+                    "p95Ms": p95,
+                    "quarantined": rule.hotspot.quarantined,
+                    "runEvery": rule.hotspot.run_every.max(1),
+                }))
+            })
+            .collect();
+        
+        serde_json::to_string(&hotspots)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize hotspots: {}", e)))
+    }
+    
+    /// Clear a rule's hotspot flag, quarantine, demotion and latency window
+    #[wasm_bindgen(js_name = releaseHotspot)]
+    pub fn release_hotspot(&mut self, id: &str) -> Result<(), JsValue> {
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.hotspot = Default::default();
+        Ok(())
+    }
+    
+    /// Get registry events (hotspots, ...) with a sequence number of at least `since_seq`
+    #[wasm_bindgen(js_name = getEvents)]
+    pub fn get_events(&self, since_seq: u64) -> Result<String, JsValue> {
+        self.events.to_json_since(since_seq)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize events: {}", e)))
+    }
+    
+    /// Clear recorded registry events
+    #[wasm_bindgen(js_name = clearEvents)]
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+}
@@ -0,0 +1,165 @@
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+
+/// Largest import accepted, in bytes
+pub(crate) const MAX_IMPORT_BYTES: usize = 8 * 1024 * 1024;
+
+/// Deepest nesting of objects and arrays accepted in an import
+pub(crate) const MAX_IMPORT_DEPTH: usize = 64;
+
+/// Numeric rule fields and the values a null is replaced with
+fn rule_template() -> Value {
+    serde_json::json!({
+        "execution_count": 0,
+        "total_execution_time_ms": 0.0,
+        "last_execution_time_ms": 0.0,
+        "created_at": 0,
+    })
+}
+
+/// An open object (with the keys seen so far) or array while scanning
+enum Frame {
+    Object { keys: HashSet<String>, expect_key: bool },
+    Array,
+}
+
+/// Check the raw text of an import before parsing it
+///
+/// Size and nesting depth limits always apply, since they protect the
+/// parser itself. Duplicate object keys, which otherwise silently keep the
+/// last value, are rejected when `strict`.
+pub(crate) fn check_structure(what: &str, json: &str, strict: bool) -> Result<(), String> {
+    if json.len() > MAX_IMPORT_BYTES {
+        return Err(format!(
+            "{} is {} bytes, over the {} byte limit",
+            what, json.len(), MAX_IMPORT_BYTES
+        ));
+    }
+    
+    let bytes = json.as_bytes();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'{' | b'[' => {
+                if stack.len() >= MAX_IMPORT_DEPTH {
+                    return Err(format!(
+                        "{} nests deeper than {} levels at byte {}",
+                        what, MAX_IMPORT_DEPTH, index
+                    ));
+                }
+                stack.push(if bytes[index] == b'{' {
+                    Frame::Object { keys: HashSet::new(), expect_key: true }
+                } else {
+                    Frame::Array
+                });
+            }
+            b'}' | b']' => {
+                stack.pop();
+            }
+            b',' => {
+                if let Some(Frame::Object { expect_key, .. }) = stack.last_mut() {
+                    *expect_key = true;
+                }
+            }
+            b'"' => {
+                let start = index;
+                index += 1;
+                while index < bytes.len() && bytes[index] != b'"' {
+                    index += if bytes[index] == b'\\' { 2 } else { 1 };
+                }
+                if let Some(Frame::Object { keys, expect_key }) = stack.last_mut() {
+                    if *expect_key {
+                        *expect_key = false;
+                        let raw = &json[start..(index + 1).min(json.len())];
+                        // Decode escapes so "\u0061" and "a" count as the same key
+                        let key = serde_json::from_str::<String>(raw).unwrap_or_else(|_| raw.to_string());
+                        if !keys.insert(key.clone()) && strict {
+                            return Err(format!("{} has a duplicate key {:?} at byte {}", what, key, start));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+/// Apply the NaN/Infinity policy to numeric fields of `value`
+///
+/// JSON has no NaN or Infinity, and `JSON.stringify` writes them as null.
+/// Wherever `template` holds a number and `value` holds null, the null is
+/// replaced by the template's number, or rejected when `strict`.
+fn settle_nulls(value: &mut Value, template: &Value, path: &str, strict: bool) -> Result<(), String> {
+    match (value, template) {
+        (Value::Object(object), Value::Object(template)) => {
+            for (key, child) in object.iter_mut() {
+                let Some(default) = template.get(key) else { continue };
+                let child_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                match child {
+                    Value::Null if default.is_number() => {
+                        if strict {
+                            return Err(non_finite(&child_path));
+                        }
+                        *child = default.clone();
+                    }
+                    _ => settle_nulls(child, default, &child_path, strict)?,
+                }
+            }
+        }
+        (Value::Array(items), Value::Array(template)) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                if item.is_null() {
+                    if strict {
+                        return Err(non_finite(&format!("{}[{}]", path, index)));
+                    }
+                    *item = template.get(index).filter(|d| d.is_number()).cloned().unwrap_or(Value::from(0.0));
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn non_finite(path: &str) -> String {
+    format!("{} is null (NaN or Infinity in the source) where a number is required", path)
+}
+
+/// Drop null entries from a map of numbers, or reject them when `strict`
+fn settle_null_entries(map: &mut Map<String, Value>, path: &str, strict: bool) -> Result<(), String> {
+    if strict {
+        if let Some(key) = map.iter().find(|(_, v)| v.is_null()).map(|(k, _)| k) {
+            return Err(non_finite(&format!("{}.{}", path, key)));
+        }
+    }
+    map.retain(|_, v| !v.is_null());
+    Ok(())
+}
+
+/// Apply the NaN/Infinity policy to a parsed organism state
+///
+/// Null core metrics fall back to the defaults of a new state and null
+/// custom metrics are dropped, unless `strict`.
+pub(crate) fn settle_state(value: &mut Value, template: &Value, strict: bool) -> Result<(), String> {
+    settle_nulls(value, template, "", strict)?;
+    if let Some(custom) = value.get_mut("custom_metrics").and_then(|v| v.as_object_mut()) {
+        settle_null_entries(custom, "custom_metrics", strict)?;
+    }
+    Ok(())
+}
+
+/// Apply the NaN/Infinity policy to a parsed registry
+///
+/// Null rule statistics are reset to zero, unless `strict`.
+pub(crate) fn settle_registry(value: &mut Value, strict: bool) -> Result<(), String> {
+    let template = rule_template();
+    if let Some(rules) = value.get_mut("rules").and_then(|v| v.as_object_mut()) {
+        for (id, rule) in rules.iter_mut() {
+            settle_nulls(rule, &template, &format!("rules.{}", id), strict)?;
+        }
+    }
+    Ok(())
+}
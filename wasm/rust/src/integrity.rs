@@ -0,0 +1,95 @@
+use wasm_bindgen::prelude::*;
+
+use crate::hash::crc32;
+
+/// Field holding a snapshot's checksum
+const CHECKSUM_FIELD: &str = "checksum";
+
+/// CRC-32 of a snapshot's canonical JSON, leaving out its own checksum field
+///
+/// Object keys serialize sorted, so the checksum survives hosts that
+/// reformat or reorder the JSON but not changed values.
+fn checksum_of(value: &serde_json::Value) -> String {
+    let text = match value.as_object() {
+        Some(object) if object.contains_key(CHECKSUM_FIELD) => {
+            let mut object = object.clone();
+            object.remove(CHECKSUM_FIELD);
+            serde_json::Value::Object(object).to_string()
+        }
+        _ => value.to_string(),
+    };
+    format!("{:08x}", crc32(text.as_bytes()))
+}
+
+/// Embed a checksum of the snapshot's contents
+pub(crate) fn seal(value: &mut serde_json::Value) {
+    let checksum = checksum_of(value);
+    if let Some(object) = value.as_object_mut() {
+        object.insert(CHECKSUM_FIELD.to_string(), serde_json::json!(checksum));
+    }
+}
+
+/// A snapshot whose contents don't match its checksum
+pub(crate) struct ChecksumMismatch {
+    expected: String,
+    actual: String,
+}
+
+impl ChecksumMismatch {
+    /// The error thrown to JS: an `Error` with `code`, `kind`, `expected` and `actual` set
+    pub(crate) fn to_js(&self, kind: &str) -> JsValue {
+        let error = js_sys::Error::new(&format!(
+            "The {} snapshot is corrupted or truncated: its checksum is {} but its contents hash to {}",
+            kind, self.expected, self.actual
+        ));
+        for (key, value) in [("code", "checksum-mismatch"), ("kind", kind), ("expected", &self.expected), ("actual", &self.actual)] {
+            let _ = js_sys::Reflect::set(&error, &JsValue::from_str(key), &JsValue::from_str(value));
+        }
+        error.into()
+    }
+    
+    pub(crate) fn message(&self) -> String {
+        format!("checksum {} does not match the contents ({})", self.expected, self.actual)
+    }
+}
+
+/// Check a snapshot against its checksum; snapshots without one pass
+pub(crate) fn verify(value: &serde_json::Value) -> Result<(), ChecksumMismatch> {
+    let Some(expected) = value.get(CHECKSUM_FIELD) else {
+        return Ok(());
+    };
+    let actual = checksum_of(value);
+    match expected.as_str() {
+        Some(expected) if expected.eq_ignore_ascii_case(&actual) => Ok(()),
+        _ => Err(ChecksumMismatch {
+            expected: expected.as_str().map_or_else(|| expected.to_string(), str::to_string),
+            actual,
+        }),
+    }
+}
+
+/// Remove a snapshot's checksum, for callers that change it on purpose before loading
+pub(crate) fn unseal(value: &mut serde_json::Value) {
+    if let Some(object) = value.as_object_mut() {
+        object.remove(CHECKSUM_FIELD);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn sealed_snapshots_verify_until_changed() {
+        let mut snapshot = serde_json::json!({ "energy": 100.0, "population": 50.0 });
+        seal(&mut snapshot);
+        assert_eq!(snapshot[CHECKSUM_FIELD], format!("{:08x}", crc32(br#"{"energy":100.0,"population":50.0}"#)));
+        assert!(verify(&snapshot).is_ok());
+        
+        snapshot["energy"] = serde_json::json!(101.0);
+        assert!(verify(&snapshot).is_err());
+        
+        unseal(&mut snapshot);
+        assert!(verify(&snapshot).is_ok());
+    }
+}
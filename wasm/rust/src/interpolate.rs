@@ -0,0 +1,103 @@
+use wasm_bindgen::prelude::*;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::migration;
+use crate::simulation::Simulation;
+use crate::{OrganismState, CORE_METRICS};
+
+/// The metrics of a state at one instant, all that blending needs
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct MetricFrame {
+    core: [f64; CORE_METRICS.len()],
+    custom: BTreeMap<String, f64>,
+}
+
+impl MetricFrame {
+    pub(crate) fn capture(state: &OrganismState) -> MetricFrame {
+        MetricFrame {
+            core: CORE_METRICS.map(|name| state.read_any_metric(name).unwrap_or_default()),
+            custom: state.custom_metrics.clone(),
+        }
+    }
+    
+    /// Blend towards `next` by `t` (0 is this frame, 1 is `next`)
+    ///
+    /// Custom metrics only one frame has snap to whichever frame is nearer.
+    fn blend(&self, next: &MetricFrame, t: f64) -> MetricFrame {
+        let t = if t.is_finite() { t.clamp(0.0, 1.0) } else { 0.0 };
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        
+        let mut core = self.core;
+        for (value, target) in core.iter_mut().zip(next.core) {
+            *value = lerp(*value, target);
+        }
+        let nearer = if t < 0.5 { self } else { next };
+        let mut custom = BTreeMap::new();
+        for name in self.custom.keys().chain(next.custom.keys()) {
+            let value = match (self.custom.get(name), next.custom.get(name)) {
+                (Some(a), Some(b)) => lerp(*a, *b),
+                _ => match nearer.custom.get(name) {
+                    Some(value) => *value,
+                    None => continue,
+                },
+            };
+            custom.insert(name.clone(), value);
+        }
+        MetricFrame { core, custom }
+    }
+    
+    /// Convert to the object shape of `getMetrics`
+    fn to_js(&self) -> Result<JsValue, JsValue> {
+        let [population, energy, generation, age, mutation_rate, selection_pressure, adaptation_score] = self.core;
+        let metrics = serde_json::json!({
+            "population": population,
+            "energy": energy,
+            "generation": generation,
+            "age": age,
+            "mutationRate": mutation_rate,
+            "selectionPressure": selection_pressure,
+            "adaptationScore": adaptation_score,
+            "custom": self.custom,
+        });
+        metrics.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize metrics: {}", e)))
+    }
+}
+
+fn parse_snapshot(snapshot_json: &str) -> Result<OrganismState, JsValue> {
+    let migrated = migration::migrate("state", snapshot_json)?;
+    OrganismState::parse_guarded(&migrated, false)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))
+}
+
+/// Blend the metrics of two state snapshots
+///
+/// Returns the `getMetrics` object for the point `t` of the way from
+/// `snapshot_a` to `snapshot_b` (clamped to 0..1), so a renderer running
+/// faster than the simulation can draw in-between frames. Counters such
+/// as `generation` and `age` blend to fractional values too.
+#[wasm_bindgen(js_name = interpolate)]
+pub fn interpolate_snapshots(snapshot_a: &str, snapshot_b: &str, t: f64) -> Result<JsValue, JsValue> {
+    let a = MetricFrame::capture(&parse_snapshot(snapshot_a)?);
+    let b = MetricFrame::capture(&parse_snapshot(snapshot_b)?);
+    a.blend(&b, t).to_js()
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Blend the metrics before and after the last tick
+    ///
+    /// `t` is how far the renderer is into the next tick, e.g. the time
+    /// since the last tick over the fixed timestep. The blend trails the
+    /// simulation by up to one tick, the usual price of smooth motion.
+    /// Before the first tick it returns the current metrics.
+    #[wasm_bindgen]
+    pub fn interpolate(&self, t: f64) -> Result<JsValue, JsValue> {
+        let current = MetricFrame::capture(self.state());
+        match &self.previous_frame {
+            Some(previous) => previous.blend(&current, t).to_js(),
+            None => current.to_js(),
+        }
+    }
+}
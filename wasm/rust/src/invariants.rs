@@ -0,0 +1,145 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{lisp, OrganismState, RuleRegistry};
+
+/// A condition that must hold after every tick
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Invariant {
+    name: String,
+    predicate: String,
+}
+
+impl Invariant {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Declared invariants and whether `tick` checks them
+#[derive(Clone, Default)]
+pub(crate) struct Invariants {
+    pub list: Vec<Invariant>,
+    pub debug: bool,
+    pub last_violation: Option<serde_json::Value>,
+}
+
+impl Invariants {
+    /// Check every invariant in declaration order and describe the first violated one
+    ///
+    /// A predicate that fails to evaluate counts as violated, with the error
+    /// attached, since the condition couldn't be confirmed.
+    pub(crate) fn first_violation(&self, state: &OrganismState, step: u64) -> Option<serde_json::Value> {
+        self.list.iter().find_map(|invariant| {
+            let error = match lisp::eval_predicate(&invariant.predicate, state) {
+                Ok(true) => return None,
+                Ok(false) => None,
+                Err(e) => Some(e),
+            };
+            Some(serde_json::json!({
+                "name": invariant.name,
+                "predicate": invariant.predicate,
+                "step": step,
+                "error": error,
+                "state": serde_json::to_value(state).unwrap_or(serde_json::Value::Null),
+            }))
+        })
+    }
+}
+
+impl RuleRegistry {
+    /// Check invariants after a tick when debug mode is on, remembering any violation
+    pub(crate) fn check_invariants_after_tick(&mut self, state: &OrganismState) -> Option<serde_json::Value> {
+        if !self.invariants.debug {
+            return None;
+        }
+        let violation = self.invariants.first_violation(state, self.current_step);
+        if violation.is_some() {
+            self.invariants.last_violation = violation.clone();
+        }
+        violation
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Declare or replace an invariant
+    ///
+    /// `predicate` is Lisp code that must be truthy for the state after
+    /// every tick, e.g. `(<= energy 10000)`, `(>= population 0)` or, for a
+    /// conservation constraint, `(< (abs (- (+ energy resource) 1000)) 0.01)`.
+    /// Invariants are only checked by `tick` while debug mode is on.
+    #[wasm_bindgen(js_name = defineInvariant)]
+    pub fn define_invariant(&mut self, name: &str, predicate: &str) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Invariant name cannot be empty"));
+        }
+        lisp::parse(predicate).map_err(|e| {
+            JsValue::from_str(&format!(
+                "Failed to parse predicate of invariant {} at {}:{}: {}",
+                name, e.span.line, e.span.column, e.message
+            ))
+        })?;
+        
+        let invariant = Invariant {
+            name: name.to_string(),
+            predicate: predicate.to_string(),
+        };
+        match self.invariants.list.iter_mut().find(|i| i.name == name) {
+            Some(existing) => *existing = invariant,
+            None => self.invariants.list.push(invariant),
+        }
+        Ok(())
+    }
+    
+    /// Remove an invariant
+    #[wasm_bindgen(js_name = removeInvariant)]
+    pub fn remove_invariant(&mut self, name: &str) -> bool {
+        let before = self.invariants.list.len();
+        self.invariants.list.retain(|i| i.name != name);
+        self.invariants.list.len() != before
+    }
+    
+    /// Get all invariants in checking order as JSON
+    #[wasm_bindgen(js_name = getInvariants)]
+    pub fn get_invariants(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.invariants.list)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize invariants: {}", e)))
+    }
+    
+    /// Turn checking invariants after every tick on or off
+    #[wasm_bindgen(js_name = setDebugMode)]
+    pub fn set_debug_mode(&mut self, enabled: bool) {
+        self.invariants.debug = enabled;
+    }
+    
+    /// Whether invariants are checked after every tick
+    #[wasm_bindgen(js_name = isDebugMode)]
+    pub fn is_debug_mode(&self) -> bool {
+        self.invariants.debug
+    }
+    
+    /// Check the invariants against `state` now, regardless of debug mode
+    ///
+    /// Returns JSON describing the first violated invariant (its `name`,
+    /// `predicate`, the `step`, any evaluation `error` and a dump of the
+    /// `state`), or undefined when all hold.
+    #[wasm_bindgen(js_name = checkInvariants)]
+    pub fn check_invariants(&self, state: &OrganismState) -> Option<String> {
+        self.invariants
+            .first_violation(state, self.current_step)
+            .map(|violation| violation.to_string())
+    }
+    
+    /// Get the most recent violation found by `tick` in debug mode, as JSON
+    #[wasm_bindgen(js_name = lastInvariantViolation)]
+    pub fn last_invariant_violation(&self) -> Option<String> {
+        self.invariants.last_violation.as_ref().map(|violation| violation.to_string())
+    }
+    
+    /// Forget the most recent invariant violation
+    #[wasm_bindgen(js_name = clearInvariantViolation)]
+    pub fn clear_invariant_violation(&mut self) {
+        self.invariants.last_violation = None;
+    }
+}
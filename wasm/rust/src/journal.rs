@@ -0,0 +1,161 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{canonical_metric, OrganismState, CORE_METRICS};
+
+/// Entries written between checkpoints when the caller passes 0
+const DEFAULT_COMPACT_EVERY: u32 = 256;
+
+/// One line of the journal
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum JournalEntry {
+    /// Full state; everything before it can be discarded
+    Checkpoint { tick: u64, state: Box<OrganismState> },
+    /// Core and custom metrics that changed since the previous entry
+    Delta { tick: u64, set: BTreeMap<String, f64> },
+}
+
+/// Journal turns state transitions into append-only bytes for the host
+///
+/// `record` returns a newline-terminated JSON line to append to durable
+/// storage. Most lines only hold the metrics that changed; every
+/// `compact_every` entries `compactionDue` becomes true and `compact`
+/// returns a checkpoint line that replaces the whole journal. After a
+/// refresh, `Journal.replay` rebuilds the latest state from the stored bytes.
+#[wasm_bindgen]
+pub struct Journal {
+    compact_every: u32,
+    entries_since_checkpoint: u32,
+    last: Option<OrganismState>,
+}
+
+impl Journal {
+    /// A checkpoint line, which may replace the journal or be appended to it
+    fn checkpoint(&mut self, state: &OrganismState, tick: u64) -> Result<Vec<u8>, JsValue> {
+        let line = Journal::encode(&JournalEntry::Checkpoint { tick, state: Box::new(state.clone()) })?;
+        self.last = Some(state.clone());
+        self.entries_since_checkpoint = 0;
+        Ok(line)
+    }
+    
+    fn encode(entry: &JournalEntry) -> Result<Vec<u8>, JsValue> {
+        let mut line = serde_json::to_vec(entry)
+            .map_err(|e| JsValue::from_str(&format!("Failed to encode journal entry: {}", e)))?;
+        line.push(b'\n');
+        Ok(line)
+    }
+}
+
+#[wasm_bindgen]
+impl Journal {
+    /// Create a journal that asks for compaction every `compact_every` entries (0 for the default)
+    #[wasm_bindgen(constructor)]
+    pub fn new(compact_every: u32) -> Journal {
+        Journal {
+            compact_every: if compact_every == 0 { DEFAULT_COMPACT_EVERY } else { compact_every },
+            entries_since_checkpoint: 0,
+            last: None,
+        }
+    }
+    
+    /// Record the state at `tick` and return the bytes to append
+    ///
+    /// The first entry is always a checkpoint, and so is the entry after
+    /// custom metrics were defined or removed, since a delta only carries
+    /// values. Returns an empty array when nothing changed.
+    #[wasm_bindgen]
+    pub fn record(&mut self, state: &OrganismState, tick: u64) -> Result<Vec<u8>, JsValue> {
+        let previous = match &self.last {
+            Some(previous) if previous.custom_metrics.keys().eq(state.custom_metrics.keys()) => previous,
+            _ => return self.checkpoint(state, tick),
+        };
+        
+        let mut set: BTreeMap<String, f64> = CORE_METRICS
+            .iter()
+            .filter_map(|metric| {
+                let value = state.metric(metric)?;
+                (previous.metric(metric) != Some(value)).then(|| (metric.to_string(), value))
+            })
+            .collect();
+        set.extend(
+            state.custom_metrics
+                .iter()
+                .filter(|(name, value)| previous.custom_metrics.get(*name) != Some(*value))
+                .map(|(name, value)| (name.clone(), *value)),
+        );
+        if set.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        let line = Journal::encode(&JournalEntry::Delta { tick, set })?;
+        self.last = Some(state.clone());
+        self.entries_since_checkpoint += 1;
+        Ok(line)
+    }
+    
+    /// Whether enough entries accumulated that the journal should be compacted
+    #[wasm_bindgen(js_name = compactionDue)]
+    pub fn compaction_due(&self) -> bool {
+        self.entries_since_checkpoint >= self.compact_every
+    }
+    
+    /// Number of delta entries since the last checkpoint
+    #[wasm_bindgen(getter, js_name = entriesSinceCheckpoint)]
+    pub fn entries_since_checkpoint(&self) -> u32 {
+        self.entries_since_checkpoint
+    }
+    
+    /// Produce a checkpoint line that replaces the entire stored journal
+    #[wasm_bindgen]
+    pub fn compact(&mut self, state: &OrganismState, tick: u64) -> Result<Vec<u8>, JsValue> {
+        self.checkpoint(state, tick)
+    }
+    
+    /// Rebuild state from stored journal bytes, returning the tick of the last applied entry
+    ///
+    /// A torn final line (the page closed mid-write) is ignored; a corrupt
+    /// line anywhere else is an error.
+    #[wasm_bindgen]
+    pub fn replay(bytes: &[u8], state: &mut OrganismState) -> Result<u64, JsValue> {
+        let text = std::str::from_utf8(bytes)
+            .map_err(|e| JsValue::from_str(&format!("Journal is not valid UTF-8: {}", e)))?;
+        let lines: Vec<&str> = text.split('\n').filter(|line| !line.trim().is_empty()).collect();
+        
+        let mut restored: Option<OrganismState> = None;
+        let mut last_tick = 0;
+        for (index, line) in lines.iter().enumerate() {
+            let entry: JournalEntry = match serde_json::from_str(line) {
+                Ok(entry) => entry,
+                Err(_) if index + 1 == lines.len() && !text.ends_with('\n') => break,
+                Err(e) => {
+                    return Err(JsValue::from_str(&format!("Corrupt journal entry {}: {}", index + 1, e)));
+                }
+            };
+            
+            match entry {
+                JournalEntry::Checkpoint { tick, mut state } => {
+                    state.sync_state_vector();
+                    restored = Some(*state);
+                    last_tick = tick;
+                }
+                JournalEntry::Delta { tick, set } => {
+                    let target = restored.as_mut()
+                        .ok_or_else(|| JsValue::from_str("Journal does not start with a checkpoint"))?;
+                    for (metric, value) in set {
+                        if canonical_metric(&metric).is_some() {
+                            target.set_metric(&metric, value).map_err(|e| JsValue::from_str(&e))?;
+                        } else {
+                            target.custom_metrics.insert(metric, value);
+                        }
+                    }
+                    last_tick = tick;
+                }
+            }
+        }
+        
+        *state = restored.ok_or_else(|| JsValue::from_str("Journal holds no checkpoint"))?;
+        Ok(last_tick)
+    }
+}
@@ -1,6 +1,157 @@
 use wasm_bindgen::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+mod territory;
+mod colony;
+mod rng;
+mod events;
+mod conflict;
+mod bundle;
+mod rule_testing;
+mod sandbox;
+mod shadow;
+mod lisp;
+mod trace;
+mod modes;
+mod hotspot;
+mod hash;
+mod persist;
+mod snapshot_store;
+mod archive;
+mod journal;
+mod snapshot_profile;
+mod migration;
+mod bookmarks;
+mod model;
+mod recovery;
+mod random;
+mod vecmath;
+mod matrix;
+mod solver;
+mod dynamics;
+mod noise;
+mod sensitivity;
+mod ensemble;
+mod bifurcation;
+mod units;
+mod accumulate;
+mod golden;
+mod invariants;
+mod goals;
+mod import_guard;
+mod msgpack;
+mod integrity;
+mod diagnostics;
+mod debug_dump;
+mod assertions;
+mod call_log;
+mod clock;
+mod features;
+mod memory;
+mod config;
+mod simulation;
+mod patch;
+mod rule_batch;
+mod rule_params;
+mod validation;
+mod state_view;
+mod multiverse;
+mod scenario;
+mod subsystem;
+mod sonify;
+mod mlp;
+mod crossover;
+mod worldgen;
+mod scoring;
+mod interpolate;
+mod genome;
+mod world;
+mod selection;
+mod lineage;
+mod speciation;
+mod environment;
+mod cycle;
+mod catastrophe;
+mod ecosystem;
+mod history;
+mod csv;
+mod arrow;
+mod delta;
+mod rewind;
+mod replay;
+mod rule_order;
+mod schedule;
+mod triggers;
+mod tags;
+mod dry_run;
+mod rule_limits;
+mod apply_all;
+mod rule_versions;
+
+pub use territory::TerritoryMap;
+pub use colony::ColonySystem;
+pub use conflict::ConflictResolver;
+pub use sandbox::Sandbox;
+pub use persist::{ChunkLoader, ChunkSet};
+pub use snapshot_store::SnapshotStore;
+pub use archive::WorldArchive;
+pub use journal::Journal;
+pub use migration::Migrations;
+pub use bookmarks::Bookmarks;
+pub use model::export_model_definition;
+pub use random::RandomToolkit;
+pub use vecmath::VecMath;
+pub use matrix::Matrix;
+pub use accumulate::PreciseAccumulator;
+pub use golden::self_check_golden_run;
+pub use diagnostics::run_diagnostics;
+pub use debug_dump::debug_dump;
+pub use assertions::Assertions;
+pub use call_log::CallLog;
+pub use clock::Clock;
+pub use features::Features;
+pub use memory::memory_stats;
+pub use config::OrganismConfig;
+pub use simulation::{Simulation, SimulationBuilder};
+pub use rule_params::apply_rule_named;
+pub use state_view::StateView;
+pub use multiverse::Multiverse;
+pub use scenario::Scenario;
+pub use subsystem::{Subsystem, SubsystemContext};
+pub use sonify::Sonifier;
+pub use mlp::Mlp;
+pub use crossover::crossover_registries;
+pub use worldgen::{generate_world, GeneratedWorld};
+pub use scoring::{Leaderboard, RunScorer};
+pub use interpolate::interpolate_snapshots;
+pub use genome::Genome;
+pub use world::World;
+pub use environment::Environment;
+pub use cycle::EnvironmentCycle;
+pub use ecosystem::Ecosystem;
+pub use history::HistoryRecorder;
+
+use bundle::InstalledBundle;
+use rule_testing::RuleTestCase;
+use shadow::ShadowCandidate;
+use modes::Mode;
+use hotspot::{HotspotPolicy, HotspotState};
+use events::EventLog;
+use dynamics::MetricGroup;
+use solver::SolverKind;
+use noise::NoiseModel;
+use units::MetricMeta;
+use accumulate::CompensatedSum;
+use assertions::Severity;
+use features::Feature;
+use invariants::{Invariant, Invariants};
+use goals::Goal;
+use rule_params::RuleParam;
+use rule_limits::RuleLimits;
+use rule_versions::RuleVersion;
+use schedule::RuleSchedule;
+use triggers::RuleTrigger;
 
 /// OrganismState represents the core metrics and state of the organism
 #[wasm_bindgen]
@@ -20,6 +171,22 @@ pub struct OrganismState {
     // State vector for numeric computations
     #[serde(skip)]
     state_vector: Vec<f64>,
+    
+    // User-defined metrics and the dynamics integrating them
+    #[serde(default)]
+    custom_metrics: BTreeMap<String, f64>,
+    #[serde(default)]
+    core_solver: SolverKind,
+    #[serde(default)]
+    dynamics: Vec<MetricGroup>,
+    #[serde(skip)]
+    dynamics_error: Option<String>,
+    #[serde(default)]
+    noise: Option<NoiseModel>,
+    
+    // Unit, precision and description overrides, keyed by metric
+    #[serde(default)]
+    metric_meta: BTreeMap<String, MetricMeta>,
 }
 
 #[wasm_bindgen]
@@ -36,38 +203,103 @@ impl OrganismState {
             selection_pressure: 0.5,
             adaptation_score: 0.0,
             state_vector: vec![100.0, 1000.0, 0.01],
+            custom_metrics: BTreeMap::new(),
+            core_solver: SolverKind::default(),
+            dynamics: Vec::new(),
+            dynamics_error: None,
+            noise: None,
+            metric_meta: BTreeMap::new(),
         }
     }
     
     /// Initialize organism state from JSON configuration
+    ///
+    /// The config is read and range-checked as by `OrganismConfig.fromJson`
+    /// with unknown fields ignored, so invalid values such as negative energy
+    /// throw instead of producing a broken state.
     #[wasm_bindgen(js_name = initFromConfig)]
     pub fn init_from_config(config_json: &str) -> Result<OrganismState, JsValue> {
-        serde_json::from_str(config_json)
-            .map_err(|e| JsValue::from_str(&format!("Failed to parse config: {}", e)))
+        OrganismState::init_from_config_checked(config_json, false)
+    }
+    
+    /// Initialize organism state from JSON configuration with an explicit strictness
+    ///
+    /// Size and nesting limits always apply. When `strict`, duplicate keys,
+    /// unknown fields and null numbers (how `JSON.stringify` writes NaN and
+    /// Infinity) are rejected; otherwise unknown fields are ignored and null
+    /// numbers fall back to their defaults.
+    #[wasm_bindgen(js_name = initFromConfigChecked)]
+    pub fn init_from_config_checked(config_json: &str, strict: bool) -> Result<OrganismState, JsValue> {
+        let _call = call_log::enter("initFromConfig", || serde_json::json!({
+            "config": call_log::text(config_json),
+            "strict": strict,
+        }));
+        let unknown_fields = if strict { "reject" } else { "ignore" };
+        OrganismConfig::parse(config_json, unknown_fields, strict)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse config: {}", e)))?
+            .build()
     }
     
     /// Perform one step of organism evolution
     #[wasm_bindgen]
     pub fn step(&mut self, delta_time: f64) -> f64 {
+        let _call = call_log::enter("step", || serde_json::json!({ "deltaTime": delta_time }));
+        self.step_with_regen(delta_time, dynamics::ENERGY_REGEN)
+    }
+    
+    /// Perform `n` steps and return the adaptation score after each
+    ///
+    /// Equivalent to calling `step` `n` times from JS, without crossing
+    /// the boundary per step.
+    #[wasm_bindgen(js_name = stepN)]
+    pub fn step_n(&mut self, n: u32, delta_time: f64) -> Vec<f64> {
+        let _call = call_log::enter("stepN", || serde_json::json!({ "n": n, "deltaTime": delta_time }));
+        (0..n).map(|_| self.step(delta_time)).collect()
+    }
+    
+    /// Calculate adaptation score based on current state
+    fn calculate_adaptation_score(&self) -> f64 {
+        let pop_score = (self.population / 100.0).min(2.0);
+        let energy_score = (self.energy / 1000.0).min(1.0);
+        let age_score = (self.age as f64 / 100.0).min(1.0);
+        
+        (pop_score + energy_score + age_score) / 3.0
+    }
+    
+    /// Step with energy regenerating at `energy_regen` per unit time
+    // `max`/`min` turn a NaN metric into the lower bound, where `clamp` would keep the NaN
+    #[allow(clippy::manual_clamp)]
+    pub(crate) fn step_with_regen(&mut self, delta_time: f64, energy_regen: f64) -> f64 {
         // Increment age
         self.age += 1;
         
-        // Update population based on energy and mutation rate
-        let growth_rate = (self.energy / 1000.0) * (1.0 - self.mutation_rate);
-        self.population += self.population * growth_rate * delta_time;
-        
-        // Energy consumption based on population
-        let energy_consumption = self.population * 0.1 * delta_time;
-        self.energy -= energy_consumption;
+        // Population grows with energy and mutation rate while consuming
+        // energy, which regenerates; the core solver integrates both
+        let mutation_rate = self.mutation_rate;
+        let current = [self.population, self.energy];
+        let mut core = |y: &[f64]| Ok(dynamics::core_derivatives(y, mutation_rate, energy_regen));
+        match self.core_solver.solver().advance(&mut core, &current, delta_time) {
+            Ok(next) => {
+                self.population = next[0];
+                self.energy = next[1];
+            }
+            Err(e) => self.dynamics_error = Some(format!("Dynamics group core failed: {}", e)),
+        }
         
-        // Energy regeneration
-        let energy_regen = 10.0 * delta_time;
-        self.energy += energy_regen;
+        // Stochastic noise terms
+        if features::enabled(Feature::Noise) {
+            features::measure(Feature::Noise, || self.apply_noise(delta_time));
+        }
         
         // Clamp values
         self.population = self.population.max(1.0);
         self.energy = self.energy.max(0.0).min(10000.0);
         
+        // User-defined metric dynamics
+        if features::enabled(Feature::Dynamics) {
+            features::measure(Feature::Dynamics, || self.step_dynamics(delta_time));
+        }
+        
         // Update state vector
         self.state_vector[0] = self.population;
         self.state_vector[1] = self.energy;
@@ -75,31 +307,134 @@ impl OrganismState {
         
         // Calculate and return adaptation score
         self.adaptation_score = self.calculate_adaptation_score();
-        self.adaptation_score
-    }
-    
-    /// Calculate adaptation score based on current state
-    fn calculate_adaptation_score(&self) -> f64 {
-        let pop_score = (self.population / 100.0).min(2.0);
-        let energy_score = (self.energy / 1000.0).min(1.0);
-        let age_score = (self.age as f64 / 100.0).min(1.0);
         
-        (pop_score + energy_score + age_score) / 3.0
+        assertions::check(
+            Severity::Error,
+            "finite-metrics",
+            || self.population.is_finite() && self.energy.is_finite() && self.mutation_rate.is_finite(),
+            || format!(
+                "Non-finite core metric after step: population {}, energy {}, mutation rate {}",
+                self.population, self.energy, self.mutation_rate
+            ),
+        );
+        assertions::check(
+            Severity::Warning,
+            "finite-custom-metrics",
+            || self.custom_metrics.values().all(|v| v.is_finite()),
+            || format!("Non-finite custom metric after step: {:?}", self.custom_metrics),
+        );
+        self.adaptation_score
     }
     
     /// Get a snapshot of the current state as JSON
     #[wasm_bindgen(js_name = getSnapshot)]
     pub fn get_snapshot(&self) -> Result<String, JsValue> {
-        serde_json::to_string(self)
-            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))
+        let json = serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))?;
+        migration::stamp("state", json)
     }
     
     /// Restore state from a JSON snapshot
     #[wasm_bindgen(js_name = loadSnapshot)]
     pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
-        let loaded: OrganismState = serde_json::from_str(snapshot_json)
+        self.load_snapshot_checked(snapshot_json, false)
+    }
+    
+    /// Restore state from a JSON snapshot with an explicit strictness, as in `initFromConfigChecked`
+    #[wasm_bindgen(js_name = loadSnapshotChecked)]
+    pub fn load_snapshot_checked(&mut self, snapshot_json: &str, strict: bool) -> Result<(), JsValue> {
+        let _call = call_log::enter("loadSnapshot", || serde_json::json!({
+            "snapshot": call_log::text(snapshot_json),
+            "strict": strict,
+        }));
+        import_guard::check_structure("Snapshot", snapshot_json, strict)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))?;
+        let migrated = migration::migrate("state", snapshot_json)?;
+        let loaded = OrganismState::parse_guarded(&migrated, strict)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))?;
         
+        *self = loaded;
+        self.sync_state_vector();
+        Ok(())
+    }
+    
+    /// Get a snapshot of the current state as a plain object
+    ///
+    /// Holds the same data as `getSnapshot` without the JSON string, for
+    /// hosts that keep snapshots as objects (IndexedDB, postMessage, ...).
+    #[wasm_bindgen(js_name = getSnapshotObject)]
+    pub fn get_snapshot_object(&self) -> Result<JsValue, JsValue> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))?;
+        migration::stamp_value("state", &mut value);
+        value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))
+    }
+    
+    /// Restore state from an object produced by `getSnapshotObject` (or a parsed `getSnapshot`)
+    ///
+    /// Host migrations and the default NaN/Infinity policy apply as in `loadSnapshot`.
+    #[wasm_bindgen(js_name = loadSnapshotObject)]
+    pub fn load_snapshot_object(&mut self, snapshot: JsValue) -> Result<(), JsValue> {
+        let value: serde_json::Value = serde_wasm_bindgen::from_value(snapshot)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read snapshot object: {}", e)))?;
+        let _call = call_log::enter("loadSnapshotObject", || serde_json::json!({
+            "snapshot": call_log::text(&value.to_string()),
+        }));
+        let migrated = migration::migrate_value("state", value)?;
+        let loaded = OrganismState::from_guarded_value(migrated, false)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))?;
+        
+        *self = loaded;
+        self.sync_state_vector();
+        Ok(())
+    }
+    
+    /// Get a snapshot of the complete state as MessagePack bytes
+    ///
+    /// Holds what `getSnapshot` does plus the whole state vector under
+    /// `state_vector`, written as `float64`s so NaN and infinities
+    /// survive. Noise RNG state travels with the noise model. Any
+    /// MessagePack reader can decode the bytes.
+    #[wasm_bindgen(js_name = getSnapshotBinary)]
+    pub fn get_snapshot_binary(&self) -> Result<Vec<u8>, JsValue> {
+        let mut value = serde_json::to_value(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize state: {}", e)))?;
+        migration::stamp_value("state", &mut value);
+        let map = value.as_object().expect("states serialize as objects");
+        let mut out = Vec::new();
+        msgpack::write_map_header(&mut out, map.len() + 1);
+        for (key, item) in map {
+            msgpack::write_str(&mut out, key);
+            msgpack::write_value(&mut out, item);
+        }
+        msgpack::write_str(&mut out, "state_vector");
+        msgpack::write_f64_array(&mut out, &self.state_vector);
+        Ok(out)
+    }
+    
+    /// Restore the complete state, state vector included, from `getSnapshotBinary` bytes
+    ///
+    /// Host migrations and the default NaN/Infinity policy apply to the
+    /// metrics as in `loadSnapshot`. A state vector shorter than 3 is
+    /// rebuilt from the metrics, and the first three entries always
+    /// follow the loaded population, energy and mutation rate.
+    #[wasm_bindgen(js_name = loadSnapshotBinary)]
+    pub fn load_snapshot_binary(&mut self, bytes: &[u8]) -> Result<(), JsValue> {
+        let _call = call_log::enter("loadSnapshotBinary", || serde_json::json!({ "bytes": bytes.len() }));
+        let (value, vector) = OrganismState::read_binary(bytes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse binary snapshot: {}", e)))?;
+        let migrated = migration::migrate_value("state", value)?;
+        let mut loaded = OrganismState::from_guarded_value(migrated, false)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse binary snapshot: {}", e)))?;
+        
+        match vector {
+            Some(mut vector) if vector.len() >= 3 => {
+                vector[..3].copy_from_slice(&[loaded.population, loaded.energy, loaded.mutation_rate]);
+                loaded.state_vector = vector;
+            }
+            _ => loaded.sync_state_vector(),
+        }
         *self = loaded;
         Ok(())
     }
@@ -143,46 +478,100 @@ impl OrganismState {
     // Setters for JavaScript access
     #[wasm_bindgen(setter)]
     pub fn set_population(&mut self, value: f64) {
+        let _call = call_log::enter("setPopulation", || serde_json::json!({ "value": value }));
         self.population = value.max(0.0);
         self.state_vector[0] = self.population;
     }
     
     #[wasm_bindgen(setter)]
     pub fn set_energy(&mut self, value: f64) {
+        let _call = call_log::enter("setEnergy", || serde_json::json!({ "value": value }));
         self.energy = value.max(0.0);
         self.state_vector[1] = self.energy;
     }
     
     #[wasm_bindgen(setter)]
     pub fn set_generation(&mut self, value: u64) {
+        let _call = call_log::enter("setGeneration", || serde_json::json!({ "value": value }));
         self.generation = value;
     }
     
     #[wasm_bindgen(setter, js_name = mutationRate)]
+    #[allow(clippy::manual_clamp)]
     pub fn set_mutation_rate(&mut self, value: f64) {
+        let _call = call_log::enter("setMutationRate", || serde_json::json!({ "value": value }));
         self.mutation_rate = value.max(0.0).min(1.0);
         self.state_vector[2] = self.mutation_rate;
     }
     
     #[wasm_bindgen(setter, js_name = selectionPressure)]
+    #[allow(clippy::manual_clamp)]
     pub fn set_selection_pressure(&mut self, value: f64) {
+        let _call = call_log::enter("setSelectionPressure", || serde_json::json!({ "value": value }));
         self.selection_pressure = value.max(0.0).min(1.0);
     }
     
+    /// Get every core metric and the custom metrics in one object
+    ///
+    /// Returns `{ population, energy, generation, age, mutationRate,
+    /// selectionPressure, adaptationScore, custom: { name: value } }`, so a
+    /// dashboard reads a frame with one call instead of one per getter.
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self) -> Result<JsValue, JsValue> {
+        let metrics = serde_json::json!({
+            "population": self.population,
+            "energy": self.energy,
+            "generation": self.generation,
+            "age": self.age,
+            "mutationRate": self.mutation_rate,
+            "selectionPressure": self.selection_pressure,
+            "adaptationScore": self.adaptation_score,
+            "custom": self.custom_metrics,
+        });
+        metrics.serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize metrics: {}", e)))
+    }
+    
     /// Get the state vector for numeric computations
     #[wasm_bindgen(js_name = getStateVector)]
     pub fn get_state_vector(&self) -> Vec<f64> {
         self.state_vector.clone()
     }
     
+    /// Get a live view of the state vector without copying it
+    ///
+    /// The view shows the values as `step` and the setters change them,
+    /// so a renderer can keep it and read it every frame. It goes stale
+    /// when WASM memory grows (its length drops to 0), when
+    /// `setStateVector` changes the vector's length, when a snapshot is
+    /// loaded into the state and when the state is freed. Check it with
+    /// `isStateVectorView` before reading and take a new one when that
+    /// says false; never write through it.
+    #[wasm_bindgen(js_name = stateVectorView)]
+    pub fn state_vector_view(&self) -> js_sys::Float64Array {
+        memory::f64_view(&self.state_vector)
+    }
+    
+    /// Whether a view from `stateVectorView` is still current
+    #[wasm_bindgen(js_name = isStateVectorView)]
+    pub fn is_state_vector_view(&self, view: &js_sys::Float64Array) -> bool {
+        memory::is_current_view(view, &self.state_vector)
+    }
+    
     /// Update the state vector
     #[wasm_bindgen(js_name = setStateVector)]
     pub fn set_state_vector(&mut self, vector: Vec<f64>) {
+        let _call = call_log::enter("setStateVector", || serde_json::json!({ "vector": vector }));
         if vector.len() >= 3 {
             self.population = vector[0];
             self.energy = vector[1];
             self.mutation_rate = vector[2];
-            self.state_vector = vector;
+            // Copy in place when the length allows, so views stay current
+            if vector.len() == self.state_vector.len() {
+                self.state_vector.copy_from_slice(&vector);
+            } else {
+                self.state_vector = vector;
+            }
         }
     }
 }
@@ -193,11 +582,124 @@ impl Default for OrganismState {
     }
 }
 
+/// Canonical names of the core metrics
+const CORE_METRICS: [&str; 7] = [
+    "population",
+    "energy",
+    "generation",
+    "age",
+    "mutation_rate",
+    "selection_pressure",
+    "adaptation_score",
+];
+
+/// Map a metric name (camelCase or snake_case) to its canonical snake_case form
+fn canonical_metric(name: &str) -> Option<&'static str> {
+    match name {
+        "population" => Some("population"),
+        "energy" => Some("energy"),
+        "generation" => Some("generation"),
+        "age" => Some("age"),
+        "mutationRate" | "mutation_rate" => Some("mutation_rate"),
+        "selectionPressure" | "selection_pressure" => Some("selection_pressure"),
+        "adaptationScore" | "adaptation_score" => Some("adaptation_score"),
+        _ => None,
+    }
+}
+
+impl OrganismState {
+    /// Parse a state from JSON text, as `from_guarded_value`
+    fn parse_guarded(json: &str, strict: bool) -> Result<OrganismState, String> {
+        let value: serde_json::Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        OrganismState::from_guarded_value(value, strict)
+    }
+    
+    /// Convert a parsed state, applying the import NaN/Infinity policy to its numbers
+    fn from_guarded_value(mut value: serde_json::Value, strict: bool) -> Result<OrganismState, String> {
+        let template = serde_json::to_value(OrganismState::new()).map_err(|e| e.to_string())?;
+        import_guard::settle_state(&mut value, &template, strict)?;
+        serde_json::from_value(value).map_err(|e| e.to_string())
+    }
+    
+    /// Split a binary snapshot into the state's JSON fields and its state vector
+    fn read_binary(bytes: &[u8]) -> Result<(serde_json::Value, Option<Vec<f64>>), String> {
+        let mut reader = msgpack::Reader::new(bytes)?;
+        let mut map = serde_json::Map::new();
+        let mut vector = None;
+        for _ in 0..reader.map_header()? {
+            let key = reader.string()?;
+            if key == "state_vector" {
+                vector = Some(reader.f64_array()?);
+            } else {
+                map.insert(key, reader.value(1)?);
+            }
+        }
+        if !reader.done() {
+            return Err("trailing bytes after the snapshot".to_string());
+        }
+        Ok((serde_json::Value::Object(map), vector))
+    }
+    
+    /// Rebuild the state vector from the metrics (it isn't serialized)
+    fn sync_state_vector(&mut self) {
+        if self.state_vector.len() == 3 {
+            self.state_vector.copy_from_slice(&[self.population, self.energy, self.mutation_rate]);
+        } else {
+            self.state_vector = vec![self.population, self.energy, self.mutation_rate];
+        }
+    }
+    
+    /// Read a core metric by name
+    fn metric(&self, name: &str) -> Option<f64> {
+        match canonical_metric(name)? {
+            "population" => Some(self.population),
+            "energy" => Some(self.energy),
+            "generation" => Some(self.generation as f64),
+            "age" => Some(self.age as f64),
+            "mutation_rate" => Some(self.mutation_rate),
+            "selection_pressure" => Some(self.selection_pressure),
+            "adaptation_score" => Some(self.adaptation_score),
+            _ => None,
+        }
+    }
+    
+    /// Write a core metric by name, going through the public setters where they exist
+    fn set_metric(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match canonical_metric(name) {
+            Some("population") => self.set_population(value),
+            Some("energy") => self.set_energy(value),
+            Some("generation") => self.set_generation(value.max(0.0) as u64),
+            Some("age") => self.age = value.max(0.0) as u64,
+            Some("mutation_rate") => self.set_mutation_rate(value),
+            Some("selection_pressure") => self.set_selection_pressure(value),
+            Some("adaptation_score") => self.adaptation_score = value,
+            _ => return Err(format!("Unknown metric: {}", name)),
+        }
+        Ok(())
+    }
+}
+
 /// RuleRegistry manages the collection of rules and their execution tracking
 #[wasm_bindgen]
+#[derive(Clone)]
 pub struct RuleRegistry {
     rules: HashMap<String, Rule>,
     execution_order: Vec<String>,
+    bundles: BTreeMap<String, InstalledBundle>,
+    modes: Vec<Mode>,
+    active_mode: Option<String>,
+    hotspot_policy: Option<HotspotPolicy>,
+    events: EventLog,
+    current_step: u64,
+    /// The state's generation at the last tick, for generation-boundary schedules
+    last_generation: Option<u64>,
+    invariants: Invariants,
+    goals: Vec<Goal>,
+    fuel_limit: u64,
+}
+
+fn default_enabled() -> bool {
+    true
 }
 
 /// Rule represents a single executable rule with metadata
@@ -206,9 +708,62 @@ struct Rule {
     id: String,
     lisp_code: String,
     execution_count: u64,
-    total_execution_time_ms: f64,
+    total_execution_time_ms: CompensatedSum,
     last_execution_time_ms: f64,
     created_at: u64,
+    /// Applications that failed, including those aborted by `limits`
+    #[serde(default)]
+    error_count: u64,
+    #[serde(default)]
+    last_error: Option<String>,
+    #[serde(default)]
+    tests: Vec<RuleTestCase>,
+    #[serde(default)]
+    shadow: Option<ShadowCandidate>,
+    #[serde(default)]
+    doc: Option<String>,
+    #[serde(default)]
+    modes: Vec<String>,
+    #[serde(default)]
+    params: Vec<RuleParam>,
+    /// Whether ticks run the rule
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+    /// Ticks run higher priorities first
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    schedule: RuleSchedule,
+    #[serde(default)]
+    trigger: Option<RuleTrigger>,
+    /// Rules this one runs after on every tick
+    #[serde(default)]
+    runs_after: Vec<String>,
+    /// Labels for `getRulesByTag`, sorted
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    limits: RuleLimits,
+    /// Counts registrations under this ID, starting at 1
+    #[serde(default = "rule_versions::default_version")]
+    version: u32,
+    /// Code the rule had before, oldest first
+    #[serde(default)]
+    versions: Vec<RuleVersion>,
+    // Derived from the code, so it is recomputed rather than stored
+    #[serde(skip)]
+    access: lisp::RuleAccess,
+    #[serde(skip)]
+    program: lisp::Program,
+    #[serde(skip)]
+    hotspot: HotspotState,
+}
+
+impl Rule {
+    /// Whether the rule is enabled and runs while `mode` is active (`None` when no mode is)
+    fn runs_in(&self, mode: Option<&str>) -> bool {
+        self.enabled && (self.modes.is_empty() || mode.is_some_and(|mode| self.modes.iter().any(|m| m == mode)))
+    }
 }
 
 #[wasm_bindgen]
@@ -219,23 +774,70 @@ impl RuleRegistry {
         RuleRegistry {
             rules: HashMap::new(),
             execution_order: Vec::new(),
+            bundles: BTreeMap::new(),
+            modes: Vec::new(),
+            active_mode: None,
+            hotspot_policy: None,
+            events: EventLog::new(),
+            current_step: 0,
+            last_generation: None,
+            invariants: Invariants::default(),
+            goals: Vec::new(),
+            fuel_limit: lisp::MAX_STEPS,
         }
     }
     
-    /// Register a new rule
+    /// Register a new rule, optionally with tags (see `setRuleTags`)
+    ///
+    /// Re-registering a rule keeps the code it replaces as a prior version
+    /// (see `getRuleHistory`), and without `tags` keeps its tags. Throws
+    /// when the code has errors `checkRuleCode` would report, listing each
+    /// with its line and column.
     #[wasm_bindgen(js_name = registerRule)]
-    pub fn register_rule(&mut self, id: &str, lisp_code: &str) -> Result<(), JsValue> {
+    pub fn register_rule(&mut self, id: &str, lisp_code: &str, tags: Option<Vec<String>>) -> Result<(), JsValue> {
+        let _call = call_log::enter("registerRule", || serde_json::json!({
+            "id": id,
+            "code": call_log::text(lisp_code),
+            "tags": tags,
+        }));
         if id.is_empty() {
             return Err(JsValue::from_str("Rule ID cannot be empty"));
         }
+        let tags = match tags {
+            Some(tags) => tags::normalize(tags).map_err(|e| JsValue::from_str(&format!("Failed to register rule {}: {}", id, e)))?,
+            None => self.rules.get(id).map(|old| old.tags.clone()).unwrap_or_default(),
+        };
+        lisp::check_code(lisp_code).map_err(|e| JsValue::from_str(&format!("Failed to register rule {}: {}", id, e)))?;
+        let program = lisp::Program::compile(lisp_code);
+        let (version, versions) = rule_versions::next_version(self.rules.get(id));
         
         let rule = Rule {
             id: id.to_string(),
             lisp_code: lisp_code.to_string(),
             execution_count: 0,
-            total_execution_time_ms: 0.0,
+            total_execution_time_ms: CompensatedSum::default(),
             last_execution_time_ms: 0.0,
-            created_at: js_sys::Date::now() as u64,
+            created_at: clock::now_ms() as u64,
+            error_count: 0,
+            last_error: None,
+            // Test cases, mode membership, parameters and scheduling survive edits so the new code can be checked against them
+            tests: self.rules.get(id).map(|old| old.tests.clone()).unwrap_or_default(),
+            shadow: None,
+            modes: self.rules.get(id).map(|old| old.modes.clone()).unwrap_or_default(),
+            params: self.rules.get(id).map(|old| old.params.clone()).unwrap_or_default(),
+            enabled: self.rules.get(id).is_none_or(|old| old.enabled),
+            priority: self.rules.get(id).map_or(0, |old| old.priority),
+            schedule: self.rules.get(id).map(|old| old.schedule.clone()).unwrap_or_default(),
+            trigger: self.rules.get(id).and_then(|old| old.trigger.clone()),
+            runs_after: self.rules.get(id).map(|old| old.runs_after.clone()).unwrap_or_default(),
+            tags,
+            limits: self.rules.get(id).map(|old| old.limits.clone()).unwrap_or_default(),
+            version,
+            versions,
+            doc: lisp::extract_docstring(lisp_code),
+            access: lisp::analyze_access(lisp_code),
+            program,
+            hotspot: HotspotState::default(),
         };
         
         self.rules.insert(id.to_string(), rule);
@@ -251,6 +853,7 @@ impl RuleRegistry {
     /// Remove a rule from the registry
     #[wasm_bindgen(js_name = removeRule)]
     pub fn remove_rule(&mut self, id: &str) -> bool {
+        let _call = call_log::enter("removeRule", || serde_json::json!({ "id": id }));
         self.execution_order.retain(|rule_id| rule_id != id);
         self.rules.remove(id).is_some()
     }
@@ -261,10 +864,30 @@ impl RuleRegistry {
         self.rules.get(id).map(|rule| rule.lisp_code.clone())
     }
     
-    /// Get all rule IDs in execution order
+    /// Get the docstring of a rule (undefined when it has none)
+    #[wasm_bindgen(js_name = getRuleDoc)]
+    pub fn get_rule_doc(&self, id: &str) -> Result<Option<String>, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.doc.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+    
+    /// Get the docstrings of all documented rules as a JSON object keyed by rule ID
+    #[wasm_bindgen(js_name = getRuleDocs)]
+    pub fn get_rule_docs(&self) -> Result<String, JsValue> {
+        let docs: BTreeMap<&str, &str> = self.rules
+            .values()
+            .filter_map(|rule| rule.doc.as_deref().map(|doc| (rule.id.as_str(), doc)))
+            .collect();
+        
+        serde_json::to_string(&docs)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule docs: {}", e)))
+    }
+    
+    /// Get all rule IDs in the order ticks run them (see `setRulePriority`)
     #[wasm_bindgen(js_name = getRuleIds)]
     pub fn get_rule_ids(&self) -> Vec<String> {
-        self.execution_order.clone()
+        self.run_order().into_iter().cloned().collect()
     }
     
     /// Get the number of rules in the registry
@@ -273,13 +896,41 @@ impl RuleRegistry {
         self.rules.len()
     }
     
+    /// Fuel a rule may spend per application before it is aborted
+    ///
+    /// Each instruction (or interpreter step) costs one unit, and copying
+    /// or building a list one more per value in it.
+    #[wasm_bindgen(getter, js_name = fuelLimit)]
+    pub fn fuel_limit(&self) -> u64 {
+        self.fuel_limit
+    }
+    
+    #[wasm_bindgen(setter, js_name = fuelLimit)]
+    pub fn set_fuel_limit(&mut self, value: u64) {
+        let _call = call_log::enter("setFuelLimit", || serde_json::json!({ "value": value }));
+        self.fuel_limit = value.max(1);
+    }
+    
     /// Record rule execution with timing information
     #[wasm_bindgen(js_name = recordExecution)]
     pub fn record_execution(&mut self, id: &str, execution_time_ms: f64) -> Result<(), JsValue> {
+        let _call = call_log::enter("recordExecution", || serde_json::json!({
+            "id": id,
+            "executionTimeMs": execution_time_ms,
+        }));
+        assertions::check(
+            Severity::Warning,
+            "execution-time",
+            || execution_time_ms.is_finite() && execution_time_ms >= 0.0,
+            || format!("Rule {} recorded an execution time of {} ms", id, execution_time_ms),
+        );
         if let Some(rule) = self.rules.get_mut(id) {
             rule.execution_count += 1;
-            rule.total_execution_time_ms += execution_time_ms;
+            rule.total_execution_time_ms.add(execution_time_ms);
             rule.last_execution_time_ms = execution_time_ms;
+            if features::enabled(Feature::Hotspot) {
+                features::measure(Feature::Hotspot, || self.observe_latency(id, execution_time_ms));
+            }
             Ok(())
         } else {
             Err(JsValue::from_str(&format!("Rule not found: {}", id)))
@@ -291,7 +942,7 @@ impl RuleRegistry {
     pub fn get_rule_stats(&self, id: &str) -> Result<String, JsValue> {
         if let Some(rule) = self.rules.get(id) {
             let avg_time = if rule.execution_count > 0 {
-                rule.total_execution_time_ms / rule.execution_count as f64
+                rule.total_execution_time_ms.value() / rule.execution_count as f64
             } else {
                 0.0
             };
@@ -299,10 +950,17 @@ impl RuleRegistry {
             let stats = serde_json::json!({
                 "id": rule.id,
                 "executionCount": rule.execution_count,
-                "totalExecutionTimeMs": rule.total_execution_time_ms,
+                "totalExecutionTimeMs": rule.total_execution_time_ms.value(),
                 "lastExecutionTimeMs": rule.last_execution_time_ms,
                 "averageExecutionTimeMs": avg_time,
                 "createdAt": rule.created_at,
+                "errorCount": rule.error_count,
+                "lastError": rule.last_error,
+                "reads": rule.access.reads,
+                "writes": rule.access.writes,
+                "bytecode": rule.program.is_bytecode(),
+                "enabled": rule.enabled,
+                "priority": rule.priority,
             });
             
             serde_json::to_string(&stats)
@@ -317,10 +975,10 @@ impl RuleRegistry {
     pub fn get_all_stats(&self) -> Result<String, JsValue> {
         let mut all_stats = Vec::new();
         
-        for rule_id in &self.execution_order {
+        for rule_id in self.run_order() {
             if let Some(rule) = self.rules.get(rule_id) {
                 let avg_time = if rule.execution_count > 0 {
-                    rule.total_execution_time_ms / rule.execution_count as f64
+                    rule.total_execution_time_ms.value() / rule.execution_count as f64
                 } else {
                     0.0
                 };
@@ -328,10 +986,17 @@ impl RuleRegistry {
                 all_stats.push(serde_json::json!({
                     "id": rule.id,
                     "executionCount": rule.execution_count,
-                    "totalExecutionTimeMs": rule.total_execution_time_ms,
+                    "totalExecutionTimeMs": rule.total_execution_time_ms.value(),
                     "lastExecutionTimeMs": rule.last_execution_time_ms,
                     "averageExecutionTimeMs": avg_time,
                     "createdAt": rule.created_at,
+                    "errorCount": rule.error_count,
+                    "lastError": rule.last_error,
+                    "reads": rule.access.reads,
+                    "writes": rule.access.writes,
+                    "bytecode": rule.program.is_bytecode(),
+                    "enabled": rule.enabled,
+                    "priority": rule.priority,
                 }));
             }
         }
@@ -345,32 +1010,121 @@ impl RuleRegistry {
     pub fn clear_stats(&mut self) {
         for rule in self.rules.values_mut() {
             rule.execution_count = 0;
-            rule.total_execution_time_ms = 0.0;
+            rule.total_execution_time_ms = CompensatedSum::default();
             rule.last_execution_time_ms = 0.0;
+            rule.error_count = 0;
+            rule.last_error = None;
         }
     }
     
+    /// Run one tick of the registry against a state
+    ///
+    /// Selects the active mode, then applies every enabled rule that runs
+    /// in it, after the rules it depends on and otherwise highest priority
+    /// first. Rules quarantined or demoted by the hotspot policy
+    /// are skipped when they are not due, as are rules whose schedule (see
+    /// `setRuleSchedule`) leaves out this step or whose trigger (see
+    /// `setRuleTrigger`) does not fire. Returns JSON with the `step`, the active `mode`,
+    /// whether the mode changed since the last tick, the `applied` rules
+    /// with their results and, in debug mode, the first violated invariant
+    /// as `invariantViolation` (null when all hold).
+    #[wasm_bindgen]
+    pub fn tick(&mut self, state: &mut OrganismState, step_index: u32) -> Result<String, JsValue> {
+        let _call = call_log::enter("tick", || serde_json::json!({ "stepIndex": step_index }));
+        self.current_step = step_index as u64;
+        let selection = self.select_mode(state)?;
+        
+        let generation_changed = self.generation_changed(state.generation);
+        self.last_generation = Some(state.generation);
+        let triggers = self.evaluate_triggers(state, selection.mode.as_deref())?;
+        let due: Vec<String> = self.due_rules(selection.mode.as_deref(), step_index, generation_changed, &triggers)
+            .into_iter()
+            .cloned()
+            .collect();
+        self.settle_triggers(&triggers);
+        
+        let mut applied = Vec::with_capacity(due.len());
+        for id in due {
+            let result = apply_rule(self, state, &id, Vec::new())?;
+            applied.push(serde_json::json!({ "ruleId": id, "result": result }));
+        }
+        let violation = if features::enabled(Feature::Invariants) {
+            features::measure(Feature::Invariants, || self.check_invariants_after_tick(state))
+        } else {
+            None
+        };
+        let goals = self.update_goals(state);
+        
+        let summary = serde_json::json!({
+            "step": step_index,
+            "mode": selection.mode,
+            "modeChanged": selection.changed,
+            "applied": applied,
+            "invariantViolation": violation,
+            "goals": goals,
+        });
+        
+        serde_json::to_string(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize tick summary: {}", e)))
+    }
+    
     /// Export the registry as JSON
     #[wasm_bindgen(js_name = exportRegistry)]
     pub fn export_registry(&self) -> Result<String, JsValue> {
         let export_data = serde_json::json!({
             "rules": self.rules,
             "executionOrder": self.execution_order,
+            "bundles": self.bundles,
+            "modes": self.modes,
+            "invariants": self.invariants.list,
+            "goals": self.goals,
+            "fuelLimit": self.fuel_limit,
         });
         
-        serde_json::to_string(&export_data)
-            .map_err(|e| JsValue::from_str(&format!("Failed to export registry: {}", e)))
+        let json = serde_json::to_string(&export_data)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export registry: {}", e)))?;
+        migration::stamp("registry", json)
     }
     
     /// Import a registry from JSON
     #[wasm_bindgen(js_name = importRegistry)]
     pub fn import_registry(&mut self, json: &str) -> Result<(), JsValue> {
-        let data: serde_json::Value = serde_json::from_str(json)
+        self.import_registry_checked(json, false)
+    }
+    
+    /// Import a registry from JSON with an explicit strictness
+    ///
+    /// Size and nesting limits always apply. When `strict`, duplicate keys
+    /// and null rule statistics are rejected; otherwise null statistics are
+    /// reset to zero.
+    #[wasm_bindgen(js_name = importRegistryChecked)]
+    pub fn import_registry_checked(&mut self, json: &str, strict: bool) -> Result<(), JsValue> {
+        let _call = call_log::enter("importRegistry", || serde_json::json!({
+            "registry": call_log::text(json),
+            "strict": strict,
+        }));
+        import_guard::check_structure("Registry", json, strict)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse registry JSON: {}", e)))?;
+        let migrated = migration::migrate("registry", json)?;
+        let mut data: serde_json::Value = serde_json::from_str(&migrated)
             .map_err(|e| JsValue::from_str(&format!("Failed to parse registry JSON: {}", e)))?;
+        import_guard::settle_registry(&mut data, strict)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse registry JSON: {}", e)))?;
+        let problems = validation::registry_shape_problems(&data);
+        if !problems.is_empty() {
+            return Err(JsValue::from_str(&format!("Failed to parse registry: {}", validation::summarize(&problems))));
+        }
         
         if let Some(rules_obj) = data.get("rules") {
-            let rules: HashMap<String, Rule> = serde_json::from_value(rules_obj.clone())
+            let mut rules: HashMap<String, Rule> = serde_json::from_value(rules_obj.clone())
                 .map_err(|e| JsValue::from_str(&format!("Failed to parse rules: {}", e)))?;
+            if let Some(cycle) = rule_order::find_cycle(&rules) {
+                return Err(JsValue::from_str(&format!("Failed to parse registry: rule dependencies form a cycle: {}", cycle.join(" -> "))));
+            }
+            for rule in rules.values_mut() {
+                rule.access = lisp::analyze_access(&rule.lisp_code);
+                rule.program = lisp::Program::compile(&rule.lisp_code);
+            }
             self.rules = rules;
         }
         
@@ -380,6 +1134,34 @@ impl RuleRegistry {
             self.execution_order = order;
         }
         
+        if let Some(bundles_obj) = data.get("bundles") {
+            let bundles: BTreeMap<String, InstalledBundle> = serde_json::from_value(bundles_obj.clone())
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse bundles: {}", e)))?;
+            self.bundles = bundles;
+        }
+        
+        if let Some(modes_arr) = data.get("modes") {
+            let modes: Vec<Mode> = serde_json::from_value(modes_arr.clone())
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse modes: {}", e)))?;
+            self.modes = modes;
+            self.active_mode = None;
+        }
+        
+        if let Some(invariants_arr) = data.get("invariants") {
+            let invariants: Vec<Invariant> = serde_json::from_value(invariants_arr.clone())
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse invariants: {}", e)))?;
+            self.invariants.list = invariants;
+        }
+        
+        if let Some(goals_arr) = data.get("goals") {
+            let goals: Vec<Goal> = serde_json::from_value(goals_arr.clone())
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse goals: {}", e)))?;
+            self.goals = goals;
+        }
+        
+        // Exports from before the limit was saved ran with the default
+        self.fuel_limit = data.get("fuelLimit").and_then(|v| v.as_u64()).unwrap_or(lisp::MAX_STEPS);
+        
         Ok(())
     }
 }
@@ -399,36 +1181,64 @@ pub fn apply_rule(
     rule_id: &str,
     params: Vec<f64>,
 ) -> Result<f64, JsValue> {
-    // Get the rule code
-    let rule_code = registry.get_rule_code(rule_id)
+    let _call = call_log::enter("applyRule", || serde_json::json!({ "ruleId": rule_id, "params": params }));
+    
+    
+    // Make sure the rule exists before doing any work, compiling it if it was deserialized
+    let rule = registry.rules.get_mut(rule_id)
         .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", rule_id)))?;
+    if !rule.program.is_compiled() {
+        rule.program = lisp::Program::compile(&rule.lisp_code);
+    }
+    
+    // Mirror the state for a shadow candidate before the live version touches it
+    let mut mirror = (features::enabled(Feature::Shadow) && registry.has_shadow(rule_id)).then(|| state.clone());
     
     // Start timing
-    let start_time = js_sys::Date::now();
+    let start_time = clock::now_ms();
     
-    // Apply rule logic based on parameters
-    // In a real implementation, this would invoke the Lisp interpreter
-    // For now, we'll do a simple parameter-based state update
-    let result = apply_rule_logic(state, &params);
+    let budget = registry.budget(rule_id);
+    let result = match apply_rule_logic(&registry.rules[rule_id].program, state, &params, budget) {
+        Ok(result) => result,
+        Err(e) => {
+            registry.record_failure(rule_id, &e);
+            return Err(JsValue::from_str(&format!("Rule {} failed: {}", rule_id, e)));
+        }
+    };
+    assertions::check(
+        Severity::Warning,
+        "finite-rule-result",
+        || result.is_finite(),
+        || format!("Rule {} returned {}", rule_id, result),
+    );
     
     // Record execution time
-    let execution_time = js_sys::Date::now() - start_time;
+    let execution_time = clock::now_ms() - start_time;
     registry.record_execution(rule_id, execution_time)?;
     
+    if let Some(mirror) = mirror.as_mut() {
+        features::measure(Feature::Shadow, || {
+            registry.run_shadow(rule_id, mirror, state, &params, result, execution_time)
+        });
+    }
+    
     Ok(result)
 }
 
-/// Internal function to apply rule logic
-/// This is a placeholder that will be replaced by Lisp interpreter integration
-fn apply_rule_logic(state: &mut OrganismState, params: &[f64]) -> f64 {
-    // Simple example: adjust mutation rate based on first parameter
-    if !params.is_empty() {
-        let adjustment = params[0];
-        state.set_mutation_rate(state.mutation_rate() + adjustment * 0.01);
+/// Run a rule's compiled code against a state
+///
+/// The code reads and writes metrics through the Lisp environment
+/// (`(population)`, `(set-mutation-rate ...)`) and `params` are bound to a
+/// top-level lambda's arguments. A rule that fails, including one that
+/// exceeds its `budget`, leaves the metrics as they were before it ran.
+fn apply_rule_logic(program: &lisp::Program, state: &mut OrganismState, params: &[f64], budget: lisp::Budget) -> Result<f64, String> {
+    let before = (state.population, state.energy, state.generation, state.mutation_rate, state.selection_pressure);
+    let result = program.run(state, params, budget);
+    if result.is_err() {
+        (state.population, state.energy, state.generation, state.mutation_rate, state.selection_pressure) = before;
+        state.sync_state_vector();
     }
-    
-    // Return the adaptation score
-    state.adaptation_score()
+    result
 }
 
 /// Initialize the WASM module
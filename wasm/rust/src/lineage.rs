@@ -0,0 +1,204 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::world::{Organism, World};
+
+/// What is remembered about an organism after it dies
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct LineageRecord {
+    parent: Option<u32>,
+    mate: Option<u32>,
+    generation: u64,
+    born_at: u64,
+    died_at: Option<u64>,
+    /// Adaptation score at death; living organisms report their current one
+    score: f64,
+}
+
+/// Every organism a world has held, linked to its parent
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct Lineage {
+    records: BTreeMap<u32, LineageRecord>,
+}
+
+impl Lineage {
+    pub(crate) fn born(&mut self, organism: &Organism) {
+        self.records.insert(organism.id, LineageRecord {
+            parent: organism.parent,
+            mate: organism.mate,
+            generation: organism.generation,
+            born_at: organism.born_at,
+            died_at: None,
+            score: organism.adaptation_score,
+        });
+    }
+    
+    pub(crate) fn died(&mut self, organism: &Organism, tick: u64) {
+        if let Some(record) = self.records.get_mut(&organism.id) {
+            record.died_at = Some(tick);
+            record.score = organism.adaptation_score;
+        }
+    }
+    
+    pub(crate) fn len(&self) -> usize {
+        self.records.len()
+    }
+    
+    /// Children of each recorded organism, in ID order
+    fn children(&self) -> BTreeMap<u32, Vec<u32>> {
+        let mut children: BTreeMap<u32, Vec<u32>> = BTreeMap::new();
+        for (id, record) in &self.records {
+            if let Some(parent) = record.parent.filter(|p| self.records.contains_key(p)) {
+                children.entry(parent).or_default().push(*id);
+            }
+        }
+        children
+    }
+    
+    /// Organisms whose parent isn't recorded: founders, or the oldest left after pruning
+    fn roots(&self) -> Vec<u32> {
+        self.records
+            .iter()
+            .filter(|(_, record)| record.parent.is_none_or(|p| !self.records.contains_key(&p)))
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl World {
+    /// Organisms recorded in the lineage, living and dead
+    #[wasm_bindgen(getter, js_name = lineageSize)]
+    pub fn lineage_size(&self) -> usize {
+        self.lineage.len()
+    }
+    
+    /// An organism's line of descent as JSON, from the organism back to its founder
+    ///
+    /// Each entry is `{id, parent, mate, generation, bornAt, diedAt,
+    /// adaptationScore, alive}`; the score of a dead organism is the one it
+    /// died with. Dead organisms can be looked up until they are pruned.
+    #[wasm_bindgen(js_name = getAncestry)]
+    pub fn get_ancestry(&self, id: u32) -> Result<String, JsValue> {
+        if !self.lineage.records.contains_key(&id) {
+            return Err(JsValue::from_str(&format!("Organism not in lineage: {}", id)));
+        }
+        let mut ancestry = Vec::new();
+        let mut current = Some(id);
+        while let Some((id, record)) = current.and_then(|id| self.lineage.records.get(&id).map(|r| (id, r))) {
+            ancestry.push(serde_json::json!({
+                "id": id,
+                "parent": record.parent,
+                "mate": record.mate,
+                "generation": record.generation,
+                "bornAt": record.born_at,
+                "diedAt": record.died_at,
+                "adaptationScore": self.score_of(id, record),
+                "alive": record.died_at.is_none(),
+            }));
+            current = record.parent;
+        }
+        serde_json::to_string(&ancestry)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize ancestry: {}", e)))
+    }
+    
+    /// IDs of an organism's recorded children
+    #[wasm_bindgen(js_name = getChildren)]
+    pub fn get_children(&self, id: u32) -> Vec<u32> {
+        self.lineage.records
+            .iter()
+            .filter(|(_, record)| record.parent == Some(id))
+            .map(|(child, _)| *child)
+            .collect()
+    }
+    
+    /// Export the phylogeny as Newick text
+    ///
+    /// Nodes are labelled with organism IDs, carry
+    /// `[&&NHX:generation=G:score=S:alive=0|1]` comments, and have branch
+    /// lengths in ticks between the parent's birth and the child's. Only
+    /// the dividing parent counts, so under sexual reproduction the tree
+    /// follows one parent per child. Several founders are joined under an
+    /// unlabelled root. With `root` given, only that organism's subtree is
+    /// exported.
+    #[wasm_bindgen(js_name = exportNewick)]
+    pub fn export_newick(&self, root: Option<u32>) -> Result<String, JsValue> {
+        let roots = match root {
+            Some(id) if self.lineage.records.contains_key(&id) => vec![id],
+            Some(id) => return Err(JsValue::from_str(&format!("Organism not in lineage: {}", id))),
+            None => self.lineage.roots(),
+        };
+        let children = self.lineage.children();
+        
+        // Post-order without recursion, since lineages can run thousands of generations deep
+        let mut text: BTreeMap<u32, String> = BTreeMap::new();
+        let mut stack: Vec<(u32, bool)> = roots.iter().rev().map(|id| (*id, false)).collect();
+        while let Some((id, expanded)) = stack.pop() {
+            let kids = children.get(&id).map(Vec::as_slice).unwrap_or(&[]);
+            if !expanded && !kids.is_empty() {
+                stack.push((id, true));
+                stack.extend(kids.iter().rev().map(|kid| (*kid, false)));
+                continue;
+            }
+            let mut node = String::new();
+            if !kids.is_empty() {
+                let parts: Vec<String> = kids.iter().filter_map(|kid| text.remove(kid)).collect();
+                node = format!("({})", parts.join(","));
+            }
+            node.push_str(&self.newick_label(id, root == Some(id)));
+            text.insert(id, node);
+        }
+        
+        let trees: Vec<String> = roots.iter().filter_map(|id| text.remove(id)).collect();
+        Ok(match trees.as_slice() {
+            [] => ";".to_string(),
+            [tree] => format!("{};", tree),
+            _ => format!("({});", trees.join(",")),
+        })
+    }
+    
+    /// Forget dead organisms with no living descendants and return how many were dropped
+    #[wasm_bindgen(js_name = pruneLineage)]
+    pub fn prune_lineage(&mut self) -> usize {
+        let mut keep = BTreeSet::new();
+        for organism in &self.organisms {
+            let mut current = Some(organism.id);
+            while let Some(id) = current {
+                if !keep.insert(id) {
+                    break;
+                }
+                current = self.lineage.records.get(&id).and_then(|record| record.parent);
+            }
+        }
+        let before = self.lineage.records.len();
+        self.lineage.records.retain(|id, _| keep.contains(id));
+        before - self.lineage.records.len()
+    }
+}
+
+impl World {
+    fn score_of(&self, id: u32, record: &LineageRecord) -> f64 {
+        match record.died_at {
+            Some(_) => record.score,
+            None => self.organism(id).map_or(record.score, |o| o.adaptation_score),
+        }
+    }
+    
+    /// `id:length[&&NHX:...]`, leaving the length off a subtree's root
+    fn newick_label(&self, id: u32, is_root: bool) -> String {
+        let record = &self.lineage.records[&id];
+        let mut label = id.to_string();
+        let parent = record.parent.and_then(|p| self.lineage.records.get(&p)).filter(|_| !is_root);
+        if let Some(parent) = parent {
+            label.push_str(&format!(":{}", record.born_at.saturating_sub(parent.born_at)));
+        }
+        label.push_str(&format!(
+            "[&&NHX:generation={}:score={}:alive={}]",
+            record.generation,
+            self.score_of(id, record),
+            if record.died_at.is_none() { 1 } else { 0 }
+        ));
+        label
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use wasm_bindgen::prelude::*;
+
+use super::language::{lookup_metric, setter_target, STATE_VECTOR_METRICS};
+use super::reader::{parse, Expr, Node};
+use crate::{Rule, RuleRegistry};
+
+/// Metrics a rule's code can read and write
+///
+/// This is a static over-approximation: every metric mentioned on any branch
+/// counts, whether or not the branch runs.
+#[derive(Clone, Default, Debug)]
+pub(crate) struct RuleAccess {
+    pub reads: BTreeSet<&'static str>,
+    pub writes: BTreeSet<&'static str>,
+}
+
+impl RuleAccess {
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "reads": self.reads,
+            "writes": self.writes,
+        })
+    }
+}
+
+/// Work out which metrics rule code reads and writes
+///
+/// Code that doesn't parse has empty access sets.
+pub(crate) fn analyze_access(source: &str) -> RuleAccess {
+    let mut access = RuleAccess::default();
+    if let Ok(forms) = parse(source) {
+        let mut bound = Vec::new();
+        for form in &forms {
+            collect(form, &mut bound, &mut access);
+        }
+    }
+    access
+}
+
+/// Names bound by a binding form, so locals that shadow metrics aren't counted
+pub(super) fn bound_names(head: &str, args: &[Node]) -> Vec<String> {
+    let symbols = |nodes: &[Node]| -> Vec<String> {
+        nodes.iter().filter_map(Node::as_symbol).map(str::to_string).collect()
+    };
+    
+    match head {
+        "lambda" => args.first().and_then(Node::as_list).map(symbols).unwrap_or_default(),
+        "let" | "let*" => args.first()
+            .and_then(Node::as_list)
+            .unwrap_or(&[])
+            .iter()
+            .filter_map(|binding| binding.as_list().and_then(|parts| parts.first()))
+            .filter_map(Node::as_symbol)
+            .map(str::to_string)
+            .collect(),
+        "dotimes" => args.first()
+            .and_then(Node::as_list)
+            .and_then(|spec| spec.first())
+            .map(|counter| symbols(std::slice::from_ref(counter)))
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    }
+}
+
+fn collect(node: &Node, bound: &mut Vec<String>, access: &mut RuleAccess) {
+    let is_bound = |bound: &[String], name: &str| bound.iter().any(|b| b == name);
+    
+    match &node.expr {
+        Expr::Symbol(name) => {
+            if !is_bound(bound, name) {
+                if let Some(metric) = lookup_metric(name) {
+                    access.reads.insert(metric.name);
+                }
+            }
+        }
+        Expr::List(items) => {
+            let head = items.first().and_then(Node::as_symbol).unwrap_or("");
+            let args = items.get(1..).unwrap_or(&[]);
+            
+            match head {
+                "quote" => return,
+                "def" | "define" => {
+                    if let Some(name) = args.first().and_then(Node::as_symbol) {
+                        bound.push(name.to_string());
+                    }
+                    for arg in args.iter().skip(1) {
+                        collect(arg, bound, access);
+                    }
+                    return;
+                }
+                "set!" => {
+                    if let Some(name) = args.first().and_then(Node::as_symbol) {
+                        if !is_bound(bound, name) {
+                            if let Some(metric) = lookup_metric(name) {
+                                access.writes.insert(metric.name);
+                            }
+                        }
+                    }
+                    for arg in args.iter().skip(1) {
+                        collect(arg, bound, access);
+                    }
+                    return;
+                }
+                _ => {}
+            }
+            
+            if let Some(metric) = setter_target(head) {
+                access.writes.insert(metric.name);
+            } else if head == "state-vector" && !is_bound(bound, head) {
+                access.reads.extend(STATE_VECTOR_METRICS);
+            } else if !is_bound(bound, head) {
+                if let Some(metric) = lookup_metric(head) {
+                    access.reads.insert(metric.name);
+                }
+            }
+            
+            let names = bound_names(head, args);
+            let scope_start = bound.len();
+            bound.extend(names);
+            for arg in args {
+                collect(arg, bound, access);
+            }
+            bound.truncate(scope_start);
+        }
+        Expr::Number(_) | Expr::Bool(_) | Expr::Str(_) => {}
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Get the metrics a rule reads and writes as JSON (`{ reads, writes }`)
+    #[wasm_bindgen(js_name = getRuleAccess)]
+    pub fn get_rule_access(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        
+        serde_json::to_string(&rule.access.to_json())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule access: {}", e)))
+    }
+    
+    /// Find metrics written by more than one rule in a tick
+    ///
+    /// Two rules that can run in the same tick and write the same metric
+    /// mean the later one silently overwrites or compounds the earlier
+    /// one's change. Disabled rules and pairs of rules whose modes never
+    /// overlap are skipped; schedules and triggers only decide which ticks
+    /// a rule runs in, so rules are counted as if they were always due.
+    /// Returns a JSON array of `{ metric, writers, readers }` with rule IDs
+    /// in run order.
+    #[wasm_bindgen(js_name = getRuleConflicts)]
+    pub fn get_rule_conflicts(&self) -> Result<String, JsValue> {
+        let rules: Vec<&Rule> = self.run_order()
+            .into_iter()
+            .filter_map(|id| self.rules.get(id))
+            .filter(|rule| rule.enabled)
+            .collect();
+        let share_tick = |a: &Rule, b: &Rule| {
+            a.modes.is_empty() || b.modes.is_empty() || a.modes.iter().any(|m| b.modes.contains(m))
+        };
+        
+        let mut writers: BTreeMap<&str, Vec<&Rule>> = BTreeMap::new();
+        let mut readers: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for rule in &rules {
+            for metric in &rule.access.writes {
+                writers.entry(metric).or_default().push(rule);
+            }
+            for metric in &rule.access.reads {
+                readers.entry(metric).or_default().push(&rule.id);
+            }
+        }
+        let writers = writers.into_iter().map(|(metric, rules)| {
+            let clashing: Vec<&str> = rules
+                .iter()
+                .filter(|rule| rules.iter().any(|other| other.id != rule.id && share_tick(rule, other)))
+                .map(|rule| rule.id.as_str())
+                .collect();
+            (metric, clashing)
+        });
+        
+        let conflicts: Vec<serde_json::Value> = writers
+            .into_iter()
+            .filter(|(_, ids)| ids.len() > 1)
+            .map(|(metric, ids)| serde_json::json!({
+                "metric": metric,
+                "writers": ids,
+                "readers": readers.get(metric).cloned().unwrap_or_default(),
+            }))
+            .collect();
+        
+        serde_json::to_string(&conflicts)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule conflicts: {}", e)))
+    }
+}
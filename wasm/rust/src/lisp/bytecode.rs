@@ -0,0 +1,766 @@
+use super::eval::{apply_builtin, error, Budget, EvalError, Meter, Value};
+use super::language::{lookup_metric, setter_target, BUILTINS, METRICS};
+use super::reader::{Expr, Node, Span};
+use crate::OrganismState;
+
+/// Nesting the compiler follows before leaving code to the interpreter,
+/// kept under the interpreter's own limit so deep code fails the same way
+const MAX_COMPILE_DEPTH: usize = 200;
+
+/// Two-argument built-ins with their own instruction
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Eq,
+}
+
+impl BinaryOp {
+    fn from_name(name: &str) -> Option<BinaryOp> {
+        match name {
+            "+" => Some(BinaryOp::Add),
+            "-" => Some(BinaryOp::Sub),
+            "*" => Some(BinaryOp::Mul),
+            "/" => Some(BinaryOp::Div),
+            "<" => Some(BinaryOp::Lt),
+            ">" => Some(BinaryOp::Gt),
+            "<=" => Some(BinaryOp::Le),
+            ">=" => Some(BinaryOp::Ge),
+            "=" => Some(BinaryOp::Eq),
+            _ => None,
+        }
+    }
+    
+    fn name(self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Lt => "<",
+            BinaryOp::Gt => ">",
+            BinaryOp::Le => "<=",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "=",
+        }
+    }
+}
+
+/// One VM instruction; jump targets are instruction indices
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Const(u32),
+    Nil,
+    Pop,
+    LoadLocal(u16),
+    /// Pop into a local
+    StoreLocal(u16),
+    /// Copy the top of the stack into a local, leaving it in place
+    SetLocal(u16),
+    LoadParam(u16),
+    LoadMetric(u8),
+    /// Fail unless the top of the stack is a number
+    CheckNumber,
+    /// Pop a number, write it to a metric and push the value stored
+    SetMetric(u8),
+    /// Resolve a name at run time: custom metric, then `nil`
+    LoadNamed(u32),
+    Jump(u32),
+    /// Pop and jump when false
+    JumpIfFalse(u32),
+    /// Jump keeping the value when false, otherwise pop it
+    JumpIfFalseKeep(u32),
+    /// Jump keeping the value when true, otherwise pop it
+    JumpIfTrueKeep(u32),
+    /// Pop a number and store it in a local as a loop count
+    StoreCount(u16),
+    /// Step a `dotimes` loop: bind the counter and advance, or jump out when done
+    LoopNext { index: u16, limit: u16, counter: u16, exit: u32 },
+    Binary(BinaryOp),
+    Call { builtin: u8, argc: u16 },
+}
+
+/// Rule code compiled to instructions for `execute`
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Bytecode {
+    ops: Vec<Op>,
+    /// Source position of each instruction, for errors
+    spans: Vec<Span>,
+    constants: Vec<Value>,
+    locals: usize,
+}
+
+impl Bytecode {
+    /// Estimated bytes held by the instructions and constants
+    pub fn heap_bytes(&self) -> usize {
+        self.ops.capacity() * std::mem::size_of::<Op>()
+            + self.spans.capacity() * std::mem::size_of::<Span>()
+            + self.constants.capacity() * std::mem::size_of::<Value>()
+    }
+
+}
+
+/// Why a rule stays on the interpreter
+type Unsupported = String;
+
+struct Compiler {
+    code: Bytecode,
+    /// Visible local names per scope, innermost last
+    scopes: Vec<Vec<(String, u16)>>,
+    /// How many conditional or looping bodies enclose the current form
+    conditional: usize,
+    depth: usize,
+}
+
+/// Compile parsed rule code, or explain why it has to be interpreted
+///
+/// The compiler covers the forms rules use in practice. Anything it
+/// doesn't (malformed forms, `define` inside a branch or loop, ...)
+/// leaves the rule on the tree-walking interpreter, which gives the same
+/// results and reports the errors.
+pub(crate) fn compile(forms: &[Node]) -> Result<Bytecode, Unsupported> {
+    let mut compiler = Compiler { code: Bytecode::default(), scopes: vec![Vec::new()], conditional: 0, depth: 0 };
+    let span = forms.first().map(|form| form.span).unwrap_or_default();
+    if forms.is_empty() {
+        compiler.emit(Op::Nil, span);
+    }
+    for (index, form) in forms.iter().enumerate() {
+        if index > 0 {
+            compiler.emit(Op::Pop, form.span);
+        }
+        match form.as_list() {
+            Some([head, rest @ ..]) if head.as_symbol() == Some("lambda") => compiler.rule_lambda(form, rest)?,
+            _ => compiler.expr(form)?,
+        }
+    }
+    Ok(compiler.code)
+}
+
+impl Compiler {
+    fn emit(&mut self, op: Op, span: Span) -> usize {
+        self.code.ops.push(op);
+        self.code.spans.push(span);
+        self.code.ops.len() - 1
+    }
+    
+    fn here(&self) -> u32 {
+        self.code.ops.len() as u32
+    }
+    
+    /// Point a jump emitted earlier at the current position
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+        match &mut self.code.ops[at] {
+            Op::Jump(to) | Op::JumpIfFalse(to) | Op::JumpIfFalseKeep(to) | Op::JumpIfTrueKeep(to) => *to = target,
+            Op::LoopNext { exit, .. } => *exit = target,
+            _ => {}
+        }
+    }
+    
+    fn constant(&mut self, value: Value) -> u32 {
+        let index = self.code.constants.iter().position(|existing| *existing == value);
+        match index {
+            Some(index) => index as u32,
+            None => {
+                self.code.constants.push(value);
+                (self.code.constants.len() - 1) as u32
+            }
+        }
+    }
+    
+    fn new_slot(&mut self) -> Result<u16, Unsupported> {
+        let slot = u16::try_from(self.code.locals).map_err(|_| "too many local variables".to_string())?;
+        self.code.locals += 1;
+        Ok(slot)
+    }
+    
+    /// Bind a name in the innermost scope, reusing the slot of an earlier binding there
+    fn bind(&mut self, name: &str) -> Result<u16, Unsupported> {
+        let existing = self.scopes.last().and_then(|scope| scope.iter().find(|(bound, _)| bound == name));
+        if let Some((_, slot)) = existing {
+            return Ok(*slot);
+        }
+        let slot = self.new_slot()?;
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push((name.to_string(), slot));
+        }
+        Ok(slot)
+    }
+    
+    fn lookup(&self, name: &str) -> Option<u16> {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.iter().rev().find(|(bound, _)| bound == name))
+            .map(|(_, slot)| *slot)
+    }
+    
+    fn metric_index(name: &str) -> Option<u8> {
+        let metric = lookup_metric(name)?;
+        METRICS.iter().position(|m| m.canonical == metric.canonical).map(|index| index as u8)
+    }
+    
+    fn rule_lambda(&mut self, form: &Node, args: &[Node]) -> Result<(), Unsupported> {
+        let params = args.first().and_then(Node::as_list).ok_or("malformed lambda")?;
+        self.scopes.push(Vec::new());
+        for (index, param) in params.iter().enumerate() {
+            let name = param.as_symbol().ok_or("malformed lambda")?;
+            match index {
+                0 => self.emit(Op::Nil, form.span),
+                _ => self.emit(Op::LoadParam((index - 1) as u16), form.span),
+            };
+            let slot = self.bind(name)?;
+            self.emit(Op::StoreLocal(slot), form.span);
+        }
+        self.body(&args[1..], form.span)?;
+        self.scopes.pop();
+        Ok(())
+    }
+    
+    /// Compile forms in sequence, leaving the last value (nil when empty)
+    fn body(&mut self, forms: &[Node], span: Span) -> Result<(), Unsupported> {
+        if forms.is_empty() {
+            self.emit(Op::Nil, span);
+        }
+        for (index, form) in forms.iter().enumerate() {
+            if index > 0 {
+                self.emit(Op::Pop, form.span);
+            }
+            self.expr(form)?;
+        }
+        Ok(())
+    }
+    
+    /// Compile a body that only runs on some paths
+    fn conditional_body(&mut self, forms: &[Node], span: Span) -> Result<(), Unsupported> {
+        self.conditional += 1;
+        let result = self.body(forms, span);
+        self.conditional -= 1;
+        result
+    }
+    
+    fn conditional_expr(&mut self, node: &Node) -> Result<(), Unsupported> {
+        self.conditional += 1;
+        let result = self.expr(node);
+        self.conditional -= 1;
+        result
+    }
+    
+    fn expr(&mut self, node: &Node) -> Result<(), Unsupported> {
+        if self.depth >= MAX_COMPILE_DEPTH {
+            return Err("nesting is too deep".to_string());
+        }
+        self.depth += 1;
+        let result = self.expr_inner(node);
+        self.depth -= 1;
+        result
+    }
+    
+    fn expr_inner(&mut self, node: &Node) -> Result<(), Unsupported> {
+        let span = node.span;
+        match &node.expr {
+            Expr::Number(value) => {
+                let index = self.constant(Value::Number(*value));
+                self.emit(Op::Const(index), span);
+            }
+            Expr::Bool(value) => {
+                let index = self.constant(Value::Bool(*value));
+                self.emit(Op::Const(index), span);
+            }
+            Expr::Str(text) => {
+                let index = self.constant(Value::Str(text.clone()));
+                self.emit(Op::Const(index), span);
+            }
+            Expr::Symbol(name) => {
+                if let Some(slot) = self.lookup(name) {
+                    self.emit(Op::LoadLocal(slot), span);
+                } else if let Some(metric) = Self::metric_index(name) {
+                    self.emit(Op::LoadMetric(metric), span);
+                } else {
+                    let index = self.constant(Value::Str(name.clone()));
+                    self.emit(Op::LoadNamed(index), span);
+                }
+            }
+            Expr::List(items) => match items.split_first() {
+                None => {
+                    self.emit(Op::Nil, span);
+                }
+                Some((head, args)) => {
+                    let name = head.as_symbol().ok_or("call of a non-symbol")?;
+                    self.list(node, name, args)?;
+                }
+            },
+        }
+        Ok(())
+    }
+    
+    fn list(&mut self, node: &Node, head: &str, args: &[Node]) -> Result<(), Unsupported> {
+        let span = node.span;
+        match head {
+            "quote" => match args {
+                [datum] => {
+                    let index = self.constant(Value::from_datum(datum));
+                    self.emit(Op::Const(index), span);
+                }
+                _ => return Err("malformed quote".to_string()),
+            },
+            "if" => {
+                if args.len() < 2 || args.len() > 3 {
+                    return Err("malformed if".to_string());
+                }
+                self.expr(&args[0])?;
+                let to_else = self.emit(Op::JumpIfFalse(0), span);
+                self.conditional_expr(&args[1])?;
+                let to_end = self.emit(Op::Jump(0), span);
+                self.patch(to_else);
+                match args.get(2) {
+                    Some(otherwise) => self.conditional_expr(otherwise)?,
+                    None => {
+                        self.emit(Op::Nil, span);
+                    }
+                }
+                self.patch(to_end);
+            }
+            "cond" => {
+                let mut to_end = Vec::new();
+                let mut exhaustive = false;
+                for clause in args {
+                    let parts = clause.as_list().ok_or("malformed cond")?;
+                    let (test, body) = parts.split_first().ok_or("malformed cond")?;
+                    if test.as_symbol() == Some("else") {
+                        self.conditional_body(body, clause.span)?;
+                        exhaustive = true;
+                        break;
+                    }
+                    self.expr(test)?;
+                    let to_next = self.emit(Op::JumpIfFalse(0), clause.span);
+                    self.conditional_body(body, clause.span)?;
+                    to_end.push(self.emit(Op::Jump(0), clause.span));
+                    self.patch(to_next);
+                }
+                if !exhaustive {
+                    self.emit(Op::Nil, span);
+                }
+                for jump in to_end {
+                    self.patch(jump);
+                }
+            }
+            "let" | "let*" => {
+                let bindings = args.first().and_then(Node::as_list).ok_or("malformed let")?;
+                let mut pairs = Vec::with_capacity(bindings.len());
+                for binding in bindings {
+                    match binding.as_list() {
+                        Some([name, value]) => pairs.push((name.as_symbol().ok_or("malformed let")?, value, binding.span)),
+                        _ => return Err("malformed let".to_string()),
+                    }
+                }
+                
+                if head == "let*" {
+                    self.scopes.push(Vec::new());
+                    for (name, value, span) in pairs {
+                        self.expr(value)?;
+                        let slot = self.bind(name)?;
+                        self.emit(Op::StoreLocal(slot), span);
+                    }
+                } else {
+                    // Every value is computed before any name is visible
+                    for (_, value, _) in &pairs {
+                        self.expr(value)?;
+                    }
+                    self.scopes.push(Vec::new());
+                    let mut slots = Vec::with_capacity(pairs.len());
+                    for (name, _, _) in &pairs {
+                        slots.push(self.bind(name)?);
+                    }
+                    // Values come off the stack last first; a repeated name keeps its last value
+                    let mut stored = Vec::new();
+                    for (slot, (_, _, span)) in slots.iter().zip(&pairs).rev() {
+                        if stored.contains(slot) {
+                            self.emit(Op::Pop, *span);
+                        } else {
+                            self.emit(Op::StoreLocal(*slot), *span);
+                            stored.push(*slot);
+                        }
+                    }
+                }
+                self.body(&args[1..], span)?;
+                self.scopes.pop();
+            }
+            "begin" => self.body(args, span)?,
+            "def" | "define" => match args {
+                [target, value] => {
+                    if self.conditional > 0 {
+                        return Err("define inside a branch or loop".to_string());
+                    }
+                    let name = target.as_symbol().ok_or("malformed define")?;
+                    self.expr(value)?;
+                    let slot = self.bind(name)?;
+                    self.emit(Op::SetLocal(slot), span);
+                }
+                _ => return Err("malformed define".to_string()),
+            },
+            "set!" => match args {
+                [target, value] => {
+                    let name = target.as_symbol().ok_or("malformed set!")?;
+                    if let Some(slot) = self.lookup(name) {
+                        self.expr(value)?;
+                        self.emit(Op::SetLocal(slot), span);
+                    } else {
+                        self.set_metric(name, value, span)?;
+                    }
+                }
+                _ => return Err("malformed set!".to_string()),
+            },
+            "and" | "or" => {
+                let (empty, jump): (bool, fn(u32) -> Op) = if head == "and" {
+                    (true, Op::JumpIfFalseKeep)
+                } else {
+                    (false, Op::JumpIfTrueKeep)
+                };
+                match args.split_last() {
+                    None => {
+                        let index = self.constant(Value::Bool(empty));
+                        self.emit(Op::Const(index), span);
+                    }
+                    Some((last, rest)) => {
+                        let mut to_end = Vec::new();
+                        for (index, arg) in rest.iter().enumerate() {
+                            if index == 0 { self.expr(arg)? } else { self.conditional_expr(arg)? }
+                            to_end.push(self.emit(jump(0), arg.span));
+                        }
+                        if rest.is_empty() { self.expr(last)? } else { self.conditional_expr(last)? }
+                        for jump in to_end {
+                            self.patch(jump);
+                        }
+                    }
+                }
+            }
+            "when" | "unless" => {
+                let test = args.first().ok_or("malformed when")?;
+                self.expr(test)?;
+                if head == "unless" {
+                    let not = BUILTINS.iter().position(|(name, _, _)| *name == "not").ok_or("no not")? as u8;
+                    self.emit(Op::Call { builtin: not, argc: 1 }, span);
+                }
+                let to_else = self.emit(Op::JumpIfFalse(0), span);
+                self.conditional_body(&args[1..], span)?;
+                let to_end = self.emit(Op::Jump(0), span);
+                self.patch(to_else);
+                self.emit(Op::Nil, span);
+                self.patch(to_end);
+            }
+            "while" => {
+                let test = args.first().ok_or("malformed while")?;
+                let start = self.here();
+                self.conditional += 1;
+                self.expr(test)?;
+                let to_end = self.emit(Op::JumpIfFalse(0), span);
+                self.body(&args[1..], span)?;
+                self.conditional -= 1;
+                self.emit(Op::Pop, span);
+                self.emit(Op::Jump(start), span);
+                self.patch(to_end);
+                self.emit(Op::Nil, span);
+            }
+            "dotimes" => {
+                let (counter, count) = match args.first().and_then(Node::as_list) {
+                    Some([counter, count]) => (counter, count),
+                    _ => return Err("malformed dotimes".to_string()),
+                };
+                let name = counter.as_symbol().ok_or("malformed dotimes")?;
+                self.expr(count)?;
+                let limit = self.new_slot()?;
+                self.emit(Op::StoreCount(limit), count.span);
+                let index = self.new_slot()?;
+                let zero = self.constant(Value::Number(0.0));
+                self.emit(Op::Const(zero), span);
+                self.emit(Op::StoreLocal(index), span);
+                
+                self.scopes.push(Vec::new());
+                let counter = self.bind(name)?;
+                let start = self.here();
+                let step = self.emit(Op::LoopNext { index, limit, counter, exit: 0 }, span);
+                self.conditional_body(&args[1..], span)?;
+                self.emit(Op::Pop, span);
+                self.emit(Op::Jump(start), span);
+                self.patch(step);
+                self.scopes.pop();
+                self.emit(Op::Nil, span);
+            }
+            "lambda" | "else" => return Err(format!("{} is not valid here", head)),
+            _ => self.call(node, head, args)?,
+        }
+        Ok(())
+    }
+    
+    fn set_metric(&mut self, name: &str, value: &Node, span: Span) -> Result<(), Unsupported> {
+        let metric = Self::metric_index(name).ok_or_else(|| format!("unknown metric {}", name))?;
+        if !METRICS[metric as usize].writable {
+            return Err(format!("{} is read-only", name));
+        }
+        self.expr(value)?;
+        self.emit(Op::CheckNumber, value.span);
+        self.emit(Op::SetMetric(metric), span);
+        Ok(())
+    }
+    
+    fn call(&mut self, node: &Node, head: &str, args: &[Node]) -> Result<(), Unsupported> {
+        if let Some(metric) = setter_target(head) {
+            return match args {
+                [value] => self.set_metric(metric.name, value, node.span),
+                _ => Err(format!("malformed {}", head)),
+            };
+        }
+        if let Some(metric) = Self::metric_index(head) {
+            if !args.is_empty() {
+                return Err(format!("malformed {}", head));
+            }
+            self.emit(Op::LoadMetric(metric), node.span);
+            return Ok(());
+        }
+        
+        let builtin = BUILTINS.iter().position(|(name, _, _)| *name == head)
+            .ok_or_else(|| format!("unknown function {}", head))?;
+        let (_, min, max) = BUILTINS[builtin];
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            return Err(format!("wrong number of arguments to {}", head));
+        }
+        for arg in args {
+            self.expr(arg)?;
+        }
+        match BinaryOp::from_name(head) {
+            Some(op) if args.len() == 2 => self.emit(Op::Binary(op), node.span),
+            _ => self.emit(Op::Call { builtin: builtin as u8, argc: args.len() as u16 }, node.span),
+        };
+        Ok(())
+    }
+}
+
+fn pop(stack: &mut Vec<Value>) -> Value {
+    stack.pop().unwrap_or(Value::Nil)
+}
+
+fn number(value: Value, span: Span) -> Result<f64, EvalError> {
+    match value {
+        Value::Number(n) => Ok(n),
+        other => Err(error(format!("Expected a number, got {}", other.type_name()), span)),
+    }
+}
+
+/// Run compiled code against a state
+///
+/// Each instruction costs one unit of fuel, and copying or building a list
+/// one more per value in it, as on the interpreter.
+pub(crate) fn execute(code: &Bytecode, state: &mut OrganismState, params: &[f64], budget: Budget) -> Result<Value, EvalError> {
+    let mut stack: Vec<Value> = Vec::with_capacity(16);
+    let mut locals = vec![Value::Nil; code.locals];
+    let mut pc = 0;
+    let mut meter = Meter::new(budget);
+    
+    while pc < code.ops.len() {
+        let span = code.spans[pc];
+        meter.step().map_err(|e| error(e, span))?;
+        let mut copy = |value: &Value| meter.copy(value).map_err(|e| error(e, span));
+        let mut next = pc + 1;
+        
+        match code.ops[pc] {
+            Op::Const(index) => {
+                let value = &code.constants[index as usize];
+                copy(value)?;
+                stack.push(value.clone());
+            }
+            Op::Nil => stack.push(Value::Nil),
+            Op::Pop => {
+                stack.pop();
+            }
+            Op::LoadLocal(slot) => {
+                copy(&locals[slot as usize])?;
+                stack.push(locals[slot as usize].clone());
+            }
+            Op::StoreLocal(slot) => locals[slot as usize] = pop(&mut stack),
+            Op::SetLocal(slot) => {
+                let value = stack.last().cloned().unwrap_or(Value::Nil);
+                copy(&value)?;
+                locals[slot as usize] = value;
+            }
+            Op::LoadParam(index) => {
+                stack.push(params.get(index as usize).map_or(Value::Nil, |p| Value::Number(*p)))
+            }
+            Op::LoadMetric(metric) => {
+                stack.push(Value::Number(state.metric(METRICS[metric as usize].canonical).unwrap_or(0.0)))
+            }
+            Op::CheckNumber => {
+                if let Some(other) = stack.last().filter(|value| !matches!(value, Value::Number(_))) {
+                    return Err(error(format!("Expected a number, got {}", other.type_name()), span));
+                }
+            }
+            Op::SetMetric(metric) => {
+                let canonical = METRICS[metric as usize].canonical;
+                let value = number(pop(&mut stack), span)?;
+                state.set_metric(canonical, value).map_err(|e| error(e, span))?;
+                // Setters clamp, so push the value that was actually stored
+                stack.push(Value::Number(state.metric(canonical).unwrap_or(0.0)));
+            }
+            Op::LoadNamed(index) => {
+                let name = match &code.constants[index as usize] {
+                    Value::Str(name) => name.as_str(),
+                    _ => "",
+                };
+                match state.get_custom_metric(name) {
+                    Some(value) => stack.push(Value::Number(value)),
+                    None if name == "nil" => stack.push(Value::Nil),
+                    None => return Err(error(format!("Unbound symbol: {}", name), span)),
+                }
+            }
+            Op::Jump(target) => next = target as usize,
+            Op::JumpIfFalse(target) => {
+                if !pop(&mut stack).truthy() {
+                    next = target as usize;
+                }
+            }
+            Op::JumpIfFalseKeep(target) | Op::JumpIfTrueKeep(target) => {
+                let jump_when = matches!(code.ops[pc], Op::JumpIfTrueKeep(_));
+                if stack.last().is_some_and(|value| value.truthy() == jump_when) {
+                    next = target as usize;
+                } else {
+                    stack.pop();
+                }
+            }
+            Op::StoreCount(slot) => {
+                let count = number(pop(&mut stack), span)?.max(0.0).floor();
+                locals[slot as usize] = Value::Number(count);
+            }
+            Op::LoopNext { index, limit, counter, exit } => {
+                let (i, n) = match (&locals[index as usize], &locals[limit as usize]) {
+                    (Value::Number(i), Value::Number(n)) => (*i, *n),
+                    _ => (0.0, 0.0),
+                };
+                if i < n {
+                    locals[counter as usize] = Value::Number(i);
+                    locals[index as usize] = Value::Number(i + 1.0);
+                } else {
+                    next = exit as usize;
+                }
+            }
+            Op::Binary(op) => {
+                let right = pop(&mut stack);
+                let left = pop(&mut stack);
+                let value = match (&left, &right) {
+                    (Value::Number(a), Value::Number(b)) => binary(op, *a, *b, span)?,
+                    // Let the built-in report its usual type error
+                    _ => apply_builtin(op.name(), &[left, right], span, state, params)?,
+                };
+                stack.push(value);
+            }
+            Op::Call { builtin, argc } => {
+                let args = stack.split_off(stack.len().saturating_sub(argc as usize));
+                let value = apply_builtin(BUILTINS[builtin as usize].0, &args, span, state, params)?;
+                copy(&value)?;
+                stack.push(value);
+            }
+        }
+        pc = next;
+    }
+    
+    Ok(pop(&mut stack))
+}
+
+fn binary(op: BinaryOp, a: f64, b: f64, span: Span) -> Result<Value, EvalError> {
+    Ok(match op {
+        BinaryOp::Add => Value::Number(a + b),
+        BinaryOp::Sub => Value::Number(a - b),
+        BinaryOp::Mul => Value::Number(a * b),
+        BinaryOp::Div if b == 0.0 => return Err(error("/: division by zero", span)),
+        BinaryOp::Div => Value::Number(a / b),
+        BinaryOp::Lt => Value::Bool(a < b),
+        BinaryOp::Gt => Value::Bool(a > b),
+        BinaryOp::Le => Value::Bool(a <= b),
+        BinaryOp::Ge => Value::Bool(a >= b),
+        BinaryOp::Eq => Value::Bool(a == b),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lisp::eval::{run_with_budget, MAX_STEPS};
+    use crate::lisp::reader::parse;
+    
+    /// Run code on the VM and on the interpreter, each against a new state
+    fn both(source: &str, budget: Budget) -> [(Result<Value, String>, String); 2] {
+        let forms = parse(source).unwrap();
+        let code = compile(&forms).unwrap_or_else(|_| panic!("{} doesn't compile", source));
+        let outcome = |run: &dyn Fn(&mut OrganismState) -> Result<Value, EvalError>| {
+            let mut state = OrganismState::new();
+            let result = run(&mut state).map_err(|e| e.message);
+            (result, serde_json::to_string(&state).unwrap())
+        };
+        [
+            outcome(&|state| execute(&code, state, &[2.0, 3.0], budget)),
+            outcome(&|state| run_with_budget(&forms, state, &[2.0, 3.0], None, budget)),
+        ]
+    }
+    
+    #[test]
+    fn vm_matches_interpreter() {
+        let sources = [
+            "(+ 1 2 3)",
+            "(- 10 (* 2 3) (/ 8 4))",
+            "(lambda (state rate bonus) (set! energy (+ energy (* rate bonus))) energy)",
+            "(let ((x 2) (y 3)) (if (> x y) x y))",
+            "(let* ((x 2) (y (* x x))) (cond ((< y 4) 1) ((= y 4) 2) (else 3)))",
+            "(define total 0) (dotimes (i 10) (set! total (+ total i))) total",
+            "(define n 0) (while (< n 5) (set! n (+ n 1))) n",
+            "(when (> population 10) (set! population (* population 0.5)))",
+            "(unless #t 1)",
+            "(and 1 #f 2)",
+            "(or #f nil 3)",
+            "(nth (list 1 (list 2 3) 4) 1)",
+            "(length (list 1 2 3))",
+            "(set! mutation_rate 5)",
+            "(+ 1 (list 1))",
+            "(nth (list 1) -1)",
+            "(clamp 5 0 1)",
+            "(clamp 5 1 0)",
+            "(clamp 0 (sqrt -1) 1)",
+            "(clamp 0 (param 0) (/ 0 0))",
+        ];
+        for source in sources {
+            let [vm, interpreter] = both(source, Budget::fuel(MAX_STEPS));
+            assert_eq!(vm, interpreter, "{}", source);
+        }
+    }
+    
+    #[test]
+    fn fuel_exhaustion_reads_the_same_on_both() {
+        for (source, fuel) in [("(while #t 1)", 1000), ("(dotimes (i 100000) i)", 500)] {
+            let [vm, interpreter] = both(source, Budget::fuel(fuel));
+            assert_eq!(vm, interpreter, "{}", source);
+            assert_eq!(vm.0, Err(format!("Fuel budget of {} exhausted", fuel)));
+        }
+    }
+    
+    #[test]
+    fn copying_lists_costs_fuel() {
+        // The same loop fits the budget when `x` is a number
+        for (result, _) in both("(define x 1) (dotimes (i 20) x) 0", Budget::fuel(200)) {
+            assert_eq!(result, Ok(Value::Number(0.0)));
+        }
+        for (result, _) in both("(define x (list 1 2 3 4 5 6 7 8 9 10)) (dotimes (i 20) x) 0", Budget::fuel(200)) {
+            assert_eq!(result, Err("Fuel budget of 200 exhausted".to_string()));
+        }
+    }
+    
+    #[test]
+    fn lists_that_double_hit_the_size_cap() {
+        let source = "(define x (list 1)) (dotimes (i 24) (set! x (list x x))) 0";
+        for (result, _) in both(source, Budget::fuel(u64::MAX)) {
+            let message = result.unwrap_err();
+            assert!(message.starts_with("list: a list can hold at most"), "{}", message);
+        }
+    }
+}
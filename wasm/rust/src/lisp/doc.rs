@@ -0,0 +1,176 @@
+/// Prefix of a documentation comment line
+const DOC_COMMENT: &str = ";;;";
+
+/// Forms whose body may start with a docstring
+const DOCUMENTED_FORMS: [&str; 2] = ["lambda", "define"];
+
+/// Extract the docstring of a rule
+///
+/// Two conventions are supported. Leading `;;;` comment lines take priority:
+///
+/// ```lisp
+/// ;;; Boosts growth when energy is plentiful.
+/// ;;; Params: [boost]
+/// (lambda (state boost) ...)
+/// ```
+///
+/// Otherwise a string literal directly after the parameter list of a leading
+/// `lambda` or `define` form is used, as is a string literal at the very
+/// start of the code:
+///
+/// ```lisp
+/// (lambda (state boost) "Boosts growth when energy is plentiful." ...)
+/// ```
+pub(crate) fn extract_docstring(code: &str) -> Option<String> {
+    let comment_lines: Vec<&str> = code
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| line.starts_with(DOC_COMMENT))
+        .map(|line| {
+            let text = line.trim_start_matches(';');
+            text.strip_prefix(' ').unwrap_or(text).trim_end()
+        })
+        .collect();
+    
+    if !comment_lines.is_empty() {
+        let doc = comment_lines.join("\n").trim().to_string();
+        return if doc.is_empty() { None } else { Some(doc) };
+    }
+    
+    let mut scanner = Scanner::new(code);
+    scanner.skip_trivia();
+    
+    match scanner.peek()? {
+        '"' => scanner.read_string(),
+        '(' => {
+            scanner.bump();
+            scanner.skip_trivia();
+            let head = scanner.read_symbol();
+            if !DOCUMENTED_FORMS.contains(&head.as_str()) {
+                return None;
+            }
+            
+            // Skip the parameter list (or the defined name) and look at the next form
+            scanner.skip_trivia();
+            scanner.skip_datum()?;
+            scanner.skip_trivia();
+            if scanner.peek()? == '"' {
+                scanner.read_string()
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Minimal character scanner over rule source
+struct Scanner<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(source: &'a str) -> Scanner<'a> {
+        Scanner {
+            chars: source.chars().peekable(),
+        }
+    }
+    
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+    
+    fn bump(&mut self) -> Option<char> {
+        self.chars.next()
+    }
+    
+    /// Skip whitespace and `;` comments
+    fn skip_trivia(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == ';' {
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+    
+    fn read_symbol(&mut self) -> String {
+        let mut symbol = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == ';' {
+                break;
+            }
+            symbol.push(c);
+            self.bump();
+        }
+        symbol
+    }
+    
+    /// Read a string literal starting at the opening quote
+    fn read_string(&mut self) -> Option<String> {
+        if self.bump()? != '"' {
+            return None;
+        }
+        
+        let mut text = String::new();
+        loop {
+            match self.bump()? {
+                '"' => break,
+                '\\' => match self.bump()? {
+                    'n' => text.push('\n'),
+                    't' => text.push('\t'),
+                    other => text.push(other),
+                },
+                c => text.push(c),
+            }
+        }
+        
+        let text = text.trim().to_string();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text)
+        }
+    }
+    
+    /// Skip one complete datum: a balanced list, a string or an atom
+    fn skip_datum(&mut self) -> Option<()> {
+        match self.peek()? {
+            '(' => {
+                let mut depth = 0usize;
+                while let Some(c) = self.peek() {
+                    match c {
+                        '"' => {
+                            self.read_string();
+                            continue;
+                        }
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                self.bump();
+                                return Some(());
+                            }
+                        }
+                        _ => {}
+                    }
+                    self.bump();
+                }
+                None
+            }
+            '"' => self.read_string().map(|_| ()),
+            _ => {
+                self.read_symbol();
+                Some(())
+            }
+        }
+    }
+}
@@ -0,0 +1,742 @@
+use super::language::{builtin_arity, lookup_metric, setter_target, MetricSpec};
+use super::reader::{parse, Expr, Node, Span, MAX_DEPTH};
+use crate::{clock, vecmath, OrganismState};
+
+/// Evaluation steps allowed per run before the rule is aborted, unless a budget is given
+pub(crate) const MAX_STEPS: u64 = 1_000_000;
+
+/// Fuel spent between clock reads when a run has a time limit
+const CLOCK_CHECK_INTERVAL: u64 = 1024;
+
+/// Values a list may hold, counting those in nested lists
+pub(super) const MAX_LIST_SIZE: u64 = 65_536;
+
+/// Limits on a single run of rule code
+///
+/// `fuel` counts evaluation steps, or instructions for bytecode, plus one
+/// unit per value in every list a run copies or builds. A time limit is
+/// checked against the engine clock each time another
+/// `CLOCK_CHECK_INTERVAL` units have been spent, so a run may overshoot
+/// it by about that much work, however the work is spread over steps.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Budget {
+    pub fuel: u64,
+    /// When the run started and how many milliseconds it may take
+    time: Option<(f64, f64)>,
+}
+
+impl Budget {
+    pub fn fuel(fuel: u64) -> Budget {
+        Budget { fuel, time: None }
+    }
+    
+    /// Limit the run to `max_ms` from now as well
+    pub fn with_time_limit(self, max_ms: Option<f64>) -> Budget {
+        Budget { time: max_ms.map(|max_ms| (clock::now_ms(), max_ms)), ..self }
+    }
+    
+    /// The error for running out of fuel, the same on the VM and the interpreter
+    fn exhausted(&self) -> String {
+        format!("Fuel budget of {} exhausted", self.fuel)
+    }
+    
+    /// The error if the run has taken longer than its time limit
+    fn out_of_time(&self) -> Option<String> {
+        let (start, max_ms) = self.time?;
+        (clock::now_ms() - start > max_ms).then(|| format!("Time budget of {} ms exceeded", max_ms))
+    }
+}
+
+/// What one run has spent of its budget, shared by the interpreter and the VM
+pub(super) struct Meter {
+    budget: Budget,
+    spent: u64,
+    /// Spending at which the clock is read next
+    next_clock_check: u64,
+}
+
+impl Meter {
+    pub fn new(budget: Budget) -> Meter {
+        Meter { budget, spent: 0, next_clock_check: CLOCK_CHECK_INTERVAL }
+    }
+    
+    /// Spend one step
+    pub fn step(&mut self) -> Result<(), String> {
+        self.spend(1)
+    }
+    
+    /// Spend the fuel for copying or building `value`, on top of its step
+    pub fn copy(&mut self, value: &Value) -> Result<(), String> {
+        self.spend(value.size() - 1)
+    }
+    
+    fn spend(&mut self, cost: u64) -> Result<(), String> {
+        self.spent = self.spent.saturating_add(cost);
+        if self.spent > self.budget.fuel {
+            return Err(self.budget.exhausted());
+        }
+        if self.spent >= self.next_clock_check {
+            self.next_clock_check = self.spent.saturating_add(CLOCK_CHECK_INTERVAL);
+            if let Some(message) = self.budget.out_of_time() {
+                return Err(message);
+            }
+        }
+        Ok(())
+    }
+}
+
+
+/// Trace entries kept per run; later entries are dropped and the trace is marked truncated
+const MAX_TRACE_ENTRIES: usize = 10_000;
+
+/// A runtime value of rule code
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    Nil,
+    Number(f64),
+    Bool(bool),
+    Str(String),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Only `#f` and nil are false
+    pub(super) fn truthy(&self) -> bool {
+        !matches!(self, Value::Nil | Value::Bool(false))
+    }
+    
+    /// How many values this one holds, itself included
+    pub(super) fn size(&self) -> u64 {
+        match self {
+            Value::List(items) => 1 + items.iter().map(Value::size).sum::<u64>(),
+            _ => 1,
+        }
+    }
+    
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Nil => "nil",
+            Value::Number(_) => "number",
+            Value::Bool(_) => "boolean",
+            Value::Str(_) => "string",
+            Value::List(_) => "list",
+        }
+    }
+    
+    pub(super) fn from_datum(node: &Node) -> Value {
+        match &node.expr {
+            Expr::Number(value) => Value::Number(*value),
+            Expr::Bool(value) => Value::Bool(*value),
+            Expr::Str(text) | Expr::Symbol(text) => Value::Str(text.clone()),
+            Expr::List(items) => Value::List(items.iter().map(Value::from_datum).collect()),
+        }
+    }
+    
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            Value::Nil => serde_json::Value::Null,
+            Value::Number(value) => serde_json::json!(value),
+            Value::Bool(value) => serde_json::json!(value),
+            Value::Str(text) => serde_json::json!(text),
+            Value::List(items) => serde_json::Value::Array(items.iter().map(Value::to_json).collect()),
+        }
+    }
+}
+
+/// A runtime error with the position of the failing form
+#[derive(Clone, Debug)]
+pub(crate) struct EvalError {
+    pub message: String,
+    pub span: Span,
+}
+
+/// The value of one evaluated sub-expression
+pub(crate) struct TraceEntry {
+    pub depth: usize,
+    pub span: Span,
+    pub expr: String,
+    pub value: Value,
+}
+
+/// Sub-expression values recorded during a traced run, innermost first
+#[derive(Default)]
+pub(crate) struct Trace {
+    pub entries: Vec<TraceEntry>,
+    pub truncated: bool,
+}
+
+impl Trace {
+    fn record(&mut self, depth: usize, node: &Node, value: &Value) {
+        if self.entries.len() >= MAX_TRACE_ENTRIES {
+            self.truncated = true;
+            return;
+        }
+        self.entries.push(TraceEntry {
+            depth,
+            span: node.span,
+            expr: node.to_string(),
+            value: value.clone(),
+        });
+    }
+}
+
+/// Run parsed rule code against a state
+///
+/// Top-level forms are evaluated in order. A top-level
+/// `(lambda (state p1 p2 ...) body...)` is invoked right away with `params`
+/// bound to the parameters after the state handle. The value of the last
+/// form is the result.
+pub(crate) fn run(
+    forms: &[Node],
+    state: &mut OrganismState,
+    params: &[f64],
+    trace: Option<&mut Trace>,
+) -> Result<Value, EvalError> {
+    run_with_budget(forms, state, params, trace, Budget::fuel(MAX_STEPS))
+}
+
+/// Run parsed rule code like `run`, aborting once `budget` is spent
+pub(crate) fn run_with_budget(
+    forms: &[Node],
+    state: &mut OrganismState,
+    params: &[f64],
+    trace: Option<&mut Trace>,
+    budget: Budget,
+) -> Result<Value, EvalError> {
+    let mut evaluator = Evaluator {
+        state,
+        params,
+        scopes: vec![Vec::new()],
+        meter: Meter::new(budget),
+        depth: 0,
+        trace,
+    };
+    
+    let mut result = Value::Nil;
+    for form in forms {
+        result = match form.as_list() {
+            Some([head, rest @ ..]) if head.as_symbol() == Some("lambda") => {
+                evaluator.invoke_rule_lambda(form, rest)?
+            }
+            _ => evaluator.eval(form)?,
+        };
+    }
+    
+    Ok(result)
+}
+
+/// Evaluate predicate code (`(< energy 200)`) against a state without changing it
+pub(crate) fn eval_predicate(source: &str, state: &OrganismState) -> Result<bool, String> {
+    let forms = parse(source)
+        .map_err(|e| format!("{}:{}: {}", e.span.line, e.span.column, e.message))?;
+    let mut scratch = state.clone();
+    run(&forms, &mut scratch, &[], None)
+        .map(|value| value.truthy())
+        .map_err(|e| format!("{}:{}: {}", e.span.line, e.span.column, e.message))
+}
+
+struct Evaluator<'a> {
+    state: &'a mut OrganismState,
+    params: &'a [f64],
+    scopes: Vec<Vec<(String, Value)>>,
+    meter: Meter,
+    depth: usize,
+    trace: Option<&'a mut Trace>,
+}
+
+pub(super) fn error(message: impl Into<String>, span: Span) -> EvalError {
+    EvalError {
+        message: message.into(),
+        span,
+    }
+}
+
+impl<'a> Evaluator<'a> {
+    fn invoke_rule_lambda(&mut self, form: &Node, args: &[Node]) -> Result<Value, EvalError> {
+        let params = args.first().and_then(Node::as_list)
+            .ok_or_else(|| error("lambda: expected a parameter list", form.span))?;
+        
+        let mut scope = Vec::with_capacity(params.len());
+        for (index, param) in params.iter().enumerate() {
+            let name = param.as_symbol()
+                .ok_or_else(|| error("lambda: parameter names must be symbols", param.span))?;
+            // The first parameter is the state handle; metrics are read through the environment
+            let value = match index {
+                0 => Value::Nil,
+                _ => self.params.get(index - 1).map_or(Value::Nil, |p| Value::Number(*p)),
+            };
+            scope.push((name.to_string(), value));
+        }
+        
+        self.depth += 1;
+        self.scopes.push(scope);
+        let result = self.eval_body(&args[1..]);
+        self.scopes.pop();
+        self.depth -= 1;
+        
+        let result = result?;
+        if let Some(trace) = self.trace.as_mut() {
+            trace.record(self.depth, form, &result);
+        }
+        Ok(result)
+    }
+    
+    fn eval(&mut self, node: &Node) -> Result<Value, EvalError> {
+        self.meter.step().map_err(|e| error(e, node.span))?;
+        if self.depth >= MAX_DEPTH {
+            return Err(error(format!("Expression nesting exceeds {} levels", MAX_DEPTH), node.span));
+        }
+        
+        self.depth += 1;
+        let result = self.eval_form(node);
+        self.depth -= 1;
+        
+        if let (Ok(value), Some(trace)) = (&result, self.trace.as_mut()) {
+            trace.record(self.depth, node, value);
+        }
+        result
+    }
+    
+    /// Charge for a value that was copied or built, then return it
+    fn copied(&mut self, value: Value, span: Span) -> Result<Value, EvalError> {
+        self.meter.copy(&value).map_err(|e| error(e, span))?;
+        Ok(value)
+    }
+    
+    fn eval_body(&mut self, body: &[Node]) -> Result<Value, EvalError> {
+        let mut result = Value::Nil;
+        for node in body {
+            result = self.eval(node)?;
+        }
+        Ok(result)
+    }
+    
+    fn eval_number(&mut self, node: &Node) -> Result<f64, EvalError> {
+        match self.eval(node)? {
+            Value::Number(value) => Ok(value),
+            other => Err(error(format!("Expected a number, got {}", other.type_name()), node.span)),
+        }
+    }
+    
+    fn lookup(&self, name: &str) -> Option<&Value> {
+        self.scopes.iter().rev()
+            .find_map(|scope| scope.iter().rev().find(|(bound, _)| bound == name))
+            .map(|(_, value)| value)
+    }
+    
+    fn assign(&mut self, name: &str, value: Value) -> bool {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(slot) = scope.iter_mut().rev().find(|(bound, _)| bound == name) {
+                slot.1 = value;
+                return true;
+            }
+        }
+        false
+    }
+    
+    fn bind(&mut self, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.last_mut() {
+            match scope.iter_mut().find(|(bound, _)| bound == name) {
+                Some(slot) => slot.1 = value,
+                None => scope.push((name.to_string(), value)),
+            }
+        }
+    }
+    
+    fn read_metric(&self, metric: &MetricSpec) -> Value {
+        Value::Number(self.state.metric(metric.canonical).unwrap_or(0.0))
+    }
+    
+    fn write_metric(&mut self, metric: &MetricSpec, value: f64, span: Span) -> Result<Value, EvalError> {
+        if !metric.writable {
+            return Err(error(format!("'{}' is read-only and cannot be set", metric.name), span));
+        }
+        self.state.set_metric(metric.canonical, value).map_err(|e| error(e, span))?;
+        // Setters clamp, so report the value that was actually stored
+        Ok(self.read_metric(metric))
+    }
+    
+    fn eval_form(&mut self, node: &Node) -> Result<Value, EvalError> {
+        match &node.expr {
+            Expr::Number(value) => Ok(Value::Number(*value)),
+            Expr::Bool(value) => Ok(Value::Bool(*value)),
+            Expr::Str(text) => Ok(Value::Str(text.clone())),
+            Expr::Symbol(name) => {
+                if let Some(value) = self.lookup(name).cloned() {
+                    return self.copied(value, node.span);
+                }
+                if let Some(metric) = lookup_metric(name) {
+                    return Ok(self.read_metric(metric));
+                }
+                if let Some(value) = self.state.get_custom_metric(name) {
+                    return Ok(Value::Number(value));
+                }
+                if name == "nil" {
+                    return Ok(Value::Nil);
+                }
+                Err(error(format!("Unbound symbol: {}", name), node.span))
+            }
+            Expr::List(items) => {
+                let head = match items.first() {
+                    None => return Ok(Value::Nil),
+                    Some(head) => head,
+                };
+                let name = head.as_symbol()
+                    .ok_or_else(|| error(format!("Cannot call {}", head), head.span))?;
+                self.eval_list(node, name, &items[1..])
+            }
+        }
+    }
+    
+    fn eval_list(&mut self, node: &Node, head: &str, args: &[Node]) -> Result<Value, EvalError> {
+        let span = node.span;
+        
+        match head {
+            "quote" => match args {
+                [datum] => Ok(Value::from_datum(datum)),
+                _ => Err(error("quote: expected exactly one form", span)),
+            },
+            "if" => {
+                if args.len() < 2 || args.len() > 3 {
+                    return Err(error("if: expected (if test then [else])", span));
+                }
+                if self.eval(&args[0])?.truthy() {
+                    self.eval(&args[1])
+                } else {
+                    args.get(2).map_or(Ok(Value::Nil), |e| self.eval(e))
+                }
+            }
+            "cond" => {
+                for clause in args {
+                    let parts = clause.as_list()
+                        .ok_or_else(|| error("cond: clauses must be lists", clause.span))?;
+                    let test = parts.first()
+                        .ok_or_else(|| error("cond: empty clause", clause.span))?;
+                    let matched = match test.as_symbol() {
+                        Some("else") => true,
+                        _ => self.eval(test)?.truthy(),
+                    };
+                    if matched {
+                        return self.eval_body(&parts[1..]);
+                    }
+                }
+                Ok(Value::Nil)
+            }
+            "let" | "let*" => {
+                let bindings = args.first().and_then(Node::as_list)
+                    .ok_or_else(|| error(format!("{}: expected a binding list", head), span))?;
+                
+                self.scopes.push(Vec::new());
+                let result = self.eval_let(head == "let*", bindings, &args[1..]);
+                self.scopes.pop();
+                result
+            }
+            "begin" => self.eval_body(args),
+            "def" | "define" => match args {
+                [target, value] => {
+                    let name = target.as_symbol()
+                        .ok_or_else(|| error("define: only (define name value) is supported", target.span))?;
+                    let value = self.eval(value)?;
+                    self.bind(name, value.clone());
+                    self.copied(value, span)
+                }
+                _ => Err(error("define: expected (define name value)", span)),
+            },
+            "set!" => match args {
+                [target, value] => {
+                    let name = target.as_symbol()
+                        .ok_or_else(|| error("set!: target must be a symbol", target.span))?;
+                    if self.lookup(name).is_some() {
+                        let value = self.eval(value)?;
+                        self.assign(name, value.clone());
+                        return self.copied(value, span);
+                    }
+                    let metric = lookup_metric(name)
+                        .ok_or_else(|| error(format!("set!: unbound symbol: {}", name), target.span))?;
+                    let value = self.eval_number(value)?;
+                    self.write_metric(metric, value, span)
+                }
+                _ => Err(error("set!: expected (set! name value)", span)),
+            },
+            "and" => {
+                let mut result = Value::Bool(true);
+                for arg in args {
+                    result = self.eval(arg)?;
+                    if !result.truthy() {
+                        break;
+                    }
+                }
+                Ok(result)
+            }
+            "or" => {
+                let mut result = Value::Bool(false);
+                for arg in args {
+                    result = self.eval(arg)?;
+                    if result.truthy() {
+                        break;
+                    }
+                }
+                Ok(result)
+            }
+            "when" | "unless" => {
+                let test = args.first()
+                    .ok_or_else(|| error(format!("{}: expected a test", head), span))?;
+                if self.eval(test)?.truthy() == (head == "when") {
+                    self.eval_body(&args[1..])
+                } else {
+                    Ok(Value::Nil)
+                }
+            }
+            "while" => {
+                let test = args.first()
+                    .ok_or_else(|| error("while: expected a test", span))?;
+                while self.eval(test)?.truthy() {
+                    self.eval_body(&args[1..])?;
+                }
+                Ok(Value::Nil)
+            }
+            "dotimes" => {
+                let spec = args.first().and_then(Node::as_list).unwrap_or(&[]);
+                let (counter, count) = match spec {
+                    [counter, count] => (counter, count),
+                    _ => return Err(error("dotimes: expected (dotimes (var count) body...)", span)),
+                };
+                let name = counter.as_symbol()
+                    .ok_or_else(|| error("dotimes: counter must be a symbol", counter.span))?;
+                let count = self.eval_number(count)?.max(0.0).floor() as u64;
+                
+                self.scopes.push(Vec::new());
+                let mut result = Ok(Value::Nil);
+                for i in 0..count {
+                    self.bind(name, Value::Number(i as f64));
+                    if let Err(e) = self.eval_body(&args[1..]) {
+                        result = Err(e);
+                        break;
+                    }
+                }
+                self.scopes.pop();
+                result
+            }
+            "lambda" => Err(error("Nested lambdas are not supported", span)),
+            "else" => Err(error("else is only valid in a cond clause", span)),
+            _ => self.eval_call(head, args, span),
+        }
+    }
+    
+    fn eval_let(&mut self, sequential: bool, bindings: &[Node], body: &[Node]) -> Result<Value, EvalError> {
+        let mut pending = Vec::with_capacity(bindings.len());
+        for binding in bindings {
+            let (name, value) = match binding.as_list() {
+                Some([name, value]) => (name, value),
+                _ => return Err(error("let: bindings must be (name value)", binding.span)),
+            };
+            let name = name.as_symbol()
+                .ok_or_else(|| error("let: binding names must be symbols", name.span))?;
+            
+            // Plain let evaluates every value before any name is visible
+            let value = if sequential {
+                self.eval(value)?
+            } else {
+                let scope = self.scopes.pop().unwrap_or_default();
+                let value = self.eval(value);
+                self.scopes.push(scope);
+                value?
+            };
+            
+            if sequential {
+                self.bind(name, value);
+            } else {
+                pending.push((name, value));
+            }
+        }
+        for (name, value) in pending {
+            self.bind(name, value);
+        }
+        
+        self.eval_body(body)
+    }
+    
+    fn eval_call(&mut self, head: &str, args: &[Node], span: Span) -> Result<Value, EvalError> {
+        if let Some(metric) = setter_target(head) {
+            return match args {
+                [value] => {
+                    let value = self.eval_number(value)?;
+                    self.write_metric(metric, value, span)
+                }
+                _ => Err(error(format!("{}: expected exactly one argument", head), span)),
+            };
+        }
+        if let Some(metric) = lookup_metric(head) {
+            if !args.is_empty() {
+                return Err(error(format!("{}: expected no arguments", head), span));
+            }
+            return Ok(self.read_metric(metric));
+        }
+        
+        let (min, max) = builtin_arity(head)
+            .ok_or_else(|| error(format!("Unknown function: {}", head), span))?;
+        if args.len() < min || max.is_some_and(|max| args.len() > max) {
+            let expected = match max {
+                Some(max) if max == min => format!("{}", min),
+                Some(max) => format!("{} to {}", min, max),
+                None => format!("at least {}", min),
+            };
+            return Err(error(format!("{}: expected {} arguments, got {}", head, expected, args.len()), span));
+        }
+        
+        let mut values = Vec::with_capacity(args.len());
+        for arg in args {
+            values.push(self.eval(arg)?);
+        }
+        let result = apply_builtin(head, &values, span, self.state, self.params)?;
+        self.copied(result, span)
+    }
+}
+
+/// Apply a built-in function to evaluated arguments (arity already checked)
+pub(super) fn apply_builtin(
+    head: &str,
+    values: &[Value],
+    span: Span,
+    state: &OrganismState,
+    params: &[f64],
+) -> Result<Value, EvalError> {
+    let numbers = || -> Result<Vec<f64>, EvalError> {
+        values.iter()
+            .map(|value| match value {
+                Value::Number(n) => Ok(*n),
+                other => Err(error(format!("{}: expected numbers, got {}", head, other.type_name()), span)),
+            })
+            .collect()
+    };
+    let vector = |index: usize| -> Result<Vec<f64>, EvalError> {
+        match &values[index] {
+            Value::List(items) => items.iter()
+                .map(|item| match item {
+                    Value::Number(n) => Ok(*n),
+                    other => Err(error(format!("{}: expected a list of numbers, got {}", head, other.type_name()), span)),
+                })
+                .collect(),
+            other => Err(error(format!("{}: expected a list, got {}", head, other.type_name()), span)),
+        }
+    };
+    let list = |numbers: Vec<f64>| Value::List(numbers.into_iter().map(Value::Number).collect());
+    let checked = |result: Result<Vec<f64>, String>| -> Result<Value, EvalError> {
+        result.map(list).map_err(|e| error(format!("{}: {}", head, e), span))
+    };
+    let compare = |test: fn(f64, f64) -> bool| -> Result<Value, EvalError> {
+        let n = numbers()?;
+        Ok(Value::Bool(n.windows(2).all(|pair| test(pair[0], pair[1]))))
+    };
+    
+    let value = match head {
+        "+" => Value::Number(numbers()?.iter().sum()),
+        "*" => Value::Number(numbers()?.iter().product()),
+        "-" => {
+            let n = numbers()?;
+            match n.as_slice() {
+                [x] => Value::Number(-x),
+                [x, rest @ ..] => Value::Number(rest.iter().fold(*x, |acc, v| acc - v)),
+                [] => unreachable!(),
+            }
+        }
+        "/" => {
+            let n = numbers()?;
+            let (first, rest) = match n.as_slice() {
+                [x] => (1.0, std::slice::from_ref(x)),
+                [x, rest @ ..] => (*x, rest),
+                [] => unreachable!(),
+            };
+            if rest.contains(&0.0) {
+                return Err(error("/: division by zero", span));
+            }
+            Value::Number(rest.iter().fold(first, |acc, v| acc / v))
+        }
+        "mod" => {
+            let n = numbers()?;
+            if n[1] == 0.0 {
+                return Err(error("mod: division by zero", span));
+            }
+            Value::Number(n[0].rem_euclid(n[1]))
+        }
+        "min" => Value::Number(numbers()?.into_iter().fold(f64::INFINITY, f64::min)),
+        "max" => Value::Number(numbers()?.into_iter().fold(f64::NEG_INFINITY, f64::max)),
+        "abs" => Value::Number(numbers()?[0].abs()),
+        "sqrt" => Value::Number(numbers()?[0].sqrt()),
+        "exp" => Value::Number(numbers()?[0].exp()),
+        "log" => Value::Number(numbers()?[0].ln()),
+        "pow" => {
+            let n = numbers()?;
+            Value::Number(n[0].powf(n[1]))
+        }
+        "floor" => Value::Number(numbers()?[0].floor()),
+        "ceil" => Value::Number(numbers()?[0].ceil()),
+        "round" => Value::Number(numbers()?[0].round()),
+        "clamp" => {
+            let n = numbers()?;
+            // NaN bounds fail too, since `f64::clamp` would panic on them
+            if n[1].is_nan() || n[2].is_nan() || n[1] > n[2] {
+                return Err(error(format!("clamp: invalid bounds {} and {}", n[1], n[2]), span));
+            }
+            Value::Number(n[0].clamp(n[1], n[2]))
+        }
+        "<" => compare(|a, b| a < b)?,
+        ">" => compare(|a, b| a > b)?,
+        "<=" => compare(|a, b| a <= b)?,
+        ">=" => compare(|a, b| a >= b)?,
+        "=" => compare(|a, b| a == b)?,
+        "!=" => Value::Bool(values[0] != values[1]),
+        "not" => Value::Bool(!values[0].truthy()),
+        "param" => {
+            let index = numbers()?[0];
+            if index < 0.0 || index.fract() != 0.0 {
+                return Err(error(format!("param: invalid index {}", index), span));
+            }
+            params.get(index as usize).map_or(Value::Nil, |p| Value::Number(*p))
+        }
+        "list" => {
+            if values.iter().map(Value::size).sum::<u64>() >= MAX_LIST_SIZE {
+                return Err(error(format!("list: a list can hold at most {} values", MAX_LIST_SIZE), span));
+            }
+            Value::List(values.to_vec())
+        }
+        "nth" => {
+            let index = match &values[1] {
+                Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => *n as usize,
+                _ => return Err(error("nth: index must be a non-negative integer", span)),
+            };
+            match &values[0] {
+                Value::List(items) => items.get(index).cloned().unwrap_or(Value::Nil),
+                other => return Err(error(format!("nth: expected a list, got {}", other.type_name()), span)),
+            }
+        }
+        "length" => match &values[0] {
+            Value::List(items) => Value::Number(items.len() as f64),
+            Value::Str(text) => Value::Number(text.chars().count() as f64),
+            Value::Nil => Value::Number(0.0),
+            other => return Err(error(format!("length: expected a list, got {}", other.type_name()), span)),
+        },
+        "state-vector" => list(state.get_state_vector()),
+        "dot" => Value::Number(vecmath::dot(&vector(0)?, &vector(1)?)
+            .map_err(|e| error(format!("dot: {}", e), span))?),
+        "norm" => Value::Number(vecmath::norm(&vector(0)?)),
+        "normalize" => list(vecmath::normalize(&vector(0)?)),
+        "distance" => Value::Number(vecmath::distance(&vector(0)?, &vector(1)?)
+            .map_err(|e| error(format!("distance: {}", e), span))?),
+        "lerp" => match (&values[0], &values[1], &values[2]) {
+            (Value::Number(a), Value::Number(b), Value::Number(t)) => Value::Number(a + (b - a) * t),
+            (_, _, Value::Number(t)) => checked(vecmath::lerp(&vector(0)?, &vector(1)?, *t))?,
+            (_, _, other) => return Err(error(format!("lerp: expected a number, got {}", other.type_name()), span)),
+        },
+        "vec+" => checked(vecmath::zip_with(&vector(0)?, &vector(1)?, |x, y| x + y))?,
+        "vec-" => checked(vecmath::zip_with(&vector(0)?, &vector(1)?, |x, y| x - y))?,
+        "vec*" => checked(vecmath::zip_with(&vector(0)?, &vector(1)?, |x, y| x * y))?,
+        "scale" => match &values[1] {
+            Value::Number(factor) => list(vector(0)?.iter().map(|x| x * factor).collect()),
+            other => return Err(error(format!("scale: expected a number, got {}", other.type_name()), span)),
+        },
+        _ => return Err(error(format!("Unknown function: {}", head), span)),
+    };
+    
+    Ok(value)
+}
@@ -0,0 +1,102 @@
+use crate::canonical_metric;
+
+/// A core metric as seen from rule code
+pub(crate) struct MetricSpec {
+    /// Name used in rule code (`mutation-rate`)
+    pub name: &'static str,
+    /// Canonical snake_case name used by `OrganismState`
+    pub canonical: &'static str,
+    /// Whether rules may write the metric
+    pub writable: bool,
+}
+
+/// Core metrics available to rules
+pub(crate) const METRICS: [MetricSpec; 7] = [
+    MetricSpec { name: "population", canonical: "population", writable: true },
+    MetricSpec { name: "energy", canonical: "energy", writable: true },
+    MetricSpec { name: "generation", canonical: "generation", writable: true },
+    MetricSpec { name: "age", canonical: "age", writable: false },
+    MetricSpec { name: "mutation-rate", canonical: "mutation_rate", writable: true },
+    MetricSpec { name: "selection-pressure", canonical: "selection_pressure", writable: true },
+    MetricSpec { name: "adaptation-score", canonical: "adaptation_score", writable: false },
+];
+
+/// Special forms with their own evaluation rules
+pub(crate) const SPECIAL_FORMS: [&str; 17] = [
+    "quote", "if", "cond", "let", "let*", "begin", "def", "define", "set!",
+    "and", "or", "when", "unless", "while", "dotimes", "lambda", "else",
+];
+
+/// Built-in functions as (name, minimum arity, maximum arity)
+pub(crate) const BUILTINS: [(&str, usize, Option<usize>); 37] = [
+    ("+", 0, None),
+    ("-", 1, None),
+    ("*", 0, None),
+    ("/", 1, None),
+    ("mod", 2, Some(2)),
+    ("min", 1, None),
+    ("max", 1, None),
+    ("abs", 1, Some(1)),
+    ("sqrt", 1, Some(1)),
+    ("exp", 1, Some(1)),
+    ("log", 1, Some(1)),
+    ("pow", 2, Some(2)),
+    ("floor", 1, Some(1)),
+    ("ceil", 1, Some(1)),
+    ("round", 1, Some(1)),
+    ("clamp", 3, Some(3)),
+    ("<", 2, None),
+    (">", 2, None),
+    ("<=", 2, None),
+    (">=", 2, None),
+    ("=", 2, None),
+    ("!=", 2, Some(2)),
+    ("not", 1, Some(1)),
+    ("param", 1, Some(1)),
+    ("list", 0, None),
+    ("nth", 2, Some(2)),
+    ("length", 1, Some(1)),
+    ("state-vector", 0, Some(0)),
+    ("dot", 2, Some(2)),
+    ("norm", 1, Some(1)),
+    ("normalize", 1, Some(1)),
+    ("distance", 2, Some(2)),
+    ("lerp", 3, Some(3)),
+    ("vec+", 2, Some(2)),
+    ("vec-", 2, Some(2)),
+    ("vec*", 2, Some(2)),
+    ("scale", 2, Some(2)),
+];
+
+/// Metrics returned by `state-vector`, in order
+pub(crate) const STATE_VECTOR_METRICS: [&str; 3] = ["population", "energy", "mutation-rate"];
+
+/// Prefix of the generated metric setters (`set-energy`)
+pub(crate) const SETTER_PREFIX: &str = "set-";
+
+/// Look up a metric by any of its spellings (`mutation-rate`, `mutation_rate`, `mutationRate`)
+pub(crate) fn lookup_metric(symbol: &str) -> Option<&'static MetricSpec> {
+    let canonical = canonical_metric(symbol).or_else(|| canonical_metric(&symbol.replace('-', "_")))?;
+    METRICS.iter().find(|metric| metric.canonical == canonical)
+}
+
+/// Metric targeted by a setter call like `set-mutation-rate`
+pub(crate) fn setter_target(symbol: &str) -> Option<&'static MetricSpec> {
+    symbol.strip_prefix(SETTER_PREFIX).and_then(lookup_metric)
+}
+
+/// Whether a name looks like a setter call, even for an unknown metric
+pub(crate) fn is_setter_name(symbol: &str) -> bool {
+    symbol.len() > SETTER_PREFIX.len() && symbol.starts_with(SETTER_PREFIX)
+}
+
+pub(crate) fn is_special_form(symbol: &str) -> bool {
+    SPECIAL_FORMS.contains(&symbol)
+}
+
+pub(crate) fn builtin_arity(symbol: &str) -> Option<(usize, Option<usize>)> {
+    BUILTINS
+        .iter()
+        .find(|(name, _, _)| *name == symbol)
+        .map(|(_, min, max)| (*min, *max))
+}
@@ -0,0 +1,480 @@
+use wasm_bindgen::prelude::*;
+
+use super::language::{builtin_arity, is_setter_name, is_special_form, lookup_metric, setter_target};
+use super::reader::{parse, Expr, Node, Span};
+use crate::RuleRegistry;
+
+/// `dotimes` counts above this are reported as expensive
+const EXPENSIVE_ITERATIONS: f64 = 10_000.0;
+
+/// Nesting depth above which a rule is reported as hard to follow
+const DEEP_NESTING: usize = 32;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+/// A single finding about a rule's code
+#[derive(Clone, Debug)]
+pub(crate) struct Diagnostic {
+    pub severity: Severity,
+    pub code: &'static str,
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(severity: Severity, code: &'static str, message: String, span: Span) -> Diagnostic {
+        Diagnostic { severity, code, message, span }
+    }
+    
+    /// The diagnostic as one line, e.g. `Error at line 2, column 5: ...`
+    pub fn describe(&self) -> String {
+        let kind = if self.code == "parse-error" { "Parse error" } else { "Error" };
+        format!("{} at line {}, column {}: {}", kind, self.span.line, self.span.column, self.message)
+    }
+    
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "severity": self.severity.as_str(),
+            "code": self.code,
+            "message": self.message,
+            "line": self.span.line,
+            "column": self.span.column,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Lint a registered rule's code for the rule editor
+    ///
+    /// Returns a JSON array of diagnostics, each with `severity` ("error",
+    /// "warning" or "info"), `code`, `message`, `line` and `column`. Checks
+    /// cover syntax errors, unused bindings, unknown metrics and functions,
+    /// writes to read-only metrics (`age`, `adaptation-score`) and expensive
+    /// constructs such as unbounded or nested loops.
+    #[wasm_bindgen(js_name = lintRule)]
+    pub fn lint_rule(&self, id: &str) -> Result<String, JsValue> {
+        let code = self.get_rule_code(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        
+        let diagnostics: Vec<serde_json::Value> = lint(&code).iter().map(Diagnostic::to_json).collect();
+        serde_json::to_string(&diagnostics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize diagnostics: {}", e)))
+    }
+    
+    /// Check rule code before registering it
+    ///
+    /// Returns `{ valid, diagnostics }`, with diagnostics shaped as
+    /// `lintRule`'s. `valid` is false when any diagnostic is an error:
+    /// syntax errors, unsupported forms, writes to read-only metrics and
+    /// calls to variables. `registerRule` and `registerRules` reject such
+    /// code, since it could only fail once applied.
+    #[wasm_bindgen(js_name = checkRuleCode)]
+    pub fn check_rule_code(code: &str) -> Result<String, JsValue> {
+        let diagnostics = lint(code);
+        let report = serde_json::json!({
+            "valid": !diagnostics.iter().any(|d| d.severity == Severity::Error),
+            "diagnostics": diagnostics.iter().map(Diagnostic::to_json).collect::<Vec<_>>(),
+        });
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize diagnostics: {}", e)))
+    }
+}
+
+/// Check that rule source has no error diagnostics
+///
+/// The message lists every error, one `describe` line each, separated by
+/// `; `.
+pub(crate) fn check(source: &str) -> Result<(), String> {
+    let errors: Vec<String> = lint(source)
+        .iter()
+        .filter(|d| d.severity == Severity::Error)
+        .map(Diagnostic::describe)
+        .collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+struct Binding {
+    name: String,
+    span: Span,
+    used: bool,
+    // Bindings that are fine to leave unused (the state handle, loop counters)
+    exempt: bool,
+}
+
+struct Linter {
+    scopes: Vec<Vec<Binding>>,
+    diagnostics: Vec<Diagnostic>,
+    loop_depth: usize,
+    reported_nesting: bool,
+}
+
+/// Validate a trigger predicate: besides errors, reject unknown functions
+/// and symbols, which would otherwise only fail once the predicate runs
+pub(crate) fn check_predicate(source: &str) -> Result<(), String> {
+    let problems: Vec<String> = lint(source)
+        .iter()
+        .filter(|d| {
+            d.severity == Severity::Error || d.code == "unknown-function" || d.code == "unknown-metric"
+        })
+        .map(Diagnostic::describe)
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+/// Lint rule source, returning every finding in source order
+pub(crate) fn lint(source: &str) -> Vec<Diagnostic> {
+    let forms = match parse(source) {
+        Ok(forms) => forms,
+        Err(error) => {
+            return vec![Diagnostic::new(Severity::Error, "parse-error", error.message, error.span)];
+        }
+    };
+    
+    let mut linter = Linter {
+        scopes: vec![Vec::new()],
+        diagnostics: Vec::new(),
+        loop_depth: 0,
+        reported_nesting: false,
+    };
+    
+    for form in &forms {
+        linter.walk(form, 0, true);
+    }
+    linter.pop_scope();
+    
+    let mut diagnostics = linter.diagnostics;
+    diagnostics.sort_by_key(|d| (d.span.line, d.span.column));
+    diagnostics
+}
+
+impl Linter {
+    fn report(&mut self, severity: Severity, code: &'static str, message: String, span: Span) {
+        self.diagnostics.push(Diagnostic::new(severity, code, message, span));
+    }
+    
+    fn bind(&mut self, name: &str, span: Span, exempt: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name: name.to_string(),
+                span,
+                used: false,
+                exempt: exempt || name.starts_with('_'),
+            });
+        }
+    }
+    
+    fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            for binding in scope.into_iter().filter(|b| !b.used && !b.exempt) {
+                self.report(
+                    Severity::Warning,
+                    "unused-binding",
+                    format!("'{}' is bound but never used", binding.name),
+                    binding.span,
+                );
+            }
+        }
+    }
+    
+    /// Mark the innermost binding of `name` as used, returning whether one exists
+    fn use_binding(&mut self, name: &str) -> bool {
+        for scope in self.scopes.iter_mut().rev() {
+            if let Some(binding) = scope.iter_mut().rev().find(|b| b.name == name) {
+                binding.used = true;
+                return true;
+            }
+        }
+        false
+    }
+    
+    fn is_bound(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| scope.iter().any(|b| b.name == name))
+    }
+    
+    fn walk(&mut self, node: &Node, depth: usize, top_level: bool) {
+        if depth > DEEP_NESTING && !self.reported_nesting {
+            self.reported_nesting = true;
+            self.report(
+                Severity::Info,
+                "deep-nesting",
+                format!("Expression nesting exceeds {} levels", DEEP_NESTING),
+                node.span,
+            );
+        }
+        
+        match &node.expr {
+            Expr::Number(_) | Expr::Bool(_) | Expr::Str(_) => {}
+            Expr::Symbol(name) => self.walk_symbol(name, node.span),
+            Expr::List(items) => self.walk_list(node, items, depth, top_level),
+        }
+    }
+    
+    fn walk_symbol(&mut self, name: &str, span: Span) {
+        if self.use_binding(name) || lookup_metric(name).is_some() || name == "nil" {
+            return;
+        }
+        if builtin_arity(name).is_some() || is_special_form(name) {
+            self.report(
+                Severity::Warning,
+                "function-as-value",
+                format!("'{}' is a function and cannot be used as a value", name),
+                span,
+            );
+            return;
+        }
+        self.report(
+            Severity::Warning,
+            "unknown-metric",
+            format!("Unknown metric or variable: {}", name),
+            span,
+        );
+    }
+    
+    fn walk_all(&mut self, nodes: &[Node], depth: usize) {
+        for node in nodes {
+            self.walk(node, depth + 1, false);
+        }
+    }
+    
+    fn walk_body_in_scope(&mut self, body: &[Node], depth: usize) {
+        self.walk_all(body, depth);
+        self.pop_scope();
+    }
+    
+    fn walk_list(&mut self, node: &Node, items: &[Node], depth: usize, top_level: bool) {
+        let head = match items.first().and_then(Node::as_symbol) {
+            Some(head) => head,
+            None => {
+                self.walk_all(items, depth);
+                return;
+            }
+        };
+        let args = &items[1..];
+        
+        match head {
+            "quote" => {}
+            "lambda" => {
+                self.scopes.push(Vec::new());
+                if let Some(params) = args.first().and_then(Node::as_list) {
+                    for (index, param) in params.iter().enumerate() {
+                        if let Some(name) = param.as_symbol() {
+                            // The first parameter of the top-level lambda is the state handle
+                            self.bind(name, param.span, top_level && index == 0);
+                        }
+                    }
+                }
+                if !top_level {
+                    self.report(
+                        Severity::Error,
+                        "unsupported-form",
+                        "Nested lambdas are not supported; only the top-level rule form may be a lambda".to_string(),
+                        node.span,
+                    );
+                }
+                self.walk_body_in_scope(args.get(1..).unwrap_or(&[]), depth);
+            }
+            "def" | "define" => match args.first() {
+                Some(target) if target.as_symbol().is_some() => {
+                    self.walk_all(&args[1..], depth);
+                    let name = target.as_symbol().unwrap_or_default();
+                    self.bind(name, target.span, false);
+                }
+                Some(target) => self.report(
+                    Severity::Error,
+                    "unsupported-form",
+                    "Only (define name value) is supported; rules cannot define functions".to_string(),
+                    target.span,
+                ),
+                None => {}
+            },
+            "let" | "let*" => {
+                let sequential = head == "let*";
+                let bindings = args.first().and_then(Node::as_list).unwrap_or(&[]);
+                
+                self.scopes.push(Vec::new());
+                let mut pending = Vec::new();
+                for binding in bindings {
+                    let parts = binding.as_list().unwrap_or(&[]);
+                    if let Some(value) = parts.get(1) {
+                        // Plain let evaluates every value in the enclosing scope
+                        if sequential {
+                            self.walk(value, depth + 1, false);
+                        } else {
+                            let scope = self.scopes.pop().unwrap_or_default();
+                            self.walk(value, depth + 1, false);
+                            self.scopes.push(scope);
+                        }
+                    }
+                    if let Some(name) = parts.first().and_then(Node::as_symbol) {
+                        if sequential {
+                            self.bind(name, parts[0].span, false);
+                        } else {
+                            pending.push((name.to_string(), parts[0].span));
+                        }
+                    }
+                }
+                for (name, span) in pending {
+                    self.bind(&name, span, false);
+                }
+                self.walk_body_in_scope(args.get(1..).unwrap_or(&[]), depth);
+            }
+            "set!" => {
+                if let Some(target) = args.first() {
+                    if let Some(name) = target.as_symbol() {
+                        self.check_write(name, target.span);
+                    }
+                }
+                self.walk_all(args.get(1..).unwrap_or(&[]), depth);
+            }
+            "while" => {
+                self.report(
+                    Severity::Warning,
+                    "unbounded-loop",
+                    "'while' loops run until their condition fails and can exhaust the execution budget".to_string(),
+                    node.span,
+                );
+                self.walk_loop(node, args, depth);
+            }
+            "dotimes" => {
+                let spec = args.first().and_then(Node::as_list).unwrap_or(&[]);
+                if let Some(count) = spec.get(1) {
+                    if let Expr::Number(n) = count.expr {
+                        if n > EXPENSIVE_ITERATIONS {
+                            self.report(
+                                Severity::Warning,
+                                "expensive-loop",
+                                format!("Loop runs {} iterations per application", n),
+                                count.span,
+                            );
+                        }
+                    }
+                    self.walk(count, depth + 1, false);
+                }
+                
+                self.scopes.push(Vec::new());
+                if let Some(counter) = spec.first() {
+                    if let Some(name) = counter.as_symbol() {
+                        self.bind(name, counter.span, true);
+                    }
+                }
+                self.walk_loop(node, args.get(1..).unwrap_or(&[]), depth);
+                self.pop_scope();
+            }
+            "cond" => {
+                for clause in args {
+                    match clause.as_list() {
+                        Some(parts) => {
+                            let test_is_else = parts.first().and_then(Node::as_symbol) == Some("else");
+                            let start = if test_is_else { 1 } else { 0 };
+                            self.walk_all(&parts[start..], depth + 1);
+                        }
+                        None => self.report(
+                            Severity::Error,
+                            "malformed-form",
+                            "cond clauses must be lists".to_string(),
+                            clause.span,
+                        ),
+                    }
+                }
+            }
+            "if" | "begin" | "and" | "or" | "when" | "unless" => self.walk_all(args, depth),
+            _ => self.walk_call(head, node, args, depth),
+        }
+    }
+    
+    fn walk_loop(&mut self, node: &Node, body: &[Node], depth: usize) {
+        if self.loop_depth > 0 {
+            self.report(
+                Severity::Warning,
+                "nested-loop",
+                "Nested loops multiply the cost of every application".to_string(),
+                node.span,
+            );
+        }
+        self.loop_depth += 1;
+        self.walk_all(body, depth);
+        self.loop_depth -= 1;
+    }
+    
+    fn walk_call(&mut self, head: &str, node: &Node, args: &[Node], depth: usize) {
+        if let Some(metric) = setter_target(head) {
+            if !metric.writable {
+                self.report(
+                    Severity::Error,
+                    "read-only-write",
+                    format!("'{}' is read-only and cannot be set", metric.name),
+                    node.span,
+                );
+            }
+        } else if is_setter_name(head) {
+            self.report(
+                Severity::Warning,
+                "unknown-metric",
+                format!("Unknown metric in setter: {}", head),
+                node.span,
+            );
+        } else if self.is_bound(head) {
+            self.use_binding(head);
+            self.report(
+                Severity::Error,
+                "not-a-function",
+                format!("'{}' is a variable, not a function", head),
+                node.span,
+            );
+        } else if lookup_metric(head).is_none() && builtin_arity(head).is_none() {
+            self.report(
+                Severity::Warning,
+                "unknown-function",
+                format!("Unknown function: {}", head),
+                node.span,
+            );
+        }
+        
+        self.walk_all(args, depth);
+    }
+    
+    fn check_write(&mut self, name: &str, span: Span) {
+        if self.is_bound(name) {
+            return;
+        }
+        match lookup_metric(name) {
+            Some(metric) if !metric.writable => self.report(
+                Severity::Error,
+                "read-only-write",
+                format!("'{}' is read-only and cannot be set", metric.name),
+                span,
+            ),
+            Some(_) => {}
+            None => self.report(
+                Severity::Warning,
+                "unknown-metric",
+                format!("Unknown metric or variable: {}", name),
+                span,
+            ),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+mod access;
+mod bytecode;
+mod doc;
+mod eval;
+mod language;
+mod lint;
+mod program;
+mod reader;
+mod units;
+
+pub(crate) use access::{analyze_access, RuleAccess};
+pub(crate) use doc::extract_docstring;
+pub(crate) use eval::{eval_predicate, run, Budget, Trace, Value, MAX_STEPS};
+pub(crate) use language::{lookup_metric, METRICS};
+pub(crate) use lint::{check as check_code, check_predicate};
+pub(crate) use program::{Program, RunError};
+pub(crate) use reader::{parse, Expr, Node};
@@ -0,0 +1,109 @@
+use super::bytecode::{self, Bytecode};
+use super::eval::{run_with_budget, Budget, EvalError, Trace, Value};
+use super::reader::{parse, Node};
+use crate::{memory, OrganismState};
+
+/// Rule code parsed and compiled once, so applying a rule doesn't re-read its source
+///
+/// Code that fails to parse keeps the error, which is reported every time
+/// the rule is applied; registration rejects such code up front, but
+/// imports keep it so a registry with one broken rule still loads. Code the
+/// bytecode compiler doesn't cover runs on the interpreter instead.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Program {
+    forms: Vec<Node>,
+    error: Option<String>,
+    bytecode: Option<Bytecode>,
+    /// False for the placeholder left by deserialization, until `compile` runs
+    compiled: bool,
+}
+
+/// Why a run of a `Program` failed
+pub(crate) enum RunError {
+    /// A runtime error at a position in the code
+    At(EvalError),
+    /// Code that didn't parse, or a result that isn't a number
+    Other(String),
+}
+
+impl RunError {
+    pub fn message(&self) -> String {
+        match self {
+            RunError::At(e) => format!("line {}, column {}: {}", e.span.line, e.span.column, e.message),
+            RunError::Other(message) => message.clone(),
+        }
+    }
+}
+
+impl Program {
+    pub fn compile(source: &str) -> Program {
+        match parse(source) {
+            Ok(forms) => {
+                let bytecode = bytecode::compile(&forms).ok();
+                Program { forms, error: None, bytecode, compiled: true }
+            }
+            Err(e) => Program {
+                forms: Vec::new(),
+                error: Some(format!("Parse error at line {}, column {}: {}", e.span.line, e.span.column, e.message)),
+                bytecode: None,
+                compiled: true,
+            },
+        }
+    }
+    
+    pub fn is_compiled(&self) -> bool {
+        self.compiled
+    }
+    
+    /// Whether the code runs on the bytecode VM rather than the interpreter
+    pub fn is_bytecode(&self) -> bool {
+        self.bytecode.is_some()
+    }
+    
+    /// Estimated bytes held by the parsed forms and bytecode
+    pub fn heap_bytes(&self) -> usize {
+        memory::forms_bytes(&self.forms)
+            + self.error.as_ref().map_or(0, String::capacity)
+            + self.bytecode.as_ref().map_or(0, Bytecode::heap_bytes)
+    }
+    
+    /// Run against a state and convert the result to a number
+    ///
+    /// A number is returned as is and a boolean as 1 or 0. Code whose last
+    /// form has no value (such as a `when` that didn't fire) returns the
+    /// state's adaptation score, so a rule that only changes metrics still
+    /// reports how the organism is doing.
+    ///
+    /// `budget` caps the instructions executed (or, for interpreted code,
+    /// the evaluation steps) plus the values in the lists copied or built,
+    /// and optionally the time taken, so a runaway loop fails instead of
+    /// hanging.
+    pub fn run(&self, state: &mut OrganismState, params: &[f64], budget: Budget) -> Result<f64, String> {
+        self.run_with(state, params, budget, None).map_err(|e| e.message())
+    }
+    
+    /// Run like `run`, recording the value of every sub-expression in `trace`
+    ///
+    /// Only the interpreter records values, so compiled code is interpreted
+    /// here and `budget` counts its evaluation steps. The result is the one
+    /// `run` returns.
+    pub fn run_traced(&self, state: &mut OrganismState, params: &[f64], budget: Budget, trace: &mut Trace) -> Result<f64, RunError> {
+        self.run_with(state, params, budget, Some(trace))
+    }
+    
+    fn run_with(&self, state: &mut OrganismState, params: &[f64], budget: Budget, trace: Option<&mut Trace>) -> Result<f64, RunError> {
+        if let Some(error) = &self.error {
+            return Err(RunError::Other(error.clone()));
+        }
+        let value = match (&self.bytecode, trace) {
+            (Some(code), None) => bytecode::execute(code, state, params, budget),
+            (_, trace) => run_with_budget(&self.forms, state, params, trace, budget),
+        };
+        match value.map_err(RunError::At)? {
+            Value::Number(number) => Ok(number),
+            Value::Bool(flag) => Ok(if flag { 1.0 } else { 0.0 }),
+            Value::Nil => Ok(state.adaptation_score()),
+            other => Err(RunError::Other(format!("Rule returned a {}, expected a number", other.type_name()))),
+        }
+    }
+}
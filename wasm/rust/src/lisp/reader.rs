@@ -0,0 +1,308 @@
+use std::fmt;
+
+/// Position of a form in the rule source (1-based)
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub(crate) struct Span {
+    pub line: u32,
+    pub column: u32,
+}
+
+/// A parsed form
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Expr {
+    Number(f64),
+    Bool(bool),
+    Str(String),
+    Symbol(String),
+    List(Vec<Node>),
+}
+
+/// A parsed form together with where it starts in the source
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Node {
+    pub expr: Expr,
+    pub span: Span,
+}
+
+impl Node {
+    pub fn as_symbol(&self) -> Option<&str> {
+        match &self.expr {
+            Expr::Symbol(name) => Some(name),
+            _ => None,
+        }
+    }
+    
+    pub fn as_list(&self) -> Option<&[Node]> {
+        match &self.expr {
+            Expr::List(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    /// Print the form back as source text
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expr {
+            Expr::Number(value) => write!(f, "{}", value),
+            Expr::Bool(true) => write!(f, "#t"),
+            Expr::Bool(false) => write!(f, "#f"),
+            Expr::Str(text) => write!(f, "{:?}", text),
+            Expr::Symbol(name) => write!(f, "{}", name),
+            Expr::List(items) => {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, " ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// Expression nesting allowed, in the reader and when a rule runs
+///
+/// Deeper code is a parse error, so nothing that walks parsed forms
+/// (lint, access analysis, the compiler, dropping the tree) can recurse
+/// further than this.
+pub(crate) const MAX_DEPTH: usize = 256;
+
+/// A syntax error with the position it was detected at
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct ParseError {
+    pub message: String,
+    pub span: Span,
+    /// The code nests deeper than `MAX_DEPTH`, rather than being malformed
+    pub too_deep: bool,
+}
+
+/// Parse rule source into its top-level forms
+pub(crate) fn parse(source: &str) -> Result<Vec<Node>, ParseError> {
+    let mut reader = Reader::new(source);
+    let mut forms = Vec::new();
+    
+    loop {
+        reader.skip_trivia();
+        match reader.peek() {
+            None => break,
+            Some(')') => return Err(reader.error("Unexpected ')'")),
+            Some(_) => forms.push(reader.read_form()?),
+        }
+    }
+    
+    Ok(forms)
+}
+
+struct Reader<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: u32,
+    column: u32,
+    /// Lists and quotes open around the form being read
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(source: &'a str) -> Reader<'a> {
+        Reader {
+            chars: source.chars().peekable(),
+            line: 1,
+            column: 1,
+            depth: 0,
+        }
+    }
+    
+    fn span(&self) -> Span {
+        Span {
+            line: self.line,
+            column: self.column,
+        }
+    }
+    
+    fn error(&self, message: &str) -> ParseError {
+        ParseError {
+            message: message.to_string(),
+            span: self.span(),
+            too_deep: false,
+        }
+    }
+    
+    fn peek(&mut self) -> Option<char> {
+        self.chars.peek().copied()
+    }
+    
+    fn bump(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+        Some(c)
+    }
+    
+    fn skip_trivia(&mut self) {
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() {
+                self.bump();
+            } else if c == ';' {
+                while let Some(c) = self.bump() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+    
+    fn read_form(&mut self) -> Result<Node, ParseError> {
+        if matches!(self.peek(), Some('(' | '\'')) {
+            if self.depth >= MAX_DEPTH {
+                return Err(ParseError {
+                    message: format!("Expression nesting exceeds {} levels", MAX_DEPTH),
+                    span: self.span(),
+                    too_deep: true,
+                });
+            }
+            self.depth += 1;
+            let form = self.read_nested();
+            self.depth -= 1;
+            return form;
+        }
+        self.read_nested()
+    }
+    
+    /// Read a form, with `depth` already counting it if it is a list or quote
+    fn read_nested(&mut self) -> Result<Node, ParseError> {
+        let span = self.span();
+        
+        match self.peek() {
+            None => Err(self.error("Unexpected end of input")),
+            Some('(') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_trivia();
+                    match self.peek() {
+                        None => {
+                            return Err(ParseError {
+                                message: "Unclosed '(' - missing ')'".to_string(),
+                                span,
+                                too_deep: false,
+                            })
+                        }
+                        Some(')') => {
+                            self.bump();
+                            break;
+                        }
+                        Some(_) => items.push(self.read_form()?),
+                    }
+                }
+                Ok(Node { expr: Expr::List(items), span })
+            }
+            Some(')') => Err(self.error("Unexpected ')'")),
+            Some('\'') => {
+                self.bump();
+                self.skip_trivia();
+                let quoted = self.read_form()?;
+                let quote = Node {
+                    expr: Expr::Symbol("quote".to_string()),
+                    span,
+                };
+                Ok(Node { expr: Expr::List(vec![quote, quoted]), span })
+            }
+            Some('"') => self.read_string(span),
+            Some(_) => self.read_atom(span),
+        }
+    }
+    
+    fn read_string(&mut self, span: Span) -> Result<Node, ParseError> {
+        self.bump();
+        let mut text = String::new();
+        
+        loop {
+            match self.bump() {
+                None => {
+                    return Err(ParseError {
+                        message: "Unterminated string literal".to_string(),
+                        span,
+                        too_deep: false,
+                    })
+                }
+                Some('"') => break,
+                Some('\\') => match self.bump() {
+                    Some('n') => text.push('\n'),
+                    Some('t') => text.push('\t'),
+                    Some(other) => text.push(other),
+                    None => {
+                        return Err(ParseError {
+                            message: "Unterminated string literal".to_string(),
+                            span,
+                            too_deep: false,
+                        })
+                    }
+                },
+                Some(c) => text.push(c),
+            }
+        }
+        
+        Ok(Node { expr: Expr::Str(text), span })
+    }
+    
+    fn read_atom(&mut self, span: Span) -> Result<Node, ParseError> {
+        let mut token = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || c == '(' || c == ')' || c == '"' || c == ';' || c == '\'' {
+                break;
+            }
+            token.push(c);
+            self.bump();
+        }
+        
+        let expr = match token.as_str() {
+            "#t" | "true" => Expr::Bool(true),
+            "#f" | "false" => Expr::Bool(false),
+            _ if looks_numeric(&token) => match token.parse::<f64>() {
+                Ok(value) => Expr::Number(value),
+                Err(_) => {
+                    return Err(ParseError {
+                        message: format!("Invalid number: {}", token),
+                        span,
+                        too_deep: false,
+                    })
+                }
+            },
+            _ if token.starts_with('#') => {
+                return Err(ParseError {
+                    message: format!("Unknown literal: {}", token),
+                    span,
+                    too_deep: false,
+                })
+            }
+            _ => Expr::Symbol(token),
+        };
+        
+        Ok(Node { expr, span })
+    }
+}
+
+/// Numbers start with a digit, or a sign/point followed by a digit
+///
+/// This keeps `+`, `-` and names like `inf` or `nan` as symbols.
+fn looks_numeric(token: &str) -> bool {
+    let mut chars = token.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_digit() => true,
+        Some('+') | Some('-') | Some('.') => {
+            let rest = chars.as_str();
+            rest.starts_with(|c: char| c.is_ascii_digit())
+                || (rest.starts_with('.') && rest[1..].starts_with(|c: char| c.is_ascii_digit()))
+        }
+        _ => false,
+    }
+}
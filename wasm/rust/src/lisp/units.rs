@@ -0,0 +1,161 @@
+use wasm_bindgen::prelude::*;
+
+use super::access::bound_names;
+use super::language::{lookup_metric, setter_target};
+use super::lint::{Diagnostic, Severity};
+use super::reader::{parse, Expr, Node};
+use crate::{OrganismState, RuleRegistry};
+
+/// Functions whose arguments must all share one unit, which is also the result's
+const SAME_UNIT: [&str; 5] = ["+", "-", "min", "max", "clamp"];
+
+/// Comparisons, whose arguments must share one unit
+const COMPARISONS: [&str; 6] = ["<", ">", "<=", ">=", "=", "!="];
+
+struct UnitChecker<'a> {
+    state: &'a OrganismState,
+    bound: Vec<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl UnitChecker<'_> {
+    fn metric_unit(&self, name: &str) -> Option<String> {
+        if self.bound.iter().any(|b| b == name) {
+            return None;
+        }
+        match lookup_metric(name) {
+            Some(metric) => self.state.unit_of(metric.canonical),
+            None => self.state.unit_of(name),
+        }
+    }
+    
+    /// Report when known units disagree; returns the first known unit
+    fn unify(&mut self, head: &str, units: &[Option<String>], node: &Node) -> Option<String> {
+        let known: Vec<&String> = units.iter().flatten().collect();
+        if let Some(other) = known.iter().find(|unit| **unit != known[0]) {
+            self.diagnostics.push(Diagnostic::new(
+                Severity::Warning,
+                "unit-mismatch",
+                format!("{} combines values in {} and {}", head, known[0], other),
+                node.span,
+            ));
+        }
+        known.first().map(|unit| unit.to_string())
+    }
+    
+    fn check_assignment(&mut self, metric: &str, value: Option<String>, node: &Node) {
+        if let (Some(expected), Some(actual)) = (self.metric_unit(metric), value) {
+            if expected != actual {
+                self.diagnostics.push(Diagnostic::new(
+                    Severity::Warning,
+                    "unit-mismatch",
+                    format!("Assigns a value in {} to {}, which is in {}", actual, metric, expected),
+                    node.span,
+                ));
+            }
+        }
+    }
+    
+    /// Walk a node and return the unit of its value when it is known
+    fn infer(&mut self, node: &Node) -> Option<String> {
+        let items = match &node.expr {
+            Expr::Symbol(name) => return self.metric_unit(name),
+            Expr::List(items) => items,
+            Expr::Number(_) | Expr::Bool(_) | Expr::Str(_) => return None,
+        };
+        let head = items.first().and_then(Node::as_symbol).unwrap_or("");
+        let args = items.get(1..).unwrap_or(&[]);
+        
+        match head {
+            "quote" => return None,
+            "set!" => {
+                let value = args.get(1).and_then(|value| self.infer(value));
+                if let Some(name) = args.first().and_then(Node::as_symbol) {
+                    self.check_assignment(name, value, node);
+                }
+                return None;
+            }
+            "def" | "define" => {
+                for arg in args.iter().skip(1) {
+                    self.infer(arg);
+                }
+                if let Some(name) = args.first().and_then(Node::as_symbol) {
+                    self.bound.push(name.to_string());
+                }
+                return None;
+            }
+            _ => {}
+        }
+        
+        if let Some(metric) = setter_target(head) {
+            let value = args.first().and_then(|value| self.infer(value));
+            self.check_assignment(metric.name, value, node);
+            return None;
+        }
+        if args.is_empty() && !self.bound.iter().any(|b| b == head) {
+            if let Some(unit) = lookup_metric(head).and_then(|_| self.metric_unit(head)) {
+                return Some(unit);
+            }
+        }
+        
+        let scope_start = self.bound.len();
+        self.bound.extend(bound_names(head, args));
+        let units: Vec<Option<String>> = args.iter().map(|arg| self.infer(arg)).collect();
+        self.bound.truncate(scope_start);
+        
+        if SAME_UNIT.contains(&head) {
+            return self.unify(head, &units, node);
+        }
+        if COMPARISONS.contains(&head) {
+            self.unify(head, &units, node);
+            return None;
+        }
+        match head {
+            // Scaling by plain numbers keeps the unit
+            "*" | "/" => {
+                let mut known = units.iter().flatten();
+                match (known.next(), known.next()) {
+                    (Some(unit), None) if head == "*" || units[0].is_some() => Some(unit.clone()),
+                    _ => None,
+                }
+            }
+            "abs" | "floor" | "ceil" | "round" => units.into_iter().next().flatten(),
+            _ => None,
+        }
+    }
+}
+
+/// Check that rule code combines and assigns metrics with consistent units
+///
+/// Units come from `state`'s metric metadata; values with no known unit
+/// (numbers, unknown locals) never cause a warning.
+pub(crate) fn check_units(source: &str, state: &OrganismState) -> Vec<Diagnostic> {
+    let forms = match parse(source) {
+        Ok(forms) => forms,
+        Err(error) => return vec![Diagnostic::new(Severity::Error, "parse-error", error.message, error.span)],
+    };
+    
+    let mut checker = UnitChecker { state, bound: Vec::new(), diagnostics: Vec::new() };
+    for form in &forms {
+        checker.infer(form);
+    }
+    checker.diagnostics
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Check a rule for unit mismatches against a state's metric units
+    ///
+    /// Returns the same diagnostic format as `lintRule`, with code
+    /// "unit-mismatch" for adding, comparing or assigning values whose units
+    /// differ, e.g. `(+ population energy)`.
+    #[wasm_bindgen(js_name = checkRuleUnits)]
+    pub fn check_rule_units(&self, id: &str, state: &OrganismState) -> Result<String, JsValue> {
+        let code = self.get_rule_code(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        
+        let diagnostics: Vec<serde_json::Value> = check_units(&code, state).iter().map(Diagnostic::to_json).collect();
+        serde_json::to_string(&diagnostics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize diagnostics: {}", e)))
+    }
+}
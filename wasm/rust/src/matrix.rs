@@ -0,0 +1,183 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::vecmath;
+use crate::OrganismState;
+
+/// Matrix is a dense row-major matrix of numbers
+///
+/// It backs species interaction matrices, Markov-style transition matrices
+/// and small neural controllers. Rows and columns are zero-based.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    fn index(&self, row: usize, col: usize) -> Result<usize, JsValue> {
+        if row >= self.rows || col >= self.cols {
+            return Err(JsValue::from_str(&format!(
+                "Matrix index ({}, {}) is outside {}x{}",
+                row, col, self.rows, self.cols
+            )));
+        }
+        Ok(row * self.cols + col)
+    }
+    
+    fn row_slice(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+    
+    /// Build a matrix from row-major values, checking their count
+    pub(crate) fn from_values(rows: usize, cols: usize, data: Vec<f64>) -> Result<Matrix, String> {
+        if data.len() != rows * cols {
+            return Err(format!("A {}x{} matrix needs {} values, got {}", rows, cols, rows * cols, data.len()));
+        }
+        Ok(Matrix { rows, cols, data })
+    }
+    
+    /// Multiply by a column vector
+    pub(crate) fn apply(&self, vector: &[f64]) -> Result<Vec<f64>, String> {
+        if vector.len() != self.cols {
+            return Err(format!(
+                "Cannot multiply a {}x{} matrix by a vector of length {}",
+                self.rows, self.cols, vector.len()
+            ));
+        }
+        (0..self.rows)
+            .map(|row| vecmath::dot(self.row_slice(row), vector))
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl Matrix {
+    /// Create a matrix from its values in row-major order
+    #[wasm_bindgen(constructor)]
+    pub fn new(rows: usize, cols: usize, data: Vec<f64>) -> Result<Matrix, JsValue> {
+        Matrix::from_values(rows, cols, data).map_err(|e| JsValue::from_str(&e))
+    }
+    
+    /// Create a matrix filled with zeros
+    #[wasm_bindgen]
+    pub fn zeros(rows: usize, cols: usize) -> Matrix {
+        Matrix { rows, cols, data: vec![0.0; rows * cols] }
+    }
+    
+    /// Create a square identity matrix
+    #[wasm_bindgen]
+    pub fn identity(size: usize) -> Matrix {
+        let mut matrix = Matrix::zeros(size, size);
+        for i in 0..size {
+            matrix.data[i * size + i] = 1.0;
+        }
+        matrix
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+    
+    #[wasm_bindgen]
+    pub fn get(&self, row: usize, col: usize) -> Result<f64, JsValue> {
+        Ok(self.data[self.index(row, col)?])
+    }
+    
+    #[wasm_bindgen]
+    pub fn set(&mut self, row: usize, col: usize, value: f64) -> Result<(), JsValue> {
+        let index = self.index(row, col)?;
+        self.data[index] = value;
+        Ok(())
+    }
+    
+    /// Get one row
+    #[wasm_bindgen]
+    pub fn row(&self, row: usize) -> Result<Vec<f64>, JsValue> {
+        self.index(row, 0)?;
+        Ok(self.row_slice(row).to_vec())
+    }
+    
+    /// Get one column
+    #[wasm_bindgen]
+    pub fn col(&self, col: usize) -> Result<Vec<f64>, JsValue> {
+        self.index(0, col)?;
+        Ok((0..self.rows).map(|row| self.data[row * self.cols + col]).collect())
+    }
+    
+    /// Get every value in row-major order
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<f64> {
+        self.data.clone()
+    }
+    
+    /// Multiply by a column vector (`M * v`)
+    #[wasm_bindgen(js_name = mulVector)]
+    pub fn mul_vector(&self, vector: &[f64]) -> Result<Vec<f64>, JsValue> {
+        self.apply(vector).map_err(|e| JsValue::from_str(&e))
+    }
+    
+    /// Multiply this matrix by another (`self * other`)
+    #[wasm_bindgen]
+    pub fn multiply(&self, other: &Matrix) -> Result<Matrix, JsValue> {
+        if self.cols != other.rows {
+            return Err(JsValue::from_str(&format!(
+                "Cannot multiply a {}x{} matrix by a {}x{} matrix",
+                self.rows, self.cols, other.rows, other.cols
+            )));
+        }
+        
+        let mut product = Matrix::zeros(self.rows, other.cols);
+        for row in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self.data[row * self.cols + k];
+                for col in 0..other.cols {
+                    product.data[row * other.cols + col] += a * other.data[k * other.cols + col];
+                }
+            }
+        }
+        Ok(product)
+    }
+    
+    /// Get the transposed matrix
+    #[wasm_bindgen]
+    pub fn transpose(&self) -> Matrix {
+        let mut transposed = Matrix::zeros(self.cols, self.rows);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                transposed.data[col * self.rows + row] = self.data[row * self.cols + col];
+            }
+        }
+        transposed
+    }
+    
+    /// Multiply by an organism's state vector, without changing the state
+    #[wasm_bindgen(js_name = mulState)]
+    pub fn mul_state(&self, state: &OrganismState) -> Result<Vec<f64>, JsValue> {
+        self.mul_vector(&state.state_vector)
+    }
+    
+    /// Get the matrix as JSON (`rows`, `cols`, `data`)
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize matrix: {}", e)))
+    }
+    
+    /// Restore a matrix from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: Matrix = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse matrix: {}", e)))?;
+        *self = Matrix::new(loaded.rows, loaded.cols, loaded.data)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use serde::Serialize;
+use std::mem::size_of;
+
+use crate::lisp::{Expr, Node};
+use crate::{assertions, call_log, OrganismState, RuleRegistry};
+
+/// Estimated heap bytes of a value, from the length of its JSON
+///
+/// Used for nested data that isn't worth walking field by field; JSON is
+/// within a small factor of the in-memory size for strings and numbers.
+pub(crate) fn json_bytes<T: Serialize>(value: &T) -> usize {
+    serde_json::to_vec(value).map_or(0, |json| json.len())
+}
+
+/// Heap bytes of parsed Lisp forms
+pub(crate) fn forms_bytes(forms: &[Node]) -> usize {
+    forms
+        .iter()
+        .map(|node| {
+            size_of::<Node>() + match &node.expr {
+                Expr::Str(text) | Expr::Symbol(text) => text.capacity(),
+                Expr::List(items) => forms_bytes(items),
+                Expr::Number(_) | Expr::Bool(_) => 0,
+            }
+        })
+        .sum()
+}
+
+/// Heap bytes of a map from names to numbers
+pub(crate) fn named_numbers_bytes<'a>(names: impl Iterator<Item = &'a String>) -> usize {
+    names.map(|name| size_of::<(String, f64)>() + name.capacity()).sum()
+}
+
+impl OrganismState {
+    fn memory_json(&self) -> serde_json::Value {
+        let metrics = size_of::<OrganismState>()
+            + self.state_vector.capacity() * size_of::<f64>()
+            + named_numbers_bytes(self.custom_metrics.keys());
+        let interpreter: usize = self.dynamics.iter().map(|g| g.forms_bytes()).sum();
+        let dynamics = json_bytes(&self.dynamics);
+        let noise = json_bytes(&self.noise);
+        let metadata = json_bytes(&self.metric_meta);
+        serde_json::json!({
+            "metrics": metrics,
+            "dynamics": dynamics,
+            "interpreter": interpreter,
+            "noise": noise,
+            "metadata": metadata,
+            "total": metrics + dynamics + interpreter + noise + metadata,
+        })
+    }
+}
+
+impl RuleRegistry {
+    fn memory_json(&self) -> serde_json::Value {
+        let rules: usize = self.rules
+            .values()
+            .map(|rule| {
+                size_of::<(String, crate::Rule)>()
+                    + rule.id.capacity() * 2
+                    + rule.lisp_code.capacity()
+                    + json_bytes(&rule.tests)
+                    + json_bytes(&rule.shadow)
+                    + json_bytes(&rule.doc)
+                    + json_bytes(&rule.modes)
+                    + json_bytes(&rule.params)
+                    + json_bytes(&rule.versions)
+                    + (rule.access.reads.len() + rule.access.writes.len()) * size_of::<&str>()
+                    + rule.program.heap_bytes()
+                    + rule.hotspot.heap_bytes()
+            })
+            .sum::<usize>()
+            + self.execution_order.iter().map(|id| size_of::<String>() + id.capacity()).sum::<usize>();
+        let events = json_bytes(&self.events);
+        let other = json_bytes(&self.bundles) + json_bytes(&self.modes) + json_bytes(&self.invariants.list)
+            + json_bytes(&self.goals);
+        serde_json::json!({
+            "rules": rules,
+            "events": events,
+            "other": other,
+            "total": size_of::<RuleRegistry>() + rules + events + other,
+        })
+    }
+}
+
+/// Size of the WASM linear memory, which only ever grows
+fn wasm_memory_bytes() -> Option<u32> {
+    let memory = wasm_bindgen::memory().dyn_into::<js_sys::WebAssembly::Memory>().ok()?;
+    let buffer = memory.buffer().dyn_into::<js_sys::ArrayBuffer>().ok()?;
+    Some(buffer.byte_length())
+}
+
+/// A `Float64Array` over `values` where they sit in WASM memory, without copying
+///
+/// The view reads the values live, but only while they stay put: it goes
+/// stale when the buffer holding them is reallocated or freed, and when
+/// WASM memory grows (any allocation may grow it), which detaches the
+/// view so its length drops to 0. Callers must hand out a way to check
+/// a view with `is_current_view` and document what reallocates.
+pub(crate) fn f64_view(values: &[f64]) -> js_sys::Float64Array {
+    // Safety: the view is only read from JS between calls into WASM, and
+    // the contract above tells JS when it must stop using it
+    unsafe { js_sys::Float64Array::view(values) }
+}
+
+/// Whether `view` still covers `values` in the current WASM memory
+pub(crate) fn is_current_view(view: &js_sys::Float64Array, values: &[f64]) -> bool {
+    let memory = match wasm_bindgen::memory().dyn_into::<js_sys::WebAssembly::Memory>() {
+        Ok(memory) => memory,
+        Err(_) => return false,
+    };
+    JsValue::from(view.buffer()) == memory.buffer()
+        && view.length() as usize == values.len()
+        && view.byte_offset() as usize == values.as_ptr() as usize
+}
+
+/// Report estimated memory use to budget WASM memory and spot leaks
+///
+/// Returns JSON with `wasmMemoryBytes`, the size of the whole linear memory,
+/// and estimated bytes held by `state` (metrics, dynamics, interpreter
+/// parse caches, noise, metadata), `registry` (rules with their tests,
+/// shadows and latency windows, events, the rest) and the global `buffers`
+/// (call log, assertion failures). Each part has a `total`. Snapshot
+/// stores, colonies and territories report their own `memoryBytes()`.
+#[wasm_bindgen(js_name = memoryStats)]
+pub fn memory_stats(state: &OrganismState, registry: &RuleRegistry) -> String {
+    let call_log = call_log::buffer_bytes();
+    let assertions = assertions::buffer_bytes();
+    serde_json::json!({
+        "wasmMemoryBytes": wasm_memory_bytes(),
+        "state": state.memory_json(),
+        "registry": registry.memory_json(),
+        "buffers": {
+            "callLog": call_log,
+            "assertions": assertions,
+            "total": call_log + assertions,
+        },
+    })
+    .to_string()
+}
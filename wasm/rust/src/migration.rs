@@ -0,0 +1,298 @@
+use wasm_bindgen::prelude::*;
+use std::cell::RefCell;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+use crate::integrity;
+
+/// Field stamped into snapshots that carry a host schema version
+const HOST_VERSION_FIELD: &str = "hostSchemaVersion";
+
+/// Field stamped into every state and registry snapshot with the module's own schema version
+const SCHEMA_VERSION_FIELD: &str = "schemaVersion";
+
+/// Default value for a field a schema step adds
+type FieldDefault = fn() -> serde_json::Value;
+
+/// Changes to the module's own snapshot layout between two schema versions
+///
+/// Applied to the top-level fields of a snapshot: each rename moves an
+/// old field to its new name (unless the new one is already present),
+/// then each added field missing from the snapshot gets its default.
+struct SchemaStep {
+    renames: &'static [(&'static str, &'static str)],
+    added: &'static [(&'static str, FieldDefault)],
+}
+
+impl SchemaStep {
+    fn apply(&self, value: &mut serde_json::Value) {
+        let Some(object) = value.as_object_mut() else {
+            return;
+        };
+        for (old, new) in self.renames {
+            if !object.contains_key(*new) {
+                if let Some(moved) = object.remove(*old) {
+                    object.insert(new.to_string(), moved);
+                }
+            }
+        }
+        for (name, default) in self.added {
+            if !object.contains_key(*name) {
+                object.insert(name.to_string(), default());
+            }
+        }
+    }
+}
+
+/// Built-in steps per snapshot kind; the current schema version is their count
+///
+/// Snapshots saved before schema versions existed are version 0. Step
+/// `n` upgrades version `n` to `n + 1`. When a field of `OrganismState`
+/// or the registry export is renamed or added without a serde default,
+/// append a step here instead of changing an existing one.
+fn schema_steps(kind: &str) -> &'static [SchemaStep] {
+    match kind {
+        // Version 1 only introduced the stamp; older layouts load as they are
+        "state" | "registry" => &[SchemaStep { renames: &[], added: &[] }],
+        _ => &[],
+    }
+}
+
+/// The module's current schema version for a snapshot kind
+pub(crate) fn schema_version(kind: &str) -> u32 {
+    schema_steps(kind).len() as u32
+}
+
+/// Snapshot kinds hosts can migrate
+const MIGRATABLE_KINDS: [&str; 2] = ["state", "registry"];
+
+/// Host-provided transforms, keyed by snapshot kind and source version
+#[derive(Default)]
+struct MigrationRegistry {
+    host_versions: BTreeMap<String, u32>,
+    steps: BTreeMap<(String, u32), js_sys::Function>,
+}
+
+thread_local! {
+    static MIGRATIONS: RefCell<MigrationRegistry> = RefCell::new(MigrationRegistry::default());
+}
+
+fn check_kind(kind: &str) -> Result<(), JsValue> {
+    if MIGRATABLE_KINDS.contains(&kind) {
+        Ok(())
+    } else {
+        Err(JsValue::from_str(&format!(
+            "Unknown snapshot kind: {} (expected {})",
+            kind,
+            MIGRATABLE_KINDS.join(" or ")
+        )))
+    }
+}
+
+fn host_version(kind: &str) -> u32 {
+    MIGRATIONS.with(|m| m.borrow().host_versions.get(kind).copied().unwrap_or(0))
+}
+
+/// Stamp the schema versions into a serialized snapshot
+pub(crate) fn stamp(kind: &str, json: String) -> Result<String, JsValue> {
+    let mut value: serde_json::Value = serde_json::from_str(&json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to stamp snapshot: {}", e)))?;
+    stamp_value(kind, &mut value);
+    serde_json::to_string(&value)
+        .map_err(|e| JsValue::from_str(&format!("Failed to stamp snapshot: {}", e)))
+}
+
+/// Stamp the module's schema version, the host's once declared, and a checksum into a snapshot value
+pub(crate) fn stamp_value(kind: &str, value: &mut serde_json::Value) {
+    let version = host_version(kind);
+    if let Some(object) = value.as_object_mut() {
+        object.insert(SCHEMA_VERSION_FIELD.to_string(), serde_json::json!(schema_version(kind)));
+        if version != 0 {
+            object.insert(HOST_VERSION_FIELD.to_string(), serde_json::json!(version));
+        }
+    }
+    integrity::seal(value);
+}
+
+/// Verify a snapshot's checksum, then run the built-in and host migrations it needs
+pub(crate) fn migrate(kind: &str, json: &str) -> Result<String, JsValue> {
+    let value: serde_json::Value = serde_json::from_str(json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse snapshot: {}", e)))?;
+    integrity::verify(&value).map_err(|e| e.to_js(kind))?;
+    if version_field(&value, SCHEMA_VERSION_FIELD) == schema_version(kind)
+        && version_field(&value, HOST_VERSION_FIELD) == host_version(kind)
+    {
+        return Ok(json.to_string());
+    }
+    Ok(upgrade(kind, value)?.to_string())
+}
+
+fn version_field(value: &serde_json::Value, field: &str) -> u32 {
+    value.get(field).and_then(|v| v.as_u64()).unwrap_or(0) as u32
+}
+
+/// Upgrade a snapshot value to the module's current schema version
+fn migrate_schema(kind: &str, value: &mut serde_json::Value) -> Result<(), JsValue> {
+    let steps = schema_steps(kind);
+    let version = version_field(value, SCHEMA_VERSION_FIELD);
+    if version > steps.len() as u32 {
+        return Err(JsValue::from_str(&format!(
+            "Snapshot schema version {} is newer than this build supports ({})",
+            version,
+            steps.len()
+        )));
+    }
+    steps[version as usize..].iter().for_each(|step| step.apply(value));
+    if let Some(object) = value.as_object_mut() {
+        object.insert(SCHEMA_VERSION_FIELD.to_string(), serde_json::json!(steps.len()));
+    }
+    Ok(())
+}
+
+/// Verify a snapshot value's checksum, then run the built-in and host migrations it needs
+pub(crate) fn migrate_value(kind: &str, value: serde_json::Value) -> Result<serde_json::Value, JsValue> {
+    integrity::verify(&value).map_err(|e| e.to_js(kind))?;
+    upgrade(kind, value)
+}
+
+/// Run the built-in migrations, then the host ones, on a verified snapshot value
+///
+/// The checksum is dropped first, since migrations change what it covers.
+fn upgrade(kind: &str, mut value: serde_json::Value) -> Result<serde_json::Value, JsValue> {
+    integrity::unseal(&mut value);
+    migrate_schema(kind, &mut value)?;
+    let target = host_version(kind);
+    let mut version = version_field(&value, HOST_VERSION_FIELD);
+    
+    if version > target {
+        return Err(JsValue::from_str(&format!(
+            "Snapshot {} is newer than host schema version {}",
+            version, target
+        )));
+    }
+    
+    while version < target {
+        let step = MIGRATIONS.with(|m| m.borrow().steps.get(&(kind.to_string(), version)).cloned())
+            .ok_or_else(|| {
+                JsValue::from_str(&format!("No {} migration registered from host schema version {}", kind, version))
+            })?;
+        
+        let input = js_sys::JSON::parse(&value.to_string())?;
+        let output = step.call1(&JsValue::NULL, &input)?;
+        // Transforms may mutate the object in place and return nothing
+        let output = if output.is_undefined() { input } else { output };
+        let text = js_sys::JSON::stringify(&output)?
+            .as_string()
+            .ok_or_else(|| JsValue::from_str("Migration returned a value that is not JSON"))?;
+        
+        value = serde_json::from_str(&text)
+            .map_err(|e| JsValue::from_str(&format!("Migration from version {} produced invalid JSON: {}", version, e)))?;
+        version += 1;
+        if let Some(object) = value.as_object_mut() {
+            object.insert(HOST_VERSION_FIELD.to_string(), serde_json::json!(version));
+        }
+    }
+    
+    Ok(value)
+}
+
+/// Migrations lets hosts evolve their own snapshot data between schema versions
+///
+/// A host declares its schema version per snapshot kind ("state" or
+/// "registry") and registers one transform per version step. Snapshots are
+/// stamped with `hostSchemaVersion` on save, and older snapshots run
+/// through the transforms automatically on load. The module stamps its
+/// own `schemaVersion` the same way and upgrades older layouts before
+/// any host transform runs.
+#[wasm_bindgen]
+pub struct Migrations;
+
+#[wasm_bindgen]
+impl Migrations {
+    /// Declare the host's current schema version for a snapshot kind
+    #[wasm_bindgen(js_name = setHostSchemaVersion)]
+    pub fn set_host_schema_version(kind: &str, version: u32) -> Result<(), JsValue> {
+        check_kind(kind)?;
+        MIGRATIONS.with(|m| m.borrow_mut().host_versions.insert(kind.to_string(), version));
+        Ok(())
+    }
+    
+    /// Get the host's declared schema version for a snapshot kind (0 when undeclared)
+    #[wasm_bindgen(js_name = hostSchemaVersion)]
+    pub fn host_schema_version(kind: &str) -> Result<u32, JsValue> {
+        check_kind(kind)?;
+        Ok(host_version(kind))
+    }
+    
+    /// Get the module's current schema version for a snapshot kind
+    #[wasm_bindgen(js_name = schemaVersion)]
+    pub fn schema_version(kind: &str) -> Result<u32, JsValue> {
+        check_kind(kind)?;
+        Ok(schema_version(kind))
+    }
+    
+    /// Read a snapshot's versions without loading it and return them as JSON
+    ///
+    /// The result is `{ schemaVersion, hostSchemaVersion,
+    /// currentSchemaVersion, currentHostSchemaVersion, needsMigration,
+    /// supported }`. Missing stamps read as 0; `supported` is false when
+    /// the snapshot is newer than the module or the host. Only the two
+    /// version fields are decoded, so the snapshot's contents aren't
+    /// checked.
+    #[wasm_bindgen(js_name = snapshotVersion)]
+    pub fn snapshot_version(kind: &str, json: &str) -> Result<String, JsValue> {
+        #[derive(Deserialize)]
+        struct Versions {
+            #[serde(rename = "schemaVersion", default)]
+            schema: Option<u64>,
+            #[serde(rename = "hostSchemaVersion", default)]
+            host: Option<u64>,
+        }
+        
+        check_kind(kind)?;
+        let versions: Versions = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read snapshot version: {}", e)))?;
+        let (schema, host) = (versions.schema.unwrap_or(0), versions.host.unwrap_or(0));
+        let (current_schema, current_host) = (schema_version(kind) as u64, host_version(kind) as u64);
+        Ok(serde_json::json!({
+            "schemaVersion": schema,
+            "hostSchemaVersion": host,
+            "currentSchemaVersion": current_schema,
+            "currentHostSchemaVersion": current_host,
+            "needsMigration": schema < current_schema || host < current_host,
+            "supported": schema <= current_schema && host <= current_host,
+        })
+        .to_string())
+    }
+    
+    /// Register the transform that upgrades a snapshot from `from_version` to `from_version + 1`
+    ///
+    /// The transform receives the snapshot as a plain object and returns the
+    /// upgraded object (or modifies it in place and returns nothing).
+    #[wasm_bindgen]
+    pub fn register(kind: &str, from_version: u32, transform: js_sys::Function) -> Result<(), JsValue> {
+        check_kind(kind)?;
+        MIGRATIONS.with(|m| m.borrow_mut().steps.insert((kind.to_string(), from_version), transform));
+        Ok(())
+    }
+    
+    /// Get the registered steps as JSON (`[{ kind, fromVersion }]`)
+    #[wasm_bindgen(js_name = getRegisteredSteps)]
+    pub fn get_registered_steps() -> Result<String, JsValue> {
+        let steps: Vec<serde_json::Value> = MIGRATIONS.with(|m| {
+            m.borrow().steps
+                .keys()
+                .map(|(kind, from)| serde_json::json!({ "kind": kind, "fromVersion": from }))
+                .collect()
+        });
+        
+        serde_json::to_string(&steps)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize migrations: {}", e)))
+    }
+    
+    /// Remove all host versions and transforms
+    #[wasm_bindgen]
+    pub fn clear() {
+        MIGRATIONS.with(|m| *m.borrow_mut() = MigrationRegistry::default());
+    }
+}
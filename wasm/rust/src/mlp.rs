@@ -0,0 +1,333 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::matrix::Matrix;
+use crate::simulation::Simulation;
+use crate::subsystem::{Subsystem, SubsystemContext};
+use crate::OrganismState;
+
+/// Magic bytes opening the binary weight format
+const BINARY_MAGIC: &[u8; 4] = b"OMLP";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Activation {
+    Linear,
+    Relu,
+    Tanh,
+    Sigmoid,
+}
+
+impl Activation {
+    fn apply(self, x: f64) -> f64 {
+        match self {
+            Activation::Linear => x,
+            Activation::Relu => x.max(0.0),
+            Activation::Tanh => x.tanh(),
+            Activation::Sigmoid => 1.0 / (1.0 + (-x).exp()),
+        }
+    }
+    
+    fn from_code(code: u8) -> Result<Activation, String> {
+        match code {
+            0 => Ok(Activation::Linear),
+            1 => Ok(Activation::Relu),
+            2 => Ok(Activation::Tanh),
+            3 => Ok(Activation::Sigmoid),
+            _ => Err(format!("Unknown activation code: {}", code)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Layer {
+    weights: Matrix,
+    bias: Vec<f64>,
+    activation: Activation,
+}
+
+/// How a controller output changes its metric
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+enum OutputMode {
+    /// Replace the metric with `scale * output`
+    #[default]
+    Set,
+    /// Add `scale * output * dt`, treating the output as a rate
+    Rate,
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Output {
+    metric: String,
+    #[serde(default)]
+    mode: OutputMode,
+    #[serde(default = "default_scale")]
+    scale: f64,
+}
+
+/// Which metrics feed the network and which its outputs drive
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+struct Wiring {
+    #[serde(default)]
+    inputs: Vec<String>,
+    #[serde(default)]
+    outputs: Vec<Output>,
+}
+
+#[derive(Deserialize)]
+struct LayerSpec {
+    /// One row per output unit
+    weights: Vec<Vec<f64>>,
+    #[serde(default)]
+    bias: Vec<f64>,
+    activation: Activation,
+}
+
+#[derive(Deserialize)]
+struct MlpSpec {
+    layers: Vec<LayerSpec>,
+    #[serde(flatten)]
+    wiring: Wiring,
+}
+
+/// Mlp is a small multi-layer perceptron for pre-trained controllers
+///
+/// Load weights with `fromJson` or `fromBinary`, then call `predict` on
+/// raw inputs, `control` to read input metrics from a state and write
+/// the outputs back, or attach it to a `Simulation` with
+/// `addMlpController` so it drives the organism every tick without a
+/// round trip to JS.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct Mlp {
+    layers: Vec<Layer>,
+    wiring: Wiring,
+}
+
+impl Mlp {
+    fn from_layers(layers: Vec<Layer>, wiring: Wiring) -> Result<Mlp, String> {
+        if layers.is_empty() {
+            return Err("A network needs at least one layer".to_string());
+        }
+        for (index, pair) in layers.windows(2).enumerate() {
+            if pair[0].weights.rows() != pair[1].weights.cols() {
+                return Err(format!(
+                    "Layer {} has {} outputs but layer {} takes {} inputs",
+                    index, pair[0].weights.rows(), index + 1, pair[1].weights.cols()
+                ));
+            }
+        }
+        let mlp = Mlp { layers, wiring: Wiring::default() };
+        mlp.with_wiring(wiring)
+    }
+    
+    fn with_wiring(mut self, wiring: Wiring) -> Result<Mlp, String> {
+        if !wiring.inputs.is_empty() && wiring.inputs.len() != self.input_size() {
+            return Err(format!("The network takes {} inputs but {} are wired", self.input_size(), wiring.inputs.len()));
+        }
+        if !wiring.outputs.is_empty() && wiring.outputs.len() != self.output_size() {
+            return Err(format!("The network has {} outputs but {} are wired", self.output_size(), wiring.outputs.len()));
+        }
+        self.wiring = wiring;
+        Ok(self)
+    }
+    
+    fn forward(&self, inputs: &[f64]) -> Result<Vec<f64>, String> {
+        let mut values = inputs.to_vec();
+        for layer in &self.layers {
+            values = layer.weights.apply(&values)?;
+            for (value, bias) in values.iter_mut().zip(layer.bias.iter().chain(std::iter::repeat(&0.0))) {
+                *value = layer.activation.apply(*value + bias);
+            }
+        }
+        Ok(values)
+    }
+    
+    /// Read the wired inputs, run the network and write the wired outputs
+    pub(crate) fn drive(&self, state: &mut OrganismState, delta_time: f64) -> Result<Vec<f64>, String> {
+        if self.wiring.inputs.is_empty() || self.wiring.outputs.is_empty() {
+            return Err("The network's inputs and outputs are not wired to metrics".to_string());
+        }
+        let inputs = self.wiring.inputs
+            .iter()
+            .map(|metric| state.read_any_metric(metric).ok_or_else(|| format!("Unknown metric: {}", metric)))
+            .collect::<Result<Vec<f64>, String>>()?;
+        let outputs = self.forward(&inputs)?;
+        
+        for (output, value) in self.wiring.outputs.iter().zip(&outputs) {
+            let metric = state.writable_metric(&output.metric)?;
+            let current = state.read_any_metric(&metric).unwrap_or_default();
+            let next = match output.mode {
+                OutputMode::Set => output.scale * value,
+                OutputMode::Rate => current + output.scale * value * delta_time,
+            };
+            if next.is_finite() {
+                state.write_any_metric(&metric, next);
+            }
+        }
+        Ok(outputs)
+    }
+    
+    fn parse_binary(bytes: &[u8]) -> Result<Vec<Layer>, String> {
+        let mut cursor = BinaryCursor { bytes, offset: 0 };
+        if cursor.take(4)? != BINARY_MAGIC {
+            return Err("Not an OMLP weight file".to_string());
+        }
+        let count = cursor.u32()? as usize;
+        let mut layers = Vec::with_capacity(count.min(64));
+        for _ in 0..count {
+            let rows = cursor.u32()? as usize;
+            let cols = cursor.u32()? as usize;
+            let activation = Activation::from_code(cursor.take(1)?[0])?;
+            let size = rows.checked_mul(cols).ok_or("Layer is too large")?;
+            let weights = (0..size).map(|_| cursor.f32()).collect::<Result<Vec<f64>, String>>()?;
+            let bias = (0..rows).map(|_| cursor.f32()).collect::<Result<Vec<f64>, String>>()?;
+            layers.push(Layer { weights: Matrix::from_values(rows, cols, weights)?, bias, activation });
+        }
+        if cursor.offset != bytes.len() {
+            return Err(format!("{} trailing bytes after the last layer", bytes.len() - cursor.offset));
+        }
+        Ok(layers)
+    }
+}
+
+/// Little-endian reader over the binary weight format
+struct BinaryCursor<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> BinaryCursor<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.offset.checked_add(len).filter(|end| *end <= self.bytes.len())
+            .ok_or_else(|| format!("Weight file ends early at byte {}", self.offset))?;
+        let slice = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(slice)
+    }
+    
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+    
+    fn f32(&mut self) -> Result<f64, String> {
+        let bytes = self.take(4)?;
+        Ok(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64)
+    }
+}
+
+#[wasm_bindgen]
+impl Mlp {
+    /// Load a network from JSON
+    ///
+    /// `json` is `{ layers, inputs, outputs }`, where each layer is
+    /// `{ weights, bias, activation }` with `weights` one row per unit,
+    /// and `activation` is "linear", "relu", "tanh" or "sigmoid".
+    /// `inputs` names the metrics fed in; each `outputs` entry is
+    /// `{ metric, mode, scale }` with `mode` "set" (the default) or
+    /// "rate". The wiring is only needed for `control`.
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<Mlp, JsValue> {
+        let spec: MlpSpec = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse network: {}", e)))?;
+        spec.layers
+            .into_iter()
+            .map(|layer| {
+                let rows = layer.weights.len();
+                let cols = layer.weights.first().map_or(0, |row| row.len());
+                if layer.weights.iter().any(|row| row.len() != cols) {
+                    return Err("Weight rows must all have the same length".to_string());
+                }
+                if !layer.bias.is_empty() && layer.bias.len() != rows {
+                    return Err(format!("A layer of {} units needs {} biases, got {}", rows, rows, layer.bias.len()));
+                }
+                let weights = Matrix::from_values(rows, cols, layer.weights.concat())?;
+                Ok(Layer { weights, bias: layer.bias, activation: layer.activation })
+            })
+            .collect::<Result<Vec<Layer>, String>>()
+            .and_then(|layers| Mlp::from_layers(layers, spec.wiring))
+            .map_err(|e| JsValue::from_str(&format!("Invalid network: {}", e)))
+    }
+    
+    /// Load a network from the binary weight format, wired with `wiring_json` (`{ inputs, outputs }`)
+    ///
+    /// The format is little-endian: "OMLP", the u32 layer count, then per
+    /// layer u32 rows, u32 cols, a u8 activation (0 linear, 1 relu, 2 tanh,
+    /// 3 sigmoid), rows x cols f32 weights in row-major order and rows f32
+    /// biases.
+    #[wasm_bindgen(js_name = fromBinary)]
+    pub fn from_binary(bytes: &[u8], wiring_json: &str) -> Result<Mlp, JsValue> {
+        let wiring: Wiring = serde_json::from_str(wiring_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse network wiring: {}", e)))?;
+        Mlp::parse_binary(bytes)
+            .and_then(|layers| Mlp::from_layers(layers, wiring))
+            .map_err(|e| JsValue::from_str(&format!("Invalid network: {}", e)))
+    }
+    
+    /// Number of inputs the first layer takes
+    #[wasm_bindgen(getter, js_name = inputSize)]
+    pub fn input_size(&self) -> usize {
+        self.layers.first().map_or(0, |layer| layer.weights.cols())
+    }
+    
+    /// Number of outputs of the last layer
+    #[wasm_bindgen(getter, js_name = outputSize)]
+    pub fn output_size(&self) -> usize {
+        self.layers.last().map_or(0, |layer| layer.weights.rows())
+    }
+    
+    /// Run the network on raw inputs
+    #[wasm_bindgen]
+    pub fn predict(&self, inputs: &[f64]) -> Result<Vec<f64>, JsValue> {
+        self.forward(inputs).map_err(|e| JsValue::from_str(&e))
+    }
+    
+    /// Drive a state once: feed the input metrics, write the outputs, and return them
+    #[wasm_bindgen]
+    pub fn control(&self, state: &mut OrganismState, delta_time: f64) -> Result<Vec<f64>, JsValue> {
+        self.drive(state, delta_time).map_err(|e| JsValue::from_str(&e))
+    }
+}
+
+/// An `Mlp` running as a simulation subsystem before every tick
+#[derive(Clone)]
+struct MlpController {
+    name: String,
+    mlp: Mlp,
+}
+
+impl Subsystem for MlpController {
+    fn name(&self) -> &str {
+        &self.name
+    }
+    
+    fn pre_tick(&mut self, context: &mut SubsystemContext) -> Result<(), String> {
+        self.mlp.drive(context.state, context.delta_time).map(|_| ())
+    }
+    
+    fn box_clone(&self) -> Box<dyn Subsystem> {
+        Box::new(self.clone())
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Run a copy of a wired network before every tick as subsystem `name`
+    #[wasm_bindgen(js_name = addMlpController)]
+    pub fn add_mlp_controller(&mut self, name: &str, mlp: &Mlp) -> Result<(), JsValue> {
+        if mlp.wiring.inputs.is_empty() || mlp.wiring.outputs.is_empty() {
+            return Err(JsValue::from_str("The network's inputs and outputs are not wired to metrics"));
+        }
+        let result = self.add_subsystem(Box::new(MlpController { name: name.to_string(), mlp: mlp.clone() }))
+            .map_err(|e| JsValue::from_str(&e));
+        // Networks have no serialized form, so a replay stops here
+        self.record_input("addMlpController", || serde_json::json!({ "name": name }), result.is_ok());
+        result
+    }
+}
@@ -0,0 +1,132 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::lisp::METRICS;
+use crate::{OrganismState, RuleRegistry};
+
+/// Format tag written into every model definition
+const MODEL_FORMAT: &str = "ouroboros-model";
+
+/// Model definition layout version
+const MODEL_VERSION: u32 = 1;
+
+/// Metrics stored as whole numbers
+const INTEGER_METRICS: [&str; 2] = ["generation", "age"];
+
+/// Describe the built-in dynamics of `OrganismState::step`
+///
+/// This must be kept in sync with `step` whenever its equations change.
+fn dynamics(state: &OrganismState) -> serde_json::Value {
+    serde_json::json!({
+        "kind": "discrete-time",
+        "timeStep": "dt",
+        "solver": state.core_solver.as_str(),
+        "constants": {
+            "energyScale": 1000.0,
+            "consumptionPerIndividual": 0.1,
+            "energyRegeneration": 10.0,
+            "minPopulation": 1.0,
+            "maxEnergy": 10000.0,
+        },
+        "equations": [
+            { "metric": "age", "update": "age + 1" },
+            { "metric": "population", "derivative": "population * (energy / energyScale) * (1 - mutation_rate)", "bounds": ["minPopulation", null] },
+            { "metric": "energy", "derivative": "energyRegeneration - population * consumptionPerIndividual", "bounds": [0, "maxEnergy"] },
+            { "metric": "adaptation_score", "update": "(min(population / 100, 2) + min(energy / 1000, 1) + min(age / 100, 1)) / 3" },
+        ],
+        "order": ["age", "population", "energy", "adaptation_score"],
+    })
+}
+
+/// Export the structure of the model as a documented JSON document
+///
+/// The document (format `ouroboros-model`, version 1) holds:
+/// - `metrics`: every core metric with its rule-code name, type, whether rules
+///   may write it, its value in `state`, and its unit, precision and description
+/// - `parameters`: the evolution parameters taken from `state`
+/// - `dynamics`: the core equations `step` integrates each tick; `age` and
+///   `adaptation_score` are assigned, population and energy are given as
+///   derivatives integrated by `solver` and then clamped to `bounds`
+/// - `customMetrics` and `metricGroups`: user-defined metrics and dynamics
+/// - `rules`: rules in execution order with their read/write sets and modes
+/// - `modes`: mode names and predicates in evaluation order
+/// - `ruleGraph`: for each metric, an edge from every rule that writes it to
+///   every other rule that reads it
+///
+/// It describes the model, not a run: execution stats and history are left out.
+#[wasm_bindgen(js_name = exportModelDefinition)]
+pub fn export_model_definition(state: &OrganismState, registry: &RuleRegistry) -> Result<String, JsValue> {
+    let metrics: Vec<serde_json::Value> = METRICS
+        .iter()
+        .map(|spec| {
+            let meta = state.metric_meta_of(spec.canonical);
+            serde_json::json!({
+                "name": spec.canonical,
+                "ruleName": spec.name,
+                "type": if INTEGER_METRICS.contains(&spec.canonical) { "integer" } else { "number" },
+                "writable": spec.writable,
+                "initial": state.metric(spec.canonical),
+                "unit": meta.as_ref().map(|m| &m.unit),
+                "precision": meta.as_ref().map(|m| m.precision),
+                "description": meta.as_ref().map(|m| &m.description),
+            })
+        })
+        .collect();
+    
+    let rules: Vec<serde_json::Value> = registry.execution_order
+        .iter()
+        .filter_map(|id| registry.rules.get(id))
+        .map(|rule| serde_json::json!({
+            "id": rule.id,
+            "code": rule.lisp_code,
+            "doc": rule.doc,
+            "reads": rule.access.reads,
+            "writes": rule.access.writes,
+            "modes": rule.modes,
+        }))
+        .collect();
+    
+    let mut readers: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for id in &registry.execution_order {
+        if let Some(rule) = registry.rules.get(id) {
+            for metric in &rule.access.reads {
+                readers.entry(metric).or_default().push(id);
+            }
+        }
+    }
+    
+    let mut edges = Vec::new();
+    for id in &registry.execution_order {
+        if let Some(rule) = registry.rules.get(id) {
+            for metric in &rule.access.writes {
+                for reader in readers.get(metric).into_iter().flatten().filter(|r| **r != id) {
+                    edges.push(serde_json::json!({
+                        "from": id,
+                        "to": reader,
+                        "metric": metric,
+                    }));
+                }
+            }
+        }
+    }
+    
+    let definition = serde_json::json!({
+        "format": MODEL_FORMAT,
+        "version": MODEL_VERSION,
+        "metrics": metrics,
+        "parameters": {
+            "mutation_rate": state.mutation_rate,
+            "selection_pressure": state.selection_pressure,
+        },
+        "dynamics": dynamics(state),
+        "customMetrics": state.custom_metrics,
+        "metricGroups": serde_json::from_str::<serde_json::Value>(&state.get_dynamics()?)
+            .map_err(|e| JsValue::from_str(&format!("Failed to export model definition: {}", e)))?,
+        "rules": rules,
+        "modes": registry.modes,
+        "ruleGraph": edges,
+    });
+    
+    serde_json::to_string(&definition)
+        .map_err(|e| JsValue::from_str(&format!("Failed to export model definition: {}", e)))
+}
@@ -0,0 +1,156 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::{lisp, OrganismState, RuleRegistry};
+
+/// A named phase of behavior, active while its predicate holds
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct Mode {
+    name: String,
+    predicate: String,
+}
+
+impl Mode {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// The mode selected on the last tick and whether it differs from the one before
+pub(crate) struct ModeSelection {
+    pub mode: Option<String>,
+    pub changed: bool,
+}
+
+impl RuleRegistry {
+    /// Pick the active mode for `state`: the first defined mode whose predicate holds
+    pub(crate) fn select_mode(&mut self, state: &OrganismState) -> Result<ModeSelection, JsValue> {
+        let selected = self.evaluate_mode(state)?;
+        let changed = selected != self.active_mode;
+        self.active_mode = selected.clone();
+        Ok(ModeSelection { mode: selected, changed })
+    }
+}
+
+/// Mode names with repeats removed, keeping the first occurrence of each
+pub(crate) fn without_duplicates(modes: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    modes.into_iter().filter(|mode| seen.insert(mode.clone())).collect()
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Define or replace a mode
+    ///
+    /// `predicate` is Lisp code evaluated against the state, e.g.
+    /// `(< energy 200)`. Modes are checked in definition order and the first
+    /// whose predicate holds is active for the tick. Replacing a mode keeps
+    /// its position.
+    #[wasm_bindgen(js_name = defineMode)]
+    pub fn define_mode(&mut self, name: &str, predicate: &str) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Mode name cannot be empty"));
+        }
+        lisp::parse(predicate).map_err(|e| {
+            JsValue::from_str(&format!(
+                "Failed to parse predicate of mode {} at {}:{}: {}",
+                name, e.span.line, e.span.column, e.message
+            ))
+        })?;
+        
+        let mode = Mode {
+            name: name.to_string(),
+            predicate: predicate.to_string(),
+        };
+        match self.modes.iter_mut().find(|m| m.name == name) {
+            Some(existing) => *existing = mode,
+            None => self.modes.push(mode),
+        }
+        Ok(())
+    }
+    
+    /// Remove a mode and drop it from every rule's mode list
+    #[wasm_bindgen(js_name = removeMode)]
+    pub fn remove_mode(&mut self, name: &str) -> bool {
+        let before = self.modes.len();
+        self.modes.retain(|m| m.name != name);
+        if self.modes.len() == before {
+            return false;
+        }
+        
+        for rule in self.rules.values_mut() {
+            rule.modes.retain(|m| m != name);
+        }
+        if self.active_mode.as_deref() == Some(name) {
+            self.active_mode = None;
+        }
+        true
+    }
+    
+    /// Get all modes in evaluation order as JSON, with the rules belonging to each
+    #[wasm_bindgen(js_name = getModes)]
+    pub fn get_modes(&self) -> Result<String, JsValue> {
+        let modes: Vec<serde_json::Value> = self.modes
+            .iter()
+            .map(|mode| {
+                let rules: Vec<&String> = self.execution_order
+                    .iter()
+                    .filter(|id| self.rules.get(*id).is_some_and(|r| r.modes.contains(&mode.name)))
+                    .collect();
+                serde_json::json!({
+                    "name": mode.name,
+                    "predicate": mode.predicate,
+                    "rules": rules,
+                })
+            })
+            .collect();
+        
+        serde_json::to_string(&modes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize modes: {}", e)))
+    }
+    
+    /// Set the modes a rule belongs to
+    ///
+    /// A rule with no modes runs in every mode, including when no mode is
+    /// active. A rule with modes only runs while one of them is active.
+    #[wasm_bindgen(js_name = setRuleModes)]
+    pub fn set_rule_modes(&mut self, id: &str, modes: Vec<String>) -> Result<(), JsValue> {
+        if let Some(unknown) = modes.iter().find(|name| !self.modes.iter().any(|m| &m.name == *name)) {
+            return Err(JsValue::from_str(&format!("Mode not defined: {}", unknown)));
+        }
+        
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.modes = without_duplicates(modes);
+        Ok(())
+    }
+    
+    /// Get the modes a rule belongs to
+    #[wasm_bindgen(js_name = getRuleModes)]
+    pub fn get_rule_modes(&self, id: &str) -> Result<Vec<String>, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.modes.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+    
+    /// Get the mode that was active on the last tick (undefined when none was)
+    #[wasm_bindgen(js_name = getActiveMode)]
+    pub fn get_active_mode(&self) -> Option<String> {
+        self.active_mode.clone()
+    }
+    
+    /// Evaluate which mode `state` would activate, without running any rules
+    #[wasm_bindgen(js_name = evaluateMode)]
+    pub fn evaluate_mode(&self, state: &OrganismState) -> Result<Option<String>, JsValue> {
+        for mode in &self.modes {
+            let active = lisp::eval_predicate(&mode.predicate, state).map_err(|e| {
+                JsValue::from_str(&format!("Failed to evaluate predicate of mode {}: {}", mode.name, e))
+            })?;
+            if active {
+                return Ok(Some(mode.name.clone()));
+            }
+        }
+        Ok(None)
+    }
+}
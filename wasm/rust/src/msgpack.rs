@@ -0,0 +1,289 @@
+use serde_json::{Map, Number, Value};
+
+use crate::import_guard::{MAX_IMPORT_BYTES, MAX_IMPORT_DEPTH};
+
+/// MessagePack `float64` marker
+const FLOAT64: u8 = 0xcb;
+
+fn write_length(out: &mut Vec<u8>, len: usize, fix: u8, fix_limit: usize, markers: [u8; 3]) {
+    if len < fix_limit {
+        out.push(fix | len as u8);
+    } else if len <= u8::MAX as usize && markers[0] != 0 {
+        out.push(markers[0]);
+        out.push(len as u8);
+    } else if len <= u16::MAX as usize {
+        out.push(markers[1]);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(markers[2]);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+/// Write a string with the shortest length prefix
+pub(crate) fn write_str(out: &mut Vec<u8>, text: &str) {
+    write_length(out, text.len(), 0xa0, 32, [0xd9, 0xda, 0xdb]);
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// Write a map header for `len` key/value pairs to follow
+pub(crate) fn write_map_header(out: &mut Vec<u8>, len: usize) {
+    write_length(out, len, 0x80, 16, [0, 0xde, 0xdf]);
+}
+
+/// Write numbers as an array of `float64`s, so NaN and infinities survive
+pub(crate) fn write_f64_array(out: &mut Vec<u8>, values: &[f64]) {
+    write_length(out, values.len(), 0x90, 16, [0, 0xdc, 0xdd]);
+    for value in values {
+        out.push(FLOAT64);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn write_number(out: &mut Vec<u8>, number: &Number) {
+    if let Some(n) = number.as_u64() {
+        if n < 0x80 {
+            out.push(n as u8);
+        } else {
+            out.push(0xcf);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    } else if let Some(n) = number.as_i64() {
+        if n >= -32 {
+            out.push(n as i8 as u8);
+        } else {
+            out.push(0xd3);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    } else {
+        out.push(FLOAT64);
+        out.extend_from_slice(&number.as_f64().unwrap_or(f64::NAN).to_be_bytes());
+    }
+}
+
+/// Encode a JSON value as MessagePack
+pub(crate) fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => write_number(out, n),
+        Value::String(s) => write_str(out, s),
+        Value::Array(items) => {
+            write_length(out, items.len(), 0x90, 16, [0, 0xdc, 0xdd]);
+            items.iter().for_each(|item| write_value(out, item));
+        }
+        Value::Object(map) => {
+            write_map_header(out, map.len());
+            for (key, item) in map {
+                write_str(out, key);
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+/// Reads MessagePack back into JSON values
+///
+/// Only what `write_value` produces plus the other integer, float and
+/// string widths is accepted; binary and extension values are not.
+/// Floats JSON can't hold (NaN, infinities) read as null, except
+/// through `f64_array`.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(bytes: &'a [u8]) -> Result<Reader<'a>, String> {
+        if bytes.len() > MAX_IMPORT_BYTES {
+            return Err(format!("{} bytes is over the {} byte limit", bytes.len(), MAX_IMPORT_BYTES));
+        }
+        Ok(Reader { bytes, pos: 0 })
+    }
+    
+    /// Whether every byte has been read
+    pub(crate) fn done(&self) -> bool {
+        self.pos == self.bytes.len()
+    }
+    
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        if self.bytes.len() - self.pos < n {
+            return Err(format!("Unexpected end of data at byte {}", self.pos));
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+    
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+    
+    fn be<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut bytes = [0; N];
+        bytes.copy_from_slice(self.take(N)?);
+        Ok(bytes)
+    }
+    
+    fn text(&mut self, len: usize) -> Result<String, String> {
+        let at = self.pos;
+        String::from_utf8(self.take(len)?.to_vec()).map_err(|_| format!("Invalid UTF-8 in string at byte {}", at))
+    }
+    
+    /// Read a length for the marker's kind: 0 for strings, 1 for arrays, 2 for maps
+    fn length(&mut self, marker: u8, kind: usize) -> Result<Option<usize>, String> {
+        let len = match (kind, marker) {
+            (0, 0xa0..=0xbf) => (marker & 0x1f) as usize,
+            (0, 0xd9) => self.byte()? as usize,
+            (0, 0xda) | (1, 0xdc) | (2, 0xde) => u16::from_be_bytes(self.be()?) as usize,
+            (0, 0xdb) | (1, 0xdd) | (2, 0xdf) => u32::from_be_bytes(self.be()?) as usize,
+            (1, 0x90..=0x9f) | (2, 0x80..=0x8f) => (marker & 0x0f) as usize,
+            _ => return Ok(None),
+        };
+        // Every element takes at least a byte, which bounds lengths by what's left
+        if len > self.bytes.len() - self.pos {
+            return Err(format!("Length {} at byte {} runs past the end of data", len, self.pos));
+        }
+        Ok(Some(len))
+    }
+    
+    /// Read a map header and return how many pairs follow
+    pub(crate) fn map_header(&mut self) -> Result<usize, String> {
+        let marker = self.byte()?;
+        self.length(marker, 2)?.ok_or_else(|| format!("Expected a map at byte {}", self.pos - 1))
+    }
+    
+    /// Read a string, as map keys are
+    pub(crate) fn string(&mut self) -> Result<String, String> {
+        let marker = self.byte()?;
+        let len = self.length(marker, 0)?.ok_or_else(|| format!("Expected a string at byte {}", self.pos - 1))?;
+        self.text(len)
+    }
+    
+    /// Read an array of numbers, keeping floats JSON can't hold
+    pub(crate) fn f64_array(&mut self) -> Result<Vec<f64>, String> {
+        let marker = self.byte()?;
+        let len = self.length(marker, 1)?.ok_or_else(|| format!("Expected an array at byte {}", self.pos - 1))?;
+        (0..len)
+            .map(|_| {
+                let at = self.pos;
+                match self.byte()? {
+                    FLOAT64 => Ok(f64::from_be_bytes(self.be()?)),
+                    0xca => Ok(f32::from_be_bytes(self.be()?) as f64),
+                    _ => {
+                        self.pos = at;
+                        self.value(0)?.as_f64().ok_or_else(|| format!("Expected a number at byte {}", at))
+                    }
+                }
+            })
+            .collect()
+    }
+    
+    /// Read any value nested `depth` levels deep
+    pub(crate) fn value(&mut self, depth: usize) -> Result<Value, String> {
+        let at = self.pos;
+        let marker = self.byte()?;
+        let float = |f: f64| Number::from_f64(f).map_or(Value::Null, Value::Number);
+        Ok(match marker {
+            0x00..=0x7f => Value::from(marker),
+            0xe0..=0xff => Value::from(marker as i8),
+            0xc0 => Value::Null,
+            0xc2 => Value::Bool(false),
+            0xc3 => Value::Bool(true),
+            0xcc => Value::from(self.byte()?),
+            0xcd => Value::from(u16::from_be_bytes(self.be()?)),
+            0xce => Value::from(u32::from_be_bytes(self.be()?)),
+            0xcf => Value::from(u64::from_be_bytes(self.be()?)),
+            0xd0 => Value::from(self.byte()? as i8),
+            0xd1 => Value::from(i16::from_be_bytes(self.be()?)),
+            0xd2 => Value::from(i32::from_be_bytes(self.be()?)),
+            0xd3 => Value::from(i64::from_be_bytes(self.be()?)),
+            0xca => float(f32::from_be_bytes(self.be()?) as f64),
+            FLOAT64 => float(f64::from_be_bytes(self.be()?)),
+            _ => {
+                if let Some(len) = self.length(marker, 0)? {
+                    Value::String(self.text(len)?)
+                } else if depth >= MAX_IMPORT_DEPTH {
+                    return Err(format!("Data nests deeper than {} levels at byte {}", MAX_IMPORT_DEPTH, at));
+                } else if let Some(len) = self.length(marker, 1)? {
+                    Value::Array((0..len).map(|_| self.value(depth + 1)).collect::<Result<_, _>>()?)
+                } else if let Some(len) = self.length(marker, 2)? {
+                    let mut map = Map::new();
+                    for _ in 0..len {
+                        let key = self.string()?;
+                        map.insert(key, self.value(depth + 1)?);
+                    }
+                    Value::Object(map)
+                } else {
+                    return Err(format!("Unsupported MessagePack type 0x{:02x} at byte {}", marker, at));
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn round_trip(value: &Value) -> Value {
+        let mut out = Vec::new();
+        write_value(&mut out, value);
+        let mut reader = Reader::new(&out).unwrap();
+        let read = reader.value(0).unwrap();
+        assert!(reader.done());
+        read
+    }
+    
+    #[test]
+    fn values_round_trip() {
+        let value = serde_json::json!({
+            "null": null,
+            "flags": [true, false],
+            "small": [0, 127, -1, -32],
+            "large": [128, u64::MAX, -33, i64::MIN],
+            "floats": [0.5, -1.25e300],
+            "nested": { "empty": {}, "list": [[], [{}]] },
+            "unicode": "snow ☃",
+        });
+        assert_eq!(round_trip(&value), value);
+    }
+    
+    #[test]
+    fn lengths_round_trip_at_every_width() {
+        for len in [0, 15, 16, 31, 32, 255, 256, 65_535, 65_536] {
+            let text = Value::String("x".repeat(len));
+            assert_eq!(round_trip(&text), text);
+            let array = Value::Array(vec![Value::from(1); len]);
+            assert_eq!(round_trip(&array), array);
+            let map = Value::Object((0..len).map(|i| (i.to_string(), Value::Null)).collect());
+            assert_eq!(round_trip(&map), map);
+        }
+    }
+    
+    #[test]
+    fn f64_arrays_keep_floats_json_cannot_hold() {
+        let values = [1.5, f64::NAN, f64::INFINITY, f64::NEG_INFINITY, -0.0];
+        let mut out = Vec::new();
+        write_f64_array(&mut out, &values);
+        let read = Reader::new(&out).unwrap().f64_array().unwrap();
+        assert_eq!(read.len(), values.len());
+        for (read, written) in read.iter().zip(values) {
+            assert_eq!(read.to_bits(), written.to_bits());
+        }
+    }
+    
+    #[test]
+    fn malformed_data_is_rejected() {
+        // Truncated float64
+        assert!(Reader::new(&[FLOAT64, 0, 0]).unwrap().value(0).is_err());
+        // A 16-element array with only one element present
+        assert!(Reader::new(&[0xdc, 0x00, 0x10, 0x01]).unwrap().value(0).is_err());
+        // Binary values are unsupported
+        assert!(Reader::new(&[0xc4, 0x01, 0x00]).unwrap().value(0).is_err());
+        
+        let deep = vec![0x91; MAX_IMPORT_DEPTH + 1].into_iter().chain([0xc0]).collect::<Vec<u8>>();
+        let error = Reader::new(&deep).unwrap().value(0).unwrap_err();
+        assert!(error.contains("nests deeper"), "{}", error);
+    }
+}
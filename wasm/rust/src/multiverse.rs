@@ -0,0 +1,255 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::simulation::Simulation;
+use crate::{canonical_metric, OrganismState, CORE_METRICS};
+
+/// Metrics of one timeline after a tick
+struct Sample {
+    tick: u64,
+    metrics: BTreeMap<String, f64>,
+}
+
+impl Sample {
+    fn of(tick: u64, state: &OrganismState) -> Sample {
+        let mut metrics: BTreeMap<String, f64> = CORE_METRICS
+            .iter()
+            .filter_map(|metric| Some((metric.to_string(), state.metric(metric)?)))
+            .collect();
+        metrics.extend(state.custom_metrics.iter().map(|(name, value)| (name.clone(), *value)));
+        Sample { tick, metrics }
+    }
+}
+
+/// A named branch of the multiverse
+///
+/// Samples before the branch point live in the parent, so branching
+/// copies no history.
+struct Timeline {
+    simulation: Simulation,
+    parent: Option<String>,
+    branched_at: u64,
+    samples: Vec<Sample>,
+}
+
+/// Multiverse manages named timelines forked from each other
+///
+/// It starts with one timeline holding a fork of the given simulation.
+/// `branch` forks a timeline at its current tick (copy-on-write, see
+/// `Simulation.fork`); each timeline is then stepped on its own and
+/// records its metrics every tick, so trajectories can be read back and
+/// compared.
+#[wasm_bindgen]
+pub struct Multiverse {
+    timelines: BTreeMap<String, Timeline>,
+}
+
+impl Multiverse {
+    fn timeline(&self, name: &str) -> Result<&Timeline, JsValue> {
+        self.timelines.get(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Timeline not found: {}", name)))
+    }
+    
+    fn timeline_mut(&mut self, name: &str) -> Result<&mut Timeline, JsValue> {
+        self.timelines.get_mut(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Timeline not found: {}", name)))
+    }
+    
+    /// A timeline and its ancestors, each with the last tick of it in this line of descent
+    fn lineage<'a>(&'a self, name: &'a str) -> Result<Vec<(&'a str, u64)>, JsValue> {
+        let mut line = Vec::new();
+        let mut current = (name, u64::MAX);
+        loop {
+            let (name, until) = current;
+            let timeline = self.timeline(name)?;
+            line.push((name, until));
+            match &timeline.parent {
+                Some(parent) => current = (parent.as_str(), timeline.branched_at),
+                None => return Ok(line),
+            }
+        }
+    }
+    
+    /// `(tick, value)` of a metric over a timeline's whole history, ancestors included
+    fn trajectory_of(&self, name: &str, metric: &str) -> Result<Vec<(u64, f64)>, JsValue> {
+        let metric = canonical_metric(metric).unwrap_or(metric);
+        let mut points = Vec::new();
+        for (name, until) in self.lineage(name)?.into_iter().rev() {
+            for sample in self.timeline(name)?.samples.iter().take_while(|s| s.tick <= until) {
+                if let Some(value) = sample.metrics.get(metric) {
+                    points.push((sample.tick, *value));
+                }
+            }
+        }
+        Ok(points)
+    }
+    
+    /// Last tick two timelines share, or `None` when they have no common root
+    fn diverged_at(&self, a: &str, b: &str) -> Result<Option<u64>, JsValue> {
+        let line_b = self.lineage(b)?;
+        for (name, until_a) in self.lineage(a)? {
+            if let Some((_, until_b)) = line_b.iter().find(|(other, _)| *other == name) {
+                let shared = until_a.min(*until_b);
+                if shared == u64::MAX {
+                    return Ok(Some(self.timeline(a)?.simulation.ticks()));
+                }
+                return Ok(Some(shared));
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[wasm_bindgen]
+impl Multiverse {
+    /// Create a multiverse whose first timeline, `name`, is a fork of `simulation`
+    #[wasm_bindgen(constructor)]
+    pub fn new(name: &str, simulation: &Simulation) -> Result<Multiverse, JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Timeline name cannot be empty"));
+        }
+        let simulation = simulation.fork();
+        let samples = vec![Sample::of(simulation.ticks(), simulation.state())];
+        let mut timelines = BTreeMap::new();
+        timelines.insert(name.to_string(), Timeline { simulation, parent: None, branched_at: 0, samples });
+        Ok(Multiverse { timelines })
+    }
+    
+    /// Fork timeline `from` at its current tick into a new timeline `name`
+    #[wasm_bindgen]
+    pub fn branch(&mut self, from: &str, name: &str) -> Result<(), JsValue> {
+        if name.is_empty() {
+            return Err(JsValue::from_str("Timeline name cannot be empty"));
+        }
+        if self.timelines.contains_key(name) {
+            return Err(JsValue::from_str(&format!("Timeline already exists: {}", name)));
+        }
+        let parent = self.timeline(from)?;
+        let simulation = parent.simulation.fork();
+        let branched_at = simulation.ticks();
+        self.timelines.insert(name.to_string(), Timeline {
+            simulation,
+            parent: Some(from.to_string()),
+            branched_at,
+            samples: Vec::new(),
+        });
+        Ok(())
+    }
+    
+    /// Remove a timeline that has no branches of its own
+    #[wasm_bindgen]
+    pub fn remove(&mut self, name: &str) -> Result<(), JsValue> {
+        self.timeline(name)?;
+        if let Some((child, _)) = self.timelines.iter().find(|(_, t)| t.parent.as_deref() == Some(name)) {
+            return Err(JsValue::from_str(&format!("Timeline {} has branch {}", name, child)));
+        }
+        self.timelines.remove(name);
+        Ok(())
+    }
+    
+    /// Advance one timeline by a tick, returning its tick summary as JSON
+    #[wasm_bindgen]
+    pub fn tick(&mut self, name: &str, delta_time: f64) -> Result<String, JsValue> {
+        let timeline = self.timeline_mut(name)?;
+        let summary = timeline.simulation.tick(delta_time)?;
+        timeline.samples.push(Sample::of(timeline.simulation.ticks(), timeline.simulation.state()));
+        Ok(summary)
+    }
+    
+    /// Advance every timeline by a tick, in name order
+    #[wasm_bindgen(js_name = tickAll)]
+    pub fn tick_all(&mut self, delta_time: f64) -> Result<(), JsValue> {
+        let names: Vec<String> = self.timelines.keys().cloned().collect();
+        for name in names {
+            self.tick(&name, delta_time)?;
+        }
+        Ok(())
+    }
+    
+    /// Apply a rule to one timeline outside of a tick, e.g. right after branching
+    #[wasm_bindgen(js_name = applyRule)]
+    pub fn apply_rule(&mut self, name: &str, rule_id: &str, params: Vec<f64>) -> Result<f64, JsValue> {
+        self.timeline_mut(name)?.simulation.apply_rule(rule_id, params)
+    }
+    
+    /// Names of the timelines
+    #[wasm_bindgen(js_name = getTimelineNames)]
+    pub fn get_timeline_names(&self) -> Vec<String> {
+        self.timelines.keys().cloned().collect()
+    }
+    
+    /// Describe the timelines as JSON (`name`, `parent`, `branchedAt`, `ticks`)
+    #[wasm_bindgen(js_name = getTimelines)]
+    pub fn get_timelines(&self) -> Result<String, JsValue> {
+        let timelines: Vec<serde_json::Value> = self.timelines
+            .iter()
+            .map(|(name, timeline)| serde_json::json!({
+                "name": name,
+                "parent": timeline.parent,
+                "branchedAt": timeline.parent.as_ref().map(|_| timeline.branched_at),
+                "ticks": timeline.simulation.ticks(),
+            }))
+            .collect();
+        serde_json::to_string(&timelines)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize timelines: {}", e)))
+    }
+    
+    /// Get a fork of a timeline's simulation (changes to it don't affect the timeline)
+    #[wasm_bindgen(js_name = getSimulation)]
+    pub fn get_simulation(&self, name: &str) -> Result<Simulation, JsValue> {
+        Ok(self.timeline(name)?.simulation.fork())
+    }
+    
+    /// Get a copy of a timeline's current state
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state(&self, name: &str) -> Result<OrganismState, JsValue> {
+        Ok(self.timeline(name)?.simulation.get_state())
+    }
+    
+    /// Get a metric's trajectory over a timeline's history, from the first tick of its root
+    ///
+    /// Returns JSON `{ ticks: [...], values: [...] }`.
+    #[wasm_bindgen]
+    pub fn trajectory(&self, name: &str, metric: &str) -> Result<String, JsValue> {
+        let points = self.trajectory_of(name, metric)?;
+        let (ticks, values): (Vec<u64>, Vec<f64>) = points.into_iter().unzip();
+        serde_json::to_string(&serde_json::json!({ "ticks": ticks, "values": values }))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize trajectory: {}", e)))
+    }
+    
+    /// Compare a metric between two timelines over the ticks both have reached
+    ///
+    /// Returns JSON with `ticks`, the values `a` and `b`, their `difference`
+    /// (b - a), `maxAbsDifference`, `finalDifference` and `divergedAt`, the
+    /// last tick of their shared history (null for unrelated timelines).
+    #[wasm_bindgen]
+    pub fn compare(&self, a: &str, b: &str, metric: &str) -> Result<String, JsValue> {
+        let points_a = self.trajectory_of(a, metric)?;
+        let points_b: BTreeMap<u64, f64> = self.trajectory_of(b, metric)?.into_iter().collect();
+        
+        let mut ticks = Vec::new();
+        let mut values_a = Vec::new();
+        let mut values_b = Vec::new();
+        for (tick, value_a) in points_a {
+            if let Some(value_b) = points_b.get(&tick) {
+                ticks.push(tick);
+                values_a.push(value_a);
+                values_b.push(*value_b);
+            }
+        }
+        let difference: Vec<f64> = values_a.iter().zip(&values_b).map(|(a, b)| b - a).collect();
+        let max_abs = difference.iter().fold(0.0_f64, |max, d| max.max(d.abs()));
+        
+        serde_json::to_string(&serde_json::json!({
+            "metric": metric,
+            "ticks": ticks,
+            "a": values_a,
+            "b": values_b,
+            "difference": difference,
+            "maxAbsDifference": max_abs,
+            "finalDifference": difference.last(),
+            "divergedAt": self.diverged_at(a, b)?,
+        }))
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize comparison: {}", e)))
+    }
+}
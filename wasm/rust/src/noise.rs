@@ -0,0 +1,155 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::call_log;
+use crate::lisp;
+use crate::rng::Rng;
+use crate::OrganismState;
+
+/// Seed used when noise is added before `setNoiseSeed` is called
+const DEFAULT_NOISE_SEED: u64 = 0;
+
+/// Stochastic process driving a noise term
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+enum NoiseProcess {
+    /// White noise: the metric performs a random walk, `dx = sigma dW`
+    Wiener { sigma: f64 },
+    /// Colored noise: `dx = eta dt` with `d eta = -theta eta dt + sigma dW`
+    OrnsteinUhlenbeck { theta: f64, sigma: f64, eta: f64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct NoiseTerm {
+    metric: String,
+    #[serde(flatten)]
+    process: NoiseProcess,
+}
+
+/// Noise terms attached to metrics and the RNG driving them
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct NoiseModel {
+    rng: Rng,
+    terms: Vec<NoiseTerm>,
+}
+
+impl NoiseModel {
+    fn new(seed: u64) -> NoiseModel {
+        NoiseModel { rng: Rng::new(seed), terms: Vec::new() }
+    }
+}
+
+impl OrganismState {
+    /// Apply one step of every noise term
+    ///
+    /// Noise is split from the deterministic dynamics: the solver advances
+    /// the drift, then each term adds its stochastic increment for the same
+    /// `dt` (Euler-Maruyama for Wiener terms, the exact update for
+    /// Ornstein-Uhlenbeck terms), so results don't depend on the solver's
+    /// internal stages.
+    pub(crate) fn apply_noise(&mut self, dt: f64) {
+        let mut model = match self.noise.take() {
+            Some(model) => model,
+            None => return,
+        };
+        
+        for term in &mut model.terms {
+            let increment = match &mut term.process {
+                NoiseProcess::Wiener { sigma } => *sigma * dt.sqrt() * model.rng.next_normal(),
+                NoiseProcess::OrnsteinUhlenbeck { theta, sigma, eta } => {
+                    let decay = (-*theta * dt).exp();
+                    let spread = *sigma * ((1.0 - decay * decay) / (2.0 * *theta)).sqrt();
+                    *eta = *eta * decay + spread * model.rng.next_normal();
+                    *eta * dt
+                }
+            };
+            if let Some(value) = self.read_any_metric(&term.metric) {
+                self.write_any_metric(&term.metric, value + increment);
+            }
+        }
+        
+        self.noise = Some(model);
+    }
+    
+    fn add_noise_term(&mut self, metric: &str, process: NoiseProcess) -> Result<(), JsValue> {
+        let metric = self.writable_metric(metric).map_err(|e| JsValue::from_str(&e))?;
+        let model = self.noise.get_or_insert_with(|| NoiseModel::new(DEFAULT_NOISE_SEED));
+        let term = NoiseTerm { metric, process };
+        match model.terms.iter_mut().find(|t| t.metric == term.metric) {
+            Some(existing) => *existing = term,
+            None => model.terms.push(term),
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Seed the RNG driving noise terms (terms already added are kept)
+    #[wasm_bindgen(js_name = setNoiseSeed)]
+    pub fn set_noise_seed(&mut self, seed: u64) {
+        let _call = call_log::enter("setNoiseSeed", || serde_json::json!({ "seed": seed }));
+        match &mut self.noise {
+            Some(model) => model.rng = Rng::new(seed),
+            None => self.noise = Some(NoiseModel::new(seed)),
+        }
+    }
+    
+    /// Add white noise to a metric, replacing any noise term it had
+    ///
+    /// Each `step(dt)` adds `sigma * sqrt(dt) * N(0, 1)`, so the metric's
+    /// variance grows by `sigma^2` per unit of time.
+    #[wasm_bindgen(js_name = addWienerNoise)]
+    pub fn add_wiener_noise(&mut self, metric: &str, sigma: f64) -> Result<(), JsValue> {
+        if !sigma.is_finite() || sigma < 0.0 {
+            return Err(JsValue::from_str("Noise sigma must be a non-negative number"));
+        }
+        self.add_noise_term(metric, NoiseProcess::Wiener { sigma })
+    }
+    
+    /// Add Ornstein-Uhlenbeck (colored) noise to a metric, replacing any noise term it had
+    ///
+    /// The metric drifts by a fluctuating rate that reverts to zero at rate
+    /// `theta` with stationary standard deviation `sigma / sqrt(2 theta)`,
+    /// giving smooth, correlated fluctuations instead of white jitter.
+    #[wasm_bindgen(js_name = addOuNoise)]
+    pub fn add_ou_noise(&mut self, metric: &str, theta: f64, sigma: f64) -> Result<(), JsValue> {
+        if !theta.is_finite() || theta <= 0.0 {
+            return Err(JsValue::from_str("Noise theta must be a positive number"));
+        }
+        if !sigma.is_finite() || sigma < 0.0 {
+            return Err(JsValue::from_str("Noise sigma must be a non-negative number"));
+        }
+        self.add_noise_term(metric, NoiseProcess::OrnsteinUhlenbeck { theta, sigma, eta: 0.0 })
+    }
+    
+    /// Remove the noise term of a metric
+    #[wasm_bindgen(js_name = removeNoise)]
+    pub fn remove_noise(&mut self, metric: &str) -> bool {
+        let metric = lisp::lookup_metric(metric).map_or(metric, |core| core.canonical);
+        match &mut self.noise {
+            Some(model) => {
+                let before = model.terms.len();
+                model.terms.retain(|t| t.metric != metric);
+                model.terms.len() != before
+            }
+            None => false,
+        }
+    }
+    
+    /// Remove every noise term
+    #[wasm_bindgen(js_name = clearNoise)]
+    pub fn clear_noise(&mut self) {
+        if let Some(model) = &mut self.noise {
+            model.terms.clear();
+        }
+    }
+    
+    /// Get the noise terms as JSON (`metric`, `kind`, and the process parameters)
+    #[wasm_bindgen(js_name = getNoise)]
+    pub fn get_noise(&self) -> Result<String, JsValue> {
+        let terms = self.noise.as_ref().map(|model| model.terms.as_slice()).unwrap_or_default();
+        serde_json::to_string(terms)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize noise terms: {}", e)))
+    }
+}
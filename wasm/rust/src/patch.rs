@@ -0,0 +1,92 @@
+use wasm_bindgen::prelude::*;
+
+use crate::call_log;
+use crate::config::check_core_value;
+use crate::import_guard;
+use crate::{canonical_metric, OrganismState};
+
+impl OrganismState {
+    /// Apply one field of a patch, returning the problem if it is rejected
+    fn patch_field(&mut self, key: &str, value: &serde_json::Value) -> Result<(), String> {
+        let number = || value.as_f64().ok_or_else(|| format!("{} must be a number", key));
+        match canonical_metric(key) {
+            Some("population") => {
+                let v = number()?;
+                check_core_value("population", v)?;
+                self.population = v;
+            }
+            Some("energy") => {
+                let v = number()?;
+                check_core_value("energy", v)?;
+                self.energy = v;
+            }
+            Some("generation") => {
+                self.generation = value.as_u64()
+                    .ok_or_else(|| format!("generation must be a non-negative integer (got {})", value))?;
+            }
+            Some("mutation_rate") => {
+                let v = number()?;
+                check_core_value("mutationRate", v)?;
+                self.mutation_rate = v;
+            }
+            Some("selection_pressure") => {
+                let v = number()?;
+                check_core_value("selectionPressure", v)?;
+                self.selection_pressure = v;
+            }
+            Some(_) => return Err(format!("{} is read-only", key)),
+            None if key == "custom" => {
+                let custom = value.as_object().ok_or("custom must be an object")?;
+                let mut errors = Vec::new();
+                for (name, value) in custom {
+                    match (self.custom_metrics.get_mut(name), value.as_f64()) {
+                        (None, _) => errors.push(format!("Custom metric not defined: {}", name)),
+                        (Some(_), Some(v)) if !v.is_finite() => {
+                            errors.push(format!("Custom metric {} must be a finite number (got {})", name, v));
+                        }
+                        (Some(slot), Some(v)) => *slot = v,
+                        (Some(_), None) => errors.push(format!("Custom metric {} must be a number", name)),
+                    }
+                }
+                if !errors.is_empty() {
+                    return Err(errors.join("; "));
+                }
+            }
+            None => return Err(format!("Unknown field: {}", key)),
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Update several fields at once, all or nothing
+    ///
+    /// `json` is an object with any of `population`, `energy`, `generation`,
+    /// `mutationRate` and `selectionPressure` (snake_case also accepted)
+    /// plus `custom: { name: value }` for defined custom metrics. Unlike the
+    /// setters, values are validated rather than clamped, with the ranges of
+    /// `OrganismConfig`. If any field is rejected the state is left
+    /// untouched and the error lists every problem.
+    #[wasm_bindgen(js_name = patchState)]
+    pub fn patch_state(&mut self, json: &str) -> Result<(), JsValue> {
+        let _call = call_log::enter("patchState", || serde_json::json!({ "json": call_log::text(json) }));
+        import_guard::check_structure("Patch", json, true).map_err(|e| JsValue::from_str(&e))?;
+        let patch: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse patch: {}", e)))?;
+        let fields = patch.as_object().ok_or_else(|| JsValue::from_str("Patch must be a JSON object"))?;
+        
+        let mut patched = self.clone();
+        let errors: Vec<String> = fields
+            .iter()
+            .filter_map(|(key, value)| patched.patch_field(key, value).err())
+            .collect();
+        if !errors.is_empty() {
+            return Err(JsValue::from_str(&format!("Invalid state patch: {}", errors.join("; "))));
+        }
+        
+        patched.sync_state_vector();
+        *self = patched;
+        Ok(())
+    }
+}
@@ -0,0 +1,276 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::hash::fnv1a64_hex;
+use crate::{ColonySystem, OrganismState, RuleRegistry, TerritoryMap};
+
+/// Format tag written into every chunk manifest
+const CHUNK_FORMAT: &str = "ouroboros-chunks";
+
+/// Manifest layout version
+const CHUNK_FORMAT_VERSION: u32 = 1;
+
+/// Chunk size used when the caller passes 0
+///
+/// IndexedDB handles values of a few hundred KiB comfortably; much larger
+/// values make individual puts and gets slow and memory-hungry.
+const DEFAULT_CHUNK_BYTES: usize = 256 * 1024;
+
+/// Smallest chunk size accepted, to keep manifests from exploding
+const MIN_CHUNK_BYTES: usize = 1024;
+
+/// One chunk as listed in a manifest
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ChunkEntry {
+    key: String,
+    bytes: usize,
+    hash: String,
+}
+
+/// Describes how to reassemble a chunked payload
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ChunkManifest {
+    format: String,
+    version: u32,
+    kind: String,
+    total_bytes: usize,
+    hash: String,
+    chunks: Vec<ChunkEntry>,
+}
+
+/// A payload split into keyed binary chunks plus a manifest
+///
+/// Chunk keys are derived from chunk contents, so a chunk that didn't change
+/// since the last save has the same key and doesn't need to be written again.
+#[wasm_bindgen]
+pub struct ChunkSet {
+    manifest: ChunkManifest,
+    data: Vec<Vec<u8>>,
+}
+
+impl ChunkSet {
+    fn split(kind: &str, payload: &[u8], max_chunk_bytes: usize) -> Result<ChunkSet, JsValue> {
+        let chunk_bytes = match max_chunk_bytes {
+            0 => DEFAULT_CHUNK_BYTES,
+            n if n < MIN_CHUNK_BYTES => {
+                return Err(JsValue::from_str(&format!("maxChunkBytes must be at least {}", MIN_CHUNK_BYTES)));
+            }
+            n => n,
+        };
+        
+        let data: Vec<Vec<u8>> = payload.chunks(chunk_bytes).map(<[u8]>::to_vec).collect();
+        let chunks = data
+            .iter()
+            .map(|chunk| {
+                let hash = fnv1a64_hex(chunk);
+                ChunkEntry {
+                    key: format!("{}-{}", kind, hash),
+                    bytes: chunk.len(),
+                    hash,
+                }
+            })
+            .collect();
+        
+        Ok(ChunkSet {
+            manifest: ChunkManifest {
+                format: CHUNK_FORMAT.to_string(),
+                version: CHUNK_FORMAT_VERSION,
+                kind: kind.to_string(),
+                total_bytes: payload.len(),
+                hash: fnv1a64_hex(payload),
+                chunks,
+            },
+            data,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl ChunkSet {
+    /// Get the manifest as JSON; store it under its own key next to the chunks
+    #[wasm_bindgen(js_name = getManifest)]
+    pub fn get_manifest(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.manifest)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize chunk manifest: {}", e)))
+    }
+    
+    /// Number of chunks
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.data.len()
+    }
+    
+    /// Get the storage keys of all chunks in order
+    #[wasm_bindgen]
+    pub fn keys(&self) -> Vec<String> {
+        self.manifest.chunks.iter().map(|c| c.key.clone()).collect()
+    }
+    
+    /// Get the storage key of a chunk
+    #[wasm_bindgen(js_name = keyAt)]
+    pub fn key_at(&self, index: usize) -> Option<String> {
+        self.manifest.chunks.get(index).map(|c| c.key.clone())
+    }
+    
+    /// Get the bytes of a chunk
+    #[wasm_bindgen(js_name = chunkAt)]
+    pub fn chunk_at(&self, index: usize) -> Option<Vec<u8>> {
+        self.data.get(index).cloned()
+    }
+}
+
+/// Collects chunks for a manifest as they are read back from storage
+#[wasm_bindgen]
+pub struct ChunkLoader {
+    manifest: ChunkManifest,
+    received: HashMap<String, Vec<u8>>,
+}
+
+#[wasm_bindgen]
+impl ChunkLoader {
+    /// Create a loader for a manifest produced by `persistChunks`
+    #[wasm_bindgen(constructor)]
+    pub fn new(manifest_json: &str) -> Result<ChunkLoader, JsValue> {
+        let manifest: ChunkManifest = serde_json::from_str(manifest_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse chunk manifest: {}", e)))?;
+        
+        if manifest.format != CHUNK_FORMAT {
+            return Err(JsValue::from_str(&format!("Unsupported chunk format: {}", manifest.format)));
+        }
+        if manifest.version > CHUNK_FORMAT_VERSION {
+            return Err(JsValue::from_str(&format!("Unsupported chunk format version: {}", manifest.version)));
+        }
+        
+        Ok(ChunkLoader {
+            manifest,
+            received: HashMap::new(),
+        })
+    }
+    
+    /// Kind of payload the manifest describes ("organism-state", "rule-registry", ...)
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        self.manifest.kind.clone()
+    }
+    
+    /// Add a chunk read back from storage, verifying its contents
+    #[wasm_bindgen(js_name = addChunk)]
+    pub fn add_chunk(&mut self, key: &str, bytes: Vec<u8>) -> Result<(), JsValue> {
+        let entry = self.manifest.chunks.iter().find(|c| c.key == key)
+            .ok_or_else(|| JsValue::from_str(&format!("Chunk not in manifest: {}", key)))?;
+        
+        if bytes.len() != entry.bytes || fnv1a64_hex(&bytes) != entry.hash {
+            return Err(JsValue::from_str(&format!("Chunk {} is corrupt", key)));
+        }
+        
+        self.received.insert(key.to_string(), bytes);
+        Ok(())
+    }
+    
+    /// Get the keys of chunks that still have to be added
+    #[wasm_bindgen(js_name = missingKeys)]
+    pub fn missing_keys(&self) -> Vec<String> {
+        let mut missing: Vec<String> = Vec::new();
+        for entry in &self.manifest.chunks {
+            if !self.received.contains_key(&entry.key) && !missing.contains(&entry.key) {
+                missing.push(entry.key.clone());
+            }
+        }
+        missing
+    }
+    
+    /// Whether every chunk has been added
+    #[wasm_bindgen(js_name = isComplete)]
+    pub fn is_complete(&self) -> bool {
+        self.manifest.chunks.iter().all(|c| self.received.contains_key(&c.key))
+    }
+}
+
+impl ChunkLoader {
+    /// Reassemble and verify the payload, checking it is of the expected kind
+    fn assemble(&self, kind: &str) -> Result<String, JsValue> {
+        if self.manifest.kind != kind {
+            return Err(JsValue::from_str(&format!(
+                "Chunks hold a {}, not a {}",
+                self.manifest.kind, kind
+            )));
+        }
+        
+        let mut payload = Vec::with_capacity(self.manifest.total_bytes);
+        for entry in &self.manifest.chunks {
+            let chunk = self.received.get(&entry.key)
+                .ok_or_else(|| JsValue::from_str(&format!("Missing chunk: {}", entry.key)))?;
+            payload.extend_from_slice(chunk);
+        }
+        
+        if payload.len() != self.manifest.total_bytes || fnv1a64_hex(&payload) != self.manifest.hash {
+            return Err(JsValue::from_str("Reassembled payload does not match the manifest"));
+        }
+        
+        String::from_utf8(payload)
+            .map_err(|e| JsValue::from_str(&format!("Failed to decode chunks: {}", e)))
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Split the state into keyed chunks of at most `max_chunk_bytes` (0 for the default)
+    #[wasm_bindgen(js_name = persistChunks)]
+    pub fn persist_chunks(&self, max_chunk_bytes: usize) -> Result<ChunkSet, JsValue> {
+        ChunkSet::split("organism-state", self.get_snapshot()?.as_bytes(), max_chunk_bytes)
+    }
+    
+    /// Restore the state from a complete chunk loader
+    #[wasm_bindgen(js_name = loadChunks)]
+    pub fn load_chunks(&mut self, loader: &ChunkLoader) -> Result<(), JsValue> {
+        self.load_snapshot(&loader.assemble("organism-state")?)
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Split the registry into keyed chunks of at most `max_chunk_bytes` (0 for the default)
+    #[wasm_bindgen(js_name = persistChunks)]
+    pub fn persist_chunks(&self, max_chunk_bytes: usize) -> Result<ChunkSet, JsValue> {
+        ChunkSet::split("rule-registry", self.export_registry()?.as_bytes(), max_chunk_bytes)
+    }
+    
+    /// Restore the registry from a complete chunk loader
+    #[wasm_bindgen(js_name = loadChunks)]
+    pub fn load_chunks(&mut self, loader: &ChunkLoader) -> Result<(), JsValue> {
+        self.import_registry(&loader.assemble("rule-registry")?)
+    }
+}
+
+#[wasm_bindgen]
+impl TerritoryMap {
+    /// Split the map into keyed chunks of at most `max_chunk_bytes` (0 for the default)
+    #[wasm_bindgen(js_name = persistChunks)]
+    pub fn persist_chunks(&self, max_chunk_bytes: usize) -> Result<ChunkSet, JsValue> {
+        ChunkSet::split("territory-map", self.get_snapshot()?.as_bytes(), max_chunk_bytes)
+    }
+    
+    /// Restore the map from a complete chunk loader
+    #[wasm_bindgen(js_name = loadChunks)]
+    pub fn load_chunks(&mut self, loader: &ChunkLoader) -> Result<(), JsValue> {
+        self.load_snapshot(&loader.assemble("territory-map")?)
+    }
+}
+
+#[wasm_bindgen]
+impl ColonySystem {
+    /// Split the colonies into keyed chunks of at most `max_chunk_bytes` (0 for the default)
+    #[wasm_bindgen(js_name = persistChunks)]
+    pub fn persist_chunks(&self, max_chunk_bytes: usize) -> Result<ChunkSet, JsValue> {
+        ChunkSet::split("colony-system", self.get_snapshot()?.as_bytes(), max_chunk_bytes)
+    }
+    
+    /// Restore the colonies from a complete chunk loader
+    #[wasm_bindgen(js_name = loadChunks)]
+    pub fn load_chunks(&mut self, loader: &ChunkLoader) -> Result<(), JsValue> {
+        self.load_snapshot(&loader.assemble("colony-system")?)
+    }
+}
@@ -0,0 +1,319 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+
+use crate::rng::Rng;
+
+/// Use direct inversion for Poisson means below this, PTRS rejection above
+const POISSON_INVERSION_LIMIT: f64 = 10.0;
+
+/// Binomial trials at or below this are sampled by inversion
+const BINOMIAL_INVERSION_LIMIT: u32 = 64;
+
+/// Natural log of the gamma function (Lanczos approximation, g = 7)
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    
+    if x < 0.5 {
+        return (PI / (PI * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let mut sum = COEFFICIENTS[0];
+    for (i, c) in COEFFICIENTS.iter().enumerate().skip(1) {
+        sum += c / (x + i as f64);
+    }
+    let t = x + 7.5;
+    0.5 * (2.0 * PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+impl Rng {
+    /// Standard normal value (Box-Muller)
+    pub fn next_normal(&mut self) -> f64 {
+        let u1 = 1.0 - self.next_f64();
+        let u2 = self.next_f64();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+    
+    /// Exponential value with rate 1
+    pub fn next_exponential(&mut self) -> f64 {
+        -(1.0 - self.next_f64()).ln()
+    }
+    
+    /// Gamma value with the given shape and scale 1 (Marsaglia-Tsang)
+    pub fn next_gamma(&mut self, shape: f64) -> f64 {
+        if shape < 1.0 {
+            // Boost to shape + 1 and scale back down
+            let u = 1.0 - self.next_f64();
+            return self.next_gamma(shape + 1.0) * u.powf(1.0 / shape);
+        }
+        
+        let d = shape - 1.0 / 3.0;
+        let c = 1.0 / (9.0 * d).sqrt();
+        loop {
+            let x = self.next_normal();
+            let v = 1.0 + c * x;
+            if v <= 0.0 {
+                continue;
+            }
+            let v = v * v * v;
+            let u = 1.0 - self.next_f64();
+            if u.ln() < 0.5 * x * x + d - d * v + d * v.ln() {
+                return d * v;
+            }
+        }
+    }
+    
+    /// Beta value
+    pub fn next_beta(&mut self, alpha: f64, beta: f64) -> f64 {
+        let x = self.next_gamma(alpha);
+        let y = self.next_gamma(beta);
+        x / (x + y)
+    }
+    
+    /// Poisson count with mean `lambda`
+    pub fn next_poisson(&mut self, lambda: f64) -> u64 {
+        if lambda <= 0.0 {
+            return 0;
+        }
+        if lambda < POISSON_INVERSION_LIMIT {
+            // Multiply uniforms until the product drops below e^-lambda
+            let limit = (-lambda).exp();
+            let mut count = 0;
+            let mut product = self.next_f64();
+            while product > limit {
+                count += 1;
+                product *= self.next_f64();
+            }
+            return count;
+        }
+        
+        // Transformed rejection with squeeze (Hörmann's PTRS)
+        let slam = lambda.sqrt();
+        let loglam = lambda.ln();
+        let b = 0.931 + 2.53 * slam;
+        let a = -0.059 + 0.02483 * b;
+        let inv_alpha = 1.1239 + 1.1328 / (b - 3.4);
+        let vr = 0.9277 - 3.6224 / (b - 2.0);
+        loop {
+            let u = self.next_f64() - 0.5;
+            let v = self.next_f64();
+            let us = 0.5 - u.abs();
+            let k = ((2.0 * a / us + b) * u + lambda + 0.43).floor();
+            if us >= 0.07 && v <= vr {
+                return k as u64;
+            }
+            if k < 0.0 || (us < 0.013 && v > us) {
+                continue;
+            }
+            if v.ln() + inv_alpha.ln() - (a / (us * us) + b).ln() <= -lambda + k * loglam - ln_gamma(k + 1.0) {
+                return k as u64;
+            }
+        }
+    }
+    
+    /// Binomial count of successes in `trials` with probability `p`
+    pub fn next_binomial(&mut self, trials: u32, p: f64) -> u32 {
+        if p <= 0.0 || trials == 0 {
+            return 0;
+        }
+        if p >= 1.0 {
+            return trials;
+        }
+        if trials <= BINOMIAL_INVERSION_LIMIT {
+            return (0..trials).filter(|_| self.next_f64() < p).count() as u32;
+        }
+        
+        // Split on the median order statistic, which is beta distributed
+        let k = trials.div_ceil(2);
+        let x = self.next_beta(k as f64, (trials + 1 - k) as f64);
+        if x < p {
+            k + self.next_binomial(trials - k, (p - x) / (1.0 - x))
+        } else {
+            self.next_binomial(k - 1, p / x)
+        }
+    }
+}
+
+fn check(valid: bool, message: &str) -> Result<(), JsValue> {
+    if valid {
+        Ok(())
+    } else {
+        Err(JsValue::from_str(message))
+    }
+}
+
+fn check_normal(sd: f64) -> Result<(), JsValue> {
+    check(sd.is_finite() && sd >= 0.0, "Standard deviation must be a non-negative number")
+}
+
+fn check_rate(rate: f64) -> Result<(), JsValue> {
+    check(rate.is_finite() && rate > 0.0, "Rate must be a positive number")
+}
+
+fn check_lambda(lambda: f64) -> Result<(), JsValue> {
+    check(lambda.is_finite() && lambda >= 0.0, "Poisson mean must be a non-negative number")
+}
+
+fn check_probability(p: f64) -> Result<(), JsValue> {
+    check((0.0..=1.0).contains(&p), "Probability must be between 0 and 1")
+}
+
+fn check_shapes(alpha: f64, beta: f64) -> Result<(), JsValue> {
+    check(
+        alpha.is_finite() && beta.is_finite() && alpha > 0.0 && beta > 0.0,
+        "Beta shape parameters must be positive numbers",
+    )
+}
+
+/// RandomToolkit samples common distributions from a seeded RNG
+///
+/// It uses the engine's own generator, so a toolkit created with the same
+/// seed produces the same numbers on every platform. Each sampler has a
+/// `fill*` variant that writes a whole Float64Array at once.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RandomToolkit {
+    rng: Rng,
+}
+
+#[wasm_bindgen]
+impl RandomToolkit {
+    /// Create a toolkit with a seeded RNG
+    #[wasm_bindgen(constructor)]
+    pub fn new(seed: u64) -> RandomToolkit {
+        RandomToolkit { rng: Rng::new(seed) }
+    }
+    
+    /// Restart the generator from `seed`, as if the toolkit were new
+    #[wasm_bindgen(js_name = setSeed)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+    
+    /// Uniform value in [0, 1)
+    #[wasm_bindgen]
+    pub fn uniform(&mut self) -> f64 {
+        self.rng.next_f64()
+    }
+    
+    /// Normal value
+    #[wasm_bindgen]
+    pub fn normal(&mut self, mean: f64, sd: f64) -> Result<f64, JsValue> {
+        check_normal(sd)?;
+        Ok(mean + sd * self.rng.next_normal())
+    }
+    
+    /// Exponential value with the given rate (mean `1 / rate`)
+    #[wasm_bindgen]
+    pub fn exponential(&mut self, rate: f64) -> Result<f64, JsValue> {
+        check_rate(rate)?;
+        Ok(self.rng.next_exponential() / rate)
+    }
+    
+    /// Poisson count with mean `lambda`
+    #[wasm_bindgen]
+    pub fn poisson(&mut self, lambda: f64) -> Result<f64, JsValue> {
+        check_lambda(lambda)?;
+        Ok(self.rng.next_poisson(lambda) as f64)
+    }
+    
+    /// Number of successes in `trials` with success probability `p`
+    #[wasm_bindgen]
+    pub fn binomial(&mut self, trials: u32, p: f64) -> Result<u32, JsValue> {
+        check_probability(p)?;
+        Ok(self.rng.next_binomial(trials, p))
+    }
+    
+    /// Beta value in [0, 1]
+    #[wasm_bindgen]
+    pub fn beta(&mut self, alpha: f64, beta: f64) -> Result<f64, JsValue> {
+        check_shapes(alpha, beta)?;
+        Ok(self.rng.next_beta(alpha, beta))
+    }
+    
+    /// Fill an array with uniform values in [0, 1)
+    #[wasm_bindgen(js_name = fillUniform)]
+    pub fn fill_uniform(&mut self, out: &mut [f64]) {
+        for value in out.iter_mut() {
+            *value = self.rng.next_f64();
+        }
+    }
+    
+    /// Fill an array with normal values
+    #[wasm_bindgen(js_name = fillNormal)]
+    pub fn fill_normal(&mut self, out: &mut [f64], mean: f64, sd: f64) -> Result<(), JsValue> {
+        check_normal(sd)?;
+        for value in out.iter_mut() {
+            *value = mean + sd * self.rng.next_normal();
+        }
+        Ok(())
+    }
+    
+    /// Fill an array with exponential values
+    #[wasm_bindgen(js_name = fillExponential)]
+    pub fn fill_exponential(&mut self, out: &mut [f64], rate: f64) -> Result<(), JsValue> {
+        check_rate(rate)?;
+        for value in out.iter_mut() {
+            *value = self.rng.next_exponential() / rate;
+        }
+        Ok(())
+    }
+    
+    /// Fill an array with Poisson counts
+    #[wasm_bindgen(js_name = fillPoisson)]
+    pub fn fill_poisson(&mut self, out: &mut [f64], lambda: f64) -> Result<(), JsValue> {
+        check_lambda(lambda)?;
+        for value in out.iter_mut() {
+            *value = self.rng.next_poisson(lambda) as f64;
+        }
+        Ok(())
+    }
+    
+    /// Fill an array with binomial counts
+    #[wasm_bindgen(js_name = fillBinomial)]
+    pub fn fill_binomial(&mut self, out: &mut [f64], trials: u32, p: f64) -> Result<(), JsValue> {
+        check_probability(p)?;
+        for value in out.iter_mut() {
+            *value = self.rng.next_binomial(trials, p) as f64;
+        }
+        Ok(())
+    }
+    
+    /// Fill an array with beta values
+    #[wasm_bindgen(js_name = fillBeta)]
+    pub fn fill_beta(&mut self, out: &mut [f64], alpha: f64, beta: f64) -> Result<(), JsValue> {
+        check_shapes(alpha, beta)?;
+        for value in out.iter_mut() {
+            *value = self.rng.next_beta(alpha, beta);
+        }
+        Ok(())
+    }
+    
+    /// Get the RNG state as JSON, to resume the same stream later
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize RNG state: {}", e)))
+    }
+    
+    /// Restore an RNG state produced by `getState`
+    ///
+    /// The all-zero state is rejected, since the generator would only
+    /// return 0 from it.
+    #[wasm_bindgen(js_name = loadState)]
+    pub fn load_state(&mut self, state_json: &str) -> Result<(), JsValue> {
+        *self = serde_json::from_str(state_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse RNG state: {}", e)))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+use wasm_bindgen::prelude::*;
+use serde::de::DeserializeOwned;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{integrity, lisp, migration, OrganismState, RuleRegistry, CORE_METRICS};
+use crate::{Goal, InstalledBundle, Invariant, Mode, Rule};
+
+/// What a lenient load kept and what it had to reset
+#[derive(Default)]
+struct RecoveryReport {
+    repaired: bool,
+    recovered: Vec<String>,
+    reset: Vec<serde_json::Value>,
+    warnings: Vec<String>,
+}
+
+impl RecoveryReport {
+    fn reset(&mut self, field: &str, reason: impl Into<String>) {
+        self.reset.push(serde_json::json!({ "field": field, "reason": reason.into() }));
+    }
+    
+    fn to_json(&self) -> Result<String, JsValue> {
+        let report = serde_json::json!({
+            "repaired": self.repaired,
+            "recovered": self.recovered,
+            "reset": self.reset,
+            "warnings": self.warnings,
+        });
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize recovery report: {}", e)))
+    }
+}
+
+/// Close a JSON document that was cut off partway through
+///
+/// The text is cut back to the last point where a complete value ended
+/// inside its container, then the open containers are closed. Returns `None`
+/// when no such point exists.
+fn repair_truncated(text: &str) -> Option<String> {
+    let mut stack: Vec<char> = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut safe: Option<(usize, Vec<char>)> = None;
+    
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                stack.push(c);
+                safe = Some((i + 1, stack.clone()));
+            }
+            '}' | ']' => {
+                stack.pop();
+                safe = Some((i + 1, stack.clone()));
+            }
+            ',' => safe = Some((i, stack.clone())),
+            _ => {}
+        }
+    }
+    
+    let (cut, open) = safe?;
+    let mut repaired = text[..cut].to_string();
+    for c in open.iter().rev() {
+        repaired.push(if *c == '{' { '}' } else { ']' });
+    }
+    Some(repaired)
+}
+
+/// Parse damaged JSON as far as possible, note a checksum mismatch, then run host migrations if they apply
+fn parse_lenient(kind: &str, text: &str, report: &mut RecoveryReport) -> serde_json::Value {
+    let mut value = match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value) => value,
+        Err(e) => {
+            report.warnings.push(format!("Snapshot is not valid JSON: {}", e));
+            match repair_truncated(text).and_then(|t| serde_json::from_str(&t).ok()) {
+                Some(value) => {
+                    report.repaired = true;
+                    value
+                }
+                None => {
+                    report.warnings.push("Snapshot could not be repaired; every field was reset".to_string());
+                    return serde_json::Value::Null;
+                }
+            }
+        }
+    };
+    
+    if let Err(mismatch) = integrity::verify(&value) {
+        report.warnings.push(format!("Snapshot is damaged: {}", mismatch.message()));
+        integrity::unseal(&mut value);
+    }
+    
+    match migration::migrate(kind, &value.to_string()) {
+        Ok(migrated) => serde_json::from_str(&migrated).unwrap_or(value),
+        Err(e) => {
+            let reason = e.as_string().unwrap_or_else(|| "migration failed".to_string());
+            report.warnings.push(format!("Host migrations were skipped: {}", reason));
+            value
+        }
+    }
+}
+
+/// Recover each entry of an optional object section on its own, as `<section>.<key>`
+///
+/// Snapshots from before a section existed don't have it, so a missing
+/// section is empty rather than a reset.
+fn salvage_entries<T: DeserializeOwned>(value: &serde_json::Value, section: &str, report: &mut RecoveryReport) -> BTreeMap<String, T> {
+    let mut kept = BTreeMap::new();
+    match value.get(section) {
+        None => {}
+        Some(serde_json::Value::Object(entries)) => {
+            for (key, entry) in entries {
+                let field = format!("{}.{}", section, key);
+                match serde_json::from_value::<T>(entry.clone()) {
+                    Ok(item) => {
+                        kept.insert(key.clone(), item);
+                        report.recovered.push(field);
+                    }
+                    Err(e) => report.reset(&field, e.to_string()),
+                }
+            }
+        }
+        Some(_) => report.reset(section, "not an object"),
+    }
+    kept
+}
+
+/// Recover an optional field, falling back to its default when it is missing or unreadable
+fn salvage_field<T: DeserializeOwned + Default>(value: &serde_json::Value, field: &str, report: &mut RecoveryReport) -> T {
+    match value.get(field) {
+        None => T::default(),
+        Some(entry) => match serde_json::from_value::<T>(entry.clone()) {
+            Ok(item) => {
+                report.recovered.push(field.to_string());
+                item
+            }
+            Err(e) => {
+                report.reset(field, e.to_string());
+                T::default()
+            }
+        },
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Restore as much state as possible from a damaged snapshot
+    ///
+    /// Truncated JSON is closed off at the last complete value, each metric
+    /// that still holds a usable number is restored, and the rest take their
+    /// default values. Custom metrics, metric metadata and dynamics groups
+    /// are recovered entry by entry, and the solver and noise model on
+    /// their own. Returns a JSON report of `repaired`, `recovered`
+    /// fields, `reset` fields with reasons, and `warnings`.
+    #[wasm_bindgen(js_name = loadSnapshotLenient)]
+    pub fn load_snapshot_lenient(&mut self, snapshot_json: &str) -> Result<String, JsValue> {
+        let mut report = RecoveryReport::default();
+        let value = parse_lenient("state", snapshot_json, &mut report);
+        
+        let mut state = OrganismState::new();
+        for field in CORE_METRICS {
+            match value.get(field) {
+                Some(v) => match v.as_f64().filter(|n| n.is_finite()) {
+                    Some(n) => match state.set_metric(field, n) {
+                        Ok(()) => report.recovered.push(field.to_string()),
+                        Err(e) => report.reset(field, e),
+                    },
+                    None => report.reset(field, format!("expected a number, found {}", v)),
+                },
+                None => report.reset(field, "missing"),
+            }
+        }
+        
+        state.custom_metrics = salvage_entries(&value, "custom_metrics", &mut report);
+        state.metric_meta = salvage_entries(&value, "metric_meta", &mut report);
+        state.core_solver = salvage_field(&value, "core_solver", &mut report);
+        state.noise = salvage_field(&value, "noise", &mut report);
+        match value.get("dynamics") {
+            None => {}
+            Some(serde_json::Value::Array(groups)) => {
+                for (index, group) in groups.iter().enumerate() {
+                    let name = group.get("name").and_then(|n| n.as_str()).map_or_else(|| index.to_string(), str::to_string);
+                    let field = format!("dynamics.{}", name);
+                    match serde_json::from_value(group.clone()) {
+                        Ok(group) => {
+                            state.dynamics.push(group);
+                            report.recovered.push(field);
+                        }
+                        Err(e) => report.reset(&field, e.to_string()),
+                    }
+                }
+            }
+            Some(_) => report.reset("dynamics", "not an array"),
+        }
+        
+        *self = state;
+        self.sync_state_vector();
+        report.to_json()
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Import as much of a damaged registry export as possible
+    ///
+    /// Each rule, bundle and mode is recovered on its own, so one corrupt
+    /// rule doesn't lose the others. Sections that can't be recovered are
+    /// left empty. The execution order keeps the recovered rules it lists
+    /// and appends any it doesn't. Returns the same report as
+    /// `OrganismState.loadSnapshotLenient`, with fields like `rules.<id>`.
+    #[wasm_bindgen(js_name = importRegistryLenient)]
+    pub fn import_registry_lenient(&mut self, json: &str) -> Result<String, JsValue> {
+        let mut report = RecoveryReport::default();
+        let value = parse_lenient("registry", json, &mut report);
+        
+        let mut rules: HashMap<String, Rule> = HashMap::new();
+        match value.get("rules").and_then(|v| v.as_object()) {
+            Some(entries) => {
+                for (id, entry) in entries {
+                    let field = format!("rules.{}", id);
+                    match serde_json::from_value::<Rule>(entry.clone()) {
+                        Ok(mut rule) if rule.id == *id => {
+                            rule.access = lisp::analyze_access(&rule.lisp_code);
+                            rule.program = lisp::Program::compile(&rule.lisp_code);
+                            rules.insert(id.clone(), rule);
+                            report.recovered.push(field);
+                        }
+                        Ok(rule) => report.reset(&field, format!("rule is stored under a different ID: {}", rule.id)),
+                        Err(e) => report.reset(&field, e.to_string()),
+                    }
+                }
+            }
+            None => report.reset("rules", "missing or not an object"),
+        }
+        
+        let mut order: Vec<String> = Vec::new();
+        match value.get("executionOrder").and_then(|v| v.as_array()) {
+            Some(ids) => {
+                for id in ids.iter().filter_map(|v| v.as_str()) {
+                    if rules.contains_key(id) && !order.iter().any(|o| o == id) {
+                        order.push(id.to_string());
+                    }
+                }
+                report.recovered.push("executionOrder".to_string());
+            }
+            None => report.reset("executionOrder", "missing or not an array"),
+        }
+        let mut unordered: Vec<&String> = rules.keys().filter(|id| !order.contains(id)).collect();
+        if !unordered.is_empty() {
+            unordered.sort();
+            report.warnings.push(format!(
+                "Rules missing from the execution order were appended: {}",
+                unordered.iter().map(|id| id.as_str()).collect::<Vec<_>>().join(", ")
+            ));
+            order.extend(unordered.into_iter().cloned());
+        }
+        
+        let mut bundles: BTreeMap<String, InstalledBundle> = BTreeMap::new();
+        match value.get("bundles").and_then(|v| v.as_object()) {
+            Some(entries) => {
+                for (name, entry) in entries {
+                    let field = format!("bundles.{}", name);
+                    match serde_json::from_value::<InstalledBundle>(entry.clone()) {
+                        Ok(bundle) => {
+                            bundles.insert(name.clone(), bundle);
+                            report.recovered.push(field);
+                        }
+                        Err(e) => report.reset(&field, e.to_string()),
+                    }
+                }
+            }
+            None => report.reset("bundles", "missing or not an object"),
+        }
+        
+        let mut modes: Vec<Mode> = Vec::new();
+        match value.get("modes").and_then(|v| v.as_array()) {
+            Some(entries) => {
+                for (index, entry) in entries.iter().enumerate() {
+                    match serde_json::from_value::<Mode>(entry.clone()) {
+                        Ok(mode) => {
+                            report.recovered.push(format!("modes.{}", mode.name()));
+                            modes.push(mode);
+                        }
+                        Err(e) => report.reset(&format!("modes.{}", index), e.to_string()),
+                    }
+                }
+            }
+            None => report.reset("modes", "missing or not an array"),
+        }
+        
+        for rule in rules.values_mut() {
+            let before = rule.modes.len();
+            rule.modes.retain(|name| modes.iter().any(|m| m.name() == name));
+            if rule.modes.len() != before {
+                report.warnings.push(format!("Rule {} lost modes that were not recovered", rule.id));
+            }
+        }
+        
+        // Older registries have no invariants, so a missing list isn't a reset
+        let mut invariants: Vec<Invariant> = Vec::new();
+        if let Some(entries) = value.get("invariants").and_then(|v| v.as_array()) {
+            for (index, entry) in entries.iter().enumerate() {
+                match serde_json::from_value::<Invariant>(entry.clone()) {
+                    Ok(invariant) => {
+                        report.recovered.push(format!("invariants.{}", invariant.name()));
+                        invariants.push(invariant);
+                    }
+                    Err(e) => report.reset(&format!("invariants.{}", index), e.to_string()),
+                }
+            }
+        }
+        
+        let mut goals: Vec<Goal> = Vec::new();
+        if let Some(entries) = value.get("goals").and_then(|v| v.as_array()) {
+            for (index, entry) in entries.iter().enumerate() {
+                match serde_json::from_value::<Goal>(entry.clone()) {
+                    Ok(goal) => {
+                        report.recovered.push(format!("goals.{}", goal.name()));
+                        goals.push(goal);
+                    }
+                    Err(e) => report.reset(&format!("goals.{}", index), e.to_string()),
+                }
+            }
+        }
+        
+        // Older registries have no saved fuel limit and ran with the default
+        let fuel_limit = match value.get("fuelLimit") {
+            None => lisp::MAX_STEPS,
+            Some(v) => match v.as_u64().filter(|fuel| *fuel > 0) {
+                Some(fuel) => {
+                    report.recovered.push("fuelLimit".to_string());
+                    fuel
+                }
+                None => {
+                    report.reset("fuelLimit", "must be a positive integer");
+                    lisp::MAX_STEPS
+                }
+            },
+        };
+        
+        self.rules = rules;
+        self.execution_order = order;
+        self.bundles = bundles;
+        self.modes = modes;
+        self.active_mode = None;
+        self.invariants.list = invariants;
+        self.goals = goals;
+        self.fuel_limit = fuel_limit;
+        report.to_json()
+    }
+}
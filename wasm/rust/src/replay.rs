@@ -0,0 +1,372 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::clock;
+use crate::cycle::EnvironmentCycle;
+use crate::hash::fnv1a64_hex;
+use crate::migration;
+use crate::simulation::Simulation;
+use crate::{OrganismState, RuleRegistry};
+
+/// Inputs a recording holds; later ones are dropped and the log marked incomplete
+const MAX_INPUTS: usize = 1_000_000;
+
+/// Format name written into every input log
+const LOG_FORMAT: &str = "ouroboros-inputs";
+
+/// One external input to a recorded simulation
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Input {
+    input: String,
+    args: Value,
+    /// Whether the call succeeded; failed calls are replayed too, since they may have changed the state
+    ok: bool,
+    /// Fingerprint of the state after the input
+    check: String,
+}
+
+/// Everything a simulation took in since `startRecording`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InputLog {
+    format: String,
+    engine_version: String,
+    /// The simulation when recording started
+    start: Value,
+    inputs: Vec<Input>,
+    /// False once inputs were dropped past the limit
+    complete: bool,
+}
+
+fn fingerprint(state: &OrganismState) -> String {
+    fnv1a64_hex(serde_json::to_string(state).unwrap_or_default().as_bytes())
+}
+
+/// Numbers as JSON writes them, with null (NaN or an infinity) read back as NaN
+fn numbers(value: &Value) -> Option<Vec<f64>> {
+    value.as_array()?.iter().map(|n| if n.is_null() { Some(f64::NAN) } else { n.as_f64() }).collect()
+}
+
+/// A state snapshot with its state vector, which snapshots leave out
+pub(crate) fn state_value(state: &OrganismState) -> Value {
+    let mut snapshot = serde_json::to_value(state).unwrap_or_default();
+    migration::stamp_value("state", &mut snapshot);
+    serde_json::json!({ "snapshot": snapshot, "stateVector": state.state_vector })
+}
+
+fn state_from(value: &Value) -> Result<OrganismState, JsValue> {
+    let mut state = OrganismState::new();
+    state.load_snapshot(&value["snapshot"].to_string())?;
+    match numbers(&value["stateVector"]) {
+        Some(vector) if vector.len() >= 3 => state.state_vector = vector,
+        _ => {}
+    }
+    Ok(state)
+}
+
+/// A registry as `exportRegistry` writes it, with what ticks have built up alongside
+///
+/// The export leaves out state that only changes as ticks run (the active
+/// mode, the last step and generation, which edge triggers held), but a
+/// replay has to start from it to run the same rules.
+pub(crate) fn registry_value(registry: &RuleRegistry) -> Value {
+    let export: Value = registry.export_registry().ok().and_then(|json| serde_json::from_str(&json).ok()).unwrap_or_default();
+    serde_json::json!({
+        "export": export,
+        "activeMode": registry.active_mode,
+        "currentStep": registry.current_step,
+        "lastGeneration": registry.last_generation,
+        "heldTriggers": registry.held_triggers(),
+        "invariantsDebug": registry.invariants.debug,
+    })
+}
+
+fn registry_from(value: &Value) -> Result<RuleRegistry, JsValue> {
+    let mut registry = RuleRegistry::new();
+    registry.import_registry(&value["export"].to_string())?;
+    registry.active_mode = value["activeMode"].as_str().map(str::to_string);
+    registry.current_step = value["currentStep"].as_u64().unwrap_or(0);
+    registry.last_generation = value["lastGeneration"].as_u64();
+    let held: Vec<String> = serde_json::from_value(value["heldTriggers"].clone()).unwrap_or_default();
+    registry.restore_held_triggers(&held);
+    registry.invariants.debug = value["invariantsDebug"].as_bool().unwrap_or(false);
+    Ok(registry)
+}
+
+impl Simulation {
+    /// Append an input to the recording, if one is running
+    ///
+    /// Call it after the input has run, so its check covers the result;
+    /// `args` is only called while recording.
+    pub(crate) fn record_input(&mut self, input: &str, args: impl FnOnce() -> Value, ok: bool) {
+        let full = match &mut self.recording {
+            Some(log) if log.inputs.len() >= MAX_INPUTS => {
+                log.complete = false;
+                true
+            }
+            Some(_) => false,
+            None => true,
+        };
+        if full {
+            return;
+        }
+        let input = Input { input: input.to_string(), args: args(), ok, check: fingerprint(self.state()) };
+        if let Some(log) = &mut self.recording {
+            log.inputs.push(input);
+        }
+    }
+    
+    /// Record the state and registry as they are now, after a change made from outside the simulation's inputs
+    ///
+    /// A scenario's events edit the state and registry directly; they are
+    /// recorded as the `setState` and `setRegistry` they amount to.
+    pub(crate) fn record_replacement(&mut self) {
+        if self.recording.is_some() {
+            let state = state_value(self.state());
+            self.record_input("setState", || state, true);
+            let registry = registry_value(self.registry());
+            self.record_input("setRegistry", || registry, true);
+        }
+    }
+    
+    /// Run one recorded input; errors are the input's own
+    fn run_input(&mut self, input: &Input) -> Result<(), JsValue> {
+        let args = &input.args;
+        let missing = |key: &str| JsValue::from_str(&format!("{} is missing {}", input.input, key));
+        let number = |key: &str| match args.get(key) {
+            Some(Value::Null) => Ok(f64::NAN),
+            Some(value) => value.as_f64().ok_or_else(|| missing(key)),
+            None => Err(missing(key)),
+        };
+        let text = |key: &str| args[key].as_str().ok_or_else(|| missing(key));
+        match input.input.as_str() {
+            "tick" => self.tick(number("deltaTime")?).map(|_| ()),
+            "applyRule" => {
+                let params = numbers(&args["params"]).ok_or_else(|| missing("params"))?;
+                self.apply_rule(text("ruleId")?, params).map(|_| ())
+            }
+            "setState" => {
+                let state = state_from(args)?;
+                self.set_state(&state);
+                Ok(())
+            }
+            "setMetric" => self.set_metric(text("name")?, number("value")?),
+            "setSeed" => {
+                self.set_seed(args["seed"].as_u64().ok_or_else(|| missing("seed"))?);
+                Ok(())
+            }
+            "setRegistry" => {
+                let registry = registry_from(args)?;
+                self.set_registry(&registry);
+                Ok(())
+            }
+            "triggerEvent" => self.trigger_event(text("kind")?, number("magnitude")?).map(|_| ()),
+            "scheduleEvents" => {
+                let seed = args["seed"].as_u64().ok_or_else(|| missing("seed"))?;
+                self.schedule_events(&args["events"].to_string(), seed)
+            }
+            "addCycle" => {
+                let cycle: EnvironmentCycle = serde_json::from_value(args["cycle"].clone())
+                    .map_err(|e| JsValue::from_str(&format!("addCycle has a bad cycle: {}", e)))?;
+                self.add_cycle(text("name")?, &cycle)
+            }
+            "setSubsystemState" => self.set_subsystem_state(&args["states"].to_string()),
+            "enableRewind" => {
+                let (capacity, interval) = (args["capacity"].as_u64(), args["interval"].as_u64());
+                let capacity = capacity.ok_or_else(|| missing("capacity"))? as usize;
+                self.enable_rewind(capacity, interval.ok_or_else(|| missing("interval"))? as u32)
+            }
+            "disableRewind" => {
+                self.disable_rewind();
+                Ok(())
+            }
+            "rewind" => self.rewind(args["steps"].as_u64().ok_or_else(|| missing("steps"))?).map(|_| ()),
+            other => Err(JsValue::from_str(&format!("{} can't be replayed", other))),
+        }
+    }
+    
+    fn subsystem_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.subsystems.iter().map(|s| s.name().to_string()).collect();
+        names.sort();
+        names
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Record every external input from now on, for `exportInputLog` and `replay`
+    ///
+    /// The simulation as it is now (state with its state vector,
+    /// registry, subsystem state, tick count and a manual clock's time)
+    /// is the log's start. Then `tick`, `applyRule`, `setState`,
+    /// `setMetric`, `setSeed`, `setRegistry`, `triggerEvent`,
+    /// `scheduleEvents`, `addCycle`, `setSubsystemState`, `enableRewind`,
+    /// `disableRewind` and `rewind` are recorded with their arguments and a
+    /// fingerprint of the state after them. Calls on state or registry
+    /// objects outside the simulation aren't inputs: they change copies
+    /// the simulation never sees. Recording again starts a new log.
+    #[wasm_bindgen(js_name = startRecording)]
+    pub fn start_recording(&mut self) -> Result<(), JsValue> {
+        let subsystems: Value = serde_json::from_str(&self.get_subsystem_state()?)
+            .map_err(|e| JsValue::from_str(&format!("Failed to record subsystem state: {}", e)))?;
+        let clock = match clock::manual_state() {
+            Some((now, auto_advance)) => serde_json::json!({ "source": "manual", "now": now, "autoAdvance": auto_advance }),
+            None => serde_json::json!({ "source": clock::Clock::source() }),
+        };
+        self.recording = Some(InputLog {
+            format: LOG_FORMAT.to_string(),
+            engine_version: env!("CARGO_PKG_VERSION").to_string(),
+            start: serde_json::json!({
+                "ticks": self.ticks(),
+                "state": state_value(self.state()),
+                "registry": registry_value(self.registry()),
+                "subsystems": subsystems,
+                "clock": clock,
+            }),
+            inputs: Vec::new(),
+            complete: true,
+        });
+        Ok(())
+    }
+    
+    /// Stop recording and drop the log
+    #[wasm_bindgen(js_name = stopRecording)]
+    pub fn stop_recording(&mut self) {
+        self.recording = None;
+    }
+    
+    /// Inputs recorded so far
+    #[wasm_bindgen(getter, js_name = recordedInputs)]
+    pub fn recorded_inputs(&self) -> usize {
+        self.recording.as_ref().map_or(0, |log| log.inputs.len())
+    }
+    
+    /// Get the recording as JSON: `{ format, engineVersion, start, inputs, complete }`
+    #[wasm_bindgen(js_name = exportInputLog)]
+    pub fn export_input_log(&self) -> Result<String, JsValue> {
+        let log = self.recording
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("Not recording; call startRecording first"))?;
+        serde_json::to_string(log).map_err(|e| JsValue::from_str(&format!("Failed to export input log: {}", e)))
+    }
+    
+    /// Reset this simulation to a log's start, run its inputs and return a report as JSON
+    ///
+    /// The simulation must have the subsystems the recorded one had when
+    /// recording started (build it the same way); those added by
+    /// recorded inputs are added again. A manual clock is reset to its
+    /// recorded time. After each input the state is compared with the
+    /// recorded fingerprint, and the replay stops at the first
+    /// difference. The report is `{ inputs, replayed, ticks, diverged }`,
+    /// where `diverged` is null or `{ index, input, reason }`.
+    #[wasm_bindgen]
+    pub fn replay(&mut self, log_json: &str) -> Result<String, JsValue> {
+        let log: InputLog = serde_json::from_str(log_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse input log: {}", e)))?;
+        if log.format != LOG_FORMAT {
+            return Err(JsValue::from_str(&format!("Not an input log: format is {}", log.format)));
+        }
+        let start = &log.start;
+        let recorded: Vec<String> = start["subsystems"]
+            .as_object()
+            .map(|states| states.keys().cloned().collect())
+            .unwrap_or_default();
+        if self.subsystem_names() != recorded {
+            return Err(JsValue::from_str(&format!(
+                "The log was recorded with subsystems [{}] but this simulation has [{}]",
+                recorded.join(", "),
+                self.subsystem_names().join(", ")
+            )));
+        }
+        
+        let state = state_from(&start["state"])?;
+        let registry = registry_from(&start["registry"])?;
+        self.recording = None;
+        self.disable_rewind();
+        self.set_state(&state);
+        self.set_registry(&registry);
+        self.set_subsystem_state(&start["subsystems"].to_string())?;
+        self.set_ticks(start["ticks"].as_u64().unwrap_or(0));
+        if start["clock"]["source"] == "manual" {
+            let time = |key: &str| start["clock"][key].as_f64().unwrap_or(0.0);
+            clock::use_manual_state(time("now"), time("autoAdvance"));
+        }
+        
+        let mut diverged = Value::Null;
+        let mut replayed = 0;
+        for (index, input) in log.inputs.iter().enumerate() {
+            let result = self.run_input(input);
+            let reason = match &result {
+                Err(e) if input.ok => Some(format!(
+                    "failed where the recording succeeded: {}",
+                    e.as_string().unwrap_or_default()
+                )),
+                Ok(_) if !input.ok => Some("succeeded where the recording failed".to_string()),
+                _ if fingerprint(self.state()) != input.check => Some("the state differs from the recording".to_string()),
+                _ => None,
+            };
+            replayed += 1;
+            if let Some(reason) = reason {
+                diverged = serde_json::json!({ "index": index, "input": input.input, "reason": reason });
+                break;
+            }
+        }
+        
+        Ok(serde_json::json!({
+            "inputs": log.inputs.len(),
+            "replayed": replayed,
+            "ticks": self.ticks(),
+            "diverged": diverged,
+        })
+        .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulation::SimulationBuilder;
+    
+    fn registry() -> RuleRegistry {
+        let mut registry = RuleRegistry::new();
+        registry.set_fuel_limit(100);
+        registry.register_rule("grow", "(set! energy (+ energy 1))", None).unwrap();
+        registry.set_rule_trigger("grow", "(> energy 0)", true).unwrap();
+        registry.define_mode("fed", "(> energy 0)").unwrap();
+        registry
+    }
+    
+    #[test]
+    fn registries_keep_what_ticks_built_up() {
+        crate::clock::use_manual_state(0.0, 1.0);
+        let mut registry = registry();
+        registry.tick(&mut OrganismState::new(), 3).unwrap();
+        
+        let restored = registry_from(&registry_value(&registry)).unwrap();
+        assert_eq!(restored.fuel_limit(), 100);
+        assert_eq!(restored.current_step, 3);
+        assert_eq!(restored.active_mode.as_deref(), Some("fed"));
+        assert_eq!(restored.last_generation, registry.last_generation);
+        assert_eq!(restored.held_triggers(), vec!["grow".to_string()]);
+    }
+    
+    #[test]
+    fn replays_keep_a_non_default_fuel_limit() {
+        crate::clock::use_manual_state(0.0, 1.0);
+        let mut sim = SimulationBuilder::new().build().unwrap();
+        sim.start_recording().unwrap();
+        sim.set_registry(&registry());
+        for _ in 0..3 {
+            sim.tick(0.1).unwrap();
+        }
+        let log = sim.export_input_log().unwrap();
+        
+        let mut other = SimulationBuilder::new().build().unwrap();
+        let report: Value = serde_json::from_str(&other.replay(&log).unwrap()).unwrap();
+        assert_eq!(report["diverged"], Value::Null);
+        assert_eq!(report["replayed"], 4);
+        assert_eq!(other.registry().fuel_limit(), 100);
+        assert_eq!(other.state().energy(), sim.state().energy());
+    }
+}
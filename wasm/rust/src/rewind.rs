@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::simulation::Simulation;
+use crate::subsystem::Subsystem;
+use crate::{OrganismState, RuleRegistry};
+
+/// Most checkpoints a rewind buffer keeps
+const MAX_CAPACITY: usize = 10_000;
+
+/// A simulation as it was after some tick
+///
+/// State and registry are shared with the simulation until either side
+/// changes them, as with `fork`, so a checkpoint costs a copy only of
+/// what the following ticks touch.
+#[derive(Clone)]
+struct Checkpoint {
+    ticks: u64,
+    state: Rc<OrganismState>,
+    registry: Rc<RuleRegistry>,
+    subsystems: Vec<Box<dyn Subsystem>>,
+}
+
+/// Checkpoints taken every `interval` ticks, keeping the last `capacity`
+#[derive(Clone)]
+pub(crate) struct Rewind {
+    capacity: usize,
+    interval: u64,
+    checkpoints: VecDeque<Checkpoint>,
+}
+
+impl Simulation {
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            ticks: self.ticks(),
+            state: self.state_rc(),
+            registry: self.registry_rc(),
+            subsystems: self.subsystems.clone(),
+        }
+    }
+    
+    /// Take a checkpoint if rewind is enabled and the tick just run is due
+    pub(crate) fn record_rewind(&mut self) {
+        let due = match &self.rewind {
+            Some(rewind) => self.ticks().is_multiple_of(rewind.interval),
+            None => false,
+        };
+        if due {
+            let checkpoint = self.checkpoint();
+            let rewind = self.rewind.as_mut().expect("rewind is enabled");
+            if rewind.checkpoints.len() == rewind.capacity {
+                rewind.checkpoints.pop_front();
+            }
+            rewind.checkpoints.push_back(checkpoint);
+        }
+    }
+    
+    fn start_rewind(&mut self, capacity: usize, interval: u32) -> Result<(), JsValue> {
+        if capacity == 0 || capacity > MAX_CAPACITY {
+            return Err(JsValue::from_str(&format!("Rewind capacity must be between 1 and {}, got {}", MAX_CAPACITY, capacity)));
+        }
+        if interval == 0 {
+            return Err(JsValue::from_str("Rewind interval must be at least 1 tick"));
+        }
+        let mut checkpoints = VecDeque::with_capacity(capacity);
+        checkpoints.push_back(self.checkpoint());
+        self.rewind = Some(Rewind { capacity, interval: interval as u64, checkpoints });
+        Ok(())
+    }
+    
+    fn go_back(&mut self, steps: u64) -> Result<u64, JsValue> {
+        let target = self.ticks().saturating_sub(steps);
+        let rewind = self.rewind
+            .as_mut()
+            .ok_or_else(|| JsValue::from_str("Rewind is not enabled; call enableRewind first"))?;
+        while rewind.checkpoints.len() > 1 && rewind.checkpoints.back().is_some_and(|c| c.ticks > target) {
+            rewind.checkpoints.pop_back();
+        }
+        let checkpoint = rewind.checkpoints.back().cloned().expect("the oldest checkpoint is kept");
+        self.restore_checkpoint(checkpoint.ticks, checkpoint.state, checkpoint.registry, checkpoint.subsystems);
+        Ok(checkpoint.ticks)
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Keep the last `capacity` checkpoints, one every `interval` ticks, for `rewind`
+    ///
+    /// The simulation as it is now is the first checkpoint. Enabling
+    /// again starts a new buffer.
+    #[wasm_bindgen(js_name = enableRewind)]
+    pub fn enable_rewind(&mut self, capacity: usize, interval: u32) -> Result<(), JsValue> {
+        let result = self.start_rewind(capacity, interval);
+        self.record_input("enableRewind", || serde_json::json!({ "capacity": capacity, "interval": interval }), result.is_ok());
+        result
+    }
+    
+    /// Stop taking checkpoints and drop the ones held
+    #[wasm_bindgen(js_name = disableRewind)]
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+        self.record_input("disableRewind", || serde_json::json!({}), true);
+    }
+    
+    /// Go back `steps` ticks and return the tick count the simulation is now at
+    ///
+    /// Restores the newest checkpoint at or before `ticks - steps`, so
+    /// with an interval above 1 the simulation may land a few ticks
+    /// earlier than asked; going back further than the oldest
+    /// checkpoint lands on that one. Checkpoints after the one restored
+    /// are dropped, so ticking again records a new future. The history
+    /// recorder keeps what it sampled.
+    #[wasm_bindgen]
+    pub fn rewind(&mut self, steps: u64) -> Result<u64, JsValue> {
+        let result = self.go_back(steps);
+        self.record_input("rewind", || serde_json::json!({ "steps": steps }), result.is_ok());
+        result
+    }
+    
+    /// Ticks of the checkpoints held, oldest first
+    #[wasm_bindgen(js_name = getRewindTicks)]
+    pub fn get_rewind_ticks(&self) -> Vec<u64> {
+        self.rewind
+            .as_ref()
+            .map(|rewind| rewind.checkpoints.iter().map(|c| c.ticks).collect())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// Crate-owned deterministic PRNG (xoshiro256**)
+///
+/// Every stochastic subsystem draws from one of these instead of `Math.random`,
+/// so a run seeded with the same value produces the same numbers on every
+/// platform. The state is serializable so it can travel inside snapshots.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(try_from = "RngState")]
+pub struct Rng {
+    s: [u64; 4],
+}
+
+/// Serialized generator state, checked before it becomes an `Rng`
+#[derive(Deserialize)]
+struct RngState {
+    s: [u64; 4],
+}
+
+impl TryFrom<RngState> for Rng {
+    type Error = String;
+    
+    /// Reject the all-zero state, from which xoshiro only ever returns 0
+    fn try_from(state: RngState) -> Result<Rng, String> {
+        if state.s == [0; 4] {
+            return Err("RNG state must not be all zeros".to_string());
+        }
+        Ok(Rng { s: state.s })
+    }
+}
+
+impl Rng {
+    /// Seed a generator, expanding the seed with SplitMix64
+    pub fn new(seed: u64) -> Rng {
+        let mut sm = seed;
+        let mut s = [0u64; 4];
+        for word in s.iter_mut() {
+            *word = splitmix64(&mut sm);
+        }
+        Rng { s }
+    }
+    
+    /// Next raw 64-bit value
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+        
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+        
+        result
+    }
+    
+    /// Uniform value in [0, 1)
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+/// SplitMix64 step, used to expand seeds into full generator state
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
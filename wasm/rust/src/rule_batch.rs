@@ -0,0 +1,113 @@
+use wasm_bindgen::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeSet;
+
+use crate::call_log;
+use crate::lisp;
+use crate::modes;
+use crate::rule_params::parse_params;
+use crate::rule_testing::RuleTestCase;
+use crate::tags;
+use crate::RuleRegistry;
+
+/// Optional extras of a rule in a batch; absent fields leave the rule's current ones
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct RuleMetadata {
+    modes: Option<Vec<String>>,
+    tests: Option<Vec<RuleTestCase>>,
+    params: Option<serde_json::Value>,
+    tags: Option<Vec<String>>,
+}
+
+/// One entry of `registerRules`
+#[derive(Deserialize)]
+struct RuleEntry {
+    id: String,
+    code: String,
+    #[serde(default)]
+    metadata: RuleMetadata,
+}
+
+impl RuleRegistry {
+    /// Check one batch entry without touching the registry
+    fn check_entry(&self, entry: &RuleEntry, seen: &mut BTreeSet<String>) -> Result<(), String> {
+        if entry.id.is_empty() {
+            return Err("Rule ID cannot be empty".to_string());
+        }
+        if !seen.insert(entry.id.clone()) {
+            return Err("Rule ID appears more than once in the batch".to_string());
+        }
+        lisp::check_code(&entry.code)?;
+        for mode in entry.metadata.modes.iter().flatten() {
+            if !self.modes.iter().any(|m| m.name() == mode) {
+                return Err(format!("Mode not defined: {}", mode));
+            }
+        }
+        for case in entry.metadata.tests.iter().flatten() {
+            case.validate()?;
+        }
+        if let Some(params) = &entry.metadata.params {
+            parse_params(params)?;
+        }
+        if let Some(tags) = &entry.metadata.tags {
+            tags::normalize(tags.clone())?;
+        }
+        Ok(())
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Register several rules at once, all or nothing
+    ///
+    /// `json` is an array of `{ id, code, metadata }`, where the optional
+    /// `metadata` may set `modes` (as `setRuleModes`), `tests` (test cases
+    /// as `addRuleTest`, replacing the rule's existing ones), `params`
+    /// (as `defineRuleParams`) and `tags` (as `setRuleTags`). Every entry
+    /// is checked first: IDs must be non-empty and unique, code must pass
+    /// `checkRuleCode`, modes must be defined and tests, parameters and
+    /// tags valid. If any entry fails nothing is registered and the error
+    /// lists each failing entry.
+    /// Returns a JSON summary with the registered IDs.
+    #[wasm_bindgen(js_name = registerRules)]
+    pub fn register_rules(&mut self, json: &str) -> Result<String, JsValue> {
+        let _call = call_log::enter("registerRules", || serde_json::json!({ "json": call_log::text(json) }));
+        let entries: Vec<RuleEntry> = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse rules: {}", e)))?;
+        
+        let mut seen = BTreeSet::new();
+        let errors: Vec<String> = entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                self.check_entry(entry, &mut seen)
+                    .err()
+                    .map(|e| format!("#{} ({}): {}", index, entry.id, e))
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(JsValue::from_str(&format!("Invalid rules: {}", errors.join("; "))));
+        }
+        
+        let mut registered = Vec::with_capacity(entries.len());
+        for entry in entries {
+            self.register_rule(&entry.id, &entry.code, entry.metadata.tags)?;
+            if let Some(rule) = self.rules.get_mut(&entry.id) {
+                if let Some(modes) = entry.metadata.modes {
+                    rule.modes = modes::without_duplicates(modes);
+                }
+                if let Some(tests) = entry.metadata.tests {
+                    rule.tests = tests;
+                }
+                if let Some(params) = entry.metadata.params {
+                    rule.params = parse_params(&params)?;
+                }
+            }
+            registered.push(entry.id);
+        }
+        
+        serde_json::to_string(&serde_json::json!({ "registered": registered }))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule summary: {}", e)))
+    }
+}
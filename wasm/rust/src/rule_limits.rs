@@ -0,0 +1,82 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::call_log;
+use crate::lisp::Budget;
+use crate::RuleRegistry;
+
+/// How much a single application of a rule may spend before it is aborted
+///
+/// Unset limits fall back to the registry's `fuelLimit` and no time limit.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct RuleLimits {
+    /// Fuel, replacing the registry's `fuelLimit`
+    #[serde(default)]
+    max_fuel: Option<u64>,
+    /// Milliseconds on the engine clock
+    #[serde(default)]
+    max_time_ms: Option<f64>,
+}
+
+impl RuleLimits {
+    fn check(&self) -> Result<(), String> {
+        if self.max_fuel == Some(0) {
+            return Err("maxFuel must be at least 1".to_string());
+        }
+        if let Some(max_time_ms) = self.max_time_ms {
+            if !max_time_ms.is_finite() || max_time_ms <= 0.0 {
+                return Err(format!("maxTimeMs must be a positive number, got {}", max_time_ms));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl RuleRegistry {
+    /// The budget for applying a rule now, with its time limit starting immediately
+    pub(crate) fn budget(&self, id: &str) -> Budget {
+        let limits = self.rules.get(id).map(|rule| &rule.limits);
+        Budget::fuel(limits.and_then(|l| l.max_fuel).unwrap_or(self.fuel_limit))
+            .with_time_limit(limits.and_then(|l| l.max_time_ms))
+    }
+    
+    /// Count a failed application in a rule's stats
+    pub(crate) fn record_failure(&mut self, id: &str, message: &str) {
+        if let Some(rule) = self.rules.get_mut(id) {
+            rule.error_count += 1;
+            rule.last_error = Some(message.to_string());
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Limit what one application of a rule may spend, from JSON
+    ///
+    /// `json` is `{ maxFuel, maxTimeMs }`, both optional. `maxFuel` caps the
+    /// fuel spent (see `fuelLimit`) in place of the registry's own limit,
+    /// and `maxTimeMs` caps the time taken on the engine clock. A rule that exceeds either is aborted with its
+    /// metric changes undone, the failure is counted in its stats
+    /// (`errorCount`, `lastError`) and `applyRule` throws. `{}` removes the
+    /// limits. They survive re-registering the rule and are exported with
+    /// the registry.
+    #[wasm_bindgen(js_name = setRuleLimits)]
+    pub fn set_rule_limits(&mut self, id: &str, json: &str) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleLimits", || serde_json::json!({ "id": id, "limits": call_log::text(json) }));
+        let limits: RuleLimits = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse rule limits: {}", e)))?;
+        limits.check().map_err(|e| JsValue::from_str(&format!("Invalid rule limits: {}", e)))?;
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.limits = limits;
+        Ok(())
+    }
+    
+    /// Get a rule's limits as JSON
+    #[wasm_bindgen(js_name = getRuleLimits)]
+    pub fn get_rule_limits(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        serde_json::to_string(&rule.limits)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule limits: {}", e)))
+    }
+}
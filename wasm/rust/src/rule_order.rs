@@ -0,0 +1,171 @@
+use wasm_bindgen::prelude::*;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::call_log;
+use crate::{Rule, RuleRegistry};
+
+/// A chain of `runs_after` dependencies that leads back to its first rule, if any
+///
+/// Dependencies on rules that aren't registered are ignored. The chain
+/// starts and ends with the same ID, e.g. `[a, b, a]`.
+pub(crate) fn find_cycle(rules: &HashMap<String, Rule>) -> Option<Vec<String>> {
+    fn visit<'a>(
+        id: &'a str,
+        rules: &'a HashMap<String, Rule>,
+        done: &mut BTreeSet<&'a str>,
+        path: &mut Vec<&'a str>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|on_path| *on_path == id) {
+            let mut cycle: Vec<String> = path[start..].iter().map(|id| id.to_string()).collect();
+            cycle.push(id.to_string());
+            return Some(cycle);
+        }
+        if done.contains(id) {
+            return None;
+        }
+        path.push(id);
+        for dependency in rules[id].runs_after.iter().filter(|d| rules.contains_key(*d)) {
+            if let Some(cycle) = visit(dependency, rules, done, path) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        done.insert(id);
+        None
+    }
+    
+    let mut ids: Vec<&String> = rules.keys().collect();
+    ids.sort();
+    let mut done = BTreeSet::new();
+    ids.into_iter().find_map(|id| visit(id, rules, &mut done, &mut Vec::new()))
+}
+
+impl RuleRegistry {
+    /// Rule IDs in the order a tick runs them
+    ///
+    /// Every rule runs after the registered rules it depends on
+    /// (`runs_after`). Among the rules free to run next, higher priorities
+    /// go first, then execution order. Rules caught in a dependency cycle,
+    /// which only a hand-edited import can produce, run last in that same
+    /// order. Disabled rules are included; callers that run rules skip
+    /// them.
+    pub(crate) fn run_order(&self) -> Vec<&String> {
+        let key = |position: usize| {
+            let priority = self.rules.get(&self.execution_order[position]).map_or(0, |rule| rule.priority);
+            (Reverse(priority), position)
+        };
+        let positions: HashMap<&str, usize> = self.execution_order
+            .iter()
+            .enumerate()
+            .map(|(position, id)| (id.as_str(), position))
+            .collect();
+        
+        let mut waiting = vec![0usize; self.execution_order.len()];
+        let mut dependents: Vec<Vec<usize>> = vec![Vec::new(); self.execution_order.len()];
+        for (position, id) in self.execution_order.iter().enumerate() {
+            let Some(rule) = self.rules.get(id) else { continue };
+            let dependencies: BTreeSet<usize> = rule.runs_after
+                .iter()
+                .filter_map(|d| positions.get(d.as_str()).copied())
+                .filter(|d| *d != position)
+                .collect();
+            waiting[position] = dependencies.len();
+            for dependency in dependencies {
+                dependents[dependency].push(position);
+            }
+        }
+        
+        let mut ready: BTreeSet<(Reverse<i32>, usize)> = (0..waiting.len()).filter(|p| waiting[*p] == 0).map(key).collect();
+        let mut order = Vec::with_capacity(self.execution_order.len());
+        while let Some((_, position)) = ready.pop_first() {
+            order.push(&self.execution_order[position]);
+            for dependent in &dependents[position] {
+                waiting[*dependent] -= 1;
+                if waiting[*dependent] == 0 {
+                    ready.insert(key(*dependent));
+                }
+            }
+        }
+        if order.len() < self.execution_order.len() {
+            let mut stuck: Vec<(Reverse<i32>, usize)> = (0..waiting.len()).filter(|p| waiting[*p] > 0).map(key).collect();
+            stuck.sort();
+            order.extend(stuck.into_iter().map(|(_, position)| &self.execution_order[position]));
+        }
+        order
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Turn a rule on or off without removing it
+    ///
+    /// Ticks skip disabled rules; `applyRule` still runs them when asked
+    /// by ID. The flag survives re-registering the rule and is exported
+    /// with the registry.
+    #[wasm_bindgen(js_name = setRuleEnabled)]
+    pub fn set_rule_enabled(&mut self, id: &str, enabled: bool) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleEnabled", || serde_json::json!({ "id": id, "enabled": enabled }));
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.enabled = enabled;
+        Ok(())
+    }
+    
+    /// Whether ticks run a rule
+    #[wasm_bindgen(js_name = isRuleEnabled)]
+    pub fn is_rule_enabled(&self, id: &str) -> Result<bool, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.enabled)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+    
+    /// Set a rule's priority; ticks run higher priorities first
+    ///
+    /// Rules start at priority 0, and rules with the same priority run in
+    /// execution order (registration order unless an import set it).
+    /// Negative priorities run after the rest. Dependencies (see
+    /// `setRuleDependencies`) come before priorities.
+    #[wasm_bindgen(js_name = setRulePriority)]
+    pub fn set_rule_priority(&mut self, id: &str, priority: i32) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRulePriority", || serde_json::json!({ "id": id, "priority": priority }));
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.priority = priority;
+        Ok(())
+    }
+    
+    /// A rule's priority
+    #[wasm_bindgen(js_name = getRulePriority)]
+    pub fn get_rule_priority(&self, id: &str) -> Result<i32, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.priority)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+    
+    /// Make a rule run after the rules in `after` on every tick
+    ///
+    /// Replaces the rule's dependencies. IDs not registered yet are kept
+    /// and count once they are. Throws, leaving the dependencies as they
+    /// were, when they would form a cycle; the message names it, e.g.
+    /// `a -> b -> a`.
+    #[wasm_bindgen(js_name = setRuleDependencies)]
+    pub fn set_rule_dependencies(&mut self, id: &str, after: Vec<String>) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleDependencies", || serde_json::json!({ "id": id, "after": after }));
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        let previous = std::mem::replace(&mut rule.runs_after, after);
+        if let Some(cycle) = find_cycle(&self.rules) {
+            if let Some(rule) = self.rules.get_mut(id) {
+                rule.runs_after = previous;
+            }
+            return Err(JsValue::from_str(&format!("Rule dependencies would form a cycle: {}", cycle.join(" -> "))));
+        }
+        Ok(())
+    }
+    
+    /// IDs of the rules a rule runs after
+    #[wasm_bindgen(js_name = getRuleDependencies)]
+    pub fn get_rule_dependencies(&self, id: &str) -> Result<Vec<String>, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.runs_after.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+}
@@ -0,0 +1,135 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+use crate::{apply_rule, OrganismState, RuleRegistry};
+
+/// Type of a named rule parameter
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+enum ParamKind {
+    #[default]
+    Number,
+    Integer,
+    Boolean,
+}
+
+/// A named rule parameter and its default; its index is its position in `params`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct RuleParam {
+    name: String,
+    #[serde(default)]
+    kind: ParamKind,
+    default: serde_json::Value,
+}
+
+impl RuleParam {
+    /// Convert a value given for this parameter to the number the rule receives
+    fn coerce(&self, value: &serde_json::Value) -> Result<f64, String> {
+        let invalid = || format!("Parameter {} must be {} (got {})", self.name, self.kind.describe(), value);
+        match self.kind {
+            ParamKind::Number => value.as_f64().filter(|v| v.is_finite()).ok_or_else(invalid),
+            ParamKind::Integer => value.as_f64().filter(|v| v.is_finite() && v.fract() == 0.0).ok_or_else(invalid),
+            ParamKind::Boolean => value.as_bool().map(|b| if b { 1.0 } else { 0.0 }).ok_or_else(invalid),
+        }
+    }
+}
+
+impl ParamKind {
+    fn describe(self) -> &'static str {
+        match self {
+            ParamKind::Number => "a finite number",
+            ParamKind::Integer => "an integer",
+            ParamKind::Boolean => "a boolean",
+        }
+    }
+}
+
+/// Parse and check a parameter list as given to `defineRuleParams`
+pub(crate) fn parse_params(json: &serde_json::Value) -> Result<Vec<RuleParam>, String> {
+    let params: Vec<RuleParam> = serde_json::from_value(json.clone()).map_err(|e| e.to_string())?;
+    let mut seen = BTreeSet::new();
+    for param in &params {
+        if param.name.is_empty() {
+            return Err("Parameter name cannot be empty".to_string());
+        }
+        if !seen.insert(param.name.as_str()) {
+            return Err(format!("Parameter {} is defined more than once", param.name));
+        }
+        param.coerce(&param.default).map_err(|e| format!("Invalid default: {}", e))?;
+    }
+    Ok(params)
+}
+
+impl RuleRegistry {
+    /// Resolve a sparse `{ name: value }` object to the rule's positional parameters
+    pub(crate) fn resolve_params(&self, id: &str, values: &serde_json::Map<String, serde_json::Value>) -> Result<Vec<f64>, String> {
+        let rule = self.rules.get(id).ok_or_else(|| format!("Rule not found: {}", id))?;
+        if let Some(unknown) = values.keys().find(|name| !rule.params.iter().any(|p| &p.name == *name)) {
+            return Err(format!("Rule {} has no parameter {}", id, unknown));
+        }
+        
+        let mut errors = Vec::new();
+        let mut params = Vec::with_capacity(rule.params.len());
+        for param in &rule.params {
+            match param.coerce(values.get(&param.name).unwrap_or(&param.default)) {
+                Ok(value) => params.push(value),
+                Err(e) => errors.push(e),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+        Ok(params)
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Name a rule's parameters and give them defaults
+    ///
+    /// `json` is an array of `{ name, kind, default }` in positional order,
+    /// where `kind` is "number" (the default), "integer" or "boolean"
+    /// (passed to the rule as 1 or 0). `applyRuleNamed` then accepts a
+    /// sparse object and fills in the rest. The parameters survive
+    /// re-registering the rule; an empty array removes them.
+    #[wasm_bindgen(js_name = defineRuleParams)]
+    pub fn define_rule_params(&mut self, id: &str, json: &str) -> Result<(), JsValue> {
+        let value: serde_json::Value = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse rule parameters: {}", e)))?;
+        let params = parse_params(&value)
+            .map_err(|e| JsValue::from_str(&format!("Invalid rule parameters: {}", e)))?;
+        
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.params = params;
+        Ok(())
+    }
+    
+    /// Get a rule's parameter definitions as JSON
+    #[wasm_bindgen(js_name = getRuleParams)]
+    pub fn get_rule_params(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        serde_json::to_string(&rule.params)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule parameters: {}", e)))
+    }
+}
+
+/// Apply a rule with named parameters, filling omitted ones with their defaults
+///
+/// `params_json` is an object such as `{ "rate": 0.5 }`. Names the rule
+/// doesn't define and values of the wrong kind are errors; the resolved
+/// values are passed to `applyRule` in definition order.
+#[wasm_bindgen(js_name = applyRuleNamed)]
+pub fn apply_rule_named(
+    registry: &mut RuleRegistry,
+    state: &mut OrganismState,
+    rule_id: &str,
+    params_json: &str,
+) -> Result<f64, JsValue> {
+    let values: serde_json::Map<String, serde_json::Value> = serde_json::from_str(params_json)
+        .map_err(|e| JsValue::from_str(&format!("Failed to parse parameters: {}", e)))?;
+    let params = registry.resolve_params(rule_id, &values).map_err(|e| JsValue::from_str(&e))?;
+    apply_rule(registry, state, rule_id, params)
+}
@@ -0,0 +1,177 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::lisp::{Budget, Program};
+use crate::{apply_rule_logic, canonical_metric, OrganismState, RuleRegistry};
+
+/// Tolerance used when a test case does not specify one
+const DEFAULT_TOLERANCE: f64 = 1e-9;
+
+/// Key in `expect` that refers to the rule's return value instead of a metric
+const RESULT_KEY: &str = "result";
+
+fn default_tolerance() -> f64 {
+    DEFAULT_TOLERANCE
+}
+
+/// Expected outcome for one metric: a bare number or a value with its own tolerance
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+enum Expectation {
+    Value(f64),
+    WithTolerance { value: f64, tolerance: f64 },
+}
+
+/// A rule test case: state fixture, parameters and expected outcomes
+///
+/// ```json
+/// {
+///   "name": "raises mutation rate",
+///   "state": { "mutationRate": 0.02 },
+///   "params": [1.0],
+///   "expect": { "mutationRate": 0.03, "result": { "value": 0.0, "tolerance": 0.1 } },
+///   "tolerance": 1e-6
+/// }
+/// ```
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct RuleTestCase {
+    name: String,
+    #[serde(default)]
+    state: BTreeMap<String, f64>,
+    #[serde(default)]
+    params: Vec<f64>,
+    #[serde(default)]
+    expect: BTreeMap<String, Expectation>,
+    #[serde(default = "default_tolerance")]
+    tolerance: f64,
+}
+
+impl RuleTestCase {
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        if self.name.is_empty() {
+            return Err("Test case name cannot be empty".to_string());
+        }
+        if let Some(unknown) = self.state.keys().find(|k| canonical_metric(k).is_none()) {
+            return Err(format!("Unknown metric in test fixture: {}", unknown));
+        }
+        if let Some(unknown) = self.expect.keys().find(|k| *k != RESULT_KEY && canonical_metric(k).is_none()) {
+            return Err(format!("Unknown metric in test expectations: {}", unknown));
+        }
+        Ok(())
+    }
+    
+    /// Run the case against a fresh state built from the fixture
+    fn run(&self, program: &Program, budget: Budget) -> serde_json::Value {
+        let mut state = OrganismState::new();
+        for (metric, value) in &self.state {
+            // Fixture keys were validated when the case was added
+            let _ = state.set_metric(metric, *value);
+        }
+        
+        let (result, error) = match apply_rule_logic(program, &mut state, &self.params, budget) {
+            Ok(result) => (result, None),
+            Err(e) => (f64::NAN, Some(e)),
+        };
+        
+        let mut failures = Vec::new();
+        for (key, expectation) in &self.expect {
+            let (expected, tolerance) = match expectation {
+                Expectation::Value(value) => (*value, self.tolerance),
+                Expectation::WithTolerance { value, tolerance } => (*value, *tolerance),
+            };
+            let actual = if key == RESULT_KEY {
+                result
+            } else {
+                state.metric(key).unwrap_or(f64::NAN)
+            };
+            
+            let within = (actual - expected).abs() <= tolerance;
+            if !within {
+                failures.push(serde_json::json!({
+                    "metric": key,
+                    "expected": expected,
+                    "actual": actual,
+                    "tolerance": tolerance,
+                }));
+            }
+        }
+        
+        serde_json::json!({
+            "name": self.name,
+            "passed": failures.is_empty() && error.is_none(),
+            "result": result,
+            "error": error,
+            "failures": failures,
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Attach a test case to a rule, replacing any existing case with the same name
+    #[wasm_bindgen(js_name = addRuleTest)]
+    pub fn add_rule_test(&mut self, id: &str, test_json: &str) -> Result<(), JsValue> {
+        let case: RuleTestCase = serde_json::from_str(test_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse test case: {}", e)))?;
+        case.validate().map_err(|e| JsValue::from_str(&e))?;
+        
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.tests.retain(|existing| existing.name != case.name);
+        rule.tests.push(case);
+        
+        Ok(())
+    }
+    
+    /// Remove all test cases from a rule
+    #[wasm_bindgen(js_name = clearRuleTests)]
+    pub fn clear_rule_tests(&mut self, id: &str) -> Result<(), JsValue> {
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.tests.clear();
+        Ok(())
+    }
+    
+    /// Get a rule's test cases as JSON
+    #[wasm_bindgen(js_name = getRuleTests)]
+    pub fn get_rule_tests(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        
+        serde_json::to_string(&rule.tests)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize test cases: {}", e)))
+    }
+    
+    /// Run a rule's test cases against cloned fixture states and return a JSON report
+    ///
+    /// Test runs never touch live state and are not counted in the rule's
+    /// execution statistics.
+    #[wasm_bindgen(js_name = runRuleTests)]
+    pub fn run_rule_tests(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        
+        // Rules loaded from a snapshot haven't been compiled yet
+        let compiled;
+        let program = if rule.program.is_compiled() {
+            &rule.program
+        } else {
+            compiled = Program::compile(&rule.lisp_code);
+            &compiled
+        };
+        let results: Vec<serde_json::Value> = rule.tests.iter().map(|case| case.run(program, self.budget(id))).collect();
+        let passed = results.iter().filter(|r| r["passed"] == serde_json::Value::Bool(true)).count();
+        
+        let report = serde_json::json!({
+            "ruleId": id,
+            "total": results.len(),
+            "passed": passed,
+            "failed": results.len() - passed,
+            "results": results,
+        });
+        
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize test report: {}", e)))
+    }
+}
@@ -0,0 +1,88 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::call_log;
+use crate::{Rule, RuleRegistry};
+
+/// Prior versions a rule keeps; registering another drops the oldest
+const MAX_VERSIONS: usize = 64;
+
+/// Code a rule had before it was re-registered
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RuleVersion {
+    version: u32,
+    lisp_code: String,
+    /// When this version was registered, in engine clock milliseconds
+    registered_at: u64,
+}
+
+pub(crate) fn default_version() -> u32 {
+    1
+}
+
+/// The version and history a rule replacing `old` starts with
+pub(crate) fn next_version(old: Option<&Rule>) -> (u32, Vec<RuleVersion>) {
+    let Some(old) = old else {
+        return (default_version(), Vec::new());
+    };
+    let mut versions = old.versions.clone();
+    versions.push(RuleVersion {
+        version: old.version,
+        lisp_code: old.lisp_code.clone(),
+        registered_at: old.created_at,
+    });
+    if versions.len() > MAX_VERSIONS {
+        versions.drain(..versions.len() - MAX_VERSIONS);
+    }
+    (old.version + 1, versions)
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Get a rule's versions as JSON, oldest first
+    ///
+    /// Each entry is `{ version, lispCode, registeredAt, current }`; the
+    /// last is the code the rule runs now. Every `registerRule` under an
+    /// existing ID adds a version, and the last 64 prior versions are
+    /// kept and exported with the registry.
+    #[wasm_bindgen(js_name = getRuleHistory)]
+    pub fn get_rule_history(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        let mut history: Vec<serde_json::Value> = rule.versions
+            .iter()
+            .map(|v| serde_json::json!({
+                "version": v.version,
+                "lispCode": v.lisp_code,
+                "registeredAt": v.registered_at,
+                "current": false,
+            }))
+            .collect();
+        history.push(serde_json::json!({
+            "version": rule.version,
+            "lispCode": rule.lisp_code,
+            "registeredAt": rule.created_at,
+            "current": true,
+        }));
+        serde_json::to_string(&history)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule history: {}", e)))
+    }
+    
+    /// Re-register a rule with the code of one of its prior versions
+    ///
+    /// The rollback is itself a new version, so the code it replaces stays
+    /// in the history. Everything else about the rule is kept, as with
+    /// `registerRule`. Returns the new version number.
+    #[wasm_bindgen(js_name = rollbackRule)]
+    pub fn rollback_rule(&mut self, id: &str, version: u32) -> Result<u32, JsValue> {
+        let _call = call_log::enter("rollbackRule", || serde_json::json!({ "id": id, "version": version }));
+        let rule = self.rules.get(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        let code = rule.versions
+            .iter()
+            .find(|v| v.version == version)
+            .map(|v| v.lisp_code.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Rule {} has no prior version {}", id, version)))?;
+        self.register_rule(id, &code, None)?;
+        Ok(self.rules[id].version)
+    }
+}
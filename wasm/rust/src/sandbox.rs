@@ -0,0 +1,122 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{apply_rule, OrganismState, RuleRegistry};
+
+/// Sandbox is an isolated environment for iterating on experimental rules
+///
+/// It owns private copies of an `OrganismState` and a subset of a
+/// `RuleRegistry`. Nothing done inside a sandbox can reach the live objects it
+/// was created from; results only leave it as copies.
+#[wasm_bindgen]
+pub struct Sandbox {
+    state: OrganismState,
+    registry: RuleRegistry,
+    initial_state: OrganismState,
+    ticks_run: u64,
+}
+
+#[wasm_bindgen]
+impl Sandbox {
+    /// Create a sandbox from copies of a state and the listed rules of a registry
+    #[wasm_bindgen(constructor)]
+    pub fn new(state: &OrganismState, registry: &RuleRegistry, rule_ids: Vec<String>) -> Result<Sandbox, JsValue> {
+        let mut private_registry = RuleRegistry::new();
+        for id in &rule_ids {
+            let rule = registry.rules.get(id).cloned()
+                .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+            private_registry.rules.insert(id.clone(), rule);
+            if !private_registry.execution_order.contains(id) {
+                private_registry.execution_order.push(id.clone());
+            }
+        }
+        private_registry.modes = registry.modes.clone();
+        private_registry.clear_stats();
+        
+        Ok(Sandbox {
+            state: state.clone(),
+            registry: private_registry,
+            initial_state: state.clone(),
+            ticks_run: 0,
+        })
+    }
+    
+    /// Register or replace a rule inside the sandbox only
+    #[wasm_bindgen(js_name = registerRule)]
+    pub fn register_rule(&mut self, id: &str, lisp_code: &str) -> Result<(), JsValue> {
+        self.registry.register_rule(id, lisp_code, None)
+    }
+    
+    /// Remove a rule from the sandbox
+    #[wasm_bindgen(js_name = removeRule)]
+    pub fn remove_rule(&mut self, id: &str) -> bool {
+        self.registry.remove_rule(id)
+    }
+    
+    /// Get the rule IDs available in the sandbox
+    #[wasm_bindgen(js_name = getRuleIds)]
+    pub fn get_rule_ids(&self) -> Vec<String> {
+        self.registry.get_rule_ids()
+    }
+    
+    /// Apply a single sandbox rule to the sandbox state
+    #[wasm_bindgen(js_name = applyRule)]
+    pub fn apply_rule(&mut self, rule_id: &str, params: Vec<f64>) -> Result<f64, JsValue> {
+        apply_rule(&mut self.registry, &mut self.state, rule_id, params)
+    }
+    
+    /// Run the sandbox for a number of ticks
+    ///
+    /// Each tick applies every enabled sandbox rule in execution order with
+    /// `params` and then steps the state by `delta_time`. Returns the
+    /// adaptation score after each tick.
+    #[wasm_bindgen]
+    pub fn run(&mut self, ticks: u32, delta_time: f64, params: Vec<f64>) -> Result<Vec<f64>, JsValue> {
+        let rule_ids: Vec<String> = self.registry.get_rule_ids()
+            .into_iter()
+            .filter(|id| self.registry.rules.get(id).is_some_and(|rule| rule.enabled))
+            .collect();
+        let mut scores = Vec::with_capacity(ticks as usize);
+        
+        for _ in 0..ticks {
+            for id in &rule_ids {
+                apply_rule(&mut self.registry, &mut self.state, id, params.clone())?;
+            }
+            scores.push(self.state.step(delta_time));
+            self.ticks_run += 1;
+        }
+        
+        Ok(scores)
+    }
+    
+    /// Get the number of ticks run since creation or the last reset
+    #[wasm_bindgen(getter, js_name = ticksRun)]
+    pub fn ticks_run(&self) -> u64 {
+        self.ticks_run
+    }
+    
+    /// Restore the sandbox state to the copy it was created with
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.state = self.initial_state.clone();
+        self.registry.clear_stats();
+        self.ticks_run = 0;
+    }
+    
+    /// Get a copy of the sandbox state
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state(&self) -> OrganismState {
+        self.state.clone()
+    }
+    
+    /// Get a snapshot of the sandbox state as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        self.state.get_snapshot()
+    }
+    
+    /// Get execution statistics for the sandbox rules as JSON
+    #[wasm_bindgen(js_name = getAllStats)]
+    pub fn get_all_stats(&self) -> Result<String, JsValue> {
+        self.registry.get_all_stats()
+    }
+}
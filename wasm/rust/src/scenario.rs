@@ -0,0 +1,239 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::import_guard;
+use crate::simulation::Simulation;
+
+/// What a scenario event does when its tick comes
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "camelCase", deny_unknown_fields)]
+enum Action {
+    /// Change state fields, as `patchState`
+    Set { values: serde_json::Map<String, serde_json::Value> },
+    /// Remove a fraction of a metric at once ("a plague kills 40%")
+    Catastrophe { metric: String, fraction: f64 },
+    /// Multiply a metric by a factor
+    Scale { metric: String, factor: f64 },
+    RegisterRule { id: String, code: String },
+    RemoveRule { id: String },
+    /// Turn a rule off until it is enabled again, as `setRuleEnabled`
+    DisableRule { id: String },
+    EnableRule { id: String },
+    ApplyRule { id: String, #[serde(default)] params: Vec<f64> },
+    /// Reseed the noise RNG
+    Seed { seed: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Event {
+    tick: u64,
+    #[serde(flatten)]
+    action: Action,
+}
+
+fn default_dt() -> f64 {
+    1.0
+}
+
+/// Scenario file as written in JSON
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ScenarioSpec {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default = "default_dt")]
+    dt: f64,
+    duration: u64,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+/// Scenario is a timeline of events played against a `Simulation`
+///
+/// ```json
+/// {
+///   "name": "drought", "dt": 0.1, "duration": 500,
+///   "events": [
+///     { "tick": 0, "type": "set", "values": { "population": 200 } },
+///     { "tick": 100, "type": "disableRule", "id": "growth" },
+///     { "tick": 150, "type": "catastrophe", "metric": "population", "fraction": 0.4 },
+///     { "tick": 300, "type": "enableRule", "id": "growth" }
+///   ]
+/// }
+/// ```
+///
+/// Events with the same tick run in file order, before that tick's rules
+/// and step. The scenario touches nothing but the simulation, so a run
+/// from the same starting simulation is always the same.
+#[wasm_bindgen]
+pub struct Scenario {
+    name: String,
+    description: String,
+    dt: f64,
+    duration: u64,
+    events: Vec<Event>,
+    next_event: usize,
+}
+
+impl Scenario {
+    fn apply(&self, simulation: &mut Simulation, action: &Action) -> Result<(), String> {
+        match action {
+            Action::Set { values } => {
+                let json = serde_json::Value::Object(values.clone()).to_string();
+                simulation.state_mut().patch_state(&json).map_err(|e| e.as_string().unwrap_or_default())?;
+            }
+            Action::Catastrophe { metric, fraction } => {
+                scale_metric(simulation, metric, 1.0 - fraction)?;
+            }
+            Action::Scale { metric, factor } => scale_metric(simulation, metric, *factor)?,
+            Action::RegisterRule { id, code } => {
+                simulation.registry_mut().register_rule(id, code, None).map_err(|e| e.as_string().unwrap_or_default())?;
+            }
+            Action::RemoveRule { id } => {
+                if !simulation.registry_mut().remove_rule(id) {
+                    return Err(format!("Rule not found: {}", id));
+                }
+            }
+            Action::DisableRule { id } => {
+                let rule = simulation.registry_mut().rules.get_mut(id).ok_or_else(|| format!("Rule not found: {}", id))?;
+                rule.enabled = false;
+            }
+            Action::EnableRule { id } => {
+                let rule = simulation.registry_mut().rules.get_mut(id).ok_or_else(|| format!("Rule not found: {}", id))?;
+                if rule.enabled {
+                    return Err(format!("Rule is not disabled: {}", id));
+                }
+                rule.enabled = true;
+            }
+            Action::ApplyRule { id, params } => {
+                simulation.apply_rule(id, params.clone()).map_err(|e| e.as_string().unwrap_or_default())?;
+            }
+            Action::Seed { seed } => simulation.state_mut().set_noise_seed(*seed),
+        }
+        Ok(())
+    }
+}
+
+fn scale_metric(simulation: &mut Simulation, metric: &str, factor: f64) -> Result<(), String> {
+    let state = simulation.state_mut();
+    let value = state.read_any_metric(metric).ok_or_else(|| format!("Unknown metric: {}", metric))?;
+    let metric = state.writable_metric(metric)?;
+    state.write_any_metric(&metric, value * factor);
+    Ok(())
+}
+
+#[wasm_bindgen]
+impl Scenario {
+    /// Read a scenario from JSON, checking every event before any can run
+    #[wasm_bindgen(js_name = fromJson)]
+    pub fn from_json(json: &str) -> Result<Scenario, JsValue> {
+        import_guard::check_structure("Scenario", json, true).map_err(|e| JsValue::from_str(&e))?;
+        let spec: ScenarioSpec = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse scenario: {}", e)))?;
+        
+        let mut errors = Vec::new();
+        if !spec.dt.is_finite() || spec.dt <= 0.0 {
+            errors.push(format!("dt must be a positive number (got {})", spec.dt));
+        }
+        for (index, event) in spec.events.iter().enumerate() {
+            let problem = match &event.action {
+                Action::Catastrophe { fraction, .. } if !(0.0..=1.0).contains(fraction) => {
+                    Some(format!("fraction must be between 0 and 1 (got {})", fraction))
+                }
+                Action::Scale { factor, .. } if !factor.is_finite() => {
+                    Some(format!("factor must be a finite number (got {})", factor))
+                }
+                _ if event.tick >= spec.duration => {
+                    Some(format!("tick {} is not before the duration {}", event.tick, spec.duration))
+                }
+                _ => None,
+            };
+            if let Some(problem) = problem {
+                errors.push(format!("events.{}: {}", index, problem));
+            }
+        }
+        if !errors.is_empty() {
+            return Err(JsValue::from_str(&format!("Invalid scenario: {}", errors.join("; "))));
+        }
+        
+        let mut events = spec.events;
+        events.sort_by_key(|event| event.tick);
+        Ok(Scenario {
+            name: spec.name,
+            description: spec.description,
+            dt: spec.dt,
+            duration: spec.duration,
+            events,
+            next_event: 0,
+        })
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn description(&self) -> String {
+        self.description.clone()
+    }
+    
+    /// Ticks the scenario runs for
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> u64 {
+        self.duration
+    }
+    
+    /// Whether `simulation` has reached the end of the scenario
+    #[wasm_bindgen(js_name = isFinished)]
+    pub fn is_finished(&self, simulation: &Simulation) -> bool {
+        simulation.ticks() >= self.duration
+    }
+    
+    /// Run the events due at the simulation's current tick, then tick it by `dt`
+    ///
+    /// Returns the tick summary with an `events` array of the events run.
+    /// A failing event (e.g. disabling a rule that doesn't exist) throws
+    /// with its tick and index.
+    #[wasm_bindgen]
+    pub fn step(&mut self, simulation: &mut Simulation) -> Result<String, JsValue> {
+        let tick = simulation.ticks();
+        let mut ran = Vec::new();
+        while let Some(event) = self.events.get(self.next_event).filter(|event| event.tick <= tick).cloned() {
+            self.apply(simulation, &event.action).map_err(|e| {
+                JsValue::from_str(&format!("Scenario event {} at tick {} failed: {}", self.next_event, event.tick, e))
+            })?;
+            ran.push(serde_json::to_value(&event).unwrap_or_default());
+            self.next_event += 1;
+        }
+        if !ran.is_empty() {
+            simulation.record_replacement();
+        }
+        
+        let summary = simulation.tick(self.dt)?;
+        let mut summary: serde_json::Value = serde_json::from_str(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read tick summary: {}", e)))?;
+        if let Some(object) = summary.as_object_mut() {
+            object.insert("events".to_string(), serde_json::json!(ran));
+        }
+        Ok(summary.to_string())
+    }
+    
+    /// Step the simulation until the end of the scenario, returning the number of ticks run
+    #[wasm_bindgen]
+    pub fn run(&mut self, simulation: &mut Simulation) -> Result<u64, JsValue> {
+        let start = simulation.ticks();
+        while !self.is_finished(simulation) {
+            self.step(simulation)?;
+        }
+        Ok(simulation.ticks() - start)
+    }
+    
+    /// Rewind the scenario to its first event, to play it against another simulation
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.next_event = 0;
+    }
+}
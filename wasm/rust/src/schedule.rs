@@ -0,0 +1,130 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::call_log;
+use crate::triggers::TriggerValues;
+use crate::{OrganismState, RuleRegistry};
+
+/// When `tick` runs a rule, on top of its modes and hotspot throttling
+///
+/// The default runs the rule on every tick.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct RuleSchedule {
+    /// Run on steps where `(step - offset) % every == 0`
+    #[serde(default)]
+    every: Option<u32>,
+    #[serde(default)]
+    offset: u32,
+    /// First step the rule runs on
+    #[serde(default)]
+    start: Option<u64>,
+    /// Step the rule stops running on (exclusive)
+    #[serde(default)]
+    end: Option<u64>,
+    /// Only run on ticks where the state's generation changed since the last tick
+    #[serde(default)]
+    generation_boundary: bool,
+}
+
+impl RuleSchedule {
+    fn check(&self) -> Result<(), String> {
+        if self.every == Some(0) {
+            return Err("every must be at least 1".to_string());
+        }
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            if end <= start {
+                return Err(format!("end ({}) must be after start ({})", end, start));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Whether the rule is scheduled for `step`
+    pub(crate) fn is_due(&self, step: u32, generation_changed: bool) -> bool {
+        let step = step as u64;
+        let periodic = match self.every {
+            Some(every) if every > 1 => step >= self.offset as u64 && (step - self.offset as u64).is_multiple_of(every as u64),
+            _ => step >= self.offset as u64,
+        };
+        periodic
+            && self.start.is_none_or(|start| step >= start)
+            && self.end.is_none_or(|end| step < end)
+            && (!self.generation_boundary || generation_changed)
+    }
+}
+
+impl RuleRegistry {
+    /// Whether `generation` differs from the one the last tick saw
+    ///
+    /// The first tick a registry runs is not a boundary.
+    pub(crate) fn generation_changed(&self, generation: u64) -> bool {
+        self.last_generation.is_some_and(|last| last != generation)
+    }
+    
+    /// Rules a tick at `step` runs while `mode` is active and `triggers` hold, in run order
+    pub(crate) fn due_rules(
+        &self,
+        mode: Option<&str>,
+        step: u32,
+        generation_changed: bool,
+        triggers: &TriggerValues,
+    ) -> Vec<&String> {
+        self.run_order()
+            .into_iter()
+            .filter(|id| {
+                self.rules.get(*id).is_some_and(|rule| {
+                    rule.runs_in(mode)
+                        && rule.hotspot.is_due(step)
+                        && rule.schedule.is_due(step, generation_changed)
+                        && rule.trigger.as_ref().is_none_or(|t| t.fires(triggers.get(*id).copied().unwrap_or(false)))
+                })
+            })
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Set when `tick` runs a rule, from JSON
+    ///
+    /// `json` is `{ every, offset, start, end, generationBoundary }`, all
+    /// optional: the rule runs on steps `offset`, `offset + every`, ...,
+    /// only from step `start` and before step `end`, and, with
+    /// `generationBoundary`, only on ticks where the state's generation
+    /// changed since the previous tick. `{}` runs it on every tick. The
+    /// schedule survives re-registering the rule and is exported with the
+    /// registry.
+    #[wasm_bindgen(js_name = setRuleSchedule)]
+    pub fn set_rule_schedule(&mut self, id: &str, json: &str) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleSchedule", || serde_json::json!({ "id": id, "schedule": call_log::text(json) }));
+        let schedule: RuleSchedule = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse rule schedule: {}", e)))?;
+        schedule.check().map_err(|e| JsValue::from_str(&format!("Invalid rule schedule: {}", e)))?;
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.schedule = schedule;
+        Ok(())
+    }
+    
+    /// Get a rule's schedule as JSON
+    #[wasm_bindgen(js_name = getRuleSchedule)]
+    pub fn get_rule_schedule(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        serde_json::to_string(&rule.schedule)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule schedule: {}", e)))
+    }
+    
+    /// IDs of the rules `tick` would run at `step_index` for `state`, in order
+    ///
+    /// Takes modes, enabled flags, hotspot throttling, schedules and
+    /// triggers into account without running anything.
+    #[wasm_bindgen(js_name = getDueRules)]
+    pub fn get_due_rules(&self, state: &OrganismState, step_index: u32) -> Result<Vec<String>, JsValue> {
+        let mode = self.evaluate_mode(state)?;
+        let triggers = self.evaluate_triggers(state, mode.as_deref())?;
+        Ok(self.due_rules(mode.as_deref(), step_index, self.generation_changed(state.generation()), &triggers)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+}
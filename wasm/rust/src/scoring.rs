@@ -0,0 +1,412 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::clock;
+use crate::hash::{hmac_sha256, sha256, to_hex};
+use crate::{OrganismState, RuleRegistry};
+
+/// Format tag written into every score export
+const SCORE_FORMAT: &str = "ouroboros-run-score";
+
+/// Score export layout version
+const SCORE_VERSION: u32 = 1;
+
+/// Bins of the adaptation histogram behind the diversity component
+const DIVERSITY_BINS: usize = 16;
+
+/// Highest adaptation score the model can reach (population, energy and age all capped)
+const MAX_ADAPTATION: f64 = 4.0 / 3.0;
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+fn default_target_ticks() -> u64 {
+    1000
+}
+
+fn default_min_population() -> f64 {
+    10.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ScoreWeights {
+    #[serde(default = "default_weight")]
+    longevity: f64,
+    #[serde(default = "default_weight")]
+    peak_adaptation: f64,
+    #[serde(default = "default_weight")]
+    diversity: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct ScoreOptions {
+    #[serde(default = "default_weights")]
+    weights: ScoreWeights,
+    /// Ticks of survival that earn full longevity
+    #[serde(default = "default_target_ticks")]
+    target_ticks: u64,
+    /// Population below which the run counts as collapsed
+    #[serde(default = "default_min_population")]
+    min_population: f64,
+}
+
+fn default_weights() -> ScoreWeights {
+    ScoreWeights { longevity: 1.0, peak_adaptation: 1.0, diversity: 1.0 }
+}
+
+impl ScoreOptions {
+    fn parse(json: &str) -> Result<ScoreOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: ScoreOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let weights = &options.weights;
+        let mut problems = Vec::new();
+        for (name, value) in [
+            ("longevity", weights.longevity),
+            ("peakAdaptation", weights.peak_adaptation),
+            ("diversity", weights.diversity),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(format!("weight {} must be non-negative, got {}", name, value));
+            }
+        }
+        if weights.longevity + weights.peak_adaptation + weights.diversity <= 0.0 {
+            problems.push("at least one weight must be positive".to_string());
+        }
+        if options.target_ticks == 0 {
+            problems.push("targetTicks must be positive".to_string());
+        }
+        if !options.min_population.is_finite() {
+            problems.push(format!("minPopulation must be finite, got {}", options.min_population));
+        }
+        if problems.is_empty() {
+            Ok(options)
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// RunScorer turns a run into one comparable score
+///
+/// Call `record(state)` after every tick. The score is the weighted mean
+/// of three components, each from 0 to 1:
+///
+/// - longevity: ticks before the population first fell below
+///   `minPopulation` (or energy ran out), over `targetTicks`
+/// - peak adaptation: the best adaptation score seen, over the most the
+///   model can reach
+/// - diversity: how evenly the run spread over the range of adaptation
+///   scores (normalized entropy of a 16-bin histogram), so runs that sit
+///   in one regime score lower than ones that explore
+///
+/// `exportScore` writes the result as a manifest that a `Leaderboard`
+/// can check.
+#[wasm_bindgen]
+pub struct RunScorer {
+    options: ScoreOptions,
+    ticks: u64,
+    survived_ticks: u64,
+    collapsed: bool,
+    peak_adaptation: f64,
+    histogram: [u64; DIVERSITY_BINS],
+}
+
+impl RunScorer {
+    fn components(&self) -> (f64, f64, f64) {
+        let longevity = (self.survived_ticks as f64 / self.options.target_ticks as f64).min(1.0);
+        let peak = (self.peak_adaptation / MAX_ADAPTATION).clamp(0.0, 1.0);
+        let total: u64 = self.histogram.iter().sum();
+        let diversity = if total == 0 {
+            0.0
+        } else {
+            let entropy: f64 = self.histogram
+                .iter()
+                .filter(|count| **count > 0)
+                .map(|count| {
+                    let p = *count as f64 / total as f64;
+                    -p * p.ln()
+                })
+                .sum();
+            entropy / (DIVERSITY_BINS as f64).ln()
+        };
+        (longevity, peak, diversity)
+    }
+    
+    fn breakdown(&self) -> serde_json::Value {
+        let (longevity, peak, diversity) = self.components();
+        serde_json::json!({
+            "score": self.score(),
+            "components": {
+                "longevity": longevity,
+                "peakAdaptation": peak,
+                "diversity": diversity,
+            },
+            "weights": self.options.weights,
+            "ticks": self.ticks,
+            "survivedTicks": self.survived_ticks,
+            "collapsed": self.collapsed,
+            "peakAdaptation": self.peak_adaptation,
+        })
+    }
+}
+
+/// Hash of a registry's rules and their order, ignoring statistics
+fn ruleset_hash(registry: &RuleRegistry) -> String {
+    let rules: Vec<(&String, &String)> = registry.execution_order
+        .iter()
+        .filter_map(|id| registry.rules.get(id).map(|rule| (id, &rule.lisp_code)))
+        .collect();
+    let canonical = serde_json::json!(rules).to_string();
+    to_hex(&sha256(canonical.as_bytes()))
+}
+
+/// Sign the canonical text of a manifest body (its JSON with sorted keys)
+fn signature(body: &serde_json::Value, key: &str) -> serde_json::Value {
+    let canonical = body.to_string();
+    if key.is_empty() {
+        serde_json::json!({ "algorithm": "sha256", "value": to_hex(&sha256(canonical.as_bytes())) })
+    } else {
+        serde_json::json!({
+            "algorithm": "hmac-sha256",
+            "value": to_hex(&hmac_sha256(key.as_bytes(), canonical.as_bytes())),
+        })
+    }
+}
+
+/// Check a score export, returning its body when the signature matches `key`
+fn verify(export_json: &str, key: &str) -> Result<serde_json::Value, String> {
+    let mut body: serde_json::Value = serde_json::from_str(export_json).map_err(|e| e.to_string())?;
+    let object = body.as_object_mut().ok_or("Score export must be an object")?;
+    let claimed = object.remove("signature").ok_or("Score export is not signed")?;
+    if object.get("format").and_then(|format| format.as_str()) != Some(SCORE_FORMAT) {
+        return Err(format!("Not a {} export", SCORE_FORMAT));
+    }
+    if object.get("version").and_then(|version| version.as_u64()) != Some(SCORE_VERSION as u64) {
+        return Err(format!("Unsupported score export version: {}", object.get("version").unwrap_or(&serde_json::Value::Null)));
+    }
+    if claimed != signature(&body, key) {
+        return Err("Score export signature does not match".to_string());
+    }
+    Ok(body)
+}
+
+#[wasm_bindgen]
+impl RunScorer {
+    /// Create a scorer from options JSON (may be empty for the defaults)
+    ///
+    /// `{ weights: { longevity, peakAdaptation, diversity }, targetTicks,
+    /// minPopulation }`; every weight defaults to 1, `targetTicks` to 1000
+    /// and `minPopulation` to 10.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options_json: &str) -> Result<RunScorer, JsValue> {
+        let options = ScoreOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid score options: {}", e)))?;
+        Ok(RunScorer {
+            options,
+            ticks: 0,
+            survived_ticks: 0,
+            collapsed: false,
+            peak_adaptation: 0.0,
+            histogram: [0; DIVERSITY_BINS],
+        })
+    }
+    
+    /// Record the state after a tick
+    #[wasm_bindgen]
+    pub fn record(&mut self, state: &OrganismState) {
+        self.ticks += 1;
+        let viable = state.population() >= self.options.min_population && state.energy() > 0.0;
+        if !viable {
+            self.collapsed = true;
+        }
+        if !self.collapsed {
+            self.survived_ticks += 1;
+        }
+        
+        let adaptation = state.adaptation_score();
+        if adaptation.is_finite() {
+            self.peak_adaptation = self.peak_adaptation.max(adaptation);
+            let bin = ((adaptation / MAX_ADAPTATION).clamp(0.0, 1.0) * DIVERSITY_BINS as f64) as usize;
+            self.histogram[bin.min(DIVERSITY_BINS - 1)] += 1;
+        }
+    }
+    
+    /// The weighted score so far, 0 to 1
+    #[wasm_bindgen]
+    pub fn score(&self) -> f64 {
+        let (longevity, peak, diversity) = self.components();
+        let weights = &self.options.weights;
+        let total = weights.longevity + weights.peak_adaptation + weights.diversity;
+        (weights.longevity * longevity + weights.peak_adaptation * peak + weights.diversity * diversity) / total
+    }
+    
+    /// Ticks recorded
+    #[wasm_bindgen(getter)]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    
+    /// Get the score, its components and the raw measurements as JSON
+    #[wasm_bindgen(js_name = getBreakdown)]
+    pub fn get_breakdown(&self) -> String {
+        self.breakdown().to_string()
+    }
+    
+    /// Forget everything recorded, keeping the options
+    #[wasm_bindgen]
+    pub fn reset(&mut self) {
+        self.ticks = 0;
+        self.survived_ticks = 0;
+        self.collapsed = false;
+        self.peak_adaptation = 0.0;
+        self.histogram = [0; DIVERSITY_BINS];
+    }
+    
+    /// Export the score as a signed run manifest
+    ///
+    /// The manifest carries the breakdown, a SHA-256 hash of the rule set
+    /// that produced it (ids and code in execution order) and `meta_json`,
+    /// free-form details such as the player or challenge. With a `key` it
+    /// is signed with HMAC-SHA256, so only holders of the key can produce
+    /// exports a leaderboard accepts; without one it only carries a
+    /// SHA-256 digest that catches accidental edits.
+    #[wasm_bindgen(js_name = exportScore)]
+    pub fn export_score(&self, registry: &RuleRegistry, meta_json: &str, key: &str) -> Result<String, JsValue> {
+        let meta: serde_json::Value = if meta_json.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str(meta_json)
+                .map_err(|e| JsValue::from_str(&format!("Failed to parse score metadata: {}", e)))?
+        };
+        
+        let mut body = serde_json::json!({
+            "format": SCORE_FORMAT,
+            "version": SCORE_VERSION,
+            "createdAt": clock::now_ms(),
+            "rulesetHash": ruleset_hash(registry),
+            "ruleCount": registry.execution_order.len(),
+            "meta": meta,
+        });
+        if let (Some(object), serde_json::Value::Object(breakdown)) = (body.as_object_mut(), self.breakdown()) {
+            object.extend(breakdown);
+        }
+        let signature = signature(&body, key);
+        if let Some(object) = body.as_object_mut() {
+            object.insert("signature".to_string(), signature);
+        }
+        Ok(body.to_string())
+    }
+    
+    /// Whether a score export is intact and signed with `key` (empty for unsigned digests)
+    #[wasm_bindgen(js_name = verifyExport)]
+    pub fn verify_export(export_json: &str, key: &str) -> bool {
+        verify(export_json, key).is_ok()
+    }
+}
+
+/// A verified entry on a leaderboard
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct LeaderboardEntry {
+    score: f64,
+    ruleset_hash: String,
+    ticks: u64,
+    submitted: u64,
+    meta: serde_json::Value,
+    signature: String,
+}
+
+/// Leaderboard ranks score exports that verify against its key
+///
+/// Exports with a bad signature are rejected, as is the same export
+/// submitted twice. Entries rank by score, then by ticks, then by who
+/// submitted first.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct Leaderboard {
+    #[serde(skip)]
+    key: String,
+    entries: Vec<LeaderboardEntry>,
+    submissions: u64,
+}
+
+#[wasm_bindgen]
+impl Leaderboard {
+    /// Create a leaderboard that accepts exports signed with `key` (empty for unsigned digests)
+    #[wasm_bindgen(constructor)]
+    pub fn new(key: &str) -> Leaderboard {
+        Leaderboard { key: key.to_string(), ..Leaderboard::default() }
+    }
+    
+    /// Verify and add a score export, returning its 1-based rank
+    #[wasm_bindgen]
+    pub fn submit(&mut self, export_json: &str) -> Result<usize, JsValue> {
+        let fail = |e: String| JsValue::from_str(&format!("Failed to submit score: {}", e));
+        let body = verify(export_json, &self.key).map_err(fail)?;
+        let signature = signature(&body, &self.key)["value"].as_str().unwrap_or_default().to_string();
+        if self.entries.iter().any(|entry| entry.signature == signature) {
+            return Err(fail("This export was already submitted".to_string()));
+        }
+        
+        let score = body["score"].as_f64().filter(|score| score.is_finite())
+            .ok_or_else(|| fail("Score export has no score".to_string()))?;
+        let entry = LeaderboardEntry {
+            score,
+            ruleset_hash: body["rulesetHash"].as_str().unwrap_or_default().to_string(),
+            ticks: body["ticks"].as_u64().unwrap_or_default(),
+            submitted: self.submissions,
+            meta: body["meta"].clone(),
+            signature,
+        };
+        self.submissions += 1;
+        
+        let rank = self.entries.partition_point(|existing| {
+            existing.score > entry.score || (existing.score == entry.score && existing.ticks >= entry.ticks)
+        });
+        self.entries.insert(rank, entry);
+        Ok(rank + 1)
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.entries.len()
+    }
+    
+    /// Get the ranked entries as JSON (`[{ rank, score, rulesetHash, ticks, meta }]`)
+    #[wasm_bindgen(js_name = getEntries)]
+    pub fn get_entries(&self, limit: Option<usize>) -> String {
+        let entries: Vec<serde_json::Value> = self.entries
+            .iter()
+            .take(limit.unwrap_or(usize::MAX))
+            .enumerate()
+            .map(|(index, entry)| serde_json::json!({
+                "rank": index + 1,
+                "score": entry.score,
+                "rulesetHash": entry.ruleset_hash,
+                "ticks": entry.ticks,
+                "meta": entry.meta,
+            }))
+            .collect();
+        serde_json::Value::Array(entries).to_string()
+    }
+    
+    /// Get the leaderboard as JSON (the key is not included)
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize leaderboard: {}", e)))
+    }
+    
+    /// Restore entries from a snapshot, keeping this leaderboard's key
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: Leaderboard = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse leaderboard: {}", e)))?;
+        self.entries = loaded.entries;
+        self.submissions = loaded.submissions;
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+use crate::world::World;
+
+/// Largest tournament, reached at a selection pressure of 1
+const MAX_TOURNAMENT: f64 = 8.0;
+
+/// How organisms are picked to reproduce
+///
+/// `selection_pressure` (0 to 1) sets how strongly each strategy favors
+/// the fittest; at 0 every strategy picks uniformly at random.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum SelectionStrategy {
+    /// The fittest of a random group, of 1 (pressure 0) to 8 (pressure 1)
+    #[default]
+    Tournament,
+    /// Odds in proportion to fitness raised to twice the pressure
+    Roulette,
+    /// Linear ranking, the best twice as likely as average at pressure 1
+    Rank,
+    /// Uniformly among the fittest 100% (pressure 0) to 10% (pressure 1)
+    Truncation,
+}
+
+impl SelectionStrategy {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            SelectionStrategy::Tournament => "tournament",
+            SelectionStrategy::Roulette => "roulette",
+            SelectionStrategy::Rank => "rank",
+            SelectionStrategy::Truncation => "truncation",
+        }
+    }
+    
+    pub(crate) fn from_name(name: &str) -> Result<SelectionStrategy, String> {
+        serde_json::from_value(serde_json::json!(name)).map_err(|_| {
+            format!("Unknown selection strategy: {} (expected tournament, roulette, rank or truncation)", name)
+        })
+    }
+}
+
+/// Pick `count` indices into `fitness`, with replacement
+pub(crate) fn select(
+    strategy: SelectionStrategy,
+    pressure: f64,
+    fitness: &[f64],
+    count: usize,
+    rng: &mut Rng,
+) -> Vec<usize> {
+    let n = fitness.len();
+    if n == 0 {
+        return Vec::new();
+    }
+    let pressure = pressure.clamp(0.0, 1.0);
+    let pick = |rng: &mut Rng, len: usize| ((rng.next_f64() * len as f64) as usize).min(len - 1);
+    
+    match strategy {
+        SelectionStrategy::Tournament => {
+            let size = 1 + (pressure * (MAX_TOURNAMENT - 1.0)).round() as usize;
+            (0..count)
+                .map(|_| {
+                    let mut best = pick(rng, n);
+                    for _ in 1..size {
+                        let challenger = pick(rng, n);
+                        if fitness[challenger] > fitness[best] {
+                            best = challenger;
+                        }
+                    }
+                    best
+                })
+                .collect()
+        }
+        SelectionStrategy::Roulette => {
+            // Shift so the least fit still has a sliver of a chance
+            let floor = fitness.iter().cloned().fold(f64::INFINITY, f64::min);
+            let weights: Vec<f64> = fitness
+                .iter()
+                .map(|f| (f - floor + f64::EPSILON).powf(2.0 * pressure))
+                .collect();
+            sample_weighted(&weights, count, rng)
+        }
+        SelectionStrategy::Rank => {
+            let order = ranked(fitness);
+            let bias = 1.0 + pressure;
+            let mut weights = vec![0.0; n];
+            for (rank, &index) in order.iter().rev().enumerate() {
+                // Rank 0 is the least fit
+                weights[index] = if n == 1 {
+                    1.0
+                } else {
+                    (2.0 - bias) / n as f64 + 2.0 * rank as f64 * (bias - 1.0) / (n * (n - 1)) as f64
+                };
+            }
+            sample_weighted(&weights, count, rng)
+        }
+        SelectionStrategy::Truncation => {
+            let order = ranked(fitness);
+            let keep = ((n as f64 * (1.0 - 0.9 * pressure)).ceil() as usize).clamp(1, n);
+            (0..count).map(|_| order[pick(rng, keep)]).collect()
+        }
+    }
+}
+
+/// Indices sorted from the fittest down
+fn ranked(fitness: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..fitness.len()).collect();
+    order.sort_by(|a, b| fitness[*b].partial_cmp(&fitness[*a]).unwrap_or(std::cmp::Ordering::Equal));
+    order
+}
+
+fn sample_weighted(weights: &[f64], count: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut cumulative = Vec::with_capacity(weights.len());
+    let mut total = 0.0;
+    for weight in weights {
+        total += if weight.is_finite() { weight.max(0.0) } else { 0.0 };
+        cumulative.push(total);
+    }
+    (0..count)
+        .map(|_| {
+            let target = rng.next_f64() * total;
+            cumulative.partition_point(|c| *c <= target).min(weights.len() - 1)
+        })
+        .collect()
+}
+
+#[wasm_bindgen]
+impl World {
+    /// `tournament`, `roulette`, `rank` or `truncation`
+    #[wasm_bindgen(getter, js_name = selectionStrategy)]
+    pub fn selection_strategy(&self) -> String {
+        self.options.selection.name().to_string()
+    }
+    
+    #[wasm_bindgen(js_name = setSelectionStrategy)]
+    pub fn set_selection_strategy(&mut self, name: &str) -> Result<(), JsValue> {
+        self.options.selection = SelectionStrategy::from_name(name).map_err(|e| JsValue::from_str(&e))?;
+        Ok(())
+    }
+    
+    /// How strongly selection favors the fittest, from 0 (not at all) to 1
+    #[wasm_bindgen(getter, js_name = selectionPressure)]
+    pub fn selection_pressure(&self) -> f64 {
+        self.options.selection_pressure
+    }
+    
+    #[wasm_bindgen(setter, js_name = selectionPressure)]
+    pub fn set_selection_pressure(&mut self, value: f64) {
+        if value.is_finite() {
+            self.options.selection_pressure = value.clamp(0.0, 1.0);
+        }
+    }
+    
+    /// Pick `count` organisms to reproduce and return their IDs
+    ///
+    /// Organisms are picked with replacement by adaptation score, using the
+    /// world's selection strategy and pressure, so a fit organism can appear
+    /// several times.
+    #[wasm_bindgen]
+    pub fn select(&mut self, count: usize) -> Vec<u32> {
+        self.select_indices(count).into_iter().map(|i| self.organisms[i].id).collect()
+    }
+}
+
+impl World {
+    pub(crate) fn select_indices(&mut self, count: usize) -> Vec<usize> {
+        let fitness: Vec<f64> = self.organisms.iter().map(|o| o.adaptation_score).collect();
+        select(self.options.selection, self.options.selection_pressure, &fitness, count, &mut self.rng)
+    }
+}
@@ -0,0 +1,157 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeMap;
+
+use crate::call_log;
+use crate::OrganismState;
+
+/// Outcome metrics of one run of `ticks` steps from `start`
+fn run_outcomes(
+    start: &OrganismState,
+    ticks: u32,
+    delta_time: f64,
+    outcomes: &[String],
+) -> Result<BTreeMap<String, f64>, String> {
+    let mut state = start.clone();
+    for _ in 0..ticks {
+        state.step(delta_time);
+    }
+    outcomes
+        .iter()
+        .map(|name| {
+            state.read_any_metric(name)
+                .map(|value| (name.clone(), value))
+                .ok_or_else(|| format!("Unknown outcome metric: {}", name))
+        })
+        .collect()
+}
+
+/// Compare baseline and perturbed outcomes
+///
+/// `elasticity` is the relative change of the outcome per relative change of
+/// the parameter; it is null when either reference value is zero.
+fn effects(
+    baseline: &BTreeMap<String, f64>,
+    perturbed: &BTreeMap<String, f64>,
+    base_param: f64,
+    delta: f64,
+) -> serde_json::Value {
+    let effects: serde_json::Map<String, serde_json::Value> = baseline
+        .iter()
+        .map(|(name, before)| {
+            let after = perturbed[name];
+            let change = after - before;
+            let relative = (*before != 0.0).then(|| change / before);
+            let elasticity = relative.filter(|_| base_param != 0.0 && delta != 0.0)
+                .map(|relative| relative / (delta / base_param));
+            (name.clone(), serde_json::json!({
+                "baseline": before,
+                "perturbed": after,
+                "change": change,
+                "relativeChange": relative,
+                "elasticity": elasticity,
+            }))
+        })
+        .collect();
+    serde_json::Value::Object(effects)
+}
+
+impl OrganismState {
+    fn sensitivity_of(
+        &self,
+        param: &str,
+        delta: f64,
+        ticks: u32,
+        outcomes: &[String],
+        delta_time: f64,
+        baseline: &BTreeMap<String, f64>,
+    ) -> Result<serde_json::Value, String> {
+        let param = self.writable_metric(param)?;
+        let base_param = self.read_any_metric(&param).unwrap_or(0.0);
+        
+        let mut start = self.clone();
+        start.write_any_metric(&param, base_param + delta);
+        // Setters may clamp, so report the perturbation actually applied
+        let applied = start.read_any_metric(&param).unwrap_or(0.0) - base_param;
+        let perturbed = run_outcomes(&start, ticks, delta_time, outcomes)?;
+        
+        Ok(serde_json::json!({
+            "param": param,
+            "value": base_param,
+            "delta": applied,
+            "effects": effects(baseline, &perturbed, base_param, applied),
+        }))
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Measure how perturbing one parameter changes chosen outcomes
+    ///
+    /// Runs a clone of the state for `ticks` steps of `delta_time` as is, and
+    /// another with `param` (a writable core or custom metric) shifted by
+    /// `delta`, then reports each outcome metric's `baseline`, `perturbed`,
+    /// `change`, `relativeChange` and `elasticity`. Both runs start from the
+    /// same RNG state, so noise affects them identically. This state isn't
+    /// changed.
+    #[wasm_bindgen]
+    pub fn sensitivity(
+        &self,
+        param: &str,
+        delta: f64,
+        ticks: u32,
+        outcomes: Vec<String>,
+        delta_time: f64,
+    ) -> Result<String, JsValue> {
+        let _call = call_log::enter("sensitivity", || serde_json::json!({
+            "param": param,
+            "delta": delta,
+            "ticks": ticks,
+            "outcomes": outcomes,
+            "deltaTime": delta_time,
+        }));
+        let to_js = |e: String| JsValue::from_str(&e);
+        let baseline = run_outcomes(self, ticks, delta_time, &outcomes).map_err(to_js)?;
+        let report = self.sensitivity_of(param, delta, ticks, &outcomes, delta_time, &baseline)
+            .map_err(to_js)?;
+        
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize sensitivity: {}", e)))
+    }
+    
+    /// One-at-a-time sensitivity scan over several parameters
+    ///
+    /// Each parameter is shifted by `fraction` of its current value (or by
+    /// `fraction` itself when the value is zero) while the others keep theirs.
+    /// Returns a JSON array with one `sensitivity` report per parameter.
+    #[wasm_bindgen(js_name = sensitivityScan)]
+    pub fn sensitivity_scan(
+        &self,
+        params: Vec<String>,
+        fraction: f64,
+        ticks: u32,
+        outcomes: Vec<String>,
+        delta_time: f64,
+    ) -> Result<String, JsValue> {
+        let _call = call_log::enter("sensitivityScan", || serde_json::json!({
+            "params": params,
+            "fraction": fraction,
+            "ticks": ticks,
+            "outcomes": outcomes,
+            "deltaTime": delta_time,
+        }));
+        let to_js = |e: String| JsValue::from_str(&e);
+        let baseline = run_outcomes(self, ticks, delta_time, &outcomes).map_err(to_js)?;
+        
+        let mut reports = Vec::with_capacity(params.len());
+        for param in &params {
+            let value = self.writable_metric(param)
+                .map(|name| self.read_any_metric(&name).unwrap_or(0.0))
+                .map_err(to_js)?;
+            let delta = if value != 0.0 { value * fraction } else { fraction };
+            reports.push(self.sensitivity_of(param, delta, ticks, &outcomes, delta_time, &baseline).map_err(to_js)?);
+        }
+        
+        serde_json::to_string(&reports)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize sensitivity scan: {}", e)))
+    }
+}
@@ -0,0 +1,210 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::accumulate::CompensatedSum;
+use crate::clock;
+use crate::lisp::{self, Program};
+use crate::{apply_rule_logic, OrganismState, RuleRegistry};
+
+/// Metrics compared between the live and mirrored state after each shadow run
+const COMPARED_METRICS: [&str; 5] = [
+    "population",
+    "energy",
+    "mutation_rate",
+    "selection_pressure",
+    "adaptation_score",
+];
+
+/// A candidate version of a rule running in shadow mode next to the live version
+#[derive(Serialize, Deserialize, Clone)]
+pub(crate) struct ShadowCandidate {
+    lisp_code: String,
+    attached_at: u64,
+    runs: u64,
+    
+    // Live version totals over the shadow runs
+    live_result_total: CompensatedSum,
+    live_time_total_ms: CompensatedSum,
+    
+    // Candidate totals over the shadow runs
+    shadow_result_total: CompensatedSum,
+    shadow_time_total_ms: CompensatedSum,
+    
+    // Accumulated absolute differences between the two versions
+    result_divergence_total: CompensatedSum,
+    state_divergence_total: CompensatedSum,
+    
+    /// Runs where the candidate failed, which aren't in the totals
+    #[serde(default)]
+    failures: u64,
+    #[serde(skip)]
+    program: Program,
+}
+
+impl ShadowCandidate {
+    fn mean(total: f64, runs: u64) -> f64 {
+        if runs > 0 {
+            total / runs as f64
+        } else {
+            0.0
+        }
+    }
+    
+    fn report(&self, id: &str) -> serde_json::Value {
+        serde_json::json!({
+            "ruleId": id,
+            "candidateCode": self.lisp_code,
+            "attachedAt": self.attached_at,
+            "runs": self.runs,
+            "failures": self.failures,
+            "live": {
+                "meanResult": Self::mean(self.live_result_total.value(), self.runs),
+                "meanExecutionTimeMs": Self::mean(self.live_time_total_ms.value(), self.runs),
+            },
+            "candidate": {
+                "meanResult": Self::mean(self.shadow_result_total.value(), self.runs),
+                "meanExecutionTimeMs": Self::mean(self.shadow_time_total_ms.value(), self.runs),
+            },
+            "meanResultDivergence": Self::mean(self.result_divergence_total.value(), self.runs),
+            "meanStateDivergence": Self::mean(self.state_divergence_total.value(), self.runs),
+        })
+    }
+    
+    /// Forget the accumulated comparison, keeping the candidate attached
+    pub(crate) fn reset_runs(&mut self) {
+        self.runs = 0;
+        self.failures = 0;
+        self.live_result_total = CompensatedSum::default();
+        self.live_time_total_ms = CompensatedSum::default();
+        self.shadow_result_total = CompensatedSum::default();
+        self.shadow_time_total_ms = CompensatedSum::default();
+        self.result_divergence_total = CompensatedSum::default();
+        self.state_divergence_total = CompensatedSum::default();
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Attach a candidate version of a rule that runs in shadow mode
+    ///
+    /// Every time the live rule is applied, the candidate runs against a
+    /// mirrored copy of the pre-application state. The mirror is discarded
+    /// afterwards; only comparative statistics are kept. Attaching a new
+    /// candidate replaces the previous one and its statistics.
+    #[wasm_bindgen(js_name = attachShadow)]
+    pub fn attach_shadow(&mut self, id: &str, candidate_code: &str) -> Result<(), JsValue> {
+        let rule = self.rules.get_mut(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        lisp::check_code(candidate_code)
+            .map_err(|e| JsValue::from_str(&format!("Failed to attach shadow to rule {}: {}", id, e)))?;
+        let program = Program::compile(candidate_code);
+        
+        rule.shadow = Some(ShadowCandidate {
+            lisp_code: candidate_code.to_string(),
+            attached_at: clock::now_ms() as u64,
+            runs: 0,
+            live_result_total: CompensatedSum::default(),
+            live_time_total_ms: CompensatedSum::default(),
+            shadow_result_total: CompensatedSum::default(),
+            shadow_time_total_ms: CompensatedSum::default(),
+            result_divergence_total: CompensatedSum::default(),
+            state_divergence_total: CompensatedSum::default(),
+            failures: 0,
+            program,
+        });
+        
+        Ok(())
+    }
+    
+    /// Stop shadowing a rule, discarding the candidate
+    #[wasm_bindgen(js_name = detachShadow)]
+    pub fn detach_shadow(&mut self, id: &str) -> bool {
+        self.rules
+            .get_mut(id)
+            .and_then(|rule| rule.shadow.take())
+            .is_some()
+    }
+    
+    /// Get the comparative statistics of a rule's shadow candidate as JSON
+    #[wasm_bindgen(js_name = getShadowReport)]
+    pub fn get_shadow_report(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        let shadow = rule.shadow.as_ref()
+            .ok_or_else(|| JsValue::from_str(&format!("Rule has no shadow candidate: {}", id)))?;
+        
+        serde_json::to_string(&shadow.report(id))
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize shadow report: {}", e)))
+    }
+    
+    /// Promote a rule's shadow candidate to the live version
+    ///
+    /// The candidate code replaces the live code, execution statistics start
+    /// over and the final shadow report is returned as JSON.
+    #[wasm_bindgen(js_name = promoteShadow)]
+    pub fn promote_shadow(&mut self, id: &str) -> Result<String, JsValue> {
+        let report = self.get_shadow_report(id)?;
+        let candidate_code = self.rules
+            .get(id)
+            .and_then(|rule| rule.shadow.as_ref())
+            .map(|shadow| shadow.lisp_code.clone())
+            .unwrap_or_default();
+        
+        // Registering drops the shadow, so a failure leaves the candidate attached
+        self.register_rule(id, &candidate_code, None)?;
+        Ok(report)
+    }
+}
+
+impl RuleRegistry {
+    pub(crate) fn has_shadow(&self, id: &str) -> bool {
+        self.rules.get(id).is_some_and(|rule| rule.shadow.is_some())
+    }
+    
+    /// Run a rule's shadow candidate against its mirror and accumulate the comparison
+    pub(crate) fn run_shadow(
+        &mut self,
+        id: &str,
+        mirror: &mut OrganismState,
+        live_state: &OrganismState,
+        params: &[f64],
+        live_result: f64,
+        live_time_ms: f64,
+    ) {
+        let budget = self.budget(id);
+        let shadow = match self.rules.get_mut(id).and_then(|rule| rule.shadow.as_mut()) {
+            Some(shadow) => shadow,
+            None => return,
+        };
+        
+        if !shadow.program.is_compiled() {
+            shadow.program = Program::compile(&shadow.lisp_code);
+        }
+        let start_time = clock::now_ms();
+        let shadow_result = match apply_rule_logic(&shadow.program, mirror, params, budget) {
+            Ok(result) => result,
+            Err(_) => {
+                shadow.failures += 1;
+                return;
+            }
+        };
+        let shadow_time = clock::now_ms() - start_time;
+        
+        let state_divergence: f64 = COMPARED_METRICS
+            .iter()
+            .map(|metric| {
+                let live = live_state.metric(metric).unwrap_or(0.0);
+                let shadowed = mirror.metric(metric).unwrap_or(0.0);
+                (live - shadowed).abs()
+            })
+            .sum();
+        
+        shadow.runs += 1;
+        shadow.live_result_total.add(live_result);
+        shadow.live_time_total_ms.add(live_time_ms);
+        shadow.shadow_result_total.add(shadow_result);
+        shadow.shadow_time_total_ms.add(shadow_time);
+        shadow.result_divergence_total.add((live_result - shadow_result).abs());
+        shadow.state_divergence_total.add(state_divergence);
+    }
+}
@@ -0,0 +1,339 @@
+use wasm_bindgen::prelude::*;
+use std::rc::Rc;
+
+use crate::clock::Clock;
+use crate::history::HistoryRecorder;
+use crate::interpolate::MetricFrame;
+use crate::replay::{self, InputLog};
+use crate::rewind::Rewind;
+use crate::subsystem::{Subsystem, SubsystemContext};
+use crate::{apply_rule, OrganismConfig, OrganismState, RuleRegistry};
+
+/// SimulationBuilder assembles a state, registry, clock and seed into a `Simulation`
+///
+/// Every part is optional: the state defaults to a new `OrganismState`
+/// (or is built from a config), the registry to an empty one and the clock
+/// to `Date.now()`. Parts given as objects are copied.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct SimulationBuilder {
+    config: Option<OrganismConfig>,
+    state: Option<OrganismState>,
+    registry: Option<RuleRegistry>,
+    seed: Option<u64>,
+    manual_clock: Option<(f64, f64)>,
+    subsystems: Vec<Box<dyn Subsystem>>,
+}
+
+impl SimulationBuilder {
+    /// Add a subsystem to the simulation, initialized when it is built
+    pub fn with_subsystem(mut self, subsystem: Box<dyn Subsystem>) -> SimulationBuilder {
+        self.subsystems.push(subsystem);
+        self
+    }
+}
+
+#[wasm_bindgen]
+impl SimulationBuilder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SimulationBuilder {
+        SimulationBuilder::default()
+    }
+    
+    /// Build the starting state from a config (replaces any state given with `withState`)
+    #[wasm_bindgen(js_name = withConfig)]
+    pub fn with_config(mut self, config: &OrganismConfig) -> SimulationBuilder {
+        self.config = Some(config.clone());
+        self.state = None;
+        self
+    }
+    
+    /// Start from a copy of an existing state (replaces any config)
+    #[wasm_bindgen(js_name = withState)]
+    pub fn with_state(mut self, state: &OrganismState) -> SimulationBuilder {
+        self.state = Some(state.clone());
+        self.config = None;
+        self
+    }
+    
+    /// Run the rules of a copy of a registry every tick
+    #[wasm_bindgen(js_name = withRegistry)]
+    pub fn with_registry(mut self, registry: &RuleRegistry) -> SimulationBuilder {
+        self.registry = Some(registry.clone());
+        self
+    }
+    
+    /// Seed the state's noise RNG, so runs with noise terms are reproducible
+    #[wasm_bindgen(js_name = withSeed)]
+    pub fn with_seed(mut self, seed: u64) -> SimulationBuilder {
+        self.seed = Some(seed);
+        self
+    }
+    
+    /// Switch the engine to a manual clock when built (see `Clock.useManual`)
+    #[wasm_bindgen(js_name = withManualClock)]
+    pub fn with_manual_clock(mut self, start_ms: f64, auto_advance_ms: f64) -> SimulationBuilder {
+        self.manual_clock = Some((start_ms, auto_advance_ms));
+        self
+    }
+    
+    /// Assemble the simulation, throwing when the config is invalid or a subsystem fails to initialize
+    #[wasm_bindgen]
+    pub fn build(self) -> Result<Simulation, JsValue> {
+        let mut state = match (self.state, &self.config) {
+            (Some(state), _) => state,
+            (None, Some(config)) => config.build()?,
+            (None, None) => OrganismState::new(),
+        };
+        if let Some(seed) = self.seed {
+            state.set_noise_seed(seed);
+        }
+        if let Some((start_ms, auto_advance_ms)) = self.manual_clock {
+            Clock::use_manual(start_ms);
+            Clock::set_auto_advance(auto_advance_ms)?;
+        }
+        
+        let mut simulation = Simulation {
+            state: Rc::new(state),
+            registry: Rc::new(self.registry.unwrap_or_default()),
+            ticks: 0,
+            subsystems: Vec::new(),
+            previous_frame: None,
+            history: None,
+            rewind: None,
+            recording: None,
+        };
+        for subsystem in self.subsystems {
+            simulation.add_subsystem(subsystem).map_err(|e| JsValue::from_str(&e))?;
+        }
+        Ok(simulation)
+    }
+}
+
+/// Simulation owns a state and registry and advances them together
+///
+/// Each `tick(dt)` runs the registry's rules for the current tick (modes,
+/// hotspot policy and invariants included) and then steps the state by
+/// `dt`. Hosts no longer need to coordinate the objects by hand.
+///
+/// The state and registry are shared copy-on-write between a simulation
+/// and its forks: each is copied the first time one side changes it.
+/// Subsystems (see `Subsystem`) hook into every tick.
+#[wasm_bindgen]
+pub struct Simulation {
+    state: Rc<OrganismState>,
+    registry: Rc<RuleRegistry>,
+    ticks: u64,
+    pub(crate) subsystems: Vec<Box<dyn Subsystem>>,
+    /// Metrics before the last tick, for `interpolate`
+    pub(crate) previous_frame: Option<MetricFrame>,
+    /// Samples of the state after every tick, once `enableHistory` is called;
+    /// shared copy-on-write with forks, like the state and registry
+    pub(crate) history: Option<Rc<HistoryRecorder>>,
+    /// Checkpoints for `rewind`, once `enableRewind` is called
+    pub(crate) rewind: Option<Rewind>,
+    /// Inputs since `startRecording`, for `replay`
+    pub(crate) recording: Option<InputLog>,
+}
+
+impl Simulation {
+    pub(crate) fn state(&self) -> &OrganismState {
+        &self.state
+    }
+    
+    pub(crate) fn registry(&self) -> &RuleRegistry {
+        &self.registry
+    }
+    
+    pub(crate) fn state_rc(&self) -> Rc<OrganismState> {
+        Rc::clone(&self.state)
+    }
+    
+    pub(crate) fn registry_rc(&self) -> Rc<RuleRegistry> {
+        Rc::clone(&self.registry)
+    }
+    
+    /// Put back the parts of a checkpoint taken after tick `ticks`
+    pub(crate) fn restore_checkpoint(
+        &mut self,
+        ticks: u64,
+        state: Rc<OrganismState>,
+        registry: Rc<RuleRegistry>,
+        subsystems: Vec<Box<dyn Subsystem>>,
+    ) {
+        self.ticks = ticks;
+        self.state = state;
+        self.registry = registry;
+        self.subsystems = subsystems;
+        self.previous_frame = None;
+    }
+    
+    /// Set the tick count, as when a replay goes back to where its recording started
+    pub(crate) fn set_ticks(&mut self, ticks: u64) {
+        self.ticks = ticks;
+        self.previous_frame = None;
+    }
+    
+    /// The state, copied first if a fork still shares it
+    pub(crate) fn state_mut(&mut self) -> &mut OrganismState {
+        Rc::make_mut(&mut self.state)
+    }
+    
+    /// The registry, copied first if a fork still shares it
+    pub(crate) fn registry_mut(&mut self) -> &mut RuleRegistry {
+        Rc::make_mut(&mut self.registry)
+    }
+    
+    /// The state and registry together, copied first if a fork still shares them
+    pub(crate) fn parts_mut(&mut self) -> (&mut OrganismState, &mut RuleRegistry) {
+        (Rc::make_mut(&mut self.state), Rc::make_mut(&mut self.registry))
+    }
+    
+    /// Run one subsystem hook for every subsystem in order
+    fn run_hooks(
+        &mut self,
+        delta_time: f64,
+        hook: &str,
+        run: fn(&mut dyn Subsystem, &mut SubsystemContext) -> Result<(), String>,
+    ) -> Result<(), JsValue> {
+        let tick = self.ticks;
+        let state = Rc::make_mut(&mut self.state);
+        let registry = Rc::make_mut(&mut self.registry);
+        for subsystem in &mut self.subsystems {
+            let mut context = SubsystemContext { state: &mut *state, registry: &mut *registry, tick, delta_time };
+            run(subsystem.as_mut(), &mut context).map_err(|e| {
+                JsValue::from_str(&format!("Subsystem {} failed in {}: {}", subsystem.name(), hook, e))
+            })?;
+        }
+        Ok(())
+    }
+    
+    /// Run one tick and return its summary; `tick` records it
+    fn run_tick(&mut self, delta_time: f64) -> Result<String, JsValue> {
+        self.previous_frame = Some(MetricFrame::capture(&self.state));
+        if !self.subsystems.is_empty() {
+            self.run_hooks(delta_time, "pre_tick", |s, context| s.pre_tick(context))?;
+        }
+        let tick = self.ticks;
+        let (state, registry) = self.parts_mut();
+        let summary = registry.tick(state, tick as u32)?;
+        let score = state.step(delta_time);
+        if !self.subsystems.is_empty() {
+            self.run_hooks(delta_time, "post_tick", |s, context| s.post_tick(context))?;
+        }
+        if let Some(history) = &mut self.history {
+            Rc::make_mut(history).observe(&self.state);
+        }
+        self.ticks += 1;
+        self.record_rewind();
+        
+        let mut summary: serde_json::Value = serde_json::from_str(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to read tick summary: {}", e)))?;
+        if let Some(object) = summary.as_object_mut() {
+            object.insert("adaptationScore".to_string(), serde_json::json!(score));
+        }
+        Ok(summary.to_string())
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Advance one tick and return the tick summary as JSON
+    ///
+    /// The summary is the registry's (`step`, `mode`, `applied`, ...) plus
+    /// the `adaptationScore` after the step.
+    #[wasm_bindgen]
+    pub fn tick(&mut self, delta_time: f64) -> Result<String, JsValue> {
+        let summary = self.run_tick(delta_time);
+        self.record_input("tick", || serde_json::json!({ "deltaTime": delta_time }), summary.is_ok());
+        summary
+    }
+    
+    /// Branch off a simulation that starts where this one is
+    ///
+    /// The state, registry and history aren't copied: each is shared until
+    /// the fork or the original changes it, so speculative "what if"
+    /// branches that are dropped cost little whatever their size. A fork
+    /// starts without a recording, since it takes its own inputs; call
+    /// `startRecording` on it to log them.
+    #[wasm_bindgen]
+    pub fn fork(&self) -> Simulation {
+        Simulation {
+            state: Rc::clone(&self.state),
+            registry: Rc::clone(&self.registry),
+            ticks: self.ticks,
+            subsystems: self.subsystems.clone(),
+            previous_frame: self.previous_frame.clone(),
+            history: self.history.as_ref().map(Rc::clone),
+            rewind: self.rewind.clone(),
+            recording: None,
+        }
+    }
+    
+    /// Apply one rule to this simulation's state outside of a tick (see `applyRule`)
+    #[wasm_bindgen(js_name = applyRule)]
+    pub fn apply_rule(&mut self, rule_id: &str, params: Vec<f64>) -> Result<f64, JsValue> {
+        let args = self.recording.is_some().then(|| serde_json::json!({ "ruleId": rule_id, "params": params }));
+        let result = apply_rule(Rc::make_mut(&mut self.registry), Rc::make_mut(&mut self.state), rule_id, params);
+        self.record_input("applyRule", || args.unwrap_or_default(), result.is_ok());
+        result
+    }
+    
+    /// Number of ticks run
+    #[wasm_bindgen(getter)]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+    
+    /// Get a copy of the state
+    #[wasm_bindgen(js_name = getState)]
+    pub fn get_state(&self) -> OrganismState {
+        OrganismState::clone(&self.state)
+    }
+    
+    /// Replace the state with a copy of `state`
+    #[wasm_bindgen(js_name = setState)]
+    pub fn set_state(&mut self, state: &OrganismState) {
+        self.state = Rc::new(state.clone());
+        self.previous_frame = None;
+        self.record_input("setState", || replay::state_value(state), true);
+    }
+    
+    /// Set a writable core metric or a defined custom metric of the state
+    ///
+    /// The simulation's state is its own copy, so changes made to a state
+    /// object outside it never reach it. Make them here (or with
+    /// `setState`), where a recording logs them.
+    #[wasm_bindgen(js_name = setMetric)]
+    pub fn set_metric(&mut self, name: &str, value: f64) -> Result<(), JsValue> {
+        let result = self.state.writable_metric(name).map(|metric| self.state_mut().write_any_metric(&metric, value));
+        self.record_input("setMetric", || serde_json::json!({ "name": name, "value": value }), result.is_ok());
+        result.map_err(|e| JsValue::from_str(&e))
+    }
+    
+    /// Reseed the state's noise RNG, as `withSeed` does when building
+    #[wasm_bindgen(js_name = setSeed)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.state_mut().set_noise_seed(seed);
+        self.record_input("setSeed", || serde_json::json!({ "seed": seed }), true);
+    }
+    
+    /// Get a copy of the registry
+    #[wasm_bindgen(js_name = getRegistry)]
+    pub fn get_registry(&self) -> RuleRegistry {
+        RuleRegistry::clone(&self.registry)
+    }
+    
+    /// Replace the registry with a copy of `registry`
+    #[wasm_bindgen(js_name = setRegistry)]
+    pub fn set_registry(&mut self, registry: &RuleRegistry) {
+        self.registry = Rc::new(registry.clone());
+        self.record_input("setRegistry", || replay::registry_value(registry), true);
+    }
+    
+    /// Get a snapshot of the state as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        self.state.get_snapshot()
+    }
+}
@@ -0,0 +1,95 @@
+use wasm_bindgen::prelude::*;
+
+use crate::{OrganismState, RuleRegistry};
+
+/// How much detail a snapshot carries
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum SnapshotProfile {
+    /// Everything needed to restore the object exactly
+    Full,
+    /// Current metric values only, for frequent UI refreshes
+    MetricsOnly,
+    /// Everything except accumulated history (execution stats, shadow runs, events)
+    NoHistory,
+    /// Everything except genomes
+    NoGenomes,
+}
+
+impl SnapshotProfile {
+    pub fn parse(name: &str) -> Result<SnapshotProfile, JsValue> {
+        match name {
+            "full" => Ok(SnapshotProfile::Full),
+            "metrics-only" => Ok(SnapshotProfile::MetricsOnly),
+            "no-history" => Ok(SnapshotProfile::NoHistory),
+            "no-genomes" => Ok(SnapshotProfile::NoGenomes),
+            _ => Err(JsValue::from_str(&format!(
+                "Unknown snapshot profile: {} (expected full, metrics-only, no-history or no-genomes)",
+                name
+            ))),
+        }
+    }
+    
+    pub fn includes_history(self) -> bool {
+        matches!(self, SnapshotProfile::Full | SnapshotProfile::NoGenomes)
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Get a snapshot with the given profile ("full", "metrics-only", "no-history", "no-genomes")
+    ///
+    /// Every profile of an organism state can be read back with `loadSnapshot`.
+    #[wasm_bindgen(js_name = getSnapshotProfile)]
+    pub fn get_snapshot_profile(&self, profile: &str) -> Result<String, JsValue> {
+        // The state holds only metrics, so all profiles currently serialize the same fields
+        SnapshotProfile::parse(profile)?;
+        self.get_snapshot()
+    }
+    
+    /// Get only the requested metrics as a JSON object
+    ///
+    /// Names may be camelCase or snake_case and are echoed back as given, so
+    /// `getSnapshotFields(["population", "mutationRate"])` returns
+    /// `{"population": 100, "mutationRate": 0.01}`.
+    #[wasm_bindgen(js_name = getSnapshotFields)]
+    pub fn get_snapshot_fields(&self, fields: Vec<String>) -> Result<String, JsValue> {
+        let mut selected = serde_json::Map::with_capacity(fields.len());
+        for field in fields {
+            let value = self.metric(&field)
+                .ok_or_else(|| JsValue::from_str(&format!("Unknown snapshot field: {}", field)))?;
+            selected.insert(field, serde_json::json!(value));
+        }
+        
+        serde_json::to_string(&selected)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize snapshot fields: {}", e)))
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Export the registry with the given profile ("full", "metrics-only", "no-history", "no-genomes")
+    ///
+    /// "metrics-only" returns just the per-rule stats in execution order.
+    /// "no-history" keeps rules, modes and bundles but drops execution
+    /// stats and shadow-run totals; it can be imported like a full export.
+    #[wasm_bindgen(js_name = exportRegistryProfile)]
+    pub fn export_registry_profile(&self, profile: &str) -> Result<String, JsValue> {
+        let profile = SnapshotProfile::parse(profile)?;
+        
+        if profile == SnapshotProfile::MetricsOnly {
+            return self.get_all_stats();
+        }
+        if profile.includes_history() {
+            return self.export_registry();
+        }
+        
+        let mut stripped = self.clone();
+        stripped.clear_stats();
+        for rule in stripped.rules.values_mut() {
+            if let Some(shadow) = rule.shadow.as_mut() {
+                shadow.reset_runs();
+            }
+        }
+        stripped.export_registry()
+    }
+}
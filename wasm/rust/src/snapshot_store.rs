@@ -0,0 +1,317 @@
+use wasm_bindgen::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::hash::fnv1a64;
+use crate::{ColonySystem, OrganismState, RuleRegistry, TerritoryMap};
+
+/// Blocks are never cut shorter than this (except at the end of a section)
+const MIN_BLOCK_BYTES: usize = 512;
+
+/// Blocks are always cut at this size
+const MAX_BLOCK_BYTES: usize = 16 * 1024;
+
+/// A boundary falls where the rolling hash has these bits clear (~4 KiB blocks)
+const BOUNDARY_MASK: u64 = 0xfff;
+
+/// Random table for the gear rolling hash, generated with SplitMix64
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut seed: u64 = 0x6f75_726f_626f_726f;
+    let mut i = 0;
+    while i < 256 {
+        seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = seed;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Split bytes into content-defined blocks
+///
+/// Boundaries depend only on nearby content, so an edit early in a section
+/// only changes the blocks around it instead of shifting every later block.
+fn split_blocks(bytes: &[u8]) -> Vec<&[u8]> {
+    let mut blocks = Vec::new();
+    let mut start = 0;
+    let mut hash: u64 = 0;
+    
+    for (i, byte) in bytes.iter().enumerate() {
+        hash = (hash << 1).wrapping_add(GEAR[*byte as usize]);
+        let len = i + 1 - start;
+        if (len >= MIN_BLOCK_BYTES && hash & BOUNDARY_MASK == 0) || len >= MAX_BLOCK_BYTES {
+            blocks.push(&bytes[start..=i]);
+            start = i + 1;
+            hash = 0;
+        }
+    }
+    if start < bytes.len() {
+        blocks.push(&bytes[start..]);
+    }
+    
+    blocks
+}
+
+/// A deduplicated block with the number of section references to it
+struct Block {
+    data: Vec<u8>,
+    refs: u32,
+}
+
+/// A saved checkpoint: each section is a list of block IDs
+struct Checkpoint {
+    label: String,
+    sections: BTreeMap<&'static str, Vec<u64>>,
+}
+
+/// SnapshotStore keeps many checkpoints while storing identical data once
+///
+/// Each captured object is serialized and cut into content-defined blocks
+/// that are stored by hash. Checkpoints only hold block IDs, so 200
+/// checkpoints of a mostly static world share nearly all of their blocks.
+#[wasm_bindgen]
+pub struct SnapshotStore {
+    blocks: HashMap<u64, Block>,
+    checkpoints: BTreeMap<u32, Checkpoint>,
+    next_id: u32,
+}
+
+impl SnapshotStore {
+    /// Store one block, returning its ID
+    fn intern(&mut self, data: &[u8]) -> u64 {
+        let mut id = fnv1a64(data);
+        loop {
+            match self.blocks.get_mut(&id) {
+                Some(block) if block.data == data => {
+                    block.refs += 1;
+                    return id;
+                }
+                // Hash collision with different content: probe the next ID
+                Some(_) => id = id.wrapping_add(1),
+                None => {
+                    self.blocks.insert(id, Block { data: data.to_vec(), refs: 1 });
+                    return id;
+                }
+            }
+        }
+    }
+    
+    fn release(&mut self, ids: &[u64]) {
+        for id in ids {
+            if let Some(block) = self.blocks.get_mut(id) {
+                block.refs -= 1;
+                if block.refs == 0 {
+                    self.blocks.remove(id);
+                }
+            }
+        }
+    }
+    
+    fn put_section(&mut self, id: u32, section: &'static str, payload: &str) -> Result<(), JsValue> {
+        if !self.checkpoints.contains_key(&id) {
+            return Err(JsValue::from_str(&format!("Checkpoint not found: {}", id)));
+        }
+        
+        let block_ids: Vec<u64> = split_blocks(payload.as_bytes())
+            .into_iter()
+            .map(|block| self.intern(block))
+            .collect();
+        
+        let replaced = self.checkpoints.get_mut(&id)
+            .and_then(|checkpoint| checkpoint.sections.insert(section, block_ids));
+        if let Some(old) = replaced {
+            self.release(&old);
+        }
+        Ok(())
+    }
+    
+    /// Size of a checkpoint if its sections were stored without sharing
+    fn logical_bytes(&self, checkpoint: &Checkpoint) -> usize {
+        checkpoint.sections
+            .values()
+            .flatten()
+            .filter_map(|id| self.blocks.get(id))
+            .map(|block| block.data.len())
+            .sum()
+    }
+    
+    fn get_section(&self, id: u32, section: &str) -> Result<String, JsValue> {
+        let checkpoint = self.checkpoints.get(&id)
+            .ok_or_else(|| JsValue::from_str(&format!("Checkpoint not found: {}", id)))?;
+        let block_ids = checkpoint.sections.get(section)
+            .ok_or_else(|| JsValue::from_str(&format!("Checkpoint {} has no {} section", id, section)))?;
+        
+        let mut payload = Vec::new();
+        for block_id in block_ids {
+            let block = self.blocks.get(block_id)
+                .ok_or_else(|| JsValue::from_str(&format!("Checkpoint {} references a missing block", id)))?;
+            payload.extend_from_slice(&block.data);
+        }
+        
+        String::from_utf8(payload)
+            .map_err(|e| JsValue::from_str(&format!("Failed to decode checkpoint section: {}", e)))
+    }
+}
+
+#[wasm_bindgen]
+impl SnapshotStore {
+    /// Create an empty store
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SnapshotStore {
+        SnapshotStore {
+            blocks: HashMap::new(),
+            checkpoints: BTreeMap::new(),
+            next_id: 0,
+        }
+    }
+    
+    /// Estimated heap bytes held by the stored blocks and checkpoints
+    #[wasm_bindgen(js_name = memoryBytes)]
+    pub fn memory_bytes(&self) -> usize {
+        let blocks: usize = self.blocks
+            .values()
+            .map(|block| std::mem::size_of::<(u64, Block)>() + block.data.capacity())
+            .sum();
+        let checkpoints: usize = self.checkpoints
+            .values()
+            .map(|checkpoint| {
+                std::mem::size_of::<(u32, Checkpoint)>()
+                    + checkpoint.label.capacity()
+                    + checkpoint.sections.values().map(|ids| std::mem::size_of::<(&str, Vec<u64>)>() + ids.capacity() * 8).sum::<usize>()
+            })
+            .sum();
+        blocks + checkpoints
+    }
+    
+    /// Create an empty checkpoint and return its ID
+    ///
+    /// Add sections to it with `addState`, `addRegistry`, `addTerritory` and
+    /// `addColonies`.
+    #[wasm_bindgen(js_name = createCheckpoint)]
+    pub fn create_checkpoint(&mut self, label: &str) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.checkpoints.insert(id, Checkpoint {
+            label: label.to_string(),
+            sections: BTreeMap::new(),
+        });
+        id
+    }
+    
+    /// Capture an organism state into a checkpoint
+    #[wasm_bindgen(js_name = addState)]
+    pub fn add_state(&mut self, id: u32, state: &OrganismState) -> Result<(), JsValue> {
+        self.put_section(id, "state", &state.get_snapshot()?)
+    }
+    
+    /// Capture a rule registry into a checkpoint
+    #[wasm_bindgen(js_name = addRegistry)]
+    pub fn add_registry(&mut self, id: u32, registry: &RuleRegistry) -> Result<(), JsValue> {
+        self.put_section(id, "registry", &registry.export_registry()?)
+    }
+    
+    /// Capture a territory map into a checkpoint
+    #[wasm_bindgen(js_name = addTerritory)]
+    pub fn add_territory(&mut self, id: u32, territory: &TerritoryMap) -> Result<(), JsValue> {
+        self.put_section(id, "territory", &territory.get_snapshot()?)
+    }
+    
+    /// Capture a colony system into a checkpoint
+    #[wasm_bindgen(js_name = addColonies)]
+    pub fn add_colonies(&mut self, id: u32, colonies: &ColonySystem) -> Result<(), JsValue> {
+        self.put_section(id, "colonies", &colonies.get_snapshot()?)
+    }
+    
+    /// Restore an organism state from a checkpoint
+    #[wasm_bindgen(js_name = restoreState)]
+    pub fn restore_state(&self, id: u32, state: &mut OrganismState) -> Result<(), JsValue> {
+        state.load_snapshot(&self.get_section(id, "state")?)
+    }
+    
+    /// Restore a rule registry from a checkpoint
+    #[wasm_bindgen(js_name = restoreRegistry)]
+    pub fn restore_registry(&self, id: u32, registry: &mut RuleRegistry) -> Result<(), JsValue> {
+        registry.import_registry(&self.get_section(id, "registry")?)
+    }
+    
+    /// Restore a territory map from a checkpoint
+    #[wasm_bindgen(js_name = restoreTerritory)]
+    pub fn restore_territory(&self, id: u32, territory: &mut TerritoryMap) -> Result<(), JsValue> {
+        territory.load_snapshot(&self.get_section(id, "territory")?)
+    }
+    
+    /// Restore a colony system from a checkpoint
+    #[wasm_bindgen(js_name = restoreColonies)]
+    pub fn restore_colonies(&self, id: u32, colonies: &mut ColonySystem) -> Result<(), JsValue> {
+        colonies.load_snapshot(&self.get_section(id, "colonies")?)
+    }
+    
+    /// Remove a checkpoint, freeing blocks no other checkpoint uses
+    #[wasm_bindgen(js_name = removeCheckpoint)]
+    pub fn remove_checkpoint(&mut self, id: u32) -> bool {
+        match self.checkpoints.remove(&id) {
+            Some(checkpoint) => {
+                for block_ids in checkpoint.sections.values() {
+                    self.release(block_ids);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// List checkpoints as JSON (`id`, `label`, `sections`, `bytes`) in creation order
+    #[wasm_bindgen(js_name = getCheckpoints)]
+    pub fn get_checkpoints(&self) -> Result<String, JsValue> {
+        let checkpoints: Vec<serde_json::Value> = self.checkpoints
+            .iter()
+            .map(|(id, checkpoint)| serde_json::json!({
+                "id": id,
+                "label": checkpoint.label,
+                "sections": checkpoint.sections.keys().collect::<Vec<_>>(),
+                "bytes": self.logical_bytes(checkpoint),
+            }))
+            .collect();
+        
+        serde_json::to_string(&checkpoints)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize checkpoints: {}", e)))
+    }
+    
+    /// Get storage statistics as JSON
+    ///
+    /// `logicalBytes` is what the checkpoints would take stored separately;
+    /// `storedBytes` is what the deduplicated blocks actually take.
+    #[wasm_bindgen(js_name = getStats)]
+    pub fn get_stats(&self) -> Result<String, JsValue> {
+        let logical: usize = self.checkpoints.values().map(|c| self.logical_bytes(c)).sum();
+        let stored: usize = self.blocks.values().map(|b| b.data.len()).sum();
+        
+        let stats = serde_json::json!({
+            "checkpoints": self.checkpoints.len(),
+            "blocks": self.blocks.len(),
+            "logicalBytes": logical,
+            "storedBytes": stored,
+            "dedupRatio": if stored > 0 { logical as f64 / stored as f64 } else { 1.0 },
+        });
+        
+        serde_json::to_string(&stats)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize store stats: {}", e)))
+    }
+    
+    /// Remove every checkpoint and block
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+        self.checkpoints.clear();
+    }
+}
+
+impl Default for SnapshotStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+
+/// Right-hand side of `dy/dt = f(y)`, which may fail (e.g. a rule error)
+pub(crate) type System<'a> = dyn FnMut(&[f64]) -> Result<Vec<f64>, String> + 'a;
+
+/// Newton iterations allowed per implicit step
+const NEWTON_MAX_ITERATIONS: usize = 20;
+
+/// Newton stops once no component moves by more than this (relative to its size)
+const NEWTON_TOLERANCE: f64 = 1e-10;
+
+/// Numerical integrator advancing a system by one time step
+pub(crate) trait Solver {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String>;
+}
+
+fn axpy(y: &[f64], dt: f64, slope: &[f64]) -> Vec<f64> {
+    y.iter().zip(slope).map(|(y, k)| y + dt * k).collect()
+}
+
+/// Semi-implicit (symplectic) Euler: components are updated in order, each
+/// seeing the components already updated this step
+pub(crate) struct SemiImplicitEuler;
+
+impl Solver for SemiImplicitEuler {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String> {
+        let mut next = y.to_vec();
+        for i in 0..next.len() {
+            let slope = system(&next)?;
+            next[i] += dt * slope[i];
+        }
+        Ok(next)
+    }
+}
+
+/// Explicit (forward) Euler
+pub(crate) struct Euler;
+
+impl Solver for Euler {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String> {
+        Ok(axpy(y, dt, &system(y)?))
+    }
+}
+
+/// Heun's method (explicit trapezoidal rule)
+pub(crate) struct Heun;
+
+impl Solver for Heun {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String> {
+        let k1 = system(y)?;
+        let k2 = system(&axpy(y, dt, &k1))?;
+        Ok(y.iter().enumerate().map(|(i, y)| y + dt * 0.5 * (k1[i] + k2[i])).collect())
+    }
+}
+
+/// Classic fourth-order Runge-Kutta
+pub(crate) struct Rk4;
+
+impl Solver for Rk4 {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String> {
+        let k1 = system(y)?;
+        let k2 = system(&axpy(y, dt * 0.5, &k1))?;
+        let k3 = system(&axpy(y, dt * 0.5, &k2))?;
+        let k4 = system(&axpy(y, dt, &k3))?;
+        Ok(y.iter()
+            .enumerate()
+            .map(|(i, y)| y + dt / 6.0 * (k1[i] + 2.0 * k2[i] + 2.0 * k3[i] + k4[i]))
+            .collect())
+    }
+}
+
+/// Implicit (backward) Euler, solved with Newton's method
+///
+/// Unconditionally stable, so stiff dynamics settle instead of oscillating
+/// at step sizes where the explicit methods blow up.
+pub(crate) struct ImplicitEuler;
+
+impl Solver for ImplicitEuler {
+    fn advance(&self, system: &mut System, y: &[f64], dt: f64) -> Result<Vec<f64>, String> {
+        let n = y.len();
+        // Start from the explicit Euler prediction
+        let mut z = axpy(y, dt, &system(y)?);
+        
+        for _ in 0..NEWTON_MAX_ITERATIONS {
+            let f = system(&z)?;
+            // Residual g(z) = z - y - dt * f(z)
+            let residual: Vec<f64> = (0..n).map(|i| z[i] - y[i] - dt * f[i]).collect();
+            
+            // Jacobian of g by forward differences: I - dt * df/dz
+            let mut jacobian = vec![0.0; n * n];
+            for j in 0..n {
+                let h = 1e-7 * z[j].abs().max(1.0);
+                let mut shifted = z.clone();
+                shifted[j] += h;
+                let fh = system(&shifted)?;
+                for i in 0..n {
+                    let identity = if i == j { 1.0 } else { 0.0 };
+                    jacobian[i * n + j] = identity - dt * (fh[i] - f[i]) / h;
+                }
+            }
+            
+            let delta = solve_linear(&mut jacobian, residual, n)
+                .ok_or("Implicit step failed: singular Jacobian")?;
+            let mut converged = true;
+            for i in 0..n {
+                z[i] -= delta[i];
+                if delta[i].abs() > NEWTON_TOLERANCE * z[i].abs().max(1.0) {
+                    converged = false;
+                }
+            }
+            if converged {
+                return Ok(z);
+            }
+        }
+        
+        Err(format!("Implicit step did not converge in {} iterations", NEWTON_MAX_ITERATIONS))
+    }
+}
+
+/// Solve `a * x = b` by Gaussian elimination with partial pivoting
+fn solve_linear(a: &mut [f64], mut b: Vec<f64>, n: usize) -> Option<Vec<f64>> {
+    for col in 0..n {
+        let pivot = (col..n).max_by(|r, s| a[r * n + col].abs().total_cmp(&a[s * n + col].abs()))?;
+        if a[pivot * n + col].abs() < 1e-300 {
+            return None;
+        }
+        if pivot != col {
+            for k in 0..n {
+                a.swap(pivot * n + k, col * n + k);
+            }
+            b.swap(pivot, col);
+        }
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            for k in col..n {
+                a[row * n + k] -= factor * a[col * n + k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: f64 = (row + 1..n).map(|k| a[row * n + k] * x[k]).sum();
+        x[row] = (b[row] - tail) / a[row * n + row];
+    }
+    Some(x)
+}
+
+/// Solver choice, as stored in snapshots
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum SolverKind {
+    #[default]
+    SemiImplicitEuler,
+    Euler,
+    Heun,
+    Rk4,
+    ImplicitEuler,
+}
+
+impl SolverKind {
+    pub fn parse(name: &str) -> Result<SolverKind, String> {
+        match name {
+            "semi-implicit-euler" => Ok(SolverKind::SemiImplicitEuler),
+            "euler" => Ok(SolverKind::Euler),
+            "heun" => Ok(SolverKind::Heun),
+            "rk4" => Ok(SolverKind::Rk4),
+            "implicit-euler" => Ok(SolverKind::ImplicitEuler),
+            _ => Err(format!(
+                "Unknown solver: {} (expected semi-implicit-euler, euler, heun, rk4 or implicit-euler)",
+                name
+            )),
+        }
+    }
+    
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SolverKind::SemiImplicitEuler => "semi-implicit-euler",
+            SolverKind::Euler => "euler",
+            SolverKind::Heun => "heun",
+            SolverKind::Rk4 => "rk4",
+            SolverKind::ImplicitEuler => "implicit-euler",
+        }
+    }
+    
+    pub fn solver(self) -> &'static dyn Solver {
+        match self {
+            SolverKind::SemiImplicitEuler => &SemiImplicitEuler,
+            SolverKind::Euler => &Euler,
+            SolverKind::Heun => &Heun,
+            SolverKind::Rk4 => &Rk4,
+            SolverKind::ImplicitEuler => &ImplicitEuler,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::OrganismState;
+
+/// Frames kept in the buffer before the oldest are dropped
+const MAX_BUFFERED_FRAMES: usize = 4096;
+
+/// Audio parameter a lane drives, which sets its default output range and curve
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum AudioParam {
+    /// Hz, 110 to 880 on an exponential curve
+    Frequency,
+    /// Gain, 0 to 1
+    Amplitude,
+    /// Stereo position, -1 to 1
+    Pan,
+    /// Unscaled 0 to 1, for anything else
+    Control,
+}
+
+impl AudioParam {
+    fn default_range(self) -> (f64, f64) {
+        match self {
+            AudioParam::Frequency => (110.0, 880.0),
+            AudioParam::Amplitude | AudioParam::Control => (0.0, 1.0),
+            AudioParam::Pan => (-1.0, 1.0),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(rename_all = "lowercase")]
+enum Curve {
+    Linear,
+    /// Equal ratios for equal steps, as pitch is heard
+    Exponential,
+}
+
+/// Maps one metric to one audio parameter
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Lane {
+    metric: String,
+    param: AudioParam,
+    /// Metric range mapped onto the output; learned from the values seen when absent
+    #[serde(default)]
+    input: Option<(f64, f64)>,
+    #[serde(default)]
+    output: Option<(f64, f64)>,
+    #[serde(default)]
+    curve: Option<Curve>,
+    #[serde(skip)]
+    seen: Option<(f64, f64)>,
+}
+
+impl Lane {
+    fn map(&mut self, value: f64) -> f64 {
+        let (low, high) = match self.input {
+            Some(range) => range,
+            None => {
+                let (seen_low, seen_high) = self.seen.unwrap_or((value, value));
+                let range = (seen_low.min(value), seen_high.max(value));
+                self.seen = Some(range);
+                range
+            }
+        };
+        let t = if high > low { ((value - low) / (high - low)).clamp(0.0, 1.0) } else { 0.5 };
+        let (out_low, out_high) = self.output.unwrap_or_else(|| self.param.default_range());
+        let curve = self.curve.unwrap_or(if self.param == AudioParam::Frequency { Curve::Exponential } else { Curve::Linear });
+        match curve {
+            Curve::Exponential if out_low > 0.0 && out_high > 0.0 => out_low * (out_high / out_low).powf(t),
+            _ => out_low + (out_high - out_low) * t,
+        }
+    }
+}
+
+/// Sonifier turns metrics into audio parameters for WebAudio
+///
+/// Each lane maps a metric onto a frequency, amplitude, pan or generic
+/// control range. `sample` returns one value per lane for the current
+/// state; `record` appends such a frame to a buffer that `takeBuffer`
+/// hands over interleaved (`frame0lane0, frame0lane1, ..., frame1lane0`),
+/// ready to schedule with `setValueAtTime`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Sonifier {
+    lanes: Vec<Lane>,
+    buffer: Vec<f32>,
+}
+
+#[wasm_bindgen]
+impl Sonifier {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Sonifier {
+        Sonifier::default()
+    }
+    
+    /// Add a lane, returning its index in every frame
+    ///
+    /// `json` is `{ metric, param, input, output, curve }`: `param` is
+    /// "frequency", "amplitude", "pan" or "control"; `input` is the
+    /// `[min, max]` of the metric (learned from the values seen when
+    /// omitted); `output` overrides the parameter's default range; `curve`
+    /// is "linear" or "exponential" (the default for frequency). Adding a
+    /// lane clears the buffer, since its frames would no longer line up.
+    #[wasm_bindgen(js_name = addLane)]
+    pub fn add_lane(&mut self, json: &str) -> Result<usize, JsValue> {
+        let lane: Lane = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse lane: {}", e)))?;
+        for (name, range) in [("input", lane.input), ("output", lane.output)] {
+            if let Some((low, high)) = range {
+                if !low.is_finite() || !high.is_finite() || low == high {
+                    return Err(JsValue::from_str(&format!("Lane {} range must be two different finite numbers", name)));
+                }
+            }
+        }
+        self.lanes.push(lane);
+        self.buffer.clear();
+        Ok(self.lanes.len() - 1)
+    }
+    
+    /// Remove every lane and the buffer
+    #[wasm_bindgen(js_name = clearLanes)]
+    pub fn clear_lanes(&mut self) {
+        self.lanes.clear();
+        self.buffer.clear();
+    }
+    
+    #[wasm_bindgen(getter, js_name = laneCount)]
+    pub fn lane_count(&self) -> usize {
+        self.lanes.len()
+    }
+    
+    /// Get the lanes as JSON
+    #[wasm_bindgen(js_name = getLanes)]
+    pub fn get_lanes(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.lanes)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize lanes: {}", e)))
+    }
+    
+    /// Map the current state to one value per lane
+    ///
+    /// A lane whose metric doesn't exist (e.g. a custom metric not yet
+    /// defined) outputs the middle of its range.
+    #[wasm_bindgen]
+    pub fn sample(&mut self, state: &OrganismState) -> Vec<f32> {
+        self.lanes
+            .iter_mut()
+            .map(|lane| {
+                let value = state.read_any_metric(&lane.metric).filter(|v| v.is_finite());
+                match value {
+                    Some(value) => lane.map(value) as f32,
+                    None => {
+                        let (low, high) = lane.output.unwrap_or_else(|| lane.param.default_range());
+                        ((low + high) / 2.0) as f32
+                    }
+                }
+            })
+            .collect()
+    }
+    
+    /// Sample the state and append the frame to the buffer (call once per tick)
+    #[wasm_bindgen]
+    pub fn record(&mut self, state: &OrganismState) {
+        let frame = self.sample(state);
+        let max_len = MAX_BUFFERED_FRAMES * frame.len();
+        self.buffer.extend(frame);
+        if self.buffer.len() > max_len {
+            let excess = self.buffer.len() - max_len;
+            self.buffer.drain(..excess);
+        }
+    }
+    
+    /// Number of frames waiting in the buffer
+    #[wasm_bindgen(getter, js_name = bufferedFrames)]
+    pub fn buffered_frames(&self) -> usize {
+        self.buffer.len().checked_div(self.lanes.len()).unwrap_or(0)
+    }
+    
+    /// Hand over the buffered frames, interleaved by lane, and empty the buffer
+    #[wasm_bindgen(js_name = takeBuffer)]
+    pub fn take_buffer(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.buffer)
+    }
+}
@@ -0,0 +1,281 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::genome::Genome;
+use crate::world::World;
+
+/// Generations of speciation history a world keeps
+const MAX_HISTORY: usize = 10_000;
+
+fn default_threshold() -> f64 {
+    0.3
+}
+
+fn default_coefficient() -> f64 {
+    1.0
+}
+
+/// How genomes are compared and grouped into species
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct SpeciationOptions {
+    /// Largest distance at which a genome still belongs to a species
+    #[serde(default = "default_threshold")]
+    threshold: f64,
+    /// Weight of the genes one genome has beyond the other's length
+    #[serde(default = "default_coefficient")]
+    excess: f64,
+    /// Weight of the mean difference between genes both genomes have
+    #[serde(default = "default_coefficient")]
+    weight: f64,
+}
+
+impl Default for SpeciationOptions {
+    fn default() -> Self {
+        SpeciationOptions {
+            threshold: default_threshold(),
+            excess: default_coefficient(),
+            weight: default_coefficient(),
+        }
+    }
+}
+
+impl SpeciationOptions {
+    fn parse(json: &str) -> Result<SpeciationOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: SpeciationOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        options.validate()?;
+        Ok(options)
+    }
+    
+    fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        for (name, value) in [("threshold", self.threshold), ("excess", self.excess), ("weight", self.weight)] {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(format!("{} must be non-negative, got {}", name, value));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+    
+    /// NEAT-style compatibility distance: `excess * E / N + weight * W`
+    ///
+    /// `E` counts the genes past the shorter genome's end, `N` is the
+    /// longer genome's length and `W` the mean absolute difference of
+    /// the genes both have.
+    fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+        let longest = a.len().max(b.len());
+        if longest == 0 {
+            return 0.0;
+        }
+        let shared = a.len().min(b.len());
+        let excess = (longest - shared) as f64 / longest as f64;
+        let difference = if shared == 0 {
+            0.0
+        } else {
+            a.iter().zip(b).map(|(x, y)| (x - y).abs()).sum::<f64>() / shared as f64
+        };
+        self.excess * excess + self.weight * difference
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct SpeciesRecord {
+    /// Genes new members are compared against: a random member's, picked as the species is regrouped
+    representative: Vec<f64>,
+    founded_at: u64,
+    extinct_at: Option<u64>,
+    size: usize,
+}
+
+/// Species counts after one regrouping
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpeciationRecord {
+    generation: u64,
+    tick: u64,
+    species: usize,
+    founded: Vec<u32>,
+    extinct: Vec<u32>,
+}
+
+/// Species a world's organisms are grouped into, and how that changed
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub(crate) struct Speciation {
+    /// Grouping is on while options are set
+    options: Option<SpeciationOptions>,
+    species: BTreeMap<u32, SpeciesRecord>,
+    next_id: u32,
+    history: Vec<SpeciationRecord>,
+}
+
+impl Speciation {
+    pub(crate) fn enabled(&self) -> bool {
+        self.options.is_some()
+    }
+}
+
+impl World {
+    /// Group every organism into a species and return the counts
+    ///
+    /// Organisms join the first living species, oldest first, whose
+    /// representative is within the threshold, and found a new one when
+    /// none is. Species left without members go extinct.
+    pub(crate) fn regroup(&mut self) -> serde_json::Value {
+        let options = self.speciation.options.clone().unwrap_or_default();
+        let (generation, tick) = (self.generation, self.tick);
+        let speciation = &mut self.speciation;
+        let mut living: Vec<u32> = speciation.species
+            .iter()
+            .filter(|(_, s)| s.extinct_at.is_none())
+            .map(|(id, _)| *id)
+            .collect();
+        let mut members: BTreeMap<u32, Vec<usize>> = BTreeMap::new();
+        let mut founded = Vec::new();
+        
+        for (index, organism) in self.organisms.iter_mut().enumerate() {
+            let genes = organism.genome.genes();
+            let found = living
+                .iter()
+                .find(|id| options.distance(&speciation.species[*id].representative, genes) <= options.threshold)
+                .copied();
+            let id = match found {
+                Some(id) => id,
+                None => {
+                    speciation.next_id += 1;
+                    let id = speciation.next_id;
+                    speciation.species.insert(id, SpeciesRecord {
+                        representative: genes.to_vec(),
+                        founded_at: generation,
+                        extinct_at: None,
+                        size: 0,
+                    });
+                    living.push(id);
+                    founded.push(id);
+                    id
+                }
+            };
+            organism.species = Some(id);
+            members.entry(id).or_default().push(index);
+        }
+        
+        let mut extinct = Vec::new();
+        for id in living {
+            let record = speciation.species.get_mut(&id).expect("living species are recorded");
+            match members.get(&id) {
+                Some(indices) => {
+                    record.size = indices.len();
+                    let pick = ((self.rng.next_f64() * indices.len() as f64) as usize).min(indices.len() - 1);
+                    record.representative = self.organisms[indices[pick]].genome.genes().to_vec();
+                }
+                None => {
+                    record.size = 0;
+                    record.extinct_at = Some(generation);
+                    extinct.push(id);
+                }
+            }
+        }
+        
+        let record = SpeciationRecord { generation, tick, species: members.len(), founded, extinct };
+        let summary = serde_json::json!(record);
+        if speciation.history.len() == MAX_HISTORY {
+            speciation.history.remove(0);
+        }
+        speciation.history.push(record);
+        summary
+    }
+}
+
+#[wasm_bindgen]
+impl World {
+    /// Group organisms into species by genome distance from now on
+    ///
+    /// Options (all optional): `threshold` (0.3), the largest distance
+    /// within a species, and the coefficients `excess` (1) and `weight`
+    /// (1) of `Genome.distance`. Once enabled, `evolve` regroups after
+    /// every generation; `speciate` does it on demand.
+    #[wasm_bindgen(js_name = enableSpeciation)]
+    pub fn enable_speciation(&mut self, options_json: &str) -> Result<(), JsValue> {
+        let options = SpeciationOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid speciation options: {}", e)))?;
+        self.speciation.options = Some(options);
+        Ok(())
+    }
+    
+    /// Stop regrouping in `evolve`; species found so far are kept
+    #[wasm_bindgen(js_name = disableSpeciation)]
+    pub fn disable_speciation(&mut self) {
+        self.speciation.options = None;
+    }
+    
+    /// Regroup every organism into species now and return the counts as JSON
+    ///
+    /// The result is `{generation, tick, species, founded, extinct}`, with
+    /// the IDs of species founded and gone extinct in this regrouping.
+    /// Uses the default options if speciation isn't enabled.
+    #[wasm_bindgen]
+    pub fn speciate(&mut self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.regroup())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize speciation: {}", e)))
+    }
+    
+    /// Species with members at the last regrouping
+    #[wasm_bindgen(getter, js_name = speciesCount)]
+    pub fn species_count(&self) -> usize {
+        self.speciation.species.values().filter(|s| s.extinct_at.is_none()).count()
+    }
+    
+    /// Every species ever founded as JSON: `[{id, size, foundedAt, extinctAt, representative}]`
+    ///
+    /// `size` is the membership at the last regrouping; children born
+    /// since then count with their parent's species only once regrouped.
+    #[wasm_bindgen(js_name = getSpecies)]
+    pub fn get_species(&self) -> Result<String, JsValue> {
+        let species: Vec<serde_json::Value> = self.speciation.species
+            .iter()
+            .map(|(id, s)| serde_json::json!({
+                "id": id,
+                "size": s.size,
+                "foundedAt": s.founded_at,
+                "extinctAt": s.extinct_at,
+                "representative": s.representative,
+            }))
+            .collect();
+        serde_json::to_string(&species)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize species: {}", e)))
+    }
+    
+    /// Counts from every regrouping, oldest first, as a JSON array
+    #[wasm_bindgen(js_name = getSpeciationHistory)]
+    pub fn get_speciation_history(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.speciation.history)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize speciation history: {}", e)))
+    }
+    
+    /// The species an organism was last grouped into
+    #[wasm_bindgen(js_name = speciesOf)]
+    pub fn species_of(&self, id: u32) -> Result<Option<u32>, JsValue> {
+        self.organism(id)
+            .map(|o| o.species)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism not found: {}", id)))
+    }
+}
+
+#[wasm_bindgen]
+impl Genome {
+    /// Compatibility distance to another genome, as speciation measures it
+    ///
+    /// `options_json` takes the `excess` and `weight` coefficients of
+    /// `World.enableSpeciation` (its `threshold` is ignored here).
+    #[wasm_bindgen]
+    pub fn distance(&self, other: &Genome, options_json: &str) -> Result<f64, JsValue> {
+        let options = SpeciationOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid speciation options: {}", e)))?;
+        Ok(options.distance(self.genes(), other.genes()))
+    }
+}
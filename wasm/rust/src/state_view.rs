@@ -0,0 +1,112 @@
+use wasm_bindgen::prelude::*;
+
+use crate::OrganismState;
+
+/// StateView is a read-only copy of a state's metrics for display code
+///
+/// It holds the core and custom metrics, the state vector and the metric
+/// metadata as of `OrganismState.view()`, but none of the dynamics, noise
+/// or solver state, so it is cheap to make every frame. It only has
+/// getters: handing it to UI components or untrusted code can't perturb
+/// the simulation.
+#[wasm_bindgen]
+pub struct StateView {
+    state: OrganismState,
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Get a read-only view of the current metrics
+    #[wasm_bindgen]
+    pub fn view(&self) -> StateView {
+        StateView {
+            state: OrganismState {
+                population: self.population,
+                energy: self.energy,
+                generation: self.generation,
+                age: self.age,
+                mutation_rate: self.mutation_rate,
+                selection_pressure: self.selection_pressure,
+                adaptation_score: self.adaptation_score,
+                state_vector: self.state_vector.clone(),
+                custom_metrics: self.custom_metrics.clone(),
+                metric_meta: self.metric_meta.clone(),
+                ..OrganismState::new()
+            },
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl StateView {
+    #[wasm_bindgen(getter)]
+    pub fn population(&self) -> f64 {
+        self.state.population
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn energy(&self) -> f64 {
+        self.state.energy
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u64 {
+        self.state.generation
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn age(&self) -> u64 {
+        self.state.age
+    }
+    
+    #[wasm_bindgen(getter, js_name = mutationRate)]
+    pub fn mutation_rate(&self) -> f64 {
+        self.state.mutation_rate
+    }
+    
+    #[wasm_bindgen(getter, js_name = selectionPressure)]
+    pub fn selection_pressure(&self) -> f64 {
+        self.state.selection_pressure
+    }
+    
+    #[wasm_bindgen(getter, js_name = adaptationScore)]
+    pub fn adaptation_score(&self) -> f64 {
+        self.state.adaptation_score
+    }
+    
+    /// Get the value of a custom metric (undefined when it isn't defined)
+    #[wasm_bindgen(js_name = getCustomMetric)]
+    pub fn get_custom_metric(&self, name: &str) -> Option<f64> {
+        self.state.get_custom_metric(name)
+    }
+    
+    /// Get all custom metrics as a JSON object
+    #[wasm_bindgen(js_name = getCustomMetrics)]
+    pub fn get_custom_metrics(&self) -> Result<String, JsValue> {
+        self.state.get_custom_metrics()
+    }
+    
+    /// Get every core metric and the custom metrics in one object, as `OrganismState.getMetrics`
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self) -> Result<JsValue, JsValue> {
+        self.state.get_metrics()
+    }
+    
+    /// Get the state vector
+    #[wasm_bindgen(js_name = getStateVector)]
+    pub fn get_state_vector(&self) -> Vec<f64> {
+        self.state.get_state_vector()
+    }
+    
+    /// Get the metadata of a metric as JSON (`unit`, `precision`, `description`)
+    #[wasm_bindgen(js_name = getMetricMeta)]
+    pub fn get_metric_meta(&self, name: &str) -> Result<String, JsValue> {
+        self.state.get_metric_meta(name)
+    }
+    
+    /// Describe every core and custom metric as a JSON array, as `OrganismState.getMetricSchema`
+    #[wasm_bindgen(js_name = getMetricSchema)]
+    pub fn get_metric_schema(&self) -> Result<String, JsValue> {
+        self.state.get_metric_schema()
+    }
+}
@@ -0,0 +1,129 @@
+use wasm_bindgen::prelude::*;
+
+use crate::simulation::Simulation;
+use crate::{OrganismState, RuleRegistry};
+
+/// What a subsystem hook may read and change
+pub struct SubsystemContext<'a> {
+    pub state: &'a mut OrganismState,
+    pub registry: &'a mut RuleRegistry,
+    /// Ticks run before this one
+    pub tick: u64,
+    /// Time step of the tick (0 in `init`)
+    pub delta_time: f64,
+}
+
+/// An engine component plugged into a `Simulation`
+///
+/// Downstream crates implement this for custom environments, analytics
+/// and the like, and add them with `Simulation::add_subsystem` or
+/// `SimulationBuilder::with_subsystem`. Every tick runs each
+/// subsystem's `pre_tick` in registration order, then the registry's
+/// rules and the state's step, then each `post_tick`. A hook returning
+/// an error aborts the tick with it.
+pub trait Subsystem {
+    /// Name the subsystem is registered and looked up under
+    fn name(&self) -> &str;
+    
+    /// Called once when the subsystem is added
+    fn init(&mut self, _context: &mut SubsystemContext) -> Result<(), String> {
+        Ok(())
+    }
+    
+    fn pre_tick(&mut self, _context: &mut SubsystemContext) -> Result<(), String> {
+        Ok(())
+    }
+    
+    fn post_tick(&mut self, _context: &mut SubsystemContext) -> Result<(), String> {
+        Ok(())
+    }
+    
+    /// The subsystem's own state, for saving and inspection
+    fn serialize(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+    
+    /// Restore state produced by `serialize`
+    fn restore(&mut self, _value: &serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+    
+    /// Copy the subsystem into a fork of its simulation
+    fn box_clone(&self) -> Box<dyn Subsystem>;
+}
+
+impl Clone for Box<dyn Subsystem> {
+    fn clone(&self) -> Box<dyn Subsystem> {
+        self.box_clone()
+    }
+}
+
+impl Simulation {
+    /// Add a subsystem, initializing it against the current state and registry
+    pub fn add_subsystem(&mut self, mut subsystem: Box<dyn Subsystem>) -> Result<(), String> {
+        if self.subsystems.iter().any(|s| s.name() == subsystem.name()) {
+            return Err(format!("Subsystem already added: {}", subsystem.name()));
+        }
+        let tick = self.ticks();
+        let (state, registry) = self.parts_mut();
+        subsystem.init(&mut SubsystemContext { state, registry, tick, delta_time: 0.0 })
+            .map_err(|e| format!("Subsystem {} failed to initialize: {}", subsystem.name(), e))?;
+        self.subsystems.push(subsystem);
+        Ok(())
+    }
+    
+    /// Look up a subsystem by name
+    pub fn subsystem(&self, name: &str) -> Option<&dyn Subsystem> {
+        self.subsystems.iter().find(|s| s.name() == name).map(|s| s.as_ref())
+    }
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Names of the subsystems, in the order their hooks run
+    #[wasm_bindgen(js_name = getSubsystemNames)]
+    pub fn get_subsystem_names(&self) -> Vec<String> {
+        self.subsystems.iter().map(|s| s.name().to_string()).collect()
+    }
+    
+    /// Get the serialized state of every subsystem as a JSON object keyed by name
+    #[wasm_bindgen(js_name = getSubsystemState)]
+    pub fn get_subsystem_state(&self) -> Result<String, JsValue> {
+        let states: serde_json::Map<String, serde_json::Value> = self.subsystems
+            .iter()
+            .map(|s| (s.name().to_string(), s.serialize()))
+            .collect();
+        serde_json::to_string(&states)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize subsystem state: {}", e)))
+    }
+    
+    /// Restore subsystems from JSON produced by `getSubsystemState`
+    ///
+    /// Subsystems missing from the JSON are left as they are.
+    #[wasm_bindgen(js_name = setSubsystemState)]
+    pub fn set_subsystem_state(&mut self, json: &str) -> Result<(), JsValue> {
+        let result = self.restore_subsystems(json);
+        self.record_input("setSubsystemState", || {
+            serde_json::json!({ "states": serde_json::from_str::<serde_json::Value>(json).unwrap_or_default() })
+        }, result.is_ok());
+        result
+    }
+}
+
+impl Simulation {
+    fn restore_subsystems(&mut self, json: &str) -> Result<(), JsValue> {
+        let states: serde_json::Map<String, serde_json::Value> = serde_json::from_str(json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse subsystem state: {}", e)))?;
+        if let Some(unknown) = states.keys().find(|name| self.subsystem(name).is_none()) {
+            return Err(JsValue::from_str(&format!("Subsystem not found: {}", unknown)));
+        }
+        for subsystem in &mut self.subsystems {
+            if let Some(value) = states.get(subsystem.name()) {
+                subsystem.restore(value).map_err(|e| {
+                    JsValue::from_str(&format!("Failed to restore subsystem {}: {}", subsystem.name(), e))
+                })?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,97 @@
+use wasm_bindgen::prelude::*;
+use std::collections::BTreeSet;
+
+use crate::call_log;
+use crate::RuleRegistry;
+
+/// Check tags and return them sorted without duplicates
+pub(crate) fn normalize(tags: Vec<String>) -> Result<Vec<String>, String> {
+    for tag in &tags {
+        if tag.is_empty() || tag.chars().any(char::is_whitespace) {
+            return Err(format!("Invalid tag {:?}: tags must be non-empty and have no whitespace", tag));
+        }
+        if tag.starts_with('/') || tag.ends_with('/') || tag.contains("//") {
+            return Err(format!("Invalid tag {:?}: namespace parts must be non-empty", tag));
+        }
+    }
+    let tags: BTreeSet<String> = tags.into_iter().collect();
+    Ok(tags.into_iter().collect())
+}
+
+/// Whether `tag` is `query` or lies in the namespace `query` (`query/...`)
+fn matches(tag: &str, query: &str) -> bool {
+    tag.strip_prefix(query).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+impl RuleRegistry {
+    /// IDs of the rules tagged `query`, in the order ticks run them
+    fn tagged(&self, query: &str) -> Vec<String> {
+        self.run_order()
+            .into_iter()
+            .filter(|id| self.rules.get(*id).is_some_and(|rule| rule.tags.iter().any(|tag| matches(tag, query))))
+            .cloned()
+            .collect()
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Replace a rule's tags
+    ///
+    /// Tags are free-form labels such as `metabolism`. A slash makes a
+    /// namespace: `metabolism/energy` is found by queries for both
+    /// `metabolism/energy` and `metabolism`.
+    #[wasm_bindgen(js_name = setRuleTags)]
+    pub fn set_rule_tags(&mut self, id: &str, tags: Vec<String>) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleTags", || serde_json::json!({ "id": id, "tags": tags }));
+        let tags = normalize(tags).map_err(|e| JsValue::from_str(&e))?;
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.tags = tags;
+        Ok(())
+    }
+    
+    /// A rule's tags, sorted
+    #[wasm_bindgen(js_name = getRuleTags)]
+    pub fn get_rule_tags(&self, id: &str) -> Result<Vec<String>, JsValue> {
+        self.rules.get(id)
+            .map(|rule| rule.tags.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))
+    }
+    
+    /// Every tag in use, sorted
+    #[wasm_bindgen(js_name = getTags)]
+    pub fn get_tags(&self) -> Vec<String> {
+        let tags: BTreeSet<&String> = self.rules.values().flat_map(|rule| &rule.tags).collect();
+        tags.into_iter().cloned().collect()
+    }
+    
+    /// IDs of the rules tagged `tag` or with a tag in its namespace, in the order ticks run them
+    #[wasm_bindgen(js_name = getRulesByTag)]
+    pub fn get_rules_by_tag(&self, tag: &str) -> Vec<String> {
+        self.tagged(tag)
+    }
+    
+    /// Enable or disable every rule `getRulesByTag(tag)` lists and return how many there were
+    #[wasm_bindgen(js_name = setRulesEnabledByTag)]
+    pub fn set_rules_enabled_by_tag(&mut self, tag: &str, enabled: bool) -> usize {
+        let _call = call_log::enter("setRulesEnabledByTag", || serde_json::json!({ "tag": tag, "enabled": enabled }));
+        let ids = self.tagged(tag);
+        for id in &ids {
+            if let Some(rule) = self.rules.get_mut(id) {
+                rule.enabled = enabled;
+            }
+        }
+        ids.len()
+    }
+    
+    /// Remove every rule `getRulesByTag(tag)` lists and return how many there were
+    #[wasm_bindgen(js_name = removeRulesByTag)]
+    pub fn remove_rules_by_tag(&mut self, tag: &str) -> usize {
+        let _call = call_log::enter("removeRulesByTag", || serde_json::json!({ "tag": tag }));
+        let ids = self.tagged(tag);
+        for id in &ids {
+            self.remove_rule(id);
+        }
+        ids.len()
+    }
+}
@@ -0,0 +1,348 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::OrganismState;
+
+/// Value returned by `getOwnershipMap` for cells nobody holds
+const UNOWNED: i32 = -1;
+
+/// Largest owner ID, so every owner fits the `i32` ownership map
+const MAX_OWNER: u32 = i32::MAX as u32;
+
+/// Strength below which a defended cell falls back to neutral
+const MIN_HOLD_STRENGTH: f64 = 1e-9;
+
+/// A single cell of the territory grid
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct TerritoryCell {
+    owner: Option<u32>,
+    strength: f64,
+    challenges: u32,
+}
+
+/// TerritoryMap tracks claimable grid cells, who holds them and how strongly
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TerritoryMap {
+    width: u32,
+    height: u32,
+    cells: Vec<TerritoryCell>,
+    
+    // Resource bonus per owner, as a fraction (0.2 = +20% yield)
+    owner_bonuses: HashMap<u32, f64>,
+    
+    // Extra weight the current holder gets when a cell is contested
+    defender_advantage: f64,
+}
+
+#[wasm_bindgen]
+impl TerritoryMap {
+    /// Create an unowned territory grid
+    #[wasm_bindgen(constructor)]
+    pub fn new(width: u32, height: u32) -> TerritoryMap {
+        TerritoryMap {
+            width,
+            height,
+            cells: vec![TerritoryCell::default(); (width as usize) * (height as usize)],
+            owner_bonuses: HashMap::new(),
+            defender_advantage: 0.1,
+        }
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    
+    #[wasm_bindgen(getter, js_name = defenderAdvantage)]
+    pub fn defender_advantage(&self) -> f64 {
+        self.defender_advantage
+    }
+    
+    #[wasm_bindgen(setter, js_name = defenderAdvantage)]
+    pub fn set_defender_advantage(&mut self, value: f64) {
+        self.defender_advantage = value.max(0.0);
+    }
+    
+    /// Claim a cell for an owner
+    ///
+    /// Unowned cells are taken outright and cells already held by the owner are
+    /// reinforced. A cell held by someone else is contested: the challenger wins
+    /// only if its strength beats the holder's strength plus the defender
+    /// advantage. Returns one of "claimed", "reinforced", "captured",
+    /// "repelled" or "neutralized". Owner IDs go up to 2^31 - 1.
+    #[wasm_bindgen]
+    pub fn claim(&mut self, x: u32, y: u32, owner: u32, strength: f64) -> Result<String, JsValue> {
+        check_owner(owner).map_err(|e| JsValue::from_str(&e))?;
+        let index = self.index(x, y)?;
+        let strength = strength.max(0.0);
+        let advantage = self.defender_advantage;
+        let cell = &mut self.cells[index];
+        
+        let outcome = match cell.owner {
+            None => {
+                cell.owner = Some(owner);
+                cell.strength = strength;
+                "claimed"
+            }
+            Some(holder) if holder == owner => {
+                cell.strength += strength;
+                "reinforced"
+            }
+            Some(_) => {
+                cell.challenges += 1;
+                let defense = cell.strength * (1.0 + advantage);
+                
+                if strength > defense {
+                    cell.owner = Some(owner);
+                    cell.strength = strength - defense;
+                    "captured"
+                } else {
+                    cell.strength = (defense - strength) / (1.0 + advantage);
+                    if cell.strength <= MIN_HOLD_STRENGTH {
+                        cell.owner = None;
+                        cell.strength = 0.0;
+                        "neutralized"
+                    } else {
+                        "repelled"
+                    }
+                }
+            }
+        };
+        
+        Ok(outcome.to_string())
+    }
+    
+    /// Release a cell back to neutral, returning the previous owner
+    #[wasm_bindgen]
+    pub fn release(&mut self, x: u32, y: u32) -> Result<Option<u32>, JsValue> {
+        let index = self.index(x, y)?;
+        let cell = &mut self.cells[index];
+        cell.strength = 0.0;
+        Ok(cell.owner.take())
+    }
+    
+    /// Get the owner of a cell (undefined when unowned)
+    #[wasm_bindgen(js_name = ownerAt)]
+    pub fn owner_at(&self, x: u32, y: u32) -> Result<Option<u32>, JsValue> {
+        Ok(self.cells[self.index(x, y)?].owner)
+    }
+    
+    /// Get the hold strength of a cell
+    #[wasm_bindgen(js_name = strengthAt)]
+    pub fn strength_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        Ok(self.cells[self.index(x, y)?].strength)
+    }
+    
+    /// Check whether a cell borders a cell held by a different owner
+    #[wasm_bindgen(js_name = isContested)]
+    pub fn is_contested(&self, x: u32, y: u32) -> Result<bool, JsValue> {
+        let index = self.index(x, y)?;
+        Ok(self.borders_rival(index))
+    }
+    
+    /// Set the resource bonus for an owner (0.2 = +20% yield on owned cells)
+    #[wasm_bindgen(js_name = setOwnerBonus)]
+    pub fn set_owner_bonus(&mut self, owner: u32, bonus: f64) {
+        self.owner_bonuses.insert(owner, bonus.max(-1.0));
+    }
+    
+    /// Get the resource bonus configured for an owner
+    #[wasm_bindgen(js_name = getOwnerBonus)]
+    pub fn get_owner_bonus(&self, owner: u32) -> f64 {
+        self.owner_bonuses.get(&owner).copied().unwrap_or(0.0)
+    }
+    
+    /// Get the resource yield multiplier of a cell based on its owner's bonus
+    #[wasm_bindgen(js_name = resourceMultiplierAt)]
+    pub fn resource_multiplier_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        let cell = &self.cells[self.index(x, y)?];
+        Ok(cell.owner.map_or(1.0, |owner| 1.0 + self.get_owner_bonus(owner)))
+    }
+    
+    /// Count the cells held by an owner
+    #[wasm_bindgen(js_name = territoryCount)]
+    pub fn territory_count(&self, owner: u32) -> u32 {
+        self.cells.iter().filter(|cell| cell.owner == Some(owner)).count() as u32
+    }
+    
+    /// Get the ownership map in row-major order, with -1 for unowned cells
+    #[wasm_bindgen(js_name = getOwnershipMap)]
+    pub fn get_ownership_map(&self) -> Vec<i32> {
+        self.cells
+            .iter()
+            .map(|cell| cell.owner.map_or(UNOWNED, |owner| owner as i32))
+            .collect()
+    }
+    
+    /// Publish an owner's territory to a state as custom metrics, so rules can read it
+    ///
+    /// Defines or overwrites `territory-cells`, `territory-share`,
+    /// `territory-strength`, `territory-contested` and `territory-bonus`
+    /// with the owner's held cells, share of the grid, total hold strength,
+    /// contested cells and resource bonus. Call it before ticking the state.
+    #[wasm_bindgen(js_name = publishTerritory)]
+    pub fn publish_territory(&self, state: &mut OrganismState, owner: u32) {
+        let (mut cells, mut strength, mut contested) = (0u32, 0.0, 0u32);
+        for (index, cell) in self.cells.iter().enumerate() {
+            if cell.owner == Some(owner) {
+                cells += 1;
+                strength += cell.strength;
+                if self.borders_rival(index) {
+                    contested += 1;
+                }
+            }
+        }
+        let metrics = &mut state.custom_metrics;
+        metrics.insert("territory-cells".to_string(), cells as f64);
+        metrics.insert("territory-share".to_string(), cells as f64 / self.cells.len().max(1) as f64);
+        metrics.insert("territory-strength".to_string(), strength);
+        metrics.insert("territory-contested".to_string(), contested as f64);
+        metrics.insert("territory-bonus".to_string(), self.get_owner_bonus(owner));
+    }
+    
+    /// Get the hold strength of every cell in row-major order
+    #[wasm_bindgen(js_name = getStrengthMap)]
+    pub fn get_strength_map(&self) -> Vec<f64> {
+        self.cells.iter().map(|cell| cell.strength).collect()
+    }
+    
+    /// Get per-owner territory statistics as JSON
+    #[wasm_bindgen(js_name = getOwnershipSummary)]
+    pub fn get_ownership_summary(&self) -> Result<String, JsValue> {
+        let mut summary: HashMap<u32, (u32, f64, u32)> = HashMap::new();
+        
+        for (index, cell) in self.cells.iter().enumerate() {
+            if let Some(owner) = cell.owner {
+                let entry = summary.entry(owner).or_insert((0, 0.0, 0));
+                entry.0 += 1;
+                entry.1 += cell.strength;
+                if self.borders_rival(index) {
+                    entry.2 += 1;
+                }
+            }
+        }
+        
+        let mut owners: Vec<u32> = summary.keys().copied().collect();
+        owners.sort_unstable();
+        
+        let total_cells = self.cells.len().max(1) as f64;
+        let entries: Vec<serde_json::Value> = owners
+            .into_iter()
+            .map(|owner| {
+                let (cells, strength, contested) = summary[&owner];
+                serde_json::json!({
+                    "owner": owner,
+                    "cells": cells,
+                    "share": cells as f64 / total_cells,
+                    "totalStrength": strength,
+                    "contestedCells": contested,
+                    "resourceBonus": self.get_owner_bonus(owner),
+                })
+            })
+            .collect();
+        
+        serde_json::to_string(&entries)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize territory summary: {}", e)))
+    }
+    
+    /// Estimated heap bytes held by the grid cells and owner bonuses
+    #[wasm_bindgen(js_name = memoryBytes)]
+    pub fn memory_bytes(&self) -> usize {
+        self.cells.capacity() * std::mem::size_of::<TerritoryCell>()
+            + self.owner_bonuses.capacity() * std::mem::size_of::<(u32, f64)>()
+    }
+    
+    /// Get a snapshot of the territory map as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize territory map: {}", e)))
+    }
+    
+    /// Restore the territory map from a JSON snapshot
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: TerritoryMap = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse territory snapshot: {}", e)))?;
+        
+        if loaded.cells.len() != (loaded.width as usize) * (loaded.height as usize) {
+            return Err(JsValue::from_str("Territory snapshot cell count does not match its dimensions"));
+        }
+        if let Some(owner) = loaded.cells.iter().filter_map(|cell| cell.owner).find(|owner| check_owner(*owner).is_err()) {
+            return Err(JsValue::from_str(&format!("Failed to parse territory snapshot: {}", check_owner(owner).unwrap_err())));
+        }
+        
+        *self = loaded;
+        Ok(())
+    }
+}
+
+impl TerritoryMap {
+    /// Set the holder of a cell directly, bypassing the claim rules
+    pub(crate) fn set_holder(&mut self, x: u32, y: u32, owner: Option<u32>, strength: f64) -> Result<(), JsValue> {
+        if let Some(owner) = owner {
+            check_owner(owner).map_err(|e| JsValue::from_str(&e))?;
+        }
+        let index = self.index(x, y)?;
+        let cell = &mut self.cells[index];
+        cell.owner = owner;
+        cell.strength = if owner.is_some() { strength.max(0.0) } else { 0.0 };
+        cell.challenges += 1;
+        Ok(())
+    }
+    
+    /// Convert grid coordinates into a cell index
+    fn index(&self, x: u32, y: u32) -> Result<usize, JsValue> {
+        if x >= self.width || y >= self.height {
+            return Err(JsValue::from_str(&format!(
+                "Cell ({}, {}) is outside the {}x{} territory map",
+                x, y, self.width, self.height
+            )));
+        }
+        Ok((y as usize) * (self.width as usize) + (x as usize))
+    }
+    
+    /// Check whether any 4-neighbour of an owned cell belongs to someone else
+    fn borders_rival(&self, index: usize) -> bool {
+        let owner = match self.cells[index].owner {
+            Some(owner) => owner,
+            None => return false,
+        };
+        
+        let width = self.width as usize;
+        let x = index % width;
+        let y = index / width;
+        let mut neighbours = Vec::with_capacity(4);
+        if x > 0 {
+            neighbours.push(index - 1);
+        }
+        if x + 1 < width {
+            neighbours.push(index + 1);
+        }
+        if y > 0 {
+            neighbours.push(index - width);
+        }
+        if y + 1 < self.height as usize {
+            neighbours.push(index + width);
+        }
+        
+        neighbours
+            .into_iter()
+            .any(|n| matches!(self.cells[n].owner, Some(other) if other != owner))
+    }
+}
+
+/// Check that an owner ID fits the ownership map
+fn check_owner(owner: u32) -> Result<(), String> {
+    if owner > MAX_OWNER {
+        return Err(format!("Owner ID {} is above the maximum of {}", owner, MAX_OWNER));
+    }
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use wasm_bindgen::prelude::*;
+
+use crate::features::{self, Feature};
+use crate::lisp::{Program, RunError, Trace, METRICS};
+use crate::{OrganismState, RuleRegistry};
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Evaluate a rule once with tracing enabled
+    ///
+    /// The rule runs against a copy of `state` with its limits, as
+    /// `applyRule` runs it, but neither the state nor the rule's stats are
+    /// touched. Returns JSON with the `result` `applyRule` would return,
+    /// any `error` (with line and column when it has a position), the
+    /// `trace` of every evaluated
+    /// sub-expression in evaluation order (innermost first, each with its
+    /// `depth`, position, source `expr` and `value`), and the metric
+    /// `changes` the run made.
+    #[wasm_bindgen(js_name = traceRule)]
+    pub fn trace_rule(&self, state: &OrganismState, rule_id: &str, params: Vec<f64>) -> Result<String, JsValue> {
+        if !features::enabled(Feature::Tracing) {
+            return Err(JsValue::from_str("Feature tracing is disabled"));
+        }
+        let rule = self.rules.get(rule_id)
+            .ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", rule_id)))?;
+        
+        // Rules loaded from a snapshot haven't been compiled yet
+        let compiled;
+        let program = if rule.program.is_compiled() {
+            &rule.program
+        } else {
+            compiled = Program::compile(&rule.lisp_code);
+            &compiled
+        };
+        
+        let mut scratch = state.clone();
+        let mut trace = Trace::default();
+        let budget = self.budget(rule_id);
+        let outcome = features::measure(Feature::Tracing, || {
+            program.run_traced(&mut scratch, &params, budget, &mut trace)
+        });
+        
+        let (result, error) = match outcome {
+            Ok(value) => (serde_json::json!(value), serde_json::Value::Null),
+            Err(RunError::At(e)) => (serde_json::Value::Null, serde_json::json!({
+                "message": e.message,
+                "line": e.span.line,
+                "column": e.span.column,
+            })),
+            Err(RunError::Other(message)) => (serde_json::Value::Null, serde_json::json!({
+                "message": message,
+                "line": null,
+                "column": null,
+            })),
+        };
+        
+        let entries: Vec<serde_json::Value> = trace.entries
+            .iter()
+            .map(|entry| serde_json::json!({
+                "depth": entry.depth,
+                "line": entry.span.line,
+                "column": entry.span.column,
+                "expr": entry.expr,
+                "value": entry.value.to_json(),
+            }))
+            .collect();
+        
+        let mut changes = serde_json::Map::new();
+        for metric in METRICS.iter() {
+            let before = state.metric(metric.canonical).unwrap_or(0.0);
+            let after = scratch.metric(metric.canonical).unwrap_or(0.0);
+            if before != after {
+                changes.insert(metric.name.to_string(), serde_json::json!({ "before": before, "after": after }));
+            }
+        }
+        
+        let report = serde_json::json!({
+            "ruleId": rule_id,
+            "params": params,
+            "result": result,
+            "error": error,
+            "trace": entries,
+            "truncated": trace.truncated,
+            "changes": changes,
+        });
+        
+        serde_json::to_string(&report)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize trace: {}", e)))
+    }
+}
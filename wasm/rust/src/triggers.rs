@@ -0,0 +1,121 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::call_log;
+use crate::{lisp, OrganismState, RuleRegistry};
+
+/// A predicate over the state that must hold for `tick` to run a rule
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RuleTrigger {
+    predicate: String,
+    /// Fire only on the tick the predicate becomes true, not on every tick it holds
+    #[serde(default)]
+    edge: bool,
+    /// Whether the predicate held at the last tick
+    #[serde(skip)]
+    held: bool,
+}
+
+impl RuleTrigger {
+    /// Whether the rule fires, given whether the predicate holds now
+    pub(crate) fn fires(&self, holds: bool) -> bool {
+        holds && !(self.edge && self.held)
+    }
+}
+
+/// Whether each triggered rule's predicate holds, by rule ID
+pub(crate) type TriggerValues = HashMap<String, bool>;
+
+impl RuleRegistry {
+    /// Evaluate the triggers of the rules that can run in `mode` against the
+    /// state at the start of a tick
+    pub(crate) fn evaluate_triggers(&self, state: &OrganismState, mode: Option<&str>) -> Result<TriggerValues, JsValue> {
+        self.rules
+            .values()
+            .filter(|rule| rule.runs_in(mode))
+            .filter_map(|rule| rule.trigger.as_ref().map(|trigger| (rule, trigger)))
+            .map(|(rule, trigger)| {
+                let holds = lisp::eval_predicate(&trigger.predicate, state).map_err(|e| {
+                    JsValue::from_str(&format!("Failed to evaluate trigger of rule {}: {}", rule.id, e))
+                })?;
+                Ok((rule.id.clone(), holds))
+            })
+            .collect()
+    }
+    
+    /// IDs of the rules whose trigger held at the last tick, sorted
+    pub(crate) fn held_triggers(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.rules
+            .values()
+            .filter(|rule| rule.trigger.as_ref().is_some_and(|trigger| trigger.held))
+            .map(|rule| rule.id.clone())
+            .collect();
+        ids.sort();
+        ids
+    }
+    
+    /// Mark exactly the triggers of `ids` as having held at the last tick
+    pub(crate) fn restore_held_triggers(&mut self, ids: &[String]) {
+        for rule in self.rules.values_mut() {
+            if let Some(trigger) = &mut rule.trigger {
+                trigger.held = ids.contains(&rule.id);
+            }
+        }
+    }
+    
+    /// Remember which triggers held, for edge triggers on the next tick
+    pub(crate) fn settle_triggers(&mut self, values: &TriggerValues) {
+        for rule in self.rules.values_mut() {
+            if let Some(trigger) = &mut rule.trigger {
+                trigger.held = values.get(&rule.id).copied().unwrap_or(false);
+            }
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Make `tick` run a rule only while a Lisp predicate holds
+    ///
+    /// `predicate` is evaluated against the state at the start of every
+    /// tick, as mode predicates are, e.g. `(< energy 200)` or
+    /// `(> adaptation-score 0.8)`. With `edge`, the rule fires only on the
+    /// first tick the predicate holds and again after it has stopped
+    /// holding. The trigger applies on top of modes, schedules and enabled
+    /// flags, survives re-registering the rule and is exported with the
+    /// registry. It is only evaluated while the rule is enabled and in the
+    /// active mode; a rule not evaluated counts as not holding. Predicates
+    /// with lint errors, unknown functions or symbols other than core
+    /// metrics and their own bindings are rejected.
+    #[wasm_bindgen(js_name = setRuleTrigger)]
+    pub fn set_rule_trigger(&mut self, id: &str, predicate: &str, edge: bool) -> Result<(), JsValue> {
+        let _call = call_log::enter("setRuleTrigger", || serde_json::json!({
+            "id": id,
+            "predicate": call_log::text(predicate),
+            "edge": edge,
+        }));
+        lisp::check_predicate(predicate)
+            .map_err(|e| JsValue::from_str(&format!("Invalid trigger of rule {}: {}", id, e)))?;
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        rule.trigger = Some(RuleTrigger { predicate: predicate.to_string(), edge, held: false });
+        Ok(())
+    }
+    
+    /// Remove a rule's trigger; returns whether it had one
+    #[wasm_bindgen(js_name = clearRuleTrigger)]
+    pub fn clear_rule_trigger(&mut self, id: &str) -> Result<bool, JsValue> {
+        let _call = call_log::enter("clearRuleTrigger", || serde_json::json!({ "id": id }));
+        let rule = self.rules.get_mut(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        Ok(rule.trigger.take().is_some())
+    }
+    
+    /// Get a rule's trigger as JSON `{ predicate, edge }` (null when it has none)
+    #[wasm_bindgen(js_name = getRuleTrigger)]
+    pub fn get_rule_trigger(&self, id: &str) -> Result<String, JsValue> {
+        let rule = self.rules.get(id).ok_or_else(|| JsValue::from_str(&format!("Rule not found: {}", id)))?;
+        serde_json::to_string(&rule.trigger)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize rule trigger: {}", e)))
+    }
+}
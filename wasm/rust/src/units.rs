@@ -0,0 +1,127 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::lisp::{self, METRICS};
+use crate::OrganismState;
+
+/// Display metadata of a metric
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct MetricMeta {
+    /// Unit string such as "individuals" or "1" for dimensionless values; empty when unknown
+    pub unit: String,
+    /// Decimal places to show
+    pub precision: u32,
+    pub description: String,
+}
+
+/// Built-in metadata of a core metric (by canonical name)
+fn core_meta(canonical: &str) -> Option<MetricMeta> {
+    let (unit, precision, description) = match canonical {
+        "population" => ("individuals", 0, "Number of individuals in the population"),
+        "energy" => ("energy units", 1, "Energy available to the population"),
+        "generation" => ("generations", 0, "Generations elapsed"),
+        "age" => ("ticks", 0, "Steps simulated so far"),
+        "mutation_rate" => ("1", 4, "Probability of mutation per individual and generation"),
+        "selection_pressure" => ("1", 3, "Strength of selection, from 0 (none) to 1"),
+        "adaptation_score" => ("1", 3, "Combined fitness score derived from population, energy and age"),
+        _ => return None,
+    };
+    Some(MetricMeta {
+        unit: unit.to_string(),
+        precision,
+        description: description.to_string(),
+    })
+}
+
+impl OrganismState {
+    /// Canonical name of a core metric, or the name of a custom one
+    fn meta_key(&self, name: &str) -> Option<String> {
+        match lisp::lookup_metric(name) {
+            Some(core) => Some(core.canonical.to_string()),
+            None => self.custom_metrics.contains_key(name).then(|| name.to_string()),
+        }
+    }
+    
+    /// Effective metadata for a metric key: the override, else the built-in default
+    fn meta_for(&self, key: &str) -> MetricMeta {
+        self.metric_meta.get(key).cloned()
+            .or_else(|| core_meta(key))
+            .unwrap_or_else(|| MetricMeta { unit: String::new(), precision: 3, description: String::new() })
+    }
+    
+    /// Effective metadata of a core or custom metric
+    pub(crate) fn metric_meta_of(&self, name: &str) -> Option<MetricMeta> {
+        self.meta_key(name).map(|key| self.meta_for(&key))
+    }
+    
+    /// Unit of a metric, if it has one
+    pub(crate) fn unit_of(&self, name: &str) -> Option<String> {
+        self.metric_meta_of(name).map(|meta| meta.unit).filter(|unit| !unit.is_empty())
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Set the unit, display precision and description of a core or custom metric
+    ///
+    /// The metadata is saved in snapshots. Use "1" as the unit of
+    /// dimensionless metrics and "" when the unit is unknown.
+    #[wasm_bindgen(js_name = setMetricMeta)]
+    pub fn set_metric_meta(&mut self, name: &str, unit: &str, precision: u32, description: &str) -> Result<(), JsValue> {
+        let key = self.meta_key(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown metric: {}", name)))?;
+        self.metric_meta.insert(key, MetricMeta {
+            unit: unit.to_string(),
+            precision,
+            description: description.to_string(),
+        });
+        Ok(())
+    }
+    
+    /// Get the metadata of a metric as JSON (`unit`, `precision`, `description`)
+    #[wasm_bindgen(js_name = getMetricMeta)]
+    pub fn get_metric_meta(&self, name: &str) -> Result<String, JsValue> {
+        let meta = self.metric_meta_of(name)
+            .ok_or_else(|| JsValue::from_str(&format!("Unknown metric: {}", name)))?;
+        serde_json::to_string(&meta)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize metric metadata: {}", e)))
+    }
+    
+    /// Describe every core and custom metric as a JSON array
+    ///
+    /// Each entry has `name`, `ruleName` (the name in rule code), `custom`,
+    /// `writable`, `unit`, `precision`, `description` and the current `value`.
+    #[wasm_bindgen(js_name = getMetricSchema)]
+    pub fn get_metric_schema(&self) -> Result<String, JsValue> {
+        let mut schema = Vec::with_capacity(METRICS.len() + self.custom_metrics.len());
+        for spec in &METRICS {
+            let meta = self.meta_for(spec.canonical);
+            schema.push(serde_json::json!({
+                "name": spec.canonical,
+                "ruleName": spec.name,
+                "custom": false,
+                "writable": spec.writable,
+                "unit": meta.unit,
+                "precision": meta.precision,
+                "description": meta.description,
+                "value": self.metric(spec.canonical),
+            }));
+        }
+        for (name, value) in &self.custom_metrics {
+            let meta = self.meta_for(name);
+            schema.push(serde_json::json!({
+                "name": name,
+                "ruleName": name,
+                "custom": true,
+                "writable": true,
+                "unit": meta.unit,
+                "precision": meta.precision,
+                "description": meta.description,
+                "value": value,
+            }));
+        }
+        
+        serde_json::to_string(&schema)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize metric schema: {}", e)))
+    }
+}
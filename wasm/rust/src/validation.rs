@@ -0,0 +1,197 @@
+use wasm_bindgen::prelude::*;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::import_guard;
+use crate::{lisp, migration, rule_order, rule_params, Goal, Invariant, InstalledBundle, Mode, Rule, RuleRegistry};
+
+/// One problem found while validating an input, located by a dotted path
+#[derive(Serialize, Clone, Debug)]
+pub(crate) struct Problem {
+    pub path: String,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(path: &str, message: impl Into<String>) -> Problem {
+        Problem { path: path.to_string(), message: message.into() }
+    }
+}
+
+/// Join problems into one error message, each prefixed with its path
+pub(crate) fn summarize(problems: &[Problem]) -> String {
+    problems
+        .iter()
+        .map(|p| if p.path.is_empty() { p.message.clone() } else { format!("{}: {}", p.path, p.message) })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Serialize problems as the JSON array returned by the `check*` methods
+pub(crate) fn to_json(problems: &[Problem]) -> Result<String, JsValue> {
+    serde_json::to_string(problems)
+        .map_err(|e| JsValue::from_str(&format!("Failed to serialize problems: {}", e)))
+}
+
+/// Problems that stop a registry from being read at all, one per broken item
+///
+/// These are the failures `importRegistry` would otherwise report one at
+/// a time: rules, bundles, modes, invariants and goals that don't deserialize,
+/// an execution order that isn't a list of IDs, and rule code nested deeper
+/// than the Lisp reader allows. Other code problems don't stop an import.
+pub(crate) fn registry_shape_problems(data: &serde_json::Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    check_section::<Rule>(&mut problems, data, "rules", true);
+    check_code_depth(&mut problems, data);
+    check_section::<String>(&mut problems, data, "executionOrder", false);
+    check_section::<InstalledBundle>(&mut problems, data, "bundles", true);
+    check_section::<Mode>(&mut problems, data, "modes", false);
+    check_section::<Invariant>(&mut problems, data, "invariants", false);
+    check_section::<Goal>(&mut problems, data, "goals", false);
+    if data.get("fuelLimit").is_some_and(|v| v.as_u64().is_none_or(|fuel| fuel == 0)) {
+        problems.push(Problem::new("fuelLimit", "must be a positive integer"));
+    }
+    problems
+}
+
+/// Deserialize each entry of an optional object (keyed) or array (indexed) section
+fn check_section<T: DeserializeOwned>(problems: &mut Vec<Problem>, data: &serde_json::Value, section: &str, keyed: bool) {
+    let entries: Vec<(String, &serde_json::Value)> = match data.get(section) {
+        None => return,
+        Some(serde_json::Value::Object(map)) if keyed => map.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Some(serde_json::Value::Array(list)) if !keyed => list.iter().enumerate().map(|(i, v)| (i.to_string(), v)).collect(),
+        Some(_) => {
+            problems.push(Problem::new(section, if keyed { "must be an object" } else { "must be an array" }));
+            return;
+        }
+    };
+    for (key, entry) in entries {
+        if let Err(e) = serde_json::from_value::<T>(entry.clone()) {
+            problems.push(Problem::new(&format!("{}.{}", section, key), e.to_string()));
+        }
+    }
+}
+
+/// Report rules whose `lisp_code` nests deeper than the reader's limit
+fn check_code_depth(problems: &mut Vec<Problem>, data: &serde_json::Value) {
+    let Some(rules) = data.get("rules").and_then(|v| v.as_object()) else { return };
+    for (id, rule) in rules {
+        let Some(code) = rule.get("lisp_code").and_then(|v| v.as_str()) else { continue };
+        match lisp::parse(code) {
+            Err(e) if e.too_deep => problems.push(Problem::new(
+                &format!("rules.{}.lisp_code", id),
+                format!("Parse error at line {}, column {}: {}", e.span.line, e.span.column, e.message),
+            )),
+            _ => {}
+        }
+    }
+}
+
+/// Problems with a readable registry's contents: code, references and rule metadata
+fn registry_content_problems(data: &serde_json::Value) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    // Entries that don't deserialize were already reported by `registry_shape_problems`
+    let rules: HashMap<String, Rule> = data.get("rules")
+        .and_then(|v| v.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(id, v)| Some((id.clone(), serde_json::from_value(v.clone()).ok()?)))
+                .collect()
+        })
+        .unwrap_or_default();
+    let modes: Vec<Mode> = data.get("modes")
+        .and_then(|v| v.as_array())
+        .map(|list| list.iter().filter_map(|v| serde_json::from_value(v.clone()).ok()).collect())
+        .unwrap_or_default();
+    
+    let mut ids: Vec<&String> = rules.keys().collect();
+    ids.sort();
+    for id in ids {
+        let rule = &rules[id];
+        let path = format!("rules.{}", id);
+        if rule.id != *id {
+            problems.push(Problem::new(&path, format!("Rule is stored under {} but has ID {}", id, rule.id)));
+        }
+        match lisp::parse(&rule.lisp_code) {
+            // Code nested too deeply was already reported by `registry_shape_problems`
+            Err(e) if !e.too_deep => problems.push(Problem::new(
+                &format!("{}.lisp_code", path),
+                format!("Parse error at line {}, column {}: {}", e.span.line, e.span.column, e.message),
+            )),
+            _ => {}
+        }
+        for mode in rule.modes.iter().filter(|name| !modes.iter().any(|m| m.name() == name.as_str())) {
+            problems.push(Problem::new(&format!("{}.modes", path), format!("Mode not defined: {}", mode)));
+        }
+        for (index, case) in rule.tests.iter().enumerate() {
+            if let Err(e) = case.validate() {
+                problems.push(Problem::new(&format!("{}.tests.{}", path, index), e));
+            }
+        }
+        if let Some(params) = data["rules"][id].get("params") {
+            if let Err(e) = rule_params::parse_params(params) {
+                problems.push(Problem::new(&format!("{}.params", path), e));
+            }
+        }
+        for dependency in rule.runs_after.iter().filter(|d| !rules.contains_key(*d)) {
+            problems.push(Problem::new(&format!("{}.runs_after", path), format!("Rule not found: {}", dependency)));
+        }
+    }
+    if let Some(cycle) = rule_order::find_cycle(&rules) {
+        problems.push(Problem::new(
+            &format!("rules.{}.runs_after", cycle[0]),
+            format!("Rule dependencies form a cycle: {}", cycle.join(" -> ")),
+        ));
+    }
+    
+    let order = data.get("executionOrder").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+    let mut seen = BTreeSet::new();
+    for (index, id) in order.iter().enumerate() {
+        let id = match id.as_str() {
+            Some(id) => id,
+            None => continue,
+        };
+        let path = format!("executionOrder.{}", index);
+        if data["rules"].get(id).is_none() {
+            problems.push(Problem::new(&path, format!("Rule not found: {}", id)));
+        } else if !seen.insert(id) {
+            problems.push(Problem::new(&path, format!("Rule {} appears more than once", id)));
+        }
+    }
+    problems
+}
+
+#[wasm_bindgen]
+impl RuleRegistry {
+    /// Check registry JSON without importing it, returning every problem found
+    ///
+    /// Returns a JSON array of `{ path, message }` (empty when the registry
+    /// is valid), where `path` locates the problem, e.g.
+    /// `rules.growth.lisp_code` or `executionOrder.3`. Beyond what
+    /// `importRegistry` needs to read the registry, it checks that rule
+    /// code parses, IDs match their keys, modes, ordered rules and
+    /// dependencies exist, dependencies form no cycle, and test cases and
+    /// parameters are valid.
+    #[wasm_bindgen(js_name = checkJson)]
+    pub fn check_json(json: &str) -> Result<String, JsValue> {
+        if let Err(e) = import_guard::check_structure("Registry", json, false) {
+            return to_json(&[Problem::new("", e)]);
+        }
+        let migrated = match migration::migrate("registry", json) {
+            Ok(migrated) => migrated,
+            Err(e) => return to_json(&[Problem::new("", e.as_string().unwrap_or_default())]),
+        };
+        let mut data: serde_json::Value = match serde_json::from_str(&migrated) {
+            Ok(data) => data,
+            Err(e) => return to_json(&[Problem::new("", e.to_string())]),
+        };
+        if let Err(e) = import_guard::settle_registry(&mut data, false) {
+            return to_json(&[Problem::new("", e)]);
+        }
+        
+        let mut problems = registry_shape_problems(&data);
+        problems.extend(registry_content_problems(&data));
+        to_json(&problems)
+    }
+}
@@ -0,0 +1,138 @@
+use wasm_bindgen::prelude::*;
+
+use crate::OrganismState;
+
+fn check_lengths(a: &[f64], b: &[f64]) -> Result<(), String> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(format!("Vector lengths differ: {} and {}", a.len(), b.len()))
+    }
+}
+
+/// Apply `f` to each pair of elements
+pub(crate) fn zip_with(a: &[f64], b: &[f64], f: impl Fn(f64, f64) -> f64) -> Result<Vec<f64>, String> {
+    check_lengths(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| f(*x, *y)).collect())
+}
+
+pub(crate) fn dot(a: &[f64], b: &[f64]) -> Result<f64, String> {
+    check_lengths(a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+pub(crate) fn norm(a: &[f64]) -> f64 {
+    a.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Scale to unit length; a zero vector stays zero
+pub(crate) fn normalize(a: &[f64]) -> Vec<f64> {
+    let length = norm(a);
+    if length == 0.0 {
+        return a.to_vec();
+    }
+    a.iter().map(|x| x / length).collect()
+}
+
+/// Linear interpolation: `t = 0` gives `a`, `t = 1` gives `b`
+pub(crate) fn lerp(a: &[f64], b: &[f64], t: f64) -> Result<Vec<f64>, String> {
+    zip_with(a, b, |x, y| x + (y - x) * t)
+}
+
+pub(crate) fn distance(a: &[f64], b: &[f64]) -> Result<f64, String> {
+    Ok(norm(&zip_with(a, b, |x, y| x - y)?))
+}
+
+fn js_error(message: String) -> JsValue {
+    JsValue::from_str(&message)
+}
+
+/// VecMath provides vector operations on Float64Arrays such as state vectors
+///
+/// Operations taking two vectors throw when their lengths differ.
+#[wasm_bindgen]
+pub struct VecMath;
+
+#[wasm_bindgen]
+impl VecMath {
+    #[wasm_bindgen]
+    pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, JsValue> {
+        dot(a, b).map_err(js_error)
+    }
+    
+    #[wasm_bindgen]
+    pub fn norm(a: &[f64]) -> f64 {
+        norm(a)
+    }
+    
+    /// Scale to unit length (a zero vector is returned unchanged)
+    #[wasm_bindgen]
+    pub fn normalize(a: &[f64]) -> Vec<f64> {
+        normalize(a)
+    }
+    
+    /// Euclidean distance between two vectors
+    #[wasm_bindgen]
+    pub fn distance(a: &[f64], b: &[f64]) -> Result<f64, JsValue> {
+        distance(a, b).map_err(js_error)
+    }
+    
+    /// Interpolate from `a` (t = 0) to `b` (t = 1)
+    #[wasm_bindgen]
+    pub fn lerp(a: &[f64], b: &[f64], t: f64) -> Result<Vec<f64>, JsValue> {
+        lerp(a, b, t).map_err(js_error)
+    }
+    
+    /// Element-wise sum
+    #[wasm_bindgen]
+    pub fn add(a: &[f64], b: &[f64]) -> Result<Vec<f64>, JsValue> {
+        zip_with(a, b, |x, y| x + y).map_err(js_error)
+    }
+    
+    /// Element-wise difference
+    #[wasm_bindgen]
+    pub fn sub(a: &[f64], b: &[f64]) -> Result<Vec<f64>, JsValue> {
+        zip_with(a, b, |x, y| x - y).map_err(js_error)
+    }
+    
+    /// Element-wise product
+    #[wasm_bindgen]
+    pub fn mul(a: &[f64], b: &[f64]) -> Result<Vec<f64>, JsValue> {
+        zip_with(a, b, |x, y| x * y).map_err(js_error)
+    }
+    
+    /// Multiply every element by `factor`
+    #[wasm_bindgen]
+    pub fn scale(a: &[f64], factor: f64) -> Vec<f64> {
+        a.iter().map(|x| x * factor).collect()
+    }
+}
+
+#[wasm_bindgen]
+impl OrganismState {
+    /// Dot product of this state's vector with another organism's
+    #[wasm_bindgen(js_name = stateDot)]
+    pub fn state_dot(&self, other: &OrganismState) -> Result<f64, JsValue> {
+        dot(&self.state_vector, &other.state_vector).map_err(js_error)
+    }
+    
+    /// Euclidean length of the state vector
+    #[wasm_bindgen(js_name = stateNorm)]
+    pub fn state_norm(&self) -> f64 {
+        norm(&self.state_vector)
+    }
+    
+    /// Euclidean distance between this state's vector and another organism's
+    #[wasm_bindgen(js_name = stateDistance)]
+    pub fn state_distance(&self, other: &OrganismState) -> Result<f64, JsValue> {
+        distance(&self.state_vector, &other.state_vector).map_err(js_error)
+    }
+    
+    /// Interpolate from this state's vector (t = 0) to another organism's (t = 1)
+    ///
+    /// Neither state changes; pass the result to `setStateVector` to apply it.
+    #[wasm_bindgen(js_name = stateLerp)]
+    pub fn state_lerp(&self, other: &OrganismState, t: f64) -> Result<Vec<f64>, JsValue> {
+        lerp(&self.state_vector, &other.state_vector, t).map_err(js_error)
+    }
+}
@@ -0,0 +1,757 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::delta::Baselines;
+use crate::genome::{CrossoverOptions, Genome, MutationOptions};
+use crate::lineage::Lineage;
+use crate::rng::Rng;
+use crate::selection::SelectionStrategy;
+use crate::speciation::Speciation;
+
+/// Most organisms a world holds, whatever its `capacity` says
+const MAX_ORGANISMS: u32 = 1_000_000;
+
+fn default_initial_count() -> u32 {
+    100
+}
+
+fn default_initial_energy() -> f64 {
+    100.0
+}
+
+fn default_food_per_step() -> f64 {
+    1000.0
+}
+
+fn default_metabolism() -> f64 {
+    5.0
+}
+
+fn default_birth_energy() -> f64 {
+    200.0
+}
+
+fn default_capacity() -> u32 {
+    10_000
+}
+
+fn default_genome_length() -> usize {
+    8
+}
+
+fn default_mutation_rate() -> f64 {
+    0.01
+}
+
+fn default_selection_pressure() -> f64 {
+    0.5
+}
+
+fn default_appetite() -> f64 {
+    10.0
+}
+
+/// How organisms in a world reproduce
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Reproduction {
+    /// A parent divides, and the child carries its mutated genome
+    #[default]
+    Asexual,
+    /// A parent divides with a random mate, and the child carries their mutated crossover
+    Sexual,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub(crate) struct WorldOptions {
+    #[serde(default = "default_initial_count")]
+    initial_count: u32,
+    #[serde(default = "default_initial_energy")]
+    initial_energy: f64,
+    /// Energy per unit time shared among all organisms
+    #[serde(default = "default_food_per_step")]
+    food_per_step: f64,
+    /// Energy an organism burns per unit time
+    #[serde(default = "default_metabolism")]
+    metabolism: f64,
+    /// Energy at which an organism divides in two
+    #[serde(default = "default_birth_energy")]
+    birth_energy: f64,
+    /// Steps an organism lives at most; 0 means no limit
+    #[serde(default)]
+    max_age: u64,
+    /// Births are suppressed once this many organisms are alive
+    #[serde(default = "default_capacity")]
+    capacity: u32,
+    #[serde(default)]
+    seed: u64,
+    /// Genes in the genomes of organisms added to the world
+    #[serde(default = "default_genome_length")]
+    genome_length: usize,
+    /// Per-gene mutation probability applied to each child's genome
+    #[serde(default = "default_mutation_rate")]
+    pub(crate) mutation_rate: f64,
+    #[serde(default)]
+    pub(crate) mutation: MutationOptions,
+    #[serde(default)]
+    pub(crate) reproduction: Reproduction,
+    #[serde(default)]
+    pub(crate) crossover: CrossoverOptions,
+    /// How organisms are picked to reproduce when a generation advances
+    #[serde(default)]
+    pub(crate) selection: SelectionStrategy,
+    #[serde(default = "default_selection_pressure")]
+    pub(crate) selection_pressure: f64,
+    /// Energy an organism harvests from its cell per unit time under `stepIn`, before its genome's scaling
+    #[serde(default = "default_appetite")]
+    pub(crate) appetite: f64,
+}
+
+impl WorldOptions {
+    fn parse(json: &str) -> Result<WorldOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: WorldOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        options.validate()?;
+        Ok(options)
+    }
+    
+    /// Check the options make sense together, as they must before a world runs with them
+    pub(crate) fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+        for (name, value) in [
+            ("initialEnergy", self.initial_energy),
+            ("birthEnergy", self.birth_energy),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                problems.push(format!("{} must be positive, got {}", name, value));
+            }
+        }
+        for (name, value) in [
+            ("foodPerStep", self.food_per_step),
+            ("metabolism", self.metabolism),
+            ("appetite", self.appetite),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                problems.push(format!("{} must be non-negative, got {}", name, value));
+            }
+        }
+        if self.capacity == 0 || self.capacity > MAX_ORGANISMS {
+            problems.push(format!("capacity must be between 1 and {}, got {}", MAX_ORGANISMS, self.capacity));
+        }
+        if !self.mutation_rate.is_finite() || !(0.0..=1.0).contains(&self.mutation_rate) {
+            problems.push(format!("mutationRate must be between 0 and 1, got {}", self.mutation_rate));
+        }
+        if !self.selection_pressure.is_finite() || !(0.0..=1.0).contains(&self.selection_pressure) {
+            problems.push(format!("selectionPressure must be between 0 and 1, got {}", self.selection_pressure));
+        }
+        if let Err(e) = self.mutation.validate() {
+            problems.push(format!("mutation: {}", e));
+        }
+        if let Err(e) = self.crossover.validate() {
+            problems.push(format!("crossover: {}", e));
+        }
+        let (min_length, max_length) = self.mutation.length_bounds();
+        if !(min_length..=max_length).contains(&self.genome_length) {
+            problems.push(format!(
+                "genomeLength must be between mutation.minLength ({}) and mutation.maxLength ({}), got {}",
+                min_length, max_length, self.genome_length
+            ));
+        }
+        if self.initial_count > self.capacity {
+            problems.push(format!(
+                "initialCount must be at most capacity ({}), got {}",
+                self.capacity, self.initial_count
+            ));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// One individual in a world
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct Organism {
+    pub(crate) id: u32,
+    pub(crate) energy: f64,
+    pub(crate) age: u64,
+    pub(crate) adaptation_score: f64,
+    /// The organism it divided from, if it was born in the world
+    pub(crate) parent: Option<u32>,
+    /// The parent's mate, for organisms born of sexual reproduction
+    #[serde(default)]
+    pub(crate) mate: Option<u32>,
+    /// Tick it was added or born on
+    pub(crate) born_at: u64,
+    /// One more than its parent's; organisms added to the world take the world's generation
+    #[serde(default)]
+    pub(crate) generation: u64,
+    #[serde(default)]
+    pub(crate) genome: Genome,
+    /// Environment cell it forages in; placed at random on its first `stepIn`
+    #[serde(default)]
+    pub(crate) cell: Option<(u32, u32)>,
+    /// Species it was last grouped into; children take their parent's until regrouped
+    #[serde(default)]
+    pub(crate) species: Option<u32>,
+}
+
+impl Organism {
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "energy": self.energy,
+            "age": self.age,
+            "adaptationScore": self.adaptation_score,
+            "parent": self.parent,
+            "mate": self.mate,
+            "bornAt": self.born_at,
+            "generation": self.generation,
+            "genes": self.genome.genes(),
+            "x": self.cell.map(|(x, _)| x),
+            "y": self.cell.map(|(_, y)| y),
+            "species": self.species,
+        })
+    }
+}
+
+/// World owns a population of individual organisms and steps them together
+///
+/// Where `OrganismState` models a population as one set of aggregate
+/// metrics, a world tracks each organism's energy, age and genome. Every
+/// step the organisms share the food supply (each forages a random
+/// 50-150% of an even share, scaled by its genome), burn energy, die when
+/// it runs out or they pass `maxAge`, and divide in two when they reach
+/// `birthEnergy`. A child inherits its parent's genome, or under sexual
+/// reproduction the crossover of its parent's and a random mate's,
+/// mutated at the world's `mutationRate`. `stepIn` swaps the shared
+/// supply for an `Environment`: each organism moves and harvests within
+/// its own cell of the grid.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct World {
+    pub(crate) options: WorldOptions,
+    pub(crate) organisms: Vec<Organism>,
+    pub(crate) next_id: u32,
+    pub(crate) tick: u64,
+    /// Generations advanced by `evolve`
+    #[serde(default)]
+    pub(crate) generation: u64,
+    pub(crate) rng: Rng,
+    /// Every organism the world has held, for ancestry and phylogeny queries
+    #[serde(default)]
+    pub(crate) lineage: Lineage,
+    #[serde(default)]
+    pub(crate) speciation: Speciation,
+    /// Snapshots deltas are taken against; not part of the world's own snapshot
+    #[serde(skip)]
+    pub(crate) baselines: Baselines,
+    births: u32,
+    deaths: u32,
+    total_births: u64,
+    total_deaths: u64,
+}
+
+#[wasm_bindgen]
+impl World {
+    /// Create a world from JSON options
+    ///
+    /// Options (all optional): `initialCount` (100), `initialEnergy` (100),
+    /// `foodPerStep` (1000), `metabolism` (5), `birthEnergy` (200),
+    /// `maxAge` (0, no limit), `capacity` (10000), `seed` (0),
+    /// `genomeLength` (8), `mutationRate` (0.01), `mutation` (the options
+    /// of `Genome.mutate`), `reproduction` (`asexual` or `sexual`) and
+    /// `crossover` (the options of `Genome.crossover`), `selection`
+    /// (`tournament`, `roulette`, `rank` or `truncation`),
+    /// `selectionPressure` (0.5) and `appetite` (10).
+    #[wasm_bindgen(constructor)]
+    pub fn new(options_json: &str) -> Result<World, JsValue> {
+        let options = WorldOptions::parse(options_json)
+            .map_err(|e| JsValue::from_str(&format!("Invalid world options: {}", e)))?;
+        let mut world = World {
+            rng: Rng::new(options.seed),
+            options,
+            organisms: Vec::new(),
+            next_id: 1,
+            tick: 0,
+            generation: 0,
+            lineage: Lineage::default(),
+            speciation: Speciation::default(),
+            baselines: Baselines::default(),
+            births: 0,
+            deaths: 0,
+            total_births: 0,
+            total_deaths: 0,
+        };
+        for _ in 0..world.options.initial_count {
+            let genome = world.random_genome();
+            world.spawn(world.options.initial_energy, None, genome);
+        }
+        Ok(world)
+    }
+    
+    /// Organisms alive
+    #[wasm_bindgen(getter)]
+    pub fn count(&self) -> usize {
+        self.organisms.len()
+    }
+    
+    /// Steps taken so far
+    #[wasm_bindgen(getter)]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    
+    /// Generations advanced by `evolve`
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+    
+    /// Restart the world's RNG from `seed`
+    ///
+    /// Foraging, mutation, mate choice and selection all draw from it, so
+    /// two worlds in the same state and seeded alike evolve identically.
+    #[wasm_bindgen(js_name = setSeed)]
+    pub fn set_seed(&mut self, seed: u64) {
+        self.options.seed = seed;
+        self.rng = Rng::new(seed);
+    }
+    
+    /// Step every organism by `delta_time` and return how many are alive
+    #[wasm_bindgen]
+    pub fn step(&mut self, delta_time: f64) -> usize {
+        let dt = if delta_time.is_finite() { delta_time.max(0.0) } else { 0.0 };
+        let share = if self.organisms.is_empty() {
+            0.0
+        } else {
+            self.options.food_per_step * dt / self.organisms.len() as f64
+        };
+        self.advance(dt, |organism, rng| share * (0.5 + rng.next_f64()) * foraging_efficiency(&organism.genome))
+    }
+    
+    /// Take `n` steps and return the mean adaptation score after each (0 once the world is empty)
+    #[wasm_bindgen(js_name = stepN)]
+    pub fn step_n(&mut self, n: u32, delta_time: f64) -> Vec<f64> {
+        (0..n)
+            .map(|_| {
+                self.step(delta_time);
+                self.mean_adaptation_score()
+            })
+            .collect()
+    }
+    
+    /// Aggregate metrics as JSON
+    ///
+    /// `births` and `deaths` count the last step; `totalBirths` and
+    /// `totalDeaths` the whole run.
+    #[wasm_bindgen(js_name = getMetrics)]
+    pub fn get_metrics(&self) -> Result<String, JsValue> {
+        let count = self.organisms.len();
+        let mean = |value: fn(&Organism) -> f64| {
+            if count == 0 {
+                0.0
+            } else {
+                self.organisms.iter().map(value).sum::<f64>() / count as f64
+            }
+        };
+        let best = self.organisms.iter().map(|o| o.adaptation_score).fold(None, |best: Option<f64>, s| {
+            Some(best.map_or(s, |b| b.max(s)))
+        });
+        let metrics = serde_json::json!({
+            "tick": self.tick,
+            "generation": self.generation,
+            "count": count,
+            "totalEnergy": self.organisms.iter().map(|o| o.energy).sum::<f64>(),
+            "meanEnergy": mean(|o| o.energy),
+            "meanAge": mean(|o| o.age as f64),
+            "meanAdaptationScore": mean(|o| o.adaptation_score),
+            "bestAdaptationScore": best.unwrap_or(0.0),
+            "births": self.births,
+            "deaths": self.deaths,
+            "totalBirths": self.total_births,
+            "totalDeaths": self.total_deaths,
+        });
+        serde_json::to_string(&metrics)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize world metrics: {}", e)))
+    }
+    
+    /// All organisms as a JSON array of `{id, energy, age, adaptationScore, parent, bornAt, ...}`
+    #[wasm_bindgen(js_name = getOrganisms)]
+    pub fn get_organisms(&self) -> Result<String, JsValue> {
+        let organisms: Vec<serde_json::Value> = self.organisms.iter().map(Organism::to_json).collect();
+        serde_json::to_string(&organisms)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize organisms: {}", e)))
+    }
+    
+    /// One organism as JSON
+    #[wasm_bindgen(js_name = getOrganism)]
+    pub fn get_organism(&self, id: u32) -> Result<String, JsValue> {
+        let organism = self.organism(id)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism not found: {}", id)))?;
+        serde_json::to_string(&organism.to_json())
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize organism: {}", e)))
+    }
+    
+    /// Add an organism with the given energy and return its ID
+    #[wasm_bindgen(js_name = addOrganism)]
+    pub fn add_organism(&mut self, energy: f64) -> Result<u32, JsValue> {
+        if !energy.is_finite() || energy <= 0.0 {
+            return Err(JsValue::from_str(&format!("Organism energy must be positive, got {}", energy)));
+        }
+        if self.organisms.len() >= self.options.capacity as usize {
+            return Err(JsValue::from_str(&format!("World is at capacity ({})", self.options.capacity)));
+        }
+        let genome = self.random_genome();
+        Ok(self.spawn(energy, None, genome))
+    }
+    
+    /// Remove an organism; returns false if there was none with that ID
+    ///
+    /// Removals aren't counted as deaths, though the lineage records the
+    /// organism as gone.
+    #[wasm_bindgen(js_name = removeOrganism)]
+    pub fn remove_organism(&mut self, id: u32) -> bool {
+        match self.organisms.iter().position(|o| o.id == id) {
+            Some(index) => {
+                let organism = self.organisms.remove(index);
+                self.lineage.died(&organism, self.tick);
+                true
+            }
+            None => false,
+        }
+    }
+    
+    /// Per-gene mutation probability applied to children
+    #[wasm_bindgen(getter, js_name = mutationRate)]
+    pub fn mutation_rate(&self) -> f64 {
+        self.options.mutation_rate
+    }
+    
+    #[wasm_bindgen(setter, js_name = mutationRate)]
+    pub fn set_mutation_rate(&mut self, value: f64) {
+        if value.is_finite() {
+            self.options.mutation_rate = value.clamp(0.0, 1.0);
+        }
+    }
+    
+    /// Advance one generation and return a summary of the turnover as JSON
+    ///
+    /// Picks parents for `n_offspring` children with the selection
+    /// strategy (a second parent each under sexual reproduction), breeds
+    /// them with crossover and mutation, and makes room by removing the
+    /// least fit organisms: the fittest `count - n_offspring` survive,
+    /// so the population keeps its size, or grows to `n_offspring`.
+    /// Children start with `initialEnergy`; culled organisms count as
+    /// deaths. The summary has the new `generation`, the number of
+    /// distinct `parents`, `survivors`, `culled`, `offspring` and `count`,
+    /// and the `bestFitness` and `meanFitness` (adaptation score) of the
+    /// generation that was selected from. With speciation enabled the
+    /// organisms are then regrouped, and the summary adds the `species`
+    /// count and the IDs of species `founded` and `extinct`.
+    #[wasm_bindgen]
+    pub fn evolve(&mut self, n_offspring: usize) -> Result<String, JsValue> {
+        let count = self.organisms.len();
+        if count == 0 {
+            return Err(JsValue::from_str("Cannot evolve an empty world"));
+        }
+        let capacity = self.options.capacity as usize;
+        let offspring = n_offspring.min(capacity);
+        let best = self.organisms.iter().map(|o| o.adaptation_score).fold(f64::NEG_INFINITY, f64::max);
+        let mean = self.organisms.iter().map(|o| o.adaptation_score).sum::<f64>() / count as f64;
+        
+        let parents = self.select_indices(offspring);
+        let mates: Vec<Option<usize>> = match self.options.reproduction {
+            Reproduction::Sexual if count >= 2 => self.select_indices(offspring)
+                .into_iter()
+                .zip(&parents)
+                .map(|(mate, &parent)| {
+                    // Nobody mates with themselves; fall back to a random partner
+                    let mate = if mate == parent {
+                        let pick = ((self.rng.next_f64() * (count - 1) as f64) as usize).min(count - 2);
+                        (parent + 1 + pick) % count
+                    } else {
+                        mate
+                    };
+                    Some(mate)
+                })
+                .collect(),
+            _ => vec![None; offspring],
+        };
+        let energy = self.options.initial_energy;
+        let children: Vec<Organism> = parents.iter().zip(mates)
+            .map(|(&parent, mate)| self.offspring(parent, mate, energy))
+            .collect();
+        let mut distinct = parents.clone();
+        distinct.sort_unstable();
+        distinct.dedup();
+        
+        let survivors = count.saturating_sub(offspring).min(capacity - offspring);
+        let mut order: Vec<usize> = (0..count).collect();
+        order.sort_by(|a, b| {
+            let (a, b) = (&self.organisms[*a], &self.organisms[*b]);
+            b.adaptation_score.partial_cmp(&a.adaptation_score).unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut keep = vec![false; count];
+        for &index in &order[..survivors] {
+            keep[index] = true;
+        }
+        let (mut index, tick, lineage) = (0, self.tick, &mut self.lineage);
+        self.organisms.retain(|o| {
+            index += 1;
+            if !keep[index - 1] {
+                lineage.died(o, tick);
+            }
+            keep[index - 1]
+        });
+        for child in &children {
+            self.lineage.born(child);
+        }
+        self.organisms.extend(children);
+        
+        let culled = count - survivors;
+        self.total_births += offspring as u64;
+        self.total_deaths += culled as u64;
+        self.generation += 1;
+        
+        let mut summary = serde_json::json!({
+            "generation": self.generation,
+            "parents": distinct.len(),
+            "survivors": survivors,
+            "culled": culled,
+            "offspring": offspring,
+            "count": self.organisms.len(),
+            "bestFitness": best,
+            "meanFitness": mean,
+        });
+        if self.speciation.enabled() {
+            let speciation = self.regroup();
+            summary["species"] = speciation["species"].clone();
+            summary["founded"] = speciation["founded"].clone();
+            summary["extinct"] = speciation["extinct"].clone();
+        }
+        serde_json::to_string(&summary)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize generation summary: {}", e)))
+    }
+    
+    /// `asexual` or `sexual`
+    #[wasm_bindgen(getter)]
+    pub fn reproduction(&self) -> String {
+        match self.options.reproduction {
+            Reproduction::Asexual => "asexual".to_string(),
+            Reproduction::Sexual => "sexual".to_string(),
+        }
+    }
+    
+    /// Switch between `asexual` and `sexual` reproduction
+    #[wasm_bindgen(js_name = setReproduction)]
+    pub fn set_reproduction(&mut self, mode: &str) -> Result<(), JsValue> {
+        self.options.reproduction = serde_json::from_value(serde_json::json!(mode))
+            .map_err(|_| JsValue::from_str(&format!("Unknown reproduction mode: {} (expected asexual or sexual)", mode)))?;
+        Ok(())
+    }
+    
+    /// Breed two organisms and return the child's ID
+    ///
+    /// The child's genome is the parents' crossover, mutated, and its
+    /// energy is half of `parent_a`'s, as when an organism divides in
+    /// `step`; `parent_b` contributes only genes. Works in either
+    /// reproduction mode and counts towards `totalBirths`.
+    #[wasm_bindgen]
+    pub fn breed(&mut self, parent_a: u32, parent_b: u32) -> Result<u32, JsValue> {
+        if parent_a == parent_b {
+            return Err(JsValue::from_str("An organism can't breed with itself"));
+        }
+        let index = |id: u32| self.organisms.iter().position(|o| o.id == id)
+            .ok_or_else(|| JsValue::from_str(&format!("Organism not found: {}", id)));
+        let (a, b) = (index(parent_a)?, index(parent_b)?);
+        if self.organisms.len() >= self.options.capacity as usize {
+            return Err(JsValue::from_str(&format!("World is at capacity ({})", self.options.capacity)));
+        }
+        let id = self.reproduce(a, Some(b));
+        self.total_births += 1;
+        Ok(id)
+    }
+    
+    /// An organism's genome
+    #[wasm_bindgen(js_name = getGenome)]
+    pub fn get_genome(&self, id: u32) -> Result<Genome, JsValue> {
+        self.organism(id)
+            .map(|o| o.genome.clone())
+            .ok_or_else(|| JsValue::from_str(&format!("Organism not found: {}", id)))
+    }
+    
+    /// Get a snapshot of the world as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize world: {}", e)))
+    }
+    
+    /// Restore the world from a JSON snapshot
+    ///
+    /// Its options are checked as the constructor checks them, so a
+    /// snapshot edited into impossible options throws instead of loading.
+    #[wasm_bindgen(js_name = loadSnapshot)]
+    pub fn load_snapshot(&mut self, snapshot_json: &str) -> Result<(), JsValue> {
+        let loaded: World = serde_json::from_str(snapshot_json)
+            .map_err(|e| JsValue::from_str(&format!("Failed to parse world snapshot: {}", e)))?;
+        loaded.options.validate()
+            .map_err(|e| JsValue::from_str(&format!("Invalid world snapshot options: {}", e)))?;
+        
+        *self = loaded;
+        Ok(())
+    }
+}
+
+impl World {
+    /// Age every organism, feed it what `forage` returns, then retire the dead and divide the rich
+    pub(crate) fn advance(&mut self, dt: f64, mut forage: impl FnMut(&mut Organism, &mut Rng) -> f64) -> usize {
+        for organism in self.organisms.iter_mut() {
+            organism.age += 1;
+            let foraged = forage(organism, &mut self.rng);
+            organism.energy += foraged - self.options.metabolism * dt;
+        }
+        
+        let before = self.organisms.len();
+        let (max_age, tick, lineage) = (self.options.max_age, self.tick, &mut self.lineage);
+        self.organisms.retain(|o| {
+            let alive = o.energy > 0.0 && (max_age == 0 || o.age <= max_age);
+            if !alive {
+                lineage.died(o, tick);
+            }
+            alive
+        });
+        self.deaths = (before - self.organisms.len()) as u32;
+        
+        self.births = 0;
+        let adults = self.organisms.len();
+        for index in 0..adults {
+            if self.organisms.len() >= self.options.capacity as usize {
+                break;
+            }
+            if self.organisms[index].energy < self.options.birth_energy {
+                continue;
+            }
+            let mate = match self.options.reproduction {
+                Reproduction::Asexual => None,
+                // Without a partner there is no birth
+                Reproduction::Sexual if adults < 2 => continue,
+                Reproduction::Sexual => {
+                    let pick = (self.rng.next_f64() * (adults - 1) as f64) as usize;
+                    Some(if pick >= index { pick + 1 } else { pick })
+                }
+            };
+            self.reproduce(index, mate);
+            self.births += 1;
+        }
+        
+        let birth_energy = self.options.birth_energy;
+        for organism in self.organisms.iter_mut() {
+            organism.adaptation_score = adaptation_score(organism, birth_energy);
+        }
+        self.total_births += self.births as u64;
+        self.total_deaths += self.deaths as u64;
+        self.tick += 1;
+        self.organisms.len()
+    }
+    
+    fn mean_adaptation_score(&self) -> f64 {
+        if self.organisms.is_empty() {
+            0.0
+        } else {
+            self.organisms.iter().map(|o| o.adaptation_score).sum::<f64>() / self.organisms.len() as f64
+        }
+    }
+    
+    pub(crate) fn organism(&self, id: u32) -> Option<&Organism> {
+        self.organisms.iter().find(|o| o.id == id)
+    }
+    
+    /// Add an organism born now and return its ID
+    pub(crate) fn spawn(&mut self, energy: f64, parent: Option<u32>, genome: Genome) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let mut organism = Organism {
+            id,
+            energy,
+            age: 0,
+            adaptation_score: 0.0,
+            parent,
+            mate: None,
+            born_at: self.tick,
+            generation: self.generation,
+            genome,
+            cell: None,
+            species: None,
+        };
+        organism.adaptation_score = adaptation_score(&organism, self.options.birth_energy);
+        self.lineage.born(&organism);
+        self.organisms.push(organism);
+        id
+    }
+    
+    /// Have the organism at `index` divide, crossing its genome with `mate`'s if given
+    ///
+    /// The parent keeps half its energy and the child gets the other half.
+    pub(crate) fn reproduce(&mut self, index: usize, mate: Option<usize>) -> u32 {
+        self.organisms[index].energy /= 2.0;
+        let child = self.offspring(index, mate, self.organisms[index].energy);
+        let id = child.id;
+        self.lineage.born(&child);
+        self.organisms.push(child);
+        id
+    }
+    
+    /// A child of the organism at `index` with the given energy, not yet added
+    fn offspring(&mut self, index: usize, mate: Option<usize>, energy: f64) -> Organism {
+        let mut genome = match mate {
+            Some(mate) => {
+                let range = self.options.mutation.range();
+                let (a, b) = (&self.organisms[index].genome, &self.organisms[mate].genome);
+                Genome::crossover_with(a, b, &self.options.crossover, range, &mut self.rng)
+            }
+            None => self.organisms[index].genome.clone(),
+        };
+        genome.mutate_with(self.options.mutation_rate, &self.options.mutation, &mut self.rng);
+        
+        let parent = &self.organisms[index];
+        let mut child = Organism {
+            id: self.next_id,
+            energy,
+            age: 0,
+            adaptation_score: 0.0,
+            parent: Some(parent.id),
+            mate: mate.map(|m| self.organisms[m].id),
+            born_at: self.tick,
+            generation: parent.generation + 1,
+            genome,
+            cell: parent.cell,
+            species: parent.species,
+        };
+        self.next_id += 1;
+        child.adaptation_score = adaptation_score(&child, self.options.birth_energy);
+        child
+    }
+    
+    pub(crate) fn random_genome(&mut self) -> Genome {
+        Genome::random_in(self.options.genome_length, self.options.mutation.range(), &mut self.rng)
+    }
+}
+
+/// How much of its share an organism forages, from 0.75 times at a mean gene
+/// of -1 (or below) to 1.25 times at 1 (or above)
+pub(crate) fn foraging_efficiency(genome: &Genome) -> f64 {
+    1.0 + 0.25 * genome.mean().clamp(-1.0, 1.0)
+}
+
+/// Score in [0, 1] from how close an organism is to dividing and how long it has lived
+fn adaptation_score(organism: &Organism, birth_energy: f64) -> f64 {
+    let energy_score = (organism.energy / birth_energy).min(1.0);
+    let age_score = (organism.age as f64 / 100.0).min(1.0);
+    
+    (energy_score + age_score) / 2.0
+}
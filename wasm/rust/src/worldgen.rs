@@ -0,0 +1,340 @@
+use wasm_bindgen::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::rng::Rng;
+use crate::OrganismState;
+
+/// Largest grid `generateWorld` builds, in cells
+const MAX_CELLS: usize = 1 << 20;
+
+/// Largest starting population `generateWorld` places
+const MAX_POPULATION: u32 = 10_000_000;
+
+fn default_size() -> u32 {
+    64
+}
+
+fn default_octaves() -> u32 {
+    4
+}
+
+fn default_feature_size() -> f64 {
+    16.0
+}
+
+fn default_patches() -> u32 {
+    8
+}
+
+fn default_patch_radius() -> f64 {
+    6.0
+}
+
+fn default_max_resource() -> f64 {
+    100.0
+}
+
+fn default_population() -> u32 {
+    100
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct WorldOptions {
+    #[serde(default = "default_size")]
+    width: u32,
+    #[serde(default = "default_size")]
+    height: u32,
+    /// Noise layers summed into the fertility field
+    #[serde(default = "default_octaves")]
+    octaves: u32,
+    /// Width in cells of the coarsest fertility features
+    #[serde(default = "default_feature_size")]
+    feature_size: f64,
+    /// Resource patches scattered over fertile ground
+    #[serde(default = "default_patches")]
+    patches: u32,
+    #[serde(default = "default_patch_radius")]
+    patch_radius: f64,
+    /// Resources in a fully fertile cell at the center of a patch
+    #[serde(default = "default_max_resource")]
+    max_resource: f64,
+    /// Individuals spread over the grid in proportion to its resources
+    #[serde(default = "default_population")]
+    population: u32,
+}
+
+impl WorldOptions {
+    fn parse(json: &str) -> Result<WorldOptions, String> {
+        let json = if json.trim().is_empty() { "{}" } else { json };
+        let options: WorldOptions = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let mut problems = Vec::new();
+        if options.width == 0 || options.height == 0 {
+            problems.push("width and height must be positive".to_string());
+        } else if (options.width as usize).saturating_mul(options.height as usize) > MAX_CELLS {
+            problems.push(format!("the grid must have at most {} cells", MAX_CELLS));
+        }
+        if options.octaves == 0 || options.octaves > 12 {
+            problems.push(format!("octaves must be between 1 and 12, got {}", options.octaves));
+        }
+        for (name, value) in [
+            ("featureSize", options.feature_size),
+            ("patchRadius", options.patch_radius),
+            ("maxResource", options.max_resource),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                problems.push(format!("{} must be positive, got {}", name, value));
+            }
+        }
+        if options.population > MAX_POPULATION {
+            problems.push(format!("population must be at most {}, got {}", MAX_POPULATION, options.population));
+        }
+        if problems.is_empty() {
+            Ok(options)
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// A resource patch: a smooth bump of resources around a center cell
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Patch {
+    x: f64,
+    y: f64,
+    radius: f64,
+    richness: f64,
+}
+
+/// Hash a lattice point to a value in [0, 1), so noise needs no table
+fn lattice_value(seed: u64, octave: u32, x: i64, y: i64) -> f64 {
+    let mut z = seed
+        ^ (octave as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+        ^ (x as u64).wrapping_mul(0xBF58_476D_1CE4_E5B9)
+        ^ (y as u64).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+}
+
+/// Smoothly interpolated value noise at a point, in [0, 1)
+fn value_noise(seed: u64, octave: u32, x: f64, y: f64) -> f64 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (sx, sy) = (fx * fx * (3.0 - 2.0 * fx), fy * fy * (3.0 - 2.0 * fy));
+    let (ix, iy) = (x0 as i64, y0 as i64);
+    
+    let top = lattice_value(seed, octave, ix, iy) * (1.0 - sx) + lattice_value(seed, octave, ix + 1, iy) * sx;
+    let bottom = lattice_value(seed, octave, ix, iy + 1) * (1.0 - sx) + lattice_value(seed, octave, ix + 1, iy + 1) * sx;
+    top * (1.0 - sy) + bottom * sy
+}
+
+/// GeneratedWorld is a procedurally generated starting layout
+///
+/// Built by `generateWorld(seed, options)`: a fertility grid of layered
+/// value noise, resources gathered into patches over fertile ground, and
+/// a starting population spread over the grid in proportion to its
+/// resources. Grids are row-major, `y * width + x`.
+#[wasm_bindgen]
+#[derive(Serialize, Deserialize, Clone)]
+pub struct GeneratedWorld {
+    seed: u64,
+    options: WorldOptions,
+    fertility: Vec<f64>,
+    resources: Vec<f64>,
+    population: Vec<u32>,
+    patches: Vec<Patch>,
+}
+
+impl GeneratedWorld {
+    fn generate(seed: u64, options: WorldOptions) -> GeneratedWorld {
+        let (width, height) = (options.width as usize, options.height as usize);
+        let mut rng = Rng::new(seed);
+        
+        // Fertility: octaves of value noise, each twice as fine and half as strong
+        let mut fertility = vec![0.0; width * height];
+        for octave in 0..options.octaves {
+            let frequency = (1u64 << octave) as f64 / options.feature_size;
+            let weight = 0.5f64.powi(octave as i32);
+            for (index, cell) in fertility.iter_mut().enumerate() {
+                let (x, y) = ((index % width) as f64, (index / width) as f64);
+                *cell += weight * value_noise(seed, octave, x * frequency, y * frequency);
+            }
+        }
+        let total_weight: f64 = (0..options.octaves).map(|octave| 0.5f64.powi(octave as i32)).sum();
+        for cell in &mut fertility {
+            *cell /= total_weight;
+        }
+        
+        // Patches land on fertile ground: keep the better of two candidate spots
+        let patches: Vec<Patch> = (0..options.patches)
+            .map(|_| {
+                let mut pick = || (rng.next_f64() * width as f64, rng.next_f64() * height as f64);
+                let (first, second) = (pick(), pick());
+                let fertility_at = |(x, y): (f64, f64)| fertility[(y as usize).min(height - 1) * width + (x as usize).min(width - 1)];
+                let (x, y) = if fertility_at(first) >= fertility_at(second) { first } else { second };
+                Patch {
+                    x,
+                    y,
+                    radius: options.patch_radius * (0.5 + rng.next_f64()),
+                    richness: 0.5 + 0.5 * rng.next_f64(),
+                }
+            })
+            .collect();
+        
+        let resources: Vec<f64> = fertility
+            .iter()
+            .enumerate()
+            .map(|(index, fertility)| {
+                let (x, y) = ((index % width) as f64 + 0.5, (index / width) as f64 + 0.5);
+                let density = patches
+                    .iter()
+                    .map(|patch| {
+                        let distance_sq = (x - patch.x).powi(2) + (y - patch.y).powi(2);
+                        patch.richness * (-distance_sq / (2.0 * patch.radius * patch.radius)).exp()
+                    })
+                    .fold(0.0, f64::max);
+                options.max_resource * fertility * density
+            })
+            .collect();
+        
+        // Individuals settle cell by cell in proportion to resources (fertility when there are none)
+        let weights: &[f64] = if resources.iter().any(|r| *r > 0.0) { &resources } else { &fertility };
+        let mut cumulative = Vec::with_capacity(weights.len());
+        let mut running = 0.0;
+        for weight in weights {
+            running += weight;
+            cumulative.push(running);
+        }
+        let mut population = vec![0u32; width * height];
+        if running > 0.0 {
+            for _ in 0..options.population {
+                let target = rng.next_f64() * running;
+                let index = cumulative.partition_point(|total| *total <= target).min(population.len() - 1);
+                population[index] += 1;
+            }
+        }
+        
+        GeneratedWorld { seed, options, fertility, resources, population, patches }
+    }
+    
+    fn index(&self, x: u32, y: u32) -> Result<usize, JsValue> {
+        if x >= self.options.width || y >= self.options.height {
+            return Err(JsValue::from_str(&format!(
+                "Cell ({}, {}) is outside the {}x{} world",
+                x, y, self.options.width, self.options.height
+            )));
+        }
+        Ok(y as usize * self.options.width as usize + x as usize)
+    }
+    pub(crate) fn resources(&self) -> &[f64] {
+        &self.resources
+    }
+}
+
+#[wasm_bindgen]
+impl GeneratedWorld {
+    #[wasm_bindgen(getter)]
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn width(&self) -> u32 {
+        self.options.width
+    }
+    
+    #[wasm_bindgen(getter)]
+    pub fn height(&self) -> u32 {
+        self.options.height
+    }
+    
+    /// Fertility of every cell, 0 to 1
+    #[wasm_bindgen(js_name = getFertility)]
+    pub fn get_fertility(&self) -> Vec<f64> {
+        self.fertility.clone()
+    }
+    
+    /// Resources in every cell
+    #[wasm_bindgen(js_name = getResources)]
+    pub fn get_resources(&self) -> Vec<f64> {
+        self.resources.clone()
+    }
+    
+    /// Starting individuals in every cell
+    #[wasm_bindgen(js_name = getPopulation)]
+    pub fn get_population(&self) -> Vec<u32> {
+        self.population.clone()
+    }
+    
+    #[wasm_bindgen(js_name = fertilityAt)]
+    pub fn fertility_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        Ok(self.fertility[self.index(x, y)?])
+    }
+    
+    #[wasm_bindgen(js_name = resourceAt)]
+    pub fn resource_at(&self, x: u32, y: u32) -> Result<f64, JsValue> {
+        Ok(self.resources[self.index(x, y)?])
+    }
+    
+    #[wasm_bindgen(js_name = populationAt)]
+    pub fn population_at(&self, x: u32, y: u32) -> Result<u32, JsValue> {
+        Ok(self.population[self.index(x, y)?])
+    }
+    
+    /// Get the resource patches as JSON (`[{ x, y, radius, richness }]`)
+    #[wasm_bindgen(js_name = getPatches)]
+    pub fn get_patches(&self) -> Result<String, JsValue> {
+        serde_json::to_string(&self.patches)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize patches: {}", e)))
+    }
+    
+    /// Get totals for the world as JSON
+    #[wasm_bindgen(js_name = getSummary)]
+    pub fn get_summary(&self) -> String {
+        let cells = self.fertility.len() as f64;
+        let total_resources: f64 = self.resources.iter().sum();
+        let total_population: u64 = self.population.iter().map(|count| *count as u64).sum();
+        let occupied = self.population.iter().filter(|count| **count > 0).count();
+        serde_json::json!({
+            "seed": self.seed,
+            "width": self.options.width,
+            "height": self.options.height,
+            "meanFertility": self.fertility.iter().sum::<f64>() / cells,
+            "totalResources": total_resources,
+            "peakResource": self.resources.iter().copied().fold(0.0, f64::max),
+            "population": total_population,
+            "occupiedCells": occupied,
+            "patches": self.patches.len(),
+        })
+        .to_string()
+    }
+    
+    /// Start a state from this world: its population becomes the total placed here
+    #[wasm_bindgen(js_name = applyTo)]
+    pub fn apply_to(&self, state: &mut OrganismState) {
+        let total: u64 = self.population.iter().map(|count| *count as u64).sum();
+        state.set_population(total as f64);
+    }
+    
+    /// Get the whole world as JSON
+    #[wasm_bindgen(js_name = getSnapshot)]
+    pub fn get_snapshot(&self) -> Result<String, JsValue> {
+        serde_json::to_string(self)
+            .map_err(|e| JsValue::from_str(&format!("Failed to serialize world: {}", e)))
+    }
+}
+
+/// Generate a starting world from a seed
+///
+/// `options_json` (may be empty) takes `width`, `height` (64), `octaves`
+/// (4), `featureSize` (16 cells), `patches` (8), `patchRadius` (6 cells),
+/// `maxResource` (100) and `population` (100). The same seed and options
+/// always generate the same world.
+#[wasm_bindgen(js_name = generateWorld)]
+pub fn generate_world(seed: u64, options_json: &str) -> Result<GeneratedWorld, JsValue> {
+    let options = WorldOptions::parse(options_json)
+        .map_err(|e| JsValue::from_str(&format!("Invalid world options: {}", e)))?;
+    Ok(GeneratedWorld::generate(seed, options))
+}